@@ -14,10 +14,26 @@
 const enc = new TextEncoder();
 const dec = new TextDecoder();
 
+// ── cancellation ────────────────────────────────────────────────────────────
+// When the page is cross-origin-isolated the main thread shares a one-slot
+// Int32Array with us and stores 1 in it (plus Atomics.notify) to request that
+// the current run stops.  The WASM module polls js_should_cancel() before
+// every statement and aborts with "Execution cancelled" — the instance stays
+// alive, so the next run doesn't have to re-instantiate it.
+
+let cancelFlag = null;
+
+function js_should_cancel() {
+  return cancelFlag ? Atomics.load(cancelFlag, 0) : 0;
+}
+
 // ── sleep host function ─────────────────────────────────────────────────────
 
 function js_sleep(ms) {
-  if (typeof SharedArrayBuffer !== 'undefined') {
+  if (cancelFlag) {
+    // Wait on the cancel flag itself so a Stop request wakes us immediately.
+    Atomics.wait(cancelFlag, 0, 0, Math.ceil(ms));
+  } else if (typeof SharedArrayBuffer !== 'undefined') {
     // Genuine OS-level block — no CPU spin.
     // Atomics.wait is allowed in workers and blocks the worker thread only.
     Atomics.wait(new Int32Array(new SharedArrayBuffer(4)), 0, 0, Math.ceil(ms));
@@ -29,16 +45,101 @@ function js_sleep(ms) {
   }
 }
 
-// ── print host function ──────────────────────────────────────────────────────
-// Called by the WASM echo built-in for each output line.
+// ── output host function ─────────────────────────────────────────────────────
+// Called by bucl_run_streaming for each output line as it is produced.
 // postMessage queues the line to the main thread immediately — the main
 // thread is never blocked, so it can update the DOM straight away.
+//
+// Older builds call the same function as js_print from the echo built-in,
+// so it is provided under both names.
 
-function js_print(ptr, len) {
+function js_emit(ptr, len) {
   const bytes = new Uint8Array(wasmExports.memory.buffer, ptr, len);
   postMessage({ type: 'output', line: dec.decode(bytes) });
 }
 
+// ── host functions for jscall ───────────────────────────────────────────────
+// `jscall "name" arg…` in a script calls hostFunctions[name](...args) with
+// every argument as a string.  Add entries here to expose more of the
+// browser to scripts — no Rust changes needed.  Functions run synchronously
+// inside the worker, so there is no DOM; use postMessage to reach the page.
+
+const hostFunctions = {
+  now: () => Date.now(),
+  log: (...args) => postMessage({ type: 'output', line: args.join(' ') }),
+  // Synchronous XHR is still permitted inside workers.
+  fetchText: (url) => {
+    const xhr = new XMLHttpRequest();
+    xhr.open('GET', url, false);
+    xhr.send();
+    if (xhr.status < 200 || xhr.status >= 300) {
+      throw new Error(`HTTP ${xhr.status} for ${url}`);
+    }
+    return xhr.responseText;
+  },
+};
+
+// Reply layout read back by jscall: [u32-le status][u32-le len][utf-8 bytes]
+// with status 0 = result, 1 = the function threw, 2 = no such function.
+function js_call(namePtr, nameLen, argPtr, argLen) {
+  const { memory, bucl_alloc } = wasmExports;
+  const name = dec.decode(new Uint8Array(memory.buffer, namePtr, nameLen));
+  const args = JSON.parse(dec.decode(new Uint8Array(memory.buffer, argPtr, argLen)));
+
+  let status = 0;
+  let text = '';
+  const fn = Object.hasOwn(hostFunctions, name) ? hostFunctions[name] : undefined;
+  if (typeof fn !== 'function') {
+    status = 2;
+  } else {
+    try {
+      const result = fn(...args);
+      text = result == null ? '' : String(result);
+    } catch (err) {
+      status = 1;
+      text = err instanceof Error ? err.message : String(err);
+    }
+  }
+
+  const bytes = enc.encode(text);
+  const ptr = bucl_alloc(8 + bytes.length);
+  // bucl_alloc may grow memory, so take views only after allocating.
+  const view = new DataView(memory.buffer, ptr, 8);
+  view.setUint32(0, status, /*littleEndian=*/true);
+  view.setUint32(4, bytes.length, /*littleEndian=*/true);
+  new Uint8Array(memory.buffer, ptr + 8, bytes.length).set(bytes);
+  return ptr;
+}
+
+// ── virtual filesystem for readfile / writefile ─────────────────────────────
+// Files live in this Map for as long as the worker does, so one run can
+// write a file and a later run can read it back.
+
+const files = new Map();
+
+// Same reply layout as js_call: [u32-le status][u32-le len][utf-8 bytes]
+// with status 0 = contents, 2 = no such file.
+function js_read_file(pathPtr, pathLen) {
+  const { memory, bucl_alloc } = wasmExports;
+  const path = dec.decode(new Uint8Array(memory.buffer, pathPtr, pathLen));
+  if (!files.has(path)) return 0;   // null → "no such file"
+
+  const bytes = enc.encode(files.get(path));
+  const ptr = bucl_alloc(8 + bytes.length);
+  const view = new DataView(memory.buffer, ptr, 8);
+  view.setUint32(0, 0, /*littleEndian=*/true);
+  view.setUint32(4, bytes.length, /*littleEndian=*/true);
+  new Uint8Array(memory.buffer, ptr + 8, bytes.length).set(bytes);
+  return ptr;
+}
+
+function js_write_file(pathPtr, pathLen, dataPtr, dataLen) {
+  const { memory } = wasmExports;
+  const path = dec.decode(new Uint8Array(memory.buffer, pathPtr, pathLen));
+  files.set(path, dec.decode(new Uint8Array(memory.buffer, dataPtr, dataLen)));
+  return 0;
+}
+
 // ── WASM bootstrap ──────────────────────────────────────────────────────────
 
 let wasmExports = null;
@@ -47,7 +148,12 @@ const imports = {
   env: {
     js_math_random: () => Math.random(),
     js_sleep,
-    js_print,
+    js_emit,
+    js_print: js_emit,
+    js_should_cancel,
+    js_call,
+    js_read_file,
+    js_write_file,
   },
 };
 
@@ -62,7 +168,10 @@ async function init() {
 // ── BUCL run ────────────────────────────────────────────────────────────────
 
 function runBucl(source) {
-  const { memory, bucl_alloc, bucl_free, bucl_run } = wasmExports;
+  const { memory, bucl_alloc, bucl_free } = wasmExports;
+  // Streaming run: output arrives via js_emit and the returned buffer only
+  // holds the error (if any).  Older builds without it stream from echo.
+  const bucl_run = wasmExports.bucl_run_streaming ?? wasmExports.bucl_run;
 
   const srcBytes = enc.encode(source);
   const srcPtr   = bucl_alloc(srcBytes.length);
@@ -71,7 +180,7 @@ function runBucl(source) {
   const outPtr = bucl_run(srcPtr, srcBytes.length);
   bucl_free(srcPtr, srcBytes.length);
 
-  // Output layout: [u32-le length][utf-8 bytes]
+  // Result layout: [u32-le length][utf-8 bytes]
   const view     = new DataView(memory.buffer, outPtr);
   const outLen   = view.getUint32(0, /*littleEndian=*/true);
   const outBytes = new Uint8Array(memory.buffer, outPtr + 4, outLen);
@@ -81,10 +190,38 @@ function runBucl(source) {
   return output;
 }
 
+// ── syntax check ────────────────────────────────────────────────────────────
+// Parse-only; returns 'ok' or the parse error.  Returns null for older
+// builds without bucl_parse so the page simply shows no diagnostics.
+
+function parseBucl(source) {
+  const { memory, bucl_alloc, bucl_free, bucl_parse } = wasmExports;
+  if (!bucl_parse) return null;
+
+  const srcBytes = enc.encode(source);
+  const srcPtr   = bucl_alloc(srcBytes.length);
+  new Uint8Array(memory.buffer, srcPtr, srcBytes.length).set(srcBytes);
+
+  const outPtr = bucl_parse(srcPtr, srcBytes.length);
+  bucl_free(srcPtr, srcBytes.length);
+
+  const outLen  = new DataView(memory.buffer, outPtr).getUint32(0, /*littleEndian=*/true);
+  const message = dec.decode(new Uint8Array(memory.buffer, outPtr + 4, outLen));
+  bucl_free(outPtr, 4 + outLen);
+  return message;
+}
+
 // ── Message handler ─────────────────────────────────────────────────────────
 
 self.onmessage = ({ data }) => {
-  if (data.type === 'run') {
+  if (data.type === 'cancel-flag') {
+    cancelFlag = data.flag;
+  } else if (data.type === 'parse') {
+    if (!wasmExports) return;
+    const message = parseBucl(data.source);
+    if (message !== null) postMessage({ type: 'diagnostic', message });
+  } else if (data.type === 'run') {
+    if (cancelFlag) Atomics.store(cancelFlag, 0, 0);
     let output;
     try {
       output = runBucl(data.source);
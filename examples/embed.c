@@ -0,0 +1,43 @@
+/* Run a BUCL script from C.
+ *
+ *   make lib
+ *   cc -Iinclude examples/embed.c -Ltarget/release -lbucl_wasm -o embed
+ *   LD_LIBRARY_PATH=target/release ./embed examples/hello.bucl
+ */
+#include <stdio.h>
+#include <string.h>
+
+#include "bucl.h"
+
+static uint32_t word(const uint8_t *buf, int i) {
+    uint32_t value;
+    memcpy(&value, buf + 4 * i, 4); /* little-endian, like every host we build for */
+    return value;
+}
+
+int main(int argc, char **argv) {
+    if (argc < 2) {
+        fprintf(stderr, "usage: %s <script.bucl>\n", argv[0]);
+        return 2;
+    }
+    FILE *f = fopen(argv[1], "rb");
+    if (!f) {
+        perror(argv[1]);
+        return 2;
+    }
+    char src[1 << 16];
+    size_t len = fread(src, 1, sizeof src, f);
+    fclose(f);
+
+    /* Functions are looked up beside the script; no file or network access. */
+    BuclOptions opts = {.base_dir = ".", .flags = BUCL_SANDBOX};
+    uint8_t *res = bucl_run_with((const uint8_t *)src, len, &opts);
+    uint32_t status = word(res, 0), out_len = word(res, 1), err_len = word(res, 2);
+    fwrite(res + 12, 1, out_len, stdout);
+    if (out_len > 0)
+        putchar('\n');
+    if (status != BUCL_STATUS_OK)
+        fprintf(stderr, "error (status %u): %.*s\n", status, (int)err_len, res + 12 + out_len);
+    bucl_free(res, 12 + out_len + err_len);
+    return status == BUCL_STATUS_OK ? 0 : 1;
+}
@@ -0,0 +1,154 @@
+/* C API for embedding BUCL (libbucl_wasm).
+ *
+ * Declares the exports of src/lib.rs and src/capi.rs; `make header`
+ * regenerates it with cbindgen (see cbindgen.toml).
+ *
+ * Every bucl_run* call returns a buffer allocated by the library; release
+ * it with bucl_free and the size given in its documentation. */
+
+#ifndef BUCL_H
+#define BUCL_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Run status reported by [`bucl_run_v2`]: the script finished normally.
+ */
+#define BUCL_STATUS_OK 0
+
+/**
+ * Run status reported by [`bucl_run_v2`]: the source failed to parse.
+ */
+#define BUCL_STATUS_PARSE_ERROR 1
+
+/**
+ * Run status reported by [`bucl_run_v2`]: the script stopped with a runtime error.
+ */
+#define BUCL_STATUS_RUNTIME_ERROR 2
+
+/**
+ * Run status reported by [`bucl_run_v2`]: the host cancelled the run.
+ */
+#define BUCL_STATUS_CANCELLED 3
+
+/**
+ * Run status reported by [`bucl_run_v2`]: the script called `exit` with a
+ * non-zero code, which the error section holds in decimal.  (`exit 0`
+ * reports [`BUCL_STATUS_OK`].)
+ */
+#define BUCL_STATUS_EXIT 4
+
+/**
+ * [`BuclOptions::flags`]: deny file and network access, like
+ * `bucl --sandbox`.
+ */
+#define BUCL_SANDBOX 1
+
+/**
+ * [`BuclOptions::flags`]: sandbox, but allow network connections, like
+ * `bucl --allow-net`.
+ */
+#define BUCL_ALLOW_NET (1 << 1)
+
+/**
+ * [`BuclOptions::flags`]: reading an unset variable is an error, like
+ * `bucl --strict`.
+ */
+#define BUCL_STRICT (1 << 2)
+
+/**
+ * How [`bucl_run_with`] runs a script.  Zero-initialised options (all
+ * pointers null) run it like `bucl_run_v2`.
+ */
+typedef struct BuclOptions {
+  /**
+   * NUL-terminated directory `functions/<name>.bucl` and `locales/` are
+   * looked up in, or null.  File built-ins such as `readfile` still
+   * resolve relative paths against the process's current directory.
+   */
+  const char *base_dir;
+  /**
+   * `BUCL_*` flags, or-ed together.
+   */
+  uint32_t flags;
+  /**
+   * NUL-terminated, comma-separated paths the script may read below
+   * (`""` for anywhere), like `--allow-read=<paths>`; implies
+   * `BUCL_SANDBOX`.  Null leaves reading to the flags.
+   */
+  const char *allow_read;
+  /**
+   * The same for writing, like `--allow-write=<paths>`.
+   */
+  const char *allow_write;
+  /**
+   * Fail the run after this many statements; 0 for no limit.
+   */
+  uint64_t max_steps;
+} BuclOptions;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Allocate a byte buffer of `size` bytes and return its pointer.
+ * The caller is responsible for freeing it with `bucl_free`.
+ */
+uint8_t *bucl_alloc(size_t size);
+
+/**
+ * Free a buffer previously returned by `bucl_alloc` or one of the
+ * `bucl_run*` functions.
+ */
+void bucl_free(uint8_t *ptr, size_t size);
+
+/**
+ * Run a BUCL script and return its buffered output, as
+ * `[u32-le output_len][output_len bytes of UTF-8]`.  Output is only
+ * returned, never printed.
+ * The caller must free the returned pointer with `bucl_free(ptr, 4 + output_len)`.
+ */
+uint8_t *bucl_run(const uint8_t *src_ptr, size_t src_len);
+
+/**
+ * Run a BUCL script and return a structured result that keeps output and
+ * errors apart, as (all integers u32 little-endian)
+ * `[status][output_len][error_len][output bytes][error bytes]`.  Output is
+ * only returned, never printed.
+ * The caller must free the returned pointer with
+ * `bucl_free(ptr, 12 + output_len + error_len)`.
+ */
+uint8_t *bucl_run_v2(const uint8_t *src_ptr, size_t src_len);
+
+/**
+ * Run a BUCL script, printing each output line to stdout as it is
+ * produced, and return only the error message (empty on success) in the
+ * layout of `bucl_run`.
+ */
+uint8_t *bucl_run_streaming(const uint8_t *src_ptr, size_t src_len);
+
+/**
+ * Check a BUCL script for syntax errors without running it.  Returns a
+ * `[u32-le len][utf-8 bytes]` buffer holding `ok` or the parse error;
+ * free it with `bucl_free(ptr, 4 + len)`.
+ */
+uint8_t *bucl_parse(const uint8_t *src_ptr, size_t src_len);
+
+/**
+ * Run a BUCL script with `options` (null for the defaults) and return a
+ * structured result laid out like `bucl_run_v2`'s.  Output is only
+ * returned, never printed.  The caller must free the returned pointer with
+ * `bucl_free(ptr, 12 + output_len + error_len)`.
+ */
+uint8_t *bucl_run_with(const uint8_t *src_ptr, size_t src_len, const struct BuclOptions *options);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* BUCL_H */
@@ -0,0 +1,61 @@
+// bucl-host.js — default host hooks for wasm-bindgen builds of BUCL.
+//
+// The module built with `--features wasm-bindgen` imports these functions
+// instead of the raw `env` imports the demo's worker.js provides.  Each one
+// defers to `globalThis.buclHost` when it defines a replacement, so
+// applications can customise behaviour without rebuilding:
+//
+//   globalThis.buclHost = {
+//     emit: line => terminal.write(line),        // bucl_run_streaming output
+//     shouldCancel: () => stopRequested,         // polled before each statement
+//     functions: { now: () => Date.now() },      // `jscall` targets
+//     files: new Map([['data.txt', 'hello']]),   // readfile / writefile
+//   };
+
+const host = () => globalThis.buclHost ?? {};
+
+export function js_math_random() {
+  return (host().random ?? Math.random)();
+}
+
+export function js_sleep(ms) {
+  if (host().sleep) return host().sleep(ms);
+  if (typeof SharedArrayBuffer !== 'undefined' && typeof Atomics.wait === 'function') {
+    try {
+      Atomics.wait(new Int32Array(new SharedArrayBuffer(4)), 0, 0, Math.ceil(ms));
+      return;
+    } catch {
+      // Atomics.wait is not allowed on the main thread; fall through.
+    }
+  }
+  const end = Date.now() + ms;
+  while (Date.now() < end) { /* spin */ }
+}
+
+export function js_emit(line) {
+  (host().emit ?? console.log)(line);
+}
+
+export function js_should_cancel() {
+  return Boolean(host().shouldCancel?.());
+}
+
+// Returns undefined for an unknown function; exceptions become jscall errors.
+export function js_call(name, argsJson) {
+  const fns = host().functions ?? {};
+  if (!Object.hasOwn(fns, name) || typeof fns[name] !== 'function') return undefined;
+  const result = fns[name](...JSON.parse(argsJson));
+  return result == null ? '' : String(result);
+}
+
+let defaultFiles = null;
+const files = () => host().files ?? (defaultFiles ??= new Map());
+
+export function js_read_file(path) {
+  return files().has(path) ? String(files().get(path)) : undefined;
+}
+
+export function js_write_file(path, contents) {
+  files().set(path, contents);
+  return true;
+}
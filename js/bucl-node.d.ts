@@ -0,0 +1,40 @@
+// bucl-node.d.ts — types for the Node.js addon built with `--features napi`
+// (`make node`).  Copy it next to bucl.node as bucl.node.d.ts, or declare
+// the module with it.
+
+export interface RunOptions {
+  /** Directory `functions/<name>.bucl` is looked up in. */
+  baseDir?: string;
+  /** Deny file and network access, like `bucl --sandbox`. */
+  sandbox?: boolean;
+  /** Sandbox, but allow reading below these paths (`[]` for anywhere). */
+  allowRead?: string[];
+  /** Sandbox, but allow writing below these paths (`[]` for anywhere). */
+  allowWrite?: string[];
+  /** Sandbox, but allow network connections. */
+  allowNet?: boolean;
+  /** Reading an unset variable is an error. */
+  strict?: boolean;
+  /** Fail the run after this many statements. */
+  maxSteps?: number;
+  /** Variables to preset, by name. */
+  vars?: Record<string, string>;
+}
+
+export interface RunResult {
+  /** 0 ok, 1 parse error, 2 runtime error, 3 cancelled, 4 exited with a non-zero code. */
+  status: number;
+  /** `true` when the script finished without an error. */
+  ok: boolean;
+  /** Output lines joined with `\n`, including any produced before an error. */
+  output: string;
+  /** The error message, or an empty string on success. */
+  error: string;
+  /** The code the script passed to `exit`, or 0. */
+  exitCode: number;
+  /** Every variable the script ended with, by name. */
+  vars: Record<string, string>;
+}
+
+/** Run a script on the thread pool; script errors are reported in the result. */
+export function run(source: string, options?: RunOptions): Promise<RunResult>;
@@ -1,13 +1,36 @@
+use std::sync::Arc;
+
 /// A parameter in a BUCL statement.
 #[derive(Debug, Clone)]
 pub enum Param {
-    /// A double-quoted string literal: `"hello {name}"`.
-    /// Variable references inside are interpolated at evaluation time.
-    Quoted(String),
+    /// A double-quoted string literal: `"hello {name}"`, with the text as
+    /// written and its literal and `{var}` parts.  The parts are split out at
+    /// parse time, so evaluating the string — once per loop iteration —
+    /// doesn't scan it for braces again; the variables are interpolated at
+    /// evaluation time.  Built-ins that take a string uninterpolated (a
+    /// `matches` pattern, an option value) use the text.
+    Quoted(String, Arc<[TemplatePart]>),
     /// A stand-alone variable reference: `{name}`.
     Variable(String),
     /// An unquoted bare word or number: `42`, `=`, `true`.
     Bare(String),
+    /// A double-quoted string parsed with non-default interpolation
+    /// delimiters (see [`crate::parser::Delimiters`]) or escaped braces, or a
+    /// raw `r"..."` string.  Only the [`TemplatePart::Var`] parts are
+    /// interpolated; braces in the text are literal.
+    Template(Vec<TemplatePart>),
+    /// An argument named at the call site: `host:"db1"`, `port:{p}`.  The
+    /// value is passed like the inner parameter, under this name.
+    Named(String, Box<Param>),
+}
+
+/// A piece of a [`Param::Quoted`] or [`Param::Template`] string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplatePart {
+    /// Literal text.
+    Text(String),
+    /// A variable reference, e.g. `name` from `${name}`.
+    Var(String),
 }
 
 /// A fully-evaluated function argument, optionally carrying the source variable name.
@@ -29,6 +52,8 @@ pub struct ResolvedArg {
 /// A single BUCL statement, parsed from one (logical) line.
 #[derive(Debug, Clone)]
 pub struct Statement {
+    /// 1-based source line the statement was parsed from.
+    pub line: usize,
     /// Optional result variable: `{var}` at the start of a line.
     pub target: Option<String>,
     /// The function / command to invoke (e.g. `=`, `if`, `length`).
@@ -0,0 +1,108 @@
+//! wasm-bindgen interface (feature `wasm-bindgen`).
+//!
+//! An ergonomic alternative to the raw C-ABI in `lib.rs` for JS users on
+//! bundlers: strings are marshalled automatically, so there is no manual
+//! `bucl_alloc` / copy / `bucl_free`.
+//!
+//! ```js
+//! import init, { run, runDetailed, checkSyntax } from './pkg/bucl_wasm.js';
+//!
+//! await init();
+//! console.log(run('echo "hi"'));            // "hi"
+//! const r = runDetailed(source);
+//! if (!r.ok) showError(r.error);            // r.status, r.output, r.error
+//! ```
+//!
+//! Host hooks (`sleep`, `random`, `jscall`, files, cancellation) come from
+//! `js/bucl-host.js`; override them through `globalThis.buclHost`.
+//! Both interfaces are exported together, so existing raw-ABI hosts keep
+//! working.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::BuclError;
+use crate::{execute, new_evaluator, parser, BUCL_STATUS_OK};
+
+/// Outcome of [`run_detailed`]: the same information as `bucl_run_v2`.
+#[wasm_bindgen]
+pub struct RunResult {
+    status: u32,
+    output: String,
+    error: String,
+    exit_code: i32,
+}
+
+#[wasm_bindgen]
+impl RunResult {
+    /// One of the `BUCL_STATUS_*` codes: 0 ok, 1 parse error, 2 runtime
+    /// error, 3 cancelled, 4 exited with a non-zero code.
+    #[wasm_bindgen(getter)]
+    pub fn status(&self) -> u32 {
+        self.status
+    }
+
+    /// `true` when the script finished without an error.
+    #[wasm_bindgen(getter)]
+    pub fn ok(&self) -> bool {
+        self.status == BUCL_STATUS_OK
+    }
+
+    /// Output lines joined with `\n`, including any produced before an error.
+    #[wasm_bindgen(getter)]
+    pub fn output(&self) -> String {
+        self.output.clone()
+    }
+
+    /// The error message, or an empty string on success.  After `exit` with
+    /// a non-zero code it holds that code; see also `exitCode`.
+    #[wasm_bindgen(getter)]
+    pub fn error(&self) -> String {
+        self.error.clone()
+    }
+
+    /// The code the script passed to `exit`, or 0 when it didn't exit early.
+    #[wasm_bindgen(getter, js_name = exitCode)]
+    pub fn exit_code(&self) -> i32 {
+        self.exit_code
+    }
+}
+
+/// Run a script and return its output, or the error text — the same result
+/// as `bucl_run`.
+#[wasm_bindgen]
+pub fn run(source: &str) -> String {
+    let mut eval = new_evaluator();
+    match execute(&mut eval, source) {
+        Ok(()) => eval.output_buffer.join("\n"),
+        Err(failure) => failure.legacy_message(),
+    }
+}
+
+/// Run a script and keep its status, output, and error apart.
+#[wasm_bindgen(js_name = runDetailed)]
+pub fn run_detailed(source: &str) -> RunResult {
+    let mut eval = new_evaluator();
+    let (status, error, exit_code) = match execute(&mut eval, source) {
+        Ok(()) => (BUCL_STATUS_OK, String::new(), 0),
+        Err(failure) => {
+            let exit_code = match failure.error {
+                BuclError::Exit(code) => code,
+                _ => 0,
+            };
+            (failure.status, failure.detail(), exit_code)
+        }
+    };
+    RunResult {
+        status,
+        output: eval.output_buffer.join("\n"),
+        error,
+        exit_code,
+    }
+}
+
+/// Syntax-check a script without running it: `undefined` when it parses,
+/// otherwise the parse error (with its line number).
+#[wasm_bindgen(js_name = checkSyntax)]
+pub fn check_syntax(source: &str) -> Option<String> {
+    parser::parse(source).err().map(|e| e.to_string())
+}
@@ -0,0 +1,235 @@
+//! Native C API (the `cdylib` built on non-WASM targets).
+//!
+//! C, C++ and Go programs link `libbucl_wasm` and call the same
+//! `bucl_alloc` / `bucl_run*` / `bucl_free` functions a browser does (see
+//! the crate docs), declared in `include/bucl.h`.  Native hosts usually
+//! also want to say where a script's `functions/` live and what it may
+//! touch, which [`bucl_run_with`] takes as a [`BuclOptions`]:
+//!
+//! ```c
+//! #include "bucl.h"
+//!
+//! BuclOptions opts = { .base_dir = "scripts", .flags = BUCL_SANDBOX,
+//!                      .allow_read = "scripts/data" };
+//! uint8_t *res = bucl_run_with((const uint8_t *)src, strlen(src), &opts);
+//! uint32_t status, out_len, err_len;
+//! memcpy(&status, res, 4); memcpy(&out_len, res + 4, 4); memcpy(&err_len, res + 8, 4);
+//! fwrite(res + 12, 1, out_len, stdout);
+//! bucl_free(res, 12 + out_len + err_len);
+//! ```
+//!
+//! Every call runs in a fresh evaluator, so calls from several threads are
+//! independent.  Rebuild the header with `make header` (cbindgen) after
+//! changing an export.
+
+use std::ffi::{c_char, CStr};
+
+use crate::evaluator::Evaluator;
+use crate::permissions::{Access, Permissions};
+use crate::{encode_result, execute, read_source, BUCL_STATUS_OK};
+
+/// [`BuclOptions::flags`]: deny file and network access, like
+/// `bucl --sandbox`.
+pub const BUCL_SANDBOX: u32 = 1;
+/// [`BuclOptions::flags`]: sandbox, but allow network connections, like
+/// `bucl --allow-net`.
+pub const BUCL_ALLOW_NET: u32 = 1 << 1;
+/// [`BuclOptions::flags`]: reading an unset variable is an error, like
+/// `bucl --strict`.
+pub const BUCL_STRICT: u32 = 1 << 2;
+
+/// How [`bucl_run_with`] runs a script.  Zero-initialised options (all
+/// pointers null) run it like `bucl_run_v2`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BuclOptions {
+    /// NUL-terminated directory `functions/<name>.bucl` and `locales/` are
+    /// looked up in, or null.  File built-ins such as `readfile` still
+    /// resolve relative paths against the process's current directory.
+    pub base_dir: *const c_char,
+    /// `BUCL_*` flags, or-ed together.
+    pub flags: u32,
+    /// NUL-terminated, comma-separated paths the script may read below
+    /// (`""` for anywhere), like `--allow-read=<paths>`; implies
+    /// `BUCL_SANDBOX`.  Null leaves reading to the flags.
+    pub allow_read: *const c_char,
+    /// The same for writing, like `--allow-write=<paths>`.
+    pub allow_write: *const c_char,
+    /// Fail the run after this many statements; 0 for no limit.
+    pub max_steps: u64,
+}
+
+/// Read an optional NUL-terminated option string (invalid UTF-8 → none).
+unsafe fn option_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(ptr) }.to_str().ok()
+}
+
+/// `""` grants everywhere, otherwise the comma-separated paths.
+fn grant(paths: &str) -> Access {
+    if paths.is_empty() {
+        Access::Allow
+    } else {
+        Access::within(paths.split(',').filter(|p| !p.is_empty()))
+    }
+}
+
+/// Build the evaluator `options` describe.
+///
+/// # Safety
+/// The option strings must be null or NUL-terminated.
+unsafe fn evaluator_for(options: &BuclOptions) -> Evaluator {
+    let (allow_read, allow_write, base_dir) = unsafe {
+        (
+            option_str(options.allow_read),
+            option_str(options.allow_write),
+            option_str(options.base_dir),
+        )
+    };
+    let flags = options.flags;
+    let permissions = Permissions::from_flags(
+        flags & BUCL_SANDBOX != 0,
+        allow_read.map(grant),
+        allow_write.map(grant),
+        (flags & BUCL_ALLOW_NET != 0).then_some(Access::Allow),
+    );
+
+    // Output comes back in the result buffer; don't also print it.
+    let mut builder = Evaluator::builder()
+        .with_stdlib()
+        .permissions(permissions)
+        .strict(flags & BUCL_STRICT != 0)
+        .output_sink(std::sync::Arc::new(|_: &str| {}));
+    if let Some(dir) = base_dir {
+        builder = builder.base_dir(dir);
+    }
+    if options.max_steps > 0 {
+        builder = builder.max_steps(options.max_steps);
+    }
+    builder.build()
+}
+
+/// Run a BUCL script with `options` (null for the defaults) and return a
+/// structured result laid out like [`bucl_run_v2`](crate::bucl_run_v2)'s:
+/// ```text
+/// [status][output_len][error_len][output_len bytes of UTF-8][error_len bytes of UTF-8]
+/// ```
+/// Output is only returned, never printed.  The caller must free the
+/// returned pointer with `bucl_free(ptr, 12 + output_len + error_len)`.
+///
+/// # Safety
+/// `src_ptr` must point to `src_len` readable bytes, and `options` must be
+/// null or point to a `BuclOptions` whose strings are null or
+/// NUL-terminated.
+#[no_mangle]
+pub unsafe extern "C" fn bucl_run_with(
+    src_ptr: *const u8,
+    src_len: usize,
+    options: *const BuclOptions,
+) -> *mut u8 {
+    let source = unsafe { read_source(src_ptr, src_len) };
+    let defaults = BuclOptions {
+        base_dir: std::ptr::null(),
+        flags: 0,
+        allow_read: std::ptr::null(),
+        allow_write: std::ptr::null(),
+        max_steps: 0,
+    };
+    let options = unsafe { options.as_ref() }.unwrap_or(&defaults);
+    let mut eval = unsafe { evaluator_for(options) };
+    let (status, error) = match execute(&mut eval, source) {
+        Ok(()) => (BUCL_STATUS_OK, String::new()),
+        Err(failure) => (failure.status, failure.detail()),
+    };
+    encode_result(status, &eval.output_buffer.join("\n"), &error)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bucl_free, BUCL_STATUS_RUNTIME_ERROR};
+
+    fn run_with(source: &str, options: Option<&BuclOptions>) -> (u32, String, String) {
+        let word =
+            |buf: &[u8], i: usize| u32::from_le_bytes(buf[i * 4..i * 4 + 4].try_into().unwrap());
+        unsafe {
+            let options = options.map_or(std::ptr::null(), |o| o as *const BuclOptions);
+            let ptr = bucl_run_with(source.as_ptr(), source.len(), options);
+            let header = std::slice::from_raw_parts(ptr, 12);
+            let (status, out_len, err_len) =
+                (word(header, 0), word(header, 1) as usize, word(header, 2) as usize);
+            let body = std::slice::from_raw_parts(ptr.add(12), out_len + err_len);
+            let output = String::from_utf8(body[..out_len].to_vec()).unwrap();
+            let error = String::from_utf8(body[out_len..].to_vec()).unwrap();
+            bucl_free(ptr, 12 + out_len + err_len);
+            (status, output, error)
+        }
+    }
+
+    #[test]
+    fn test_options_configure_the_run() {
+        let sandbox = BuclOptions {
+            base_dir: std::ptr::null(),
+            flags: BUCL_SANDBOX | BUCL_STRICT,
+            allow_read: std::ptr::null(),
+            allow_write: std::ptr::null(),
+            max_steps: 0,
+        };
+        let (status, _, error) = run_with("echo {unset}\n", Some(&sandbox));
+        assert_eq!(status, BUCL_STATUS_RUNTIME_ERROR);
+        assert!(error.contains("unset"), "{}", error);
+
+        let limited = BuclOptions {
+            max_steps: 2,
+            ..sandbox
+        };
+        assert_eq!(run_with("echo 1\necho 2\n", Some(&limited)).0, BUCL_STATUS_OK);
+        assert_eq!(
+            run_with("echo 1\necho 2\necho 3\n", Some(&limited)).0,
+            BUCL_STATUS_RUNTIME_ERROR
+        );
+
+        #[cfg(feature = "fs")]
+        {
+            let read = "{t} readfile \"Cargo.toml\"\necho \"read\"\n";
+            assert_eq!(run_with(read, None), (BUCL_STATUS_OK, "read".into(), String::new()));
+            let (status, _, error) = run_with(read, Some(&sandbox));
+            assert_eq!(status, BUCL_STATUS_RUNTIME_ERROR);
+            assert!(error.contains("reading files is not permitted"), "{}", error);
+            let allowed = BuclOptions {
+                allow_read: c"".as_ptr(),
+                ..sandbox
+            };
+            assert_eq!(run_with(read, Some(&allowed)).0, BUCL_STATUS_OK);
+        }
+    }
+
+    #[test]
+    fn test_header_declares_every_export() {
+        let header = include_str!("../include/bucl.h");
+        let sources = [include_str!("lib.rs"), include_str!("capi.rs")];
+        for source in sources {
+            for line in source.lines() {
+                let Some(rest) = line.split("extern \"C\" fn ").nth(1) else {
+                    continue;
+                };
+                let name = rest.split('(').next().unwrap();
+                let declared = [" ", "*"].iter().any(|before| {
+                    header
+                        .lines()
+                        .any(|l| !l.trim_start().starts_with('*') && l.contains(&format!("{}{}(", before, name)))
+                });
+                assert!(declared, "bucl.h lacks {}", name);
+            }
+        }
+        for constant in ["BUCL_SANDBOX", "BUCL_ALLOW_NET", "BUCL_STRICT", "BUCL_STATUS_EXIT"] {
+            assert!(header.contains(&format!("#define {} ", constant)), "bucl.h lacks {}", constant);
+        }
+    }
+}
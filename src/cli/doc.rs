@@ -0,0 +1,205 @@
+// `bucl doc [--markdown] [name]` — documentation for the functions a script
+// can call.
+//
+// Built-ins describe themselves through `BuclFunction::description`; a
+// .bucl function is documented by the `#` comment lines at the top of its
+// file.  Without a name every function is listed with a one-line summary;
+// with one, that function's full documentation is shown.  `--markdown`
+// writes Markdown instead of terminal text, e.g. for a docs page:
+//
+//   bucl doc strpos
+//   bucl doc --markdown > FUNCTIONS.md
+//
+// Functions are looked up like a script in the current directory looks them
+// up: the built-ins, the embedded standard library, then functions/*.bucl.
+
+use bucl_wasm::evaluator::{Evaluator, FunctionOrigin};
+
+/// One documented function.
+struct Entry {
+    name: String,
+    /// `built-in`, `standard library` or the file it is read from.
+    origin: String,
+    /// Prose for built-ins; the comment text, line by line, for .bucl files.
+    doc: String,
+    builtin: bool,
+}
+
+impl Entry {
+    /// The first line of the documentation, without a leading `name —`.
+    fn summary(&self) -> &str {
+        let first = self.doc.lines().next().unwrap_or("");
+        first
+            .strip_prefix(self.name.as_str())
+            .and_then(|rest| rest.trim_start().strip_prefix('—'))
+            .map_or(first, str::trim)
+    }
+}
+
+/// Entry point for `bucl doc`; `args` are the arguments after `doc`.
+pub fn main(args: &[String]) -> i32 {
+    let mut markdown = false;
+    let mut name = None;
+    for arg in args {
+        match arg.as_str() {
+            "--markdown" => markdown = true,
+            flag if flag.starts_with("--") => {
+                eprintln!("Unknown option for 'bucl doc': {}", flag);
+                return 1;
+            }
+            _ => name = Some(arg.as_str()),
+        }
+    }
+
+    let eval = Evaluator::builder().with_stdlib().build();
+    let entries = entries(&eval);
+    match name {
+        None if markdown => print!("{}", markdown_page(&entries)),
+        None => print!("{}", overview(&entries)),
+        Some(name) => match entries.iter().find(|e| e.name == name) {
+            Some(entry) if markdown => print!("{}", markdown_entry(entry)),
+            Some(entry) => print!("{}", help(entry)),
+            None => {
+                eprintln!("No function named '{}'", name);
+                return 1;
+            }
+        },
+    }
+    0
+}
+
+/// Every function `eval` can call, sorted by name.
+fn entries(eval: &Evaluator) -> Vec<Entry> {
+    eval.callable_functions()
+        .into_iter()
+        .map(|(name, origin)| {
+            let doc = match origin {
+                FunctionOrigin::Builtin => {
+                    eval.builtin(&name).map_or("", |f| f.description()).to_string()
+                }
+                _ => leading_comment(&eval.bucl_function_source(&name).unwrap_or_default()),
+            };
+            Entry {
+                builtin: origin == FunctionOrigin::Builtin,
+                origin: origin_label(&origin),
+                doc,
+                name,
+            }
+        })
+        .collect()
+}
+
+/// How the CLI names where a function comes from.  The CLI embeds nothing
+/// but the standard library.
+pub fn origin_label(origin: &FunctionOrigin) -> String {
+    match origin {
+        FunctionOrigin::Builtin => "built-in".into(),
+        FunctionOrigin::Embedded => "standard library".into(),
+        FunctionOrigin::File(path) => path.display().to_string(),
+    }
+}
+
+/// The `#` comment lines a .bucl file starts with, without the `#` and a
+/// space after it.  A `# bucl-delimiters:` line is not documentation.
+fn leading_comment(source: &str) -> String {
+    let mut lines = Vec::new();
+    for line in source.lines() {
+        let Some(text) = line.trim_start().strip_prefix('#') else {
+            break;
+        };
+        if text.trim_start().starts_with("bucl-delimiters:") {
+            continue;
+        }
+        lines.push(text.strip_prefix(' ').unwrap_or(text).trim_end());
+    }
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Terminal listing: one line per function with its summary.
+fn overview(entries: &[Entry]) -> String {
+    let width = entries.iter().map(|e| e.name.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for (builtin, heading) in [(true, "Built-in functions:"), (false, "BUCL functions:")] {
+        out.push_str(heading);
+        out.push('\n');
+        for entry in entries.iter().filter(|e| e.builtin == builtin) {
+            let summary = entry.summary();
+            let line = format!("  {:width$}  {}", entry.name, summary, width = width);
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    out.push_str("Run 'bucl doc <name>' for the details of one function.\n");
+    out
+}
+
+/// Terminal help for one function.
+fn help(entry: &Entry) -> String {
+    let doc = if entry.doc.is_empty() { "(undocumented)" } else { &entry.doc };
+    format!("{} ({})\n\n{}\n", entry.name, entry.origin, doc)
+}
+
+/// A Markdown section for one function.  Comment text keeps its layout in a
+/// code block, since it is written for a terminal.
+fn markdown_entry(entry: &Entry) -> String {
+    let mut out = format!("### `{}`\n\n", entry.name);
+    if entry.builtin {
+        if !entry.doc.is_empty() {
+            out.push_str(&format!("{}\n\n", entry.doc));
+        }
+    } else {
+        out.push_str(&format!("*{}*\n\n", entry.origin));
+        if !entry.doc.is_empty() {
+            out.push_str(&format!("```text\n{}\n```\n\n", entry.doc));
+        }
+    }
+    out
+}
+
+/// A Markdown page documenting every function.
+fn markdown_page(entries: &[Entry]) -> String {
+    let mut out = String::from("# BUCL functions\n\n");
+    for (builtin, heading) in [(true, "Built-in functions"), (false, "BUCL functions")] {
+        out.push_str(&format!("## {}\n\n", heading));
+        for entry in entries.iter().filter(|e| e.builtin == builtin) {
+            out.push_str(&markdown_entry(entry));
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_document_builtins_and_bucl_functions() {
+        let mut eval = Evaluator::builder().with_stdlib().build();
+        eval.embed_function(
+            "greet",
+            "# bucl-delimiters: ${ }\n# greet — say hello.\n#\n# Usage:\n#   greet \"Ada\"\n\necho \"hi ${0}\"\n",
+        );
+        let entries = entries(&eval);
+
+        let greet = entries.iter().find(|e| e.name == "greet").unwrap();
+        assert_eq!(greet.doc, "greet — say hello.\n\nUsage:\n  greet \"Ada\"");
+        assert_eq!(greet.summary(), "say hello.");
+        assert_eq!(greet.origin, "standard library");
+
+        let undocumented: Vec<&str> = entries
+            .iter()
+            .filter(|e| e.builtin && e.doc.is_empty())
+            .map(|e| e.name.as_str())
+            .collect();
+        assert!(undocumented.is_empty(), "built-ins without a description: {:?}", undocumented);
+        assert!(overview(&entries).contains("\n  echo "));
+    }
+}
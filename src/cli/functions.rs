@@ -0,0 +1,65 @@
+// `bucl functions` — list every function a script in the current directory
+// can call, one per line, with where it comes from: `built-in`, `standard
+// library` or the path of the `.bucl` file a call would load.
+//
+//   $ bucl functions
+//   append         built-in
+//   ...
+//   connect_test   functions/connect_test.bucl
+//   strpos         standard library
+//
+// Scripts get the same names as an array from the `functions` built-in.
+
+use bucl_wasm::evaluator::Evaluator;
+
+use super::doc::origin_label;
+
+/// Entry point for `bucl functions`; `args` are the arguments after
+/// `functions`.
+pub fn main(args: &[String]) -> i32 {
+    if let Some(arg) = args.first() {
+        eprintln!("Unexpected argument for 'bucl functions': {}", arg);
+        return 1;
+    }
+    let eval = Evaluator::builder().with_stdlib().build();
+    print!("{}", listing(&eval));
+    0
+}
+
+/// One `name  origin` line per callable function, names aligned.
+fn listing(eval: &Evaluator) -> String {
+    let functions = eval.callable_functions();
+    let width = functions.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    functions
+        .iter()
+        .map(|(name, origin)| format!("{:width$}  {}\n", name, origin_label(origin), width = width))
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listing_shows_where_each_function_comes_from() {
+        let mut eval = Evaluator::new();
+        bucl_wasm::functions::register_all(&mut eval);
+        eval.embed_function("greet", "echo \"hi\"\n");
+        eval.embed_function("echo", "pass\n");
+        let listing = listing(&eval);
+        let line = |name: &str| {
+            listing
+                .lines()
+                .find(|l| l.split_whitespace().next() == Some(name))
+                .map(|l| l.split_whitespace().skip(1).collect::<Vec<_>>().join(" "))
+        };
+        assert_eq!(line("greet").as_deref(), Some("standard library"));
+        // The built-in shadows the embedded function of the same name.
+        assert_eq!(listing.matches("\necho ").count(), 1);
+        assert_eq!(line("echo").as_deref(), Some("built-in"));
+    }
+}
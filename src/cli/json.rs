@@ -0,0 +1,85 @@
+// `bucl --output json <script>` — print the result of a run as one JSON
+// object instead of the raw output lines:
+//
+//   {"ok":true,"output":["line",…],"error":null,"exit_code":0}
+//
+// `output` holds every line the script produced, `error` the message that
+// would otherwise go to stderr (a runtime error or the failed assertions),
+// and `ok` is true exactly when `exit_code` is 0.  Wrappers get one parse
+// instead of telling output apart from error text.
+
+use std::fmt::Write as _;
+
+/// What a finished run reports.
+pub struct RunResult<'a> {
+    pub output: &'a [String],
+    pub error: Option<String>,
+    pub exit_code: i32,
+}
+
+impl RunResult<'_> {
+    pub fn to_json(&self) -> String {
+        let mut out = format!("{{\"ok\":{},\"output\":[", self.exit_code == 0);
+        for (i, line) in self.output.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            push_string(&mut out, line);
+        }
+        out.push_str("],\"error\":");
+        match &self.error {
+            Some(error) => push_string(&mut out, error),
+            None => out.push_str("null"),
+        }
+        let _ = write!(out, ",\"exit_code\":{}}}", self.exit_code);
+        out
+    }
+}
+
+/// Append `s` as a JSON string literal.
+fn push_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_result_json() {
+        let output = vec!["say \"hi\"".to_string(), "a\tb\u{1}".to_string()];
+        let ok = RunResult {
+            output: &output,
+            error: None,
+            exit_code: 0,
+        };
+        assert_eq!(
+            ok.to_json(),
+            r#"{"ok":true,"output":["say \"hi\"","a\tb\u0001"],"error":null,"exit_code":0}"#
+        );
+
+        let failed = RunResult {
+            output: &[],
+            error: Some("Runtime error: boom\nat line 2".into()),
+            exit_code: 1,
+        };
+        assert_eq!(
+            failed.to_json(),
+            r#"{"ok":false,"output":[],"error":"Runtime error: boom\nat line 2","exit_code":1}"#
+        );
+    }
+}
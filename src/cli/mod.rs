@@ -0,0 +1,12 @@
+// CLI-only subcommands.  These live in the binary crate because they drive
+// the filesystem and process exit codes; the library stays host-agnostic.
+
+pub mod doc; // bucl doc — documentation of built-in and .bucl functions
+pub mod functions; // bucl functions — list every callable function
+pub mod json; // bucl --output json — the run's result as one JSON object
+pub mod report; // bucl --report — local crash report bundles
+pub mod serve; // bucl serve — answer HTTP requests with .bucl scripts
+#[cfg(feature = "signing")]
+pub mod sign; // bucl keygen / sign / --verify — ed25519 script signatures
+pub mod test; // bucl test — run *_test.bucl files
+pub mod watch; // bucl --watch — re-run a script when it changes
@@ -0,0 +1,183 @@
+// `bucl --report [--redact NAME]... <script>` — write a local crash report.
+//
+// When the run fails with an error, or the interpreter itself panics, a
+// `bucl-report-<unix-time>/` directory is written to the current directory:
+//
+//   error.txt         interpreter version, platform, the error, the BUCL call
+//                     stack and (for panics) the Rust backtrace
+//   script.bucl       the script as it was run
+//   functions/*.bucl  every .bucl function the run loaded
+//   variables.txt     the top-level variable store, with sensitive values
+//                     replaced by [redacted]
+//
+// Nothing is sent anywhere; the user decides what to attach to a bug report.
+// A variable is redacted when its name contains (case-insensitively) one of
+// DEFAULT_REDACT or a `--redact` word.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bucl_wasm::error::{BuclError, Result};
+use bucl_wasm::evaluator::Evaluator;
+use bucl_wasm::snapshot;
+
+/// Name fragments whose values are always redacted.
+pub const DEFAULT_REDACT: &[&str] = &["password", "passwd", "secret", "token", "apikey", "api_key"];
+
+/// Options for `--report`, parsed from the command line.
+#[derive(Debug, Default)]
+pub struct ReportOptions {
+    /// Write a report when the run fails.
+    pub enabled: bool,
+    /// Extra variable-name fragments to redact (`--redact NAME`).
+    pub redact: Vec<String>,
+}
+
+/// Run `execute` and, if it fails or panics, write a report bundle.
+///
+/// A panic is turned into a runtime error after the bundle is written, so
+/// the caller reports it like any other failure.
+pub fn capture(
+    eval: &mut Evaluator,
+    script: Option<&Path>,
+    source: &str,
+    opts: &ReportOptions,
+    execute: impl FnOnce(&mut Evaluator) -> Result<()>,
+) -> Result<()> {
+    let panic_info: Arc<Mutex<Option<String>>> = Arc::default();
+    let slot = panic_info.clone();
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        *slot.lock().unwrap() = Some(format!("{}\n\nRust backtrace:\n{}", info, backtrace));
+    }));
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| execute(eval)));
+    panic::set_hook(previous_hook);
+
+    let (error, panic_details) = match outcome {
+        Ok(Ok(())) => return Ok(()),
+        Ok(Err(e @ BuclError::Exit(_))) => return Err(e),
+        Ok(Err(e)) => (e, None),
+        Err(_) => {
+            let details = panic_info.lock().unwrap().take().unwrap_or_default();
+            let summary = details.lines().next().unwrap_or("unknown panic").to_string();
+            (
+                BuclError::RuntimeError(format!("interpreter panicked: {}", summary)),
+                Some(details),
+            )
+        }
+    };
+
+    let dir = report_dir();
+    match write_bundle(&dir, eval, script, source, &error, panic_details.as_deref(), opts) {
+        Ok(()) => eprintln!(
+            "Crash report written to {} — review it before attaching it to a bug report.",
+            dir.display()
+        ),
+        Err(e) => eprintln!("Could not write crash report to {}: {}", dir.display(), e),
+    }
+    Err(error)
+}
+
+fn report_dir() -> PathBuf {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let mut dir = PathBuf::from(format!("bucl-report-{}", secs));
+    let mut n = 1;
+    while dir.exists() {
+        n += 1;
+        dir = PathBuf::from(format!("bucl-report-{}-{}", secs, n));
+    }
+    dir
+}
+
+fn write_bundle(
+    dir: &Path,
+    eval: &Evaluator,
+    script: Option<&Path>,
+    source: &str,
+    error: &BuclError,
+    panic_details: Option<&str>,
+    opts: &ReportOptions,
+) -> std::io::Result<()> {
+    fs::create_dir_all(dir.join("functions"))?;
+    fs::write(dir.join("script.bucl"), source)?;
+    for (name, function_source) in &eval.loaded_functions {
+        fs::write(dir.join("functions").join(format!("{}.bucl", name)), function_source)?;
+    }
+    fs::write(dir.join("variables.txt"), format_variables(eval, &opts.redact))?;
+    fs::write(dir.join("error.txt"), format_error(eval, script, error, panic_details))
+}
+
+fn format_error(
+    eval: &Evaluator,
+    script: Option<&Path>,
+    error: &BuclError,
+    panic_details: Option<&str>,
+) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "bucl {} ({}/{})",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let script_name = script.map_or("<stdin>".to_string(), |p| p.display().to_string());
+    let _ = writeln!(out, "script: {}\n", script_name);
+    let _ = writeln!(out, "{}\n", error);
+    let _ = writeln!(out, "BUCL call stack (innermost first):");
+    for frame in &eval.error_trace {
+        let _ = writeln!(out, "  at {}", frame);
+    }
+    let _ = writeln!(out, "  at {} line {}", script_name, eval.current_line);
+    if let Some(details) = panic_details {
+        let _ = writeln!(out, "\nPanic: {}", details);
+    }
+    out
+}
+
+fn format_variables(eval: &Evaluator, extra_redact: &[String]) -> String {
+    let mut out = String::new();
+    for (name, value) in eval.variables_snapshot() {
+        let value = if is_redacted(&name, extra_redact) {
+            "[redacted]".to_string()
+        } else {
+            snapshot::escape(&value)
+        };
+        let _ = writeln!(out, "{} = {}", name, value);
+    }
+    out
+}
+
+fn is_redacted(name: &str, extra: &[String]) -> bool {
+    let name = name.to_lowercase();
+    DEFAULT_REDACT
+        .iter()
+        .copied()
+        .chain(extra.iter().map(String::as_str))
+        .any(|word| name.contains(&word.to_lowercase()))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redaction_matches_name_fragments() {
+        let extra = vec!["Card".to_string()];
+        assert!(is_redacted("db/password", &extra));
+        assert!(is_redacted("GITHUB_TOKEN", &extra));
+        assert!(is_redacted("creditcard", &extra));
+        assert!(!is_redacted("username", &extra));
+    }
+}
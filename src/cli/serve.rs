@@ -0,0 +1,560 @@
+// `bucl serve [--host <addr>] [--port <n>] [--route <path>=<script>] [dir]`
+// — answer HTTP requests by running .bucl scripts, for webhooks and small
+// automation endpoints.
+//
+// A request for /hooks/deploy runs <dir>/hooks/deploy.bucl, and / runs
+// <dir>/index.bucl; `--route /path=script.bucl` (repeatable) maps a path to
+// any script instead.  The script sees the request as variables:
+//
+//   {request/method}          GET, POST, …
+//   {request/path}            /hooks/deploy
+//   {request/query}           the raw query string, and each parameter
+//   {request/query/<name>}    decoded
+//   {request/headers/<name>}  each header, name in lowercase
+//   {request/body}            the request body
+//
+// and answers through {return}:
+//
+//   {return}                  the response body (default: the lines the
+//                             script echoed)
+//   {return/status}           status code (default 200)
+//   {return/type}             Content-Type (default text/plain)
+//   {return/headers/<name>}   further response headers
+//
+// A script that fails answers 500 with the error message, and a path
+// without a script 404.  Every request runs in a fresh evaluator on its own
+// thread, with the script's directory as its base_dir; while MAX_CONNECTIONS
+// requests are in progress, further ones get 503.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bucl_wasm::error::BuclError;
+use bucl_wasm::evaluator::Evaluator;
+use bucl_wasm::parser;
+
+/// Largest request body accepted; bigger ones get 413.
+const MAX_BODY: usize = 10 * 1024 * 1024;
+/// Largest request line plus headers accepted; bigger ones get 431.
+const MAX_HEADERS: usize = 64 * 1024;
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// Requests handled at once; connections beyond this get 503.
+const MAX_CONNECTIONS: usize = 64;
+
+/// Where to listen and which scripts answer, parsed from the command line.
+#[derive(Debug)]
+pub struct ServeOptions {
+    pub host: String,
+    pub port: u16,
+    /// Directory request paths are looked up in.
+    pub dir: PathBuf,
+    /// Explicit `--route` mappings, by request path.
+    pub routes: HashMap<String, PathBuf>,
+}
+
+impl Default for ServeOptions {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".into(),
+            port: 8080,
+            dir: PathBuf::from("."),
+            routes: HashMap::new(),
+        }
+    }
+}
+
+/// Entry point for `bucl serve`; `args` are the arguments after `serve`.
+pub fn main(args: &[String]) -> i32 {
+    let mut opts = ServeOptions::default();
+    let mut rest = args;
+    loop {
+        match rest {
+            [flag, host, tail @ ..] if flag == "--host" => {
+                opts.host = host.clone();
+                rest = tail;
+            }
+            [flag, port, tail @ ..] if flag == "--port" => {
+                let Ok(port) = port.parse() else {
+                    eprintln!("--port expects a port number, got '{}'", port);
+                    return 1;
+                };
+                opts.port = port;
+                rest = tail;
+            }
+            [flag, route, tail @ ..] if flag == "--route" => {
+                let Some((path, script)) = route.split_once('=') else {
+                    eprintln!("--route expects <path>=<script>, got '{}'", route);
+                    return 1;
+                };
+                opts.routes.insert(path.to_string(), PathBuf::from(script));
+                rest = tail;
+            }
+            [flag, ..] if flag.starts_with("--") => {
+                eprintln!("Unknown option for 'bucl serve': {}", flag);
+                return 1;
+            }
+            [dir, tail @ ..] => {
+                opts.dir = PathBuf::from(dir);
+                rest = tail;
+            }
+            [] => break,
+        }
+    }
+    match serve(opts) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("bucl serve: {}", e);
+            1
+        }
+    }
+}
+
+/// Accept connections until the process is stopped.
+pub fn serve(opts: ServeOptions) -> io::Result<()> {
+    let listener = TcpListener::bind((opts.host.as_str(), opts.port))?;
+    eprintln!(
+        "Serving {} on http://{}",
+        opts.dir.display(),
+        listener.local_addr()?
+    );
+    let opts = Arc::new(opts);
+    let active = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else { continue };
+        let Some(slot) = Slot::take(&active) else {
+            let busy = Response::text(503, "too many requests in progress");
+            let _ = stream.write_all(&busy.to_bytes());
+            continue;
+        };
+        let opts = Arc::clone(&opts);
+        std::thread::spawn(move || {
+            let _slot = slot;
+            if let Err(e) = handle(stream, &opts) {
+                eprintln!("bucl serve: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// One of the MAX_CONNECTIONS handler places, given back when dropped.
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    fn take(active: &Arc<AtomicUsize>) -> Option<Slot> {
+        if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            active.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(Slot(Arc::clone(active)))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Reads from a connection that all have to finish by `deadline`, so a
+/// client trickling bytes cannot hold its handler past READ_TIMEOUT.
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "request took too long"));
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+fn handle(mut stream: TcpStream, opts: &ServeOptions) -> io::Result<()> {
+    let reader = Deadline {
+        stream: &stream,
+        deadline: Instant::now() + READ_TIMEOUT,
+    };
+    let response = match read_request(&mut BufReader::new(reader)) {
+        Ok(request) => {
+            let response = respond(&request, opts);
+            eprintln!("{} {} {}", request.method, request.path, response.status);
+            response
+        }
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => Response::text(400, e.to_string()),
+        Err(e) if e.kind() == io::ErrorKind::FileTooLarge => Response::text(413, e.to_string()),
+        Err(e) if e.kind() == io::ErrorKind::QuotaExceeded => Response::text(431, e.to_string()),
+        Err(e) => return Err(e),
+    };
+    stream.write_all(&response.to_bytes())?;
+    stream.flush()
+}
+
+// ---------------------------------------------------------------------------
+// Requests
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+pub struct Request {
+    pub method: String,
+    /// Path without the query string.
+    pub path: String,
+    pub query: String,
+    /// Header names in lowercase, in the order received.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+fn bad_request(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// Read one line of the request head, counting it against the MAX_HEADERS
+/// bytes left in `budget`.
+fn read_head_line(
+    reader: &mut impl BufRead,
+    line: &mut String,
+    budget: &mut usize,
+) -> io::Result<usize> {
+    let read = reader.take(*budget as u64).read_line(line)?;
+    *budget -= read;
+    if *budget == 0 && !line.ends_with('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::QuotaExceeded,
+            format!("request headers over {} bytes", MAX_HEADERS),
+        ));
+    }
+    Ok(read)
+}
+
+/// Read one HTTP/1.x request: request line, headers and a
+/// `Content-Length` body.
+pub fn read_request(reader: &mut impl BufRead) -> io::Result<Request> {
+    let mut budget = MAX_HEADERS;
+    let mut line = String::new();
+    read_head_line(reader, &mut line, &mut budget)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(bad_request("malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        ..Request::default()
+    };
+
+    loop {
+        line.clear();
+        if read_head_line(reader, &mut line, &mut budget)? == 0 {
+            return Err(bad_request("connection closed inside the headers"));
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(bad_request("malformed header"));
+        };
+        request
+            .headers
+            .push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+    }
+
+    let length = match request.header("content-length") {
+        Some(length) => length
+            .parse::<usize>()
+            .map_err(|_| bad_request("malformed Content-Length"))?,
+        None => 0,
+    };
+    if length > MAX_BODY {
+        return Err(io::Error::new(
+            io::ErrorKind::FileTooLarge,
+            format!("request body over {} bytes", MAX_BODY),
+        ));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    request.body = String::from_utf8_lossy(&body).into_owned();
+    Ok(request)
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The variables a script sees for this request.
+    fn variables(&self) -> Vec<(String, String)> {
+        let mut vars = vec![
+            ("request/method".to_string(), self.method.clone()),
+            ("request/path".to_string(), self.path.clone()),
+            ("request/query".to_string(), self.query.clone()),
+            ("request/body".to_string(), self.body.clone()),
+        ];
+        for pair in self.query.split('&').filter(|p| !p.is_empty()) {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            vars.push((format!("request/query/{}", url_decode(name)), url_decode(value)));
+        }
+        for (name, value) in &self.headers {
+            vars.push((format!("request/headers/{}", name), value.clone()));
+        }
+        vars
+    }
+}
+
+/// Decode `%XX` escapes and `+` as in a form-encoded query string.
+fn url_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |b: u8| (b as char).to_digit(16);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        out.push((high * 16 + low) as u8);
+                        i += 2;
+                    }
+                    _ => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// ---------------------------------------------------------------------------
+// Responses
+// ---------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    fn text(status: u16, body: impl Into<String>) -> Self {
+        let mut body = body.into();
+        if !body.ends_with('\n') {
+            body.push('\n');
+        }
+        Self {
+            status,
+            headers: vec![("Content-Type".into(), "text/plain; charset=utf-8".into())],
+            body,
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = format!("HTTP/1.1 {} {}\r\n", self.status, reason(self.status));
+        for (name, value) in &self.headers {
+            out.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        out.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n",
+            self.body.len()
+        ));
+        out.push_str(&self.body);
+        out.into_bytes()
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        422 => "Unprocessable Entity",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
+
+/// The script answering `path`: a `--route`, or the matching file below
+/// the served directory.  Paths with `..` or hidden segments never match.
+fn script_for(opts: &ServeOptions, path: &str) -> Option<PathBuf> {
+    if let Some(script) = opts.routes.get(path) {
+        return Some(script.clone());
+    }
+    let relative = path.trim_matches('/');
+    let relative = if relative.is_empty() { "index" } else { relative };
+    if relative.split('/').any(|seg| seg.is_empty() || seg.starts_with('.')) {
+        return None;
+    }
+    let script = opts.dir.join(format!("{}.bucl", relative));
+    script.is_file().then_some(script)
+}
+
+/// Run the script for `request` and turn its `{return}` into a response.
+pub fn respond(request: &Request, opts: &ServeOptions) -> Response {
+    let Some(script) = script_for(opts, &request.path) else {
+        return Response::text(404, format!("no script for {}", request.path));
+    };
+    let source = match fs::read_to_string(&script) {
+        Ok(source) => source,
+        Err(e) => return Response::text(500, format!("{}: {}", script.display(), e)),
+    };
+
+    let mut builder = Evaluator::builder().output_sink(Arc::new(|_: &str| {}));
+    if let Some(dir) = script.parent().and_then(|d| d.canonicalize().ok()) {
+        builder = builder.base_dir(dir);
+    }
+    for (name, value) in request.variables() {
+        builder = builder.var(name, value);
+    }
+    let mut eval = builder.build();
+    let result = parser::parse(&source).and_then(|stmts| eval.evaluate_statements(&stmts));
+    eval.flush_output();
+    match result {
+        Ok(()) | Err(BuclError::Exit(0)) => {}
+        Err(e) => return Response::text(500, e.to_string()),
+    }
+    script_response(&eval)
+}
+
+fn script_response(eval: &Evaluator) -> Response {
+    let status = match eval.resolve_var("return/status") {
+        s if s.is_empty() => 200,
+        s => match s.trim().parse() {
+            Ok(status) if (100..600).contains(&status) => status,
+            _ => return Response::text(500, format!("{{return/status}} is not a status code: '{}'", s)),
+        },
+    };
+    let body = if eval.var_exists("return") {
+        eval.resolve_var("return")
+    } else {
+        let mut out = eval.output_buffer.join("\n");
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out
+    };
+    let content_type = match eval.resolve_var("return/type") {
+        t if t.is_empty() => "text/plain; charset=utf-8".to_string(),
+        t => t,
+    };
+    let mut headers = vec![("Content-Type".to_string(), content_type)];
+    for (name, value) in eval.variables_snapshot() {
+        if let Some(header) = name.strip_prefix("return/headers/") {
+            headers.push((header.to_string(), value));
+        }
+    }
+    // A line break would let request data echoed into a header start a
+    // header (or a whole response) of its own.
+    for (name, value) in &headers {
+        let bad_name = name.is_empty()
+            || !name.bytes().all(|b| b.is_ascii_graphic() && b != b':');
+        if bad_name || value.contains(['\r', '\n']) {
+            let name = name.escape_debug();
+            return Response::text(500, format!("response header '{}' is not valid", name));
+        }
+    }
+    Response { status, headers, body }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_runs_script() {
+        let dir = std::env::temp_dir().join(format!("bucl-serve-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("hooks")).unwrap();
+        fs::write(
+            dir.join("hooks").join("greet.bucl"),
+            concat!(
+                "{return} = \"{request/method} hello {request/query/name}, {request/headers/x-token}: {request/body}\"\n",
+                "{return/status} = \"201\"\n",
+                "{return/headers/X-Handled} = \"yes\"\n",
+            ),
+        )
+        .unwrap();
+        fs::write(dir.join("index.bucl"), "echo \"home\"\n").unwrap();
+        fs::write(dir.join("broken.bucl"), "error \"boom\"\n").unwrap();
+        let opts = ServeOptions {
+            dir: dir.clone(),
+            ..ServeOptions::default()
+        };
+
+        let raw = "POST /hooks/greet?name=Ada%20L&x=1 HTTP/1.1\r\nHost: x\r\nX-Token: t1\r\nContent-Length: 4\r\n\r\nping";
+        let request = read_request(&mut raw.as_bytes()).unwrap();
+        let response = respond(&request, &opts);
+        assert_eq!(response.status, 201);
+        assert_eq!(response.body, "POST hello Ada L, t1: ping");
+        assert!(response.headers.contains(&("X-Handled".into(), "yes".into())));
+
+        let get = |path: &str| {
+            let raw = format!("GET {} HTTP/1.1\r\n\r\n", path);
+            respond(&read_request(&mut raw.as_bytes()).unwrap(), &opts)
+        };
+        let home = get("/");
+        assert_eq!((home.status, home.body.as_str()), (200, "home\n"));
+        assert_eq!(get("/missing").status, 404);
+        assert_eq!(get("/../index").status, 404);
+        let broken = get("/broken");
+        assert_eq!(broken.status, 500);
+        assert!(broken.body.contains("boom"), "{}", broken.body);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_oversized_head_and_header_injection_are_refused() {
+        let raw = format!("GET / HTTP/1.1\r\nX-Pad: {}\r\n\r\n", "a".repeat(MAX_HEADERS));
+        let err = read_request(&mut raw.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::QuotaExceeded);
+
+        let dir = std::env::temp_dir().join(format!("bucl-serve-inject-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("index.bucl"), "{return/headers/X-Echo} = \"{request/query/v}\"\n")
+            .unwrap();
+        let opts = ServeOptions {
+            dir: dir.clone(),
+            ..ServeOptions::default()
+        };
+        let raw = "GET /?v=a%0D%0ASet-Cookie:%20x HTTP/1.1\r\n\r\n";
+        let response = respond(&read_request(&mut raw.as_bytes()).unwrap(), &opts);
+        assert_eq!(response.status, 500);
+        assert!(!String::from_utf8(response.to_bytes()).unwrap().contains("Set-Cookie"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,153 @@
+// `bucl keygen`, `bucl sign` and `bucl --verify` (feature `signing`).
+//
+//   bucl keygen release            writes release.key (secret) + release.pub
+//   bucl sign --key release.key script.bucl
+//   bucl sign --key release.key --detached script.bucl   -> script.bucl.sig
+//   bucl --verify release.pub script.bucl
+//
+// `--verify` may be given several times; the script must be signed by one of
+// the keys.  `functions/*.bucl` files it calls are held to the same keys,
+// each with an embedded signature or a `.sig` file beside it, so a signed
+// script can't be changed through the functions it loads.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use bucl_wasm::error::BuclError;
+use bucl_wasm::evaluator::FunctionCheck;
+use bucl_wasm::signing::{self, TrustPolicy};
+
+/// Entry point for `bucl keygen <name>`.
+pub fn keygen(args: &[String]) -> i32 {
+    let [name] = args else {
+        eprintln!("Usage: bucl keygen <name>");
+        return 1;
+    };
+    let secret_path = PathBuf::from(format!("{}.key", name));
+    let public_path = PathBuf::from(format!("{}.pub", name));
+    if secret_path.exists() {
+        eprintln!("Refusing to overwrite existing key '{}'", secret_path.display());
+        return 1;
+    }
+
+    let key = signing::generate_key();
+    let written = write_secret(&secret_path, &signing::format_signing_key(&key)).and_then(|_| {
+        fs::write(&public_path, signing::format_verifying_key(&key.verifying_key()) + "\n")
+    });
+    if let Err(e) = written {
+        eprintln!("Error writing key files: {}", e);
+        return 1;
+    }
+    println!("Wrote {} (keep secret) and {}", secret_path.display(), public_path.display());
+    0
+}
+
+/// Entry point for `bucl sign --key <file> [--detached] <script>`.
+pub fn sign(args: &[String]) -> i32 {
+    let mut key_path = None;
+    let mut detached = false;
+    let mut script = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--key" => key_path = iter.next(),
+            "--detached" => detached = true,
+            flag if flag.starts_with("--") => {
+                eprintln!("Unknown option for 'bucl sign': {}", flag);
+                return 1;
+            }
+            _ => script = Some(arg),
+        }
+    }
+    let (Some(key_path), Some(script)) = (key_path, script) else {
+        eprintln!("Usage: bucl sign --key <file.key> [--detached] <script>");
+        return 1;
+    };
+
+    let result = read(Path::new(key_path))
+        .and_then(|text| signing::parse_signing_key(&text).map_err(|e| e.to_string()))
+        .and_then(|key| {
+            let source = read(Path::new(script))?;
+            if detached {
+                let path = detached_path(Path::new(script));
+                let (body, _) = signing::split_signature(&source);
+                fs::write(&path, signing::sign(body, &key) + "\n")
+                    .map_err(|e| format!("Error writing '{}': {}", path.display(), e))
+            } else {
+                fs::write(script, signing::sign_embedded(&source, &key))
+                    .map_err(|e| format!("Error writing '{}': {}", script, e))
+            }
+        });
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+/// Load the public keys named by `--verify` flags.
+pub fn load_policy(key_paths: &[String]) -> Result<TrustPolicy, String> {
+    let mut keys = Vec::new();
+    for path in key_paths {
+        let text = read(Path::new(path))?;
+        keys.push(
+            signing::parse_verifying_key(&text).map_err(|e| format!("'{}': {}", path, e))?,
+        );
+    }
+    Ok(TrustPolicy::new(keys))
+}
+
+/// Verify `source` (read from `script`, or stdin when `None`) and return the
+/// signed body.  A detached `<script>.sig` is used when the script carries no
+/// embedded signature.
+pub fn verify(policy: &TrustPolicy, script: Option<&Path>, source: &str) -> Result<String, String> {
+    let detached = script
+        .map(detached_path)
+        .and_then(|path| fs::read_to_string(path).ok());
+    let name = script.map_or("<stdin>".to_string(), |p| p.display().to_string());
+    policy
+        .verify(source, detached.as_deref())
+        .map(str::to_string)
+        .map_err(|e| format!("Refusing to run '{}': {}", name, e))
+}
+
+/// An [`Evaluator::function_check`](bucl_wasm::evaluator::Evaluator::function_check)
+/// that runs `.bucl` function files only when `policy` verifies them.
+pub fn function_check(policy: TrustPolicy) -> FunctionCheck {
+    Arc::new(move |path, source| {
+        verify(&policy, Some(path), &source).map_err(BuclError::RuntimeError)
+    })
+}
+
+fn detached_path(script: &Path) -> PathBuf {
+    let mut path = script.as_os_str().to_owned();
+    path.push(".sig");
+    PathBuf::from(path)
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Error reading '{}': {}", path.display(), e))
+}
+
+/// Write a secret key readable only by the owner where the OS supports it.
+fn write_secret(path: &Path, hex: &str) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(path)?;
+        writeln!(file, "{}", hex)
+    }
+    #[cfg(not(unix))]
+    {
+        fs::write(path, format!("{}\n", hex))
+    }
+}
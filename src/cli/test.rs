@@ -0,0 +1,255 @@
+// `bucl test [--snapshot] [--update] [dir]` — run every `*_test.bucl` file
+// below `dir`.
+//
+// Each file runs in a fresh evaluator whose base_dir is the file's own
+// directory, so `functions/` is resolved exactly as when the file is run
+// directly.  A file passes when it finishes without a runtime error and
+// without any failed `assert`.
+//
+// With `--snapshot` the file's output is also compared against a golden
+// `<name>.expected` file next to it; `--update` (re)writes those files from
+// the current output instead of comparing.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use bucl_wasm::error::{BuclError, Result};
+use bucl_wasm::evaluator::Evaluator;
+use bucl_wasm::parser;
+
+/// Options for a test run, parsed from the `bucl test` command line.
+#[derive(Debug, Default)]
+pub struct TestOptions {
+    /// Compare each file's output against its `.expected` snapshot.
+    pub snapshot: bool,
+    /// Write the current output to the `.expected` files instead of comparing.
+    pub update: bool,
+}
+
+/// Entry point for `bucl test`; `args` are the arguments after `test`.
+pub fn main(args: &[String]) -> i32 {
+    let mut opts = TestOptions::default();
+    let mut dir = PathBuf::from(".");
+    for arg in args {
+        match arg.as_str() {
+            "--snapshot" => opts.snapshot = true,
+            "--update" => {
+                opts.snapshot = true;
+                opts.update = true;
+            }
+            flag if flag.starts_with("--") => {
+                eprintln!("Unknown option for 'bucl test': {}", flag);
+                return 1;
+            }
+            _ => dir = PathBuf::from(arg),
+        }
+    }
+    run(&dir, &opts)
+}
+
+/// Run the test suite and return the process exit code (0 = all passed).
+pub fn run(dir: &Path, opts: &TestOptions) -> i32 {
+    let mut files = Vec::new();
+    if let Err(e) = collect_test_files(dir, &mut files) {
+        eprintln!("Error reading '{}': {}", dir.display(), e);
+        return 1;
+    }
+    files.sort();
+
+    if files.is_empty() {
+        eprintln!("No *_test.bucl files found in '{}'", dir.display());
+        return 1;
+    }
+
+    let mut files_failed = 0;
+    let mut asserts_passed = 0;
+    let mut asserts_failed = 0;
+
+    for path in &files {
+        // Output is collected in output_buffer for --snapshot, not printed
+        // between the PASS / FAIL lines.
+        let mut builder = Evaluator::builder().output_sink(Arc::new(|_: &str| {}));
+        if let Some(dir) = path.parent().and_then(|d| d.canonicalize().ok()) {
+            builder = builder.base_dir(dir);
+        }
+        let mut eval = builder.build();
+
+        let result = run_file(&mut eval, path);
+        asserts_passed += eval.asserts_passed;
+        asserts_failed += eval.assert_failures.len();
+
+        let snapshot = if opts.snapshot && result.is_ok() {
+            check_snapshot(path, &eval.output_buffer, opts.update)
+        } else {
+            Ok(())
+        };
+
+        if result.is_ok() && eval.assert_failures.is_empty() && snapshot.is_ok() {
+            println!("PASS {} ({} assertions)", path.display(), eval.asserts_passed);
+            continue;
+        }
+
+        files_failed += 1;
+        println!("FAIL {}", path.display());
+        for failure in &eval.assert_failures {
+            println!("    {}", failure);
+        }
+        if let Err(e) = result {
+            println!("    {}", e);
+        }
+        if let Err(msg) = snapshot {
+            println!("    {}", msg);
+        }
+    }
+
+    println!();
+    println!(
+        "{} files: {} passed, {} failed; {} assertions: {} passed, {} failed",
+        files.len(),
+        files.len() - files_failed,
+        files_failed,
+        asserts_passed + asserts_failed,
+        asserts_passed,
+        asserts_failed,
+    );
+
+    if files_failed == 0 { 0 } else { 1 }
+}
+
+fn run_file(eval: &mut Evaluator, path: &Path) -> Result<()> {
+    let source = fs::read_to_string(path)?;
+    let stmts = parser::parse(&source)?;
+    let result = eval.evaluate_statements(&stmts);
+    eval.flush_output();
+    match result {
+        Err(BuclError::Exit(0)) => Ok(()),
+        result => result,
+    }
+}
+
+/// Compare `output` against the `.expected` file belonging to `test_path`,
+/// or overwrite that file when `update` is set.
+fn check_snapshot(
+    test_path: &Path,
+    output: &[String],
+    update: bool,
+) -> std::result::Result<(), String> {
+    let expected_path = test_path.with_extension("expected");
+    let mut actual = output.join("\n");
+    if !actual.is_empty() {
+        actual.push('\n');
+    }
+
+    if update {
+        return fs::write(&expected_path, &actual)
+            .map_err(|e| format!("cannot write '{}': {}", expected_path.display(), e));
+    }
+
+    let expected = fs::read_to_string(&expected_path).map_err(|_| {
+        format!(
+            "missing snapshot '{}' (run with --update to create it)",
+            expected_path.display()
+        )
+    })?;
+    if expected == actual {
+        return Ok(());
+    }
+
+    // Report the first differing line; that's usually enough to spot the change.
+    let mut exp_lines = expected.lines();
+    let mut act_lines = actual.lines();
+    let mut lineno = 1;
+    loop {
+        match (exp_lines.next(), act_lines.next()) {
+            (Some(e), Some(a)) if e == a => lineno += 1,
+            (e, a) => {
+                return Err(format!(
+                    "output differs from '{}' at line {}:\n      expected: {}\n      actual:   {}",
+                    expected_path.display(),
+                    lineno,
+                    e.map_or("<end of output>".to_string(), |l| format!("{:?}", l)),
+                    a.map_or("<end of output>".to_string(), |l| format!("{:?}", l)),
+                ));
+            }
+        }
+    }
+}
+
+/// Recursively gather `*_test.bucl` files below `dir`.  `functions/`
+/// directories hold library code (`connect_test.bucl` is a function, not a
+/// test), so they are skipped, as are `target/` and hidden directories.
+fn collect_test_files(dir: &Path, out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let skipped = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n == "functions" || n == "target" || n.starts_with('.'));
+            if !skipped {
+                collect_test_files(&path, out)?;
+            }
+        } else if path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.ends_with("_test.bucl"))
+        {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_skips_functions_target_and_hidden_dirs() {
+        let dir = std::env::temp_dir().join(format!("bucl-test-collect-{}", std::process::id()));
+        for sub in ["unit", "functions", "target/debug", ".git", "unit/.cache"] {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+            fs::write(dir.join(sub).join("a_test.bucl"), "").unwrap();
+        }
+        fs::write(dir.join("top_test.bucl"), "").unwrap();
+        fs::write(dir.join("unit").join("helper.bucl"), "").unwrap();
+
+        let mut files = Vec::new();
+        collect_test_files(&dir, &mut files).unwrap();
+        files.sort();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(files, [dir.join("top_test.bucl"), dir.join("unit").join("a_test.bucl")]);
+    }
+
+    #[test]
+    fn test_snapshots_update_match_and_mismatch() {
+        let dir = std::env::temp_dir().join(format!("bucl-test-snapshot-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let test = dir.join("out_test.bucl");
+        let expected = dir.join("out_test.expected");
+        fs::write(&test, "echo \"one\"\necho \"two\"\n").unwrap();
+        let snapshot = TestOptions { snapshot: true, update: false };
+        let update = TestOptions { snapshot: true, update: true };
+
+        let missing = check_snapshot(&test, &["one".into()], false).unwrap_err();
+        assert!(missing.contains("missing snapshot"), "{}", missing);
+        assert_eq!(run(&dir, &snapshot), 1);
+
+        assert_eq!(run(&dir, &update), 0);
+        assert_eq!(fs::read_to_string(&expected).unwrap(), "one\ntwo\n");
+        assert_eq!(run(&dir, &snapshot), 0);
+
+        fs::write(&test, "echo \"one\"\necho \"three\"\n").unwrap();
+        assert_eq!(run(&dir, &snapshot), 1);
+        let mismatch = check_snapshot(&test, &["one".into(), "three".into()], false).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(mismatch.contains("at line 2"), "{}", mismatch);
+        assert!(mismatch.contains("expected: \"two\""), "{}", mismatch);
+        assert!(mismatch.contains("actual:   \"three\""), "{}", mismatch);
+    }
+}
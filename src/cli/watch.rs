@@ -0,0 +1,76 @@
+// `bucl --watch <script>` — run the script, then run it again whenever it or
+// a file in its `functions/` directory changes, until interrupted.
+//
+// Every run gets a fresh evaluator from the caller, so no variables, loaded
+// functions or seeded random state carry over.  Changes are found by polling
+// modification times, which needs no platform-specific watcher.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use bucl_wasm::error::BuclError;
+use bucl_wasm::evaluator::Evaluator;
+use bucl_wasm::parser;
+
+/// How often the watched files are checked.
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Run `script` with an evaluator from `new_evaluator`, re-running it on
+/// every change.  Never returns.
+pub fn run(script: &Path, new_evaluator: impl Fn() -> Evaluator) -> ! {
+    let functions_dir = script
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.parent().map(|d| d.join("functions")));
+    loop {
+        let stamps = watched_files(script, functions_dir.as_deref());
+        run_once(script, new_evaluator());
+        eprintln!("-- watching {} for changes (Ctrl-C to stop)", script.display());
+        while watched_files(script, functions_dir.as_deref()) == stamps {
+            thread::sleep(POLL_INTERVAL);
+        }
+        eprintln!("-- change detected, re-running");
+    }
+}
+
+/// Execute the script once, reporting errors and failed assertions on
+/// stderr the way a normal run does.
+fn run_once(script: &Path, mut eval: Evaluator) {
+    let source = match fs::read_to_string(script) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("Error reading '{}': {}", script.display(), e);
+            return;
+        }
+    };
+    let result = parser::parse_with(&source, &eval.delimiters)
+        .and_then(|stmts| eval.evaluate_statements(&stmts));
+    eval.flush_output();
+    match result {
+        Ok(()) | Err(BuclError::Exit(0)) => {}
+        Err(BuclError::Exit(code)) => eprintln!("-- exited with code {}", code),
+        Err(e) => eprintln!("{}", e),
+    }
+    for failure in &eval.assert_failures {
+        eprintln!("{}", failure);
+    }
+}
+
+/// The script and every file in `functions_dir`, with their modification
+/// times.  Comparing two of these detects edits, new files and deletions.
+fn watched_files(script: &Path, functions_dir: Option<&Path>) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mut paths = vec![script.to_path_buf()];
+    if let Some(entries) = functions_dir.and_then(|dir| fs::read_dir(dir).ok()) {
+        paths.extend(entries.filter_map(|e| e.ok()).map(|e| e.path()));
+    }
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect()
+}
@@ -6,6 +6,10 @@ pub enum BuclError {
     RuntimeError(String),
     UnknownFunction(String),
     IoError(std::io::Error),
+    /// The host asked the run to stop (see `Evaluator::cancel_check`).
+    Cancelled,
+    /// The script called `exit` with this code.
+    Exit(i32),
 }
 
 impl fmt::Display for BuclError {
@@ -15,12 +19,60 @@ impl fmt::Display for BuclError {
             Self::RuntimeError(msg) => write!(f, "Runtime error: {}", msg),
             Self::UnknownFunction(name) => write!(f, "Unknown function: '{}'", name),
             Self::IoError(e) => write!(f, "IO error: {}", e),
+            Self::Cancelled => write!(f, "Execution cancelled"),
+            Self::Exit(code) => write!(f, "Exited with code {}", code),
         }
     }
 }
 
 impl std::error::Error for BuclError {}
 
+impl BuclError {
+    /// A parse error at 1-based `lineno` and `column` (in characters) of
+    /// `source`, quoting the line with a caret under the column:
+    ///
+    /// ```text
+    /// Parse error: line 3: unexpected indentation: expected 4 spaces/tabs, got 6
+    ///   3 |       echo b
+    ///     |       ^
+    /// ```
+    pub(crate) fn parse_at(source: &str, lineno: usize, column: usize, msg: &str) -> Self {
+        let Some(text) = source.lines().nth(lineno.wrapping_sub(1)) else {
+            return BuclError::ParseError(format!("line {}: {}", lineno, msg));
+        };
+        let text = text.trim_end_matches('\r');
+        // Keep tabs under tabs so the caret lines up however they render.
+        let pad: String = text
+            .chars()
+            .take(column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let width = lineno.to_string().len() + 1;
+        BuclError::ParseError(format!(
+            "line {}: {}\n{:>w$} | {}\n{:>w$} | {}^",
+            lineno,
+            msg,
+            lineno,
+            text,
+            "",
+            pad,
+            w = width
+        ))
+    }
+
+    /// One parse error listing every error in `errors`, in order.
+    pub(crate) fn parse_errors(errors: Vec<BuclError>) -> Self {
+        let messages: Vec<String> = errors
+            .into_iter()
+            .map(|e| match e {
+                BuclError::ParseError(msg) => msg,
+                other => other.to_string(),
+            })
+            .collect();
+        BuclError::ParseError(messages.join("\n"))
+    }
+}
+
 impl From<std::io::Error> for BuclError {
     fn from(e: std::io::Error) -> Self {
         BuclError::IoError(e)
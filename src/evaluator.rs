@@ -1,10 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
-use crate::ast::{Param, ResolvedArg, Statement};
+use crate::ast::{Param, ResolvedArg, Statement, TemplatePart};
 use crate::error::{BuclError, Result};
+use crate::fs::FileSystem;
+use crate::functions::log::LogLevel;
+#[cfg(feature = "random")]
+use crate::functions::random::SeededRng;
 use crate::functions::BuclFunction;
+use crate::parser::Delimiters;
+use crate::permissions::Permissions;
+use crate::snapshot::StateSnapshot;
 
 // ---------------------------------------------------------------------------
 // Helpers (free functions)
@@ -35,6 +43,15 @@ fn extract_param_name(var_name: &str) -> Option<String> {
     Some(base.to_string())
 }
 
+/// How deep a variable name's references may nest, and how many times a
+/// name may be re-interpolated because the name its references produced has
+/// references of its own (`{{a}}` with `{a}` = `"{b}"`), before the run fails.
+const MAX_NAME_DEPTH: usize = 64;
+
+/// Longest name that is re-interpolated; a value with several references
+/// to a growing chain of names would otherwise double in size every pass.
+const MAX_NAME_LEN: usize = 4096;
+
 /// Check for duplicate named parameters and return an error if found.
 fn check_duplicate_names(resolved: &[ResolvedArg]) -> Result<()> {
     let mut seen: HashMap<&str, usize> = HashMap::new();
@@ -51,49 +68,779 @@ fn check_duplicate_names(resolved: &[ResolvedArg]) -> Result<()> {
     Ok(())
 }
 
+/// The caller's side of a `.bucl` function call that owns a block: the
+/// block, the caller's target variable, and the caller's variable store,
+/// moved out of the caller for the duration of the call.
+pub(crate) struct CallerScope {
+    block: Vec<Statement>,
+    target: Option<String>,
+    variables: HashMap<String, String>,
+    readonly: HashSet<String>,
+}
+
+/// A `local` declaration: the variable and the entries (its own and its
+/// sub-variables') that it shadowed.
+pub(crate) struct LocalVar {
+    name: String,
+    shadowed: Vec<(String, String)>,
+}
+
+/// A parsed `.bucl` function, reused while its source and the delimiters it
+/// was parsed with stay the same (see [`Evaluator::parse_function`]).
+struct ParsedFunction {
+    source: String,
+    delimiters: Delimiters,
+    stmts: Arc<Vec<Statement>>,
+}
+
+/// What a slice reference (`{items/1..3}`) selects.
+enum Slice {
+    /// Items of an array variable.
+    Items(Vec<String>),
+    /// Characters of a plain value.
+    Chars(String),
+}
+
+// ---------------------------------------------------------------------------
+// Assertions
+// ---------------------------------------------------------------------------
+
+/// A failed `assert` recorded during evaluation.
+#[derive(Debug, Clone)]
+pub struct AssertFailure {
+    /// Source line of the `assert` statement (1-based).
+    pub line: usize,
+    /// The `.bucl` function the assertion ran in, or `None` for the script itself.
+    pub function: Option<String>,
+    /// The user-supplied message, or a description of the failed condition.
+    pub message: String,
+}
+
+impl std::fmt::Display for AssertFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.function {
+            Some(func) => write!(f, "{}.bucl line {}: {}", func, self.line, self.message),
+            None => write!(f, "line {}: {}", self.line, self.message),
+        }
+    }
+}
+
+/// A `.bucl` function call a failed run was inside (see
+/// [`Evaluator::error_trace`]).
+#[derive(Debug, Clone)]
+pub struct StackFrame {
+    /// Name of the `.bucl` function.
+    pub function: String,
+    /// Line within that function where execution stopped (1-based).
+    pub line: usize,
+}
+
+impl std::fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.bucl line {}", self.function, self.line)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Evaluator
 // ---------------------------------------------------------------------------
 
+/// Callback receiving each output line (see [`Evaluator::output_sink`]).
+pub type OutputSink = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Callback receiving each `log` message that passes
+/// [`Evaluator::log_level`] (see [`Evaluator::log_sink`]).
+pub type LogSink = Arc<dyn Fn(LogLevel, &str) + Send + Sync>;
+
+/// Callback polled before every statement (see [`Evaluator::cancel_check`]).
+pub type CancelCheck = Arc<dyn Fn() -> bool + Send + Sync>;
+
+/// Callback given each `.bucl` function file read from disk (see
+/// [`Evaluator::function_check`]).
+pub type FunctionCheck = Arc<dyn Fn(&Path, String) -> Result<String> + Send + Sync>;
+
+/// Supplies values for variables that are not set (see
+/// [`Evaluator::add_resolver`]), e.g. secrets from a vault or `{env/HOME}`
+/// from the environment, computed when a script reads them.
+///
+/// Any `Fn(&str) -> Option<String>` closure is a resolver.
+pub trait VarResolver: Send + Sync {
+    /// The value of variable `name` (sub-variables included, as in
+    /// `secret/api_key`), or `None` to leave it unset.
+    fn resolve(&self, name: &str) -> Option<String>;
+}
+
+impl<F: Fn(&str) -> Option<String> + Send + Sync> VarResolver for F {
+    fn resolve(&self, name: &str) -> Option<String> {
+        self(name)
+    }
+}
+
+/// When a [`Hook`] is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPoint {
+    /// The statement is about to run.  An error from the hook stops the run
+    /// before it does.
+    Before,
+    /// The statement (and its block, if any) finished.  An error from the
+    /// hook fails the run.
+    After,
+    /// The statement failed (or called `exit`); its error is reported and
+    /// errors from the hook are ignored.
+    Failed,
+}
+
+/// Where a callable function comes from (see
+/// [`Evaluator::callable_functions`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FunctionOrigin {
+    /// A Rust built-in.
+    Builtin,
+    /// A `.bucl` function embedded in the evaluator (e.g. the standard
+    /// library).
+    Embedded,
+    /// A `.bucl` file on disk.
+    File(PathBuf),
+}
+
+/// Callback run around every statement (see [`Evaluator::add_hook`]).  It
+/// can read variables through the evaluator it is given.
+pub type Hook = Arc<dyn Fn(HookPoint, &Statement, &Evaluator) -> Result<()> + Send + Sync>;
+
+/// Receives execution events (see [`Evaluator::observer`]).
+///
+/// Every method has an empty default, so an observer only implements the
+/// events it cares about.  Events nest: each `enter_*` is matched by the
+/// corresponding `exit_*`, even when the call or block fails.
+pub trait Observer: Send + Sync {
+    /// A statement is about to run.
+    fn statement(&self, _stmt: &Statement) {}
+    /// A `.bucl` function is about to run.
+    fn enter_function(&self, _name: &str) {}
+    /// The `.bucl` function entered last has finished.
+    fn exit_function(&self, _name: &str) {}
+    /// A statement that owns a block (`if`, `repeat`, `each`, …) is about to
+    /// run.  Statements its block executes are reported in between.
+    fn enter_block(&self, _stmt: &Statement) {}
+    /// The block statement entered last has finished.
+    fn exit_block(&self, _stmt: &Statement) {}
+}
+
 /// The runtime environment: variable store + function registry.
 pub struct Evaluator {
     pub(crate) variables: HashMap<String, String>,
-    functions: HashMap<String, Arc<dyn BuclFunction>>,
+    /// Built-ins by name.  Shared with child evaluators; copied on the first
+    /// [`register`](Evaluator::register) after sharing.
+    functions: Arc<HashMap<String, Arc<dyn BuclFunction>>>,
     /// Directory to resolve `functions/<name>.bucl` lookups against.
     /// Typically the directory containing the script being run.
     pub base_dir: Option<PathBuf>,
     /// Captured output lines.  Every call to `echo` appends here.
-    /// On native targets the line is also printed to stdout immediately.
+    /// On native targets the line is also printed to stdout immediately
+    /// unless an [`output_sink`](Evaluator::output_sink) is installed.
     pub output_buffer: Vec<String>,
+    /// Optional callback invoked with every output line as it is produced.
+    ///
+    /// Replaces the native stdout print; used by `bucl_run_streaming` to
+    /// forward lines to the JS host.  Shared with child evaluators so output
+    /// from `.bucl` functions streams too.
+    pub output_sink: Option<OutputSink>,
+    /// Least severe `log` level that is reported; lower ones are dropped.
+    /// Defaults to [`LogLevel::Info`].  Copied to child evaluators.
+    pub log_level: LogLevel,
+    /// Optional callback receiving `log` messages.  Replaces the native
+    /// stderr print; without one, WASM builds drop log messages.  Shared
+    /// with child evaluators.
+    pub log_sink: Option<LogSink>,
+    /// Optional callback polled before every statement; when it returns
+    /// `true` the run stops with [`BuclError::Cancelled`].
+    ///
+    /// Lets a host abort a runaway script cooperatively (the WASM build
+    /// wires it to the imported `js_should_cancel`).  Shared with child
+    /// evaluators so long-running `.bucl` functions can be cancelled too.
+    pub cancel_check: Option<CancelCheck>,
+    /// Optional callback given the path and source of every `.bucl` function
+    /// file read from disk, before it is parsed.  It returns the source to
+    /// run, or an error that fails the call; `bucl --verify` uses it to
+    /// check the files' signatures.  Embedded functions don't pass through
+    /// it.  Shared with child evaluators.
+    pub function_check: Option<FunctionCheck>,
+    /// Optional [`Observer`] told about every statement, `.bucl` function
+    /// call and block as it runs.  Shared with child evaluators.  Used by
+    /// `bucl --emit-trace-graph` (see [`crate::trace_graph`]).
+    pub observer: Option<Arc<dyn Observer>>,
+    /// Callbacks added by [`add_hook`](Evaluator::add_hook), in order.
+    /// Shared with child evaluators.
+    hooks: Vec<Hook>,
+    /// Fallbacks added by [`add_resolver`](Evaluator::add_resolver), asked
+    /// in order.  Shared with child evaluators.
+    resolvers: Vec<Arc<dyn VarResolver>>,
+    /// Where `readfile` / `writefile` read and write.  Defaults to the real
+    /// filesystem on native targets and to the JS host on WASM (see
+    /// [`crate::fs`]).  Shared with child evaluators.
+    pub fs: Arc<dyn FileSystem>,
+    /// What file access built-ins are allowed; everything by default,
+    /// nothing under `bucl --sandbox`, chosen paths with `--allow-read` /
+    /// `--allow-write` (see [`crate::permissions`]).  Copied to child
+    /// evaluators.
+    pub permissions: Permissions,
     /// Pre-loaded BUCL function sources keyed by function name (no `.bucl`
     /// extension).  Checked before the filesystem so WASM builds can embed
-    /// the standard library with `include_str!`.
-    pub embedded_functions: HashMap<String, String>,
+    /// the standard library with `include_str!`.  Shared with child
+    /// evaluators; change it through `Arc::make_mut` or
+    /// [`embed_function`](Evaluator::embed_function).
+    pub embedded_functions: Arc<HashMap<String, String>>,
+    /// Message catalogs used by `t`, keyed by locale, each mapping message
+    /// keys to translations.  Filled lazily from `locales/<locale>.json|toml`,
+    /// by `tload`, or directly by embedders.
+    pub catalogs: HashMap<String, HashMap<String, String>>,
+    /// Databases opened by `sqlopen`, by handle.  Shared with child
+    /// evaluators so `.bucl` functions can query them.
+    #[cfg(all(feature = "sqlite", not(target_arch = "wasm32")))]
+    pub(crate) databases: crate::functions::sqlite::Databases,
+    /// TCP connections opened by `netsend` / `netrecv`, by address.  Shared
+    /// with child evaluators.
+    #[cfg(all(feature = "net", not(target_arch = "wasm32")))]
+    pub(crate) connections: crate::functions::net::Connections,
+    /// Generator used by `random` after `randomseed`; `None` means system
+    /// randomness.  Handed to child evaluators and taken back afterwards, so
+    /// `.bucl` functions continue the same sequence.
+    #[cfg(feature = "random")]
+    pub rng: Option<SeededRng>,
+    /// Parsed `.bucl` functions by name, shared with child evaluators and
+    /// kept by [`reset`](Evaluator::reset).
+    parsed_functions: Arc<Mutex<HashMap<String, ParsedFunction>>>,
+    /// Source of every `.bucl` function loaded during this run, by name.
+    /// Merged up from child evaluators; used by `bucl --report`.
+    pub loaded_functions: HashMap<String, String>,
+    /// The `.bucl` calls a failed run was inside, innermost first.  Empty
+    /// when the error came from the top-level script itself.
+    pub error_trace: Vec<StackFrame>,
+    /// How `if`, `elseif` and `assert` compare numbers: `None` (the default)
+    /// compares exactly, `Some(eps)` treats numbers within `eps` (relative
+    /// for magnitudes above 1) as equal.  Set by `strictfloat`; copied to
+    /// child evaluators.
+    pub float_tolerance: Option<f64>,
+    /// Whether `style` adds ANSI color codes.  Defaults to on when stdout
+    /// is a terminal and `NO_COLOR` is not set (off on WASM).  Copied to
+    /// child evaluators.
+    #[cfg(feature = "text")]
+    pub color: bool,
+    /// Whether reading a variable that was never set is an error instead of
+    /// giving `""`.  Parameters that name a variable rather than read it
+    /// (`local {tmp}`, `if exists {x}`) are exempt.  Not copied to child
+    /// evaluators, as `.bucl` functions commonly probe optional arguments,
+    /// but kept for `each … parallel` blocks, which are the script's own code.
+    pub strict: bool,
+    /// Most statements a run may execute, counting those inside `.bucl`
+    /// functions; one more fails it.  `None` (the default) is unlimited.
+    /// Copied to child evaluators.
+    pub max_steps: Option<u64>,
+    /// Statements executed so far, shared with child evaluators.
+    steps: Arc<AtomicU64>,
+    /// In strict mode, the first unset variable read since it was last
+    /// cleared (see [`check_defined`](Evaluator::check_defined)).
+    undefined_var: Mutex<Option<String>>,
+    /// How many variable names are being expanded inside one another (see
+    /// [`expand_name`](Evaluator::expand_name)).
+    name_depth: AtomicUsize,
+    /// The first runaway variable name met since the last statement
+    /// checked; reported by [`check_names`](Evaluator::check_names).
+    name_error: Mutex<Option<String>>,
+    /// Interpolation delimiters for `.bucl` function files that don't set
+    /// their own (see [`crate::parser::Delimiters`]).  Copied to child
+    /// evaluators.
+    pub delimiters: Delimiters,
+    /// Output of the `capture` blocks currently running, innermost last.
+    /// While non-empty, [`emit`](Evaluator::emit) appends here instead of
+    /// producing output.
+    pub(crate) captures: Vec<Vec<String>>,
+    /// Output written by [`emit_partial`](Evaluator::emit_partial) that no
+    /// line ending has completed yet.  Moves into `.bucl` function calls
+    /// and back, so a line can be continued across them.
+    pub(crate) partial_line: String,
+    /// The scope of the statement that called this `.bucl` function with a
+    /// block, while the call runs; see [`Evaluator::call_block`].
+    pub(crate) caller: Option<Box<CallerScope>>,
+    /// Variables made read-only by `const` / `readonly`; assigning to them or
+    /// their sub-variables is a runtime error.
+    pub(crate) readonly: HashSet<String>,
+    /// Variables declared with `local`, one frame per running statement list
+    /// (innermost last), each with the entries it shadowed.  Restored when
+    /// the statement list finishes.
+    pub(crate) locals: Vec<Vec<LocalVar>>,
+    /// Unevaluated parameters of the current built-in call, for built-ins
+    /// that need a variable's name rather than its value (`if exists {x}`).
+    pub(crate) call_params: Vec<Param>,
+    /// The name each argument of the current built-in call carries
+    /// (`{port}`, `port:"80"`), or `None`, in the order the built-in gets
+    /// the values; see [`bind_args`](crate::functions::bind_args).
+    pub(crate) call_arg_names: Vec<Option<String>>,
+    /// The arguments of the running `.bucl` function call (or of the script,
+    /// see [`set_args`](Evaluator::set_args)) with the names the caller gave
+    /// them, for `params`.
+    pub(crate) call_args: Vec<ResolvedArg>,
     /// Named arguments for the current function call.
     ///
     /// Set before each function dispatch, cleared afterward.  Built-in Rust
     /// functions can read these via [`named_arg`](Evaluator::named_arg).
     pub call_named_args: HashMap<String, String>,
+    /// Source line of the statement currently being executed (1-based).
+    pub current_line: usize,
+    /// Number of `assert` checks that held during this run.
+    pub asserts_passed: usize,
+    /// Every `assert` check that failed during this run, in order.
+    pub assert_failures: Vec<AssertFailure>,
 }
 
 impl Evaluator {
+    /// Start configuring an evaluator; see [`EvaluatorBuilder`].
+    pub fn builder() -> EvaluatorBuilder {
+        EvaluatorBuilder::default()
+    }
+
     pub fn new() -> Self {
         Self {
             variables: HashMap::new(),
-            functions: HashMap::new(),
+            functions: Arc::new(HashMap::new()),
             base_dir: None,
             output_buffer: Vec::new(),
-            embedded_functions: HashMap::new(),
+            output_sink: None,
+            log_level: LogLevel::default(),
+            log_sink: None,
+            cancel_check: None,
+            observer: None,
+            hooks: Vec::new(),
+            resolvers: Vec::new(),
+            fs: crate::fs::default_fs(),
+            permissions: Permissions::default(),
+            embedded_functions: Arc::new(HashMap::new()),
+            catalogs: HashMap::new(),
+            #[cfg(all(feature = "sqlite", not(target_arch = "wasm32")))]
+            databases: Default::default(),
+            #[cfg(all(feature = "net", not(target_arch = "wasm32")))]
+            connections: Default::default(),
+            #[cfg(feature = "random")]
+            rng: None,
+            parsed_functions: Arc::new(Mutex::new(HashMap::new())),
+            function_check: None,
+            loaded_functions: HashMap::new(),
+            error_trace: Vec::new(),
+            float_tolerance: None,
+            #[cfg(feature = "text")]
+            color: crate::functions::style::color_default(),
+            strict: false,
+            max_steps: None,
+            steps: Arc::new(AtomicU64::new(0)),
+            undefined_var: Mutex::new(None),
+            name_depth: AtomicUsize::new(0),
+            name_error: Mutex::new(None),
+            delimiters: Delimiters::Braces,
+            captures: Vec::new(),
+            partial_line: String::new(),
+            caller: None,
+            readonly: HashSet::new(),
+            locals: Vec::new(),
+            call_params: Vec::new(),
+            call_arg_names: Vec::new(),
+            call_args: Vec::new(),
             call_named_args: HashMap::new(),
+            current_line: 0,
+            asserts_passed: 0,
+            assert_failures: Vec::new(),
+        }
+    }
+
+    /// Forget everything a run left behind — variables, output, assertion
+    /// results, the error trace, the step count, `randomseed`, `strictfloat`,
+    /// open `sqlopen` databases and network connections — so the evaluator
+    /// can run another script.
+    ///
+    /// Registered built-ins, embedded and already parsed `.bucl` functions,
+    /// catalogs and the host configuration (sinks, filesystem, permissions,
+    /// limits, …) are kept, which makes a reused evaluator much cheaper than
+    /// a new one for servers and REPLs.
+    pub fn reset(&mut self) {
+        self.variables.clear();
+        self.output_buffer.clear();
+        #[cfg(feature = "random")]
+        {
+            self.rng = None;
+        }
+        self.loaded_functions.clear();
+        self.error_trace.clear();
+        self.float_tolerance = None;
+        self.steps.store(0, Ordering::Relaxed);
+        self.captures.clear();
+        self.partial_line.clear();
+        self.caller = None;
+        self.readonly.clear();
+        self.locals.clear();
+        self.call_params.clear();
+        self.call_arg_names.clear();
+        self.call_args.clear();
+        self.call_named_args.clear();
+        *self.name_error.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.current_line = 0;
+        self.asserts_passed = 0;
+        self.assert_failures.clear();
+        #[cfg(all(feature = "sqlite", not(target_arch = "wasm32")))]
+        self.databases.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        #[cfg(all(feature = "net", not(target_arch = "wasm32")))]
+        self.connections.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Builder
+// ---------------------------------------------------------------------------
+
+/// Configuration for a new [`Evaluator`], with every built-in registered.
+///
+/// ```ignore
+/// let mut eval = Evaluator::builder()
+///     .base_dir("scripts")
+///     .with_stdlib()
+///     .strict(true)
+///     .max_steps(100_000)
+///     .build();
+/// ```
+///
+/// Settings not given keep the defaults of [`Evaluator::new`].
+#[derive(Default)]
+pub struct EvaluatorBuilder {
+    base_dir: Option<PathBuf>,
+    stdlib: bool,
+    strict: bool,
+    max_steps: Option<u64>,
+    delimiters: Option<Delimiters>,
+    log_level: Option<LogLevel>,
+    permissions: Option<Permissions>,
+    fs: Option<Arc<dyn FileSystem>>,
+    output_sink: Option<OutputSink>,
+    cancel_check: Option<CancelCheck>,
+    hooks: Vec<Hook>,
+    resolvers: Vec<Arc<dyn VarResolver>>,
+    state: Option<StateSnapshot>,
+    args: Option<Vec<String>>,
+    vars: Vec<(String, String)>,
+}
+
+impl EvaluatorBuilder {
+    /// Directory `functions/<name>.bucl` is looked up in.
+    pub fn base_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.base_dir = Some(dir.into());
+        self
+    }
+
+    /// Embed the standard library (see [`crate::embed_stdlib`]).
+    pub fn with_stdlib(mut self) -> Self {
+        self.stdlib = true;
+        self
+    }
+
+    /// Make reading an unset variable an error (see [`Evaluator::strict`]).
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Fail the run after `max` statements (see [`Evaluator::max_steps`]).
+    pub fn max_steps(mut self, max: u64) -> Self {
+        self.max_steps = Some(max);
+        self
+    }
+
+    pub fn delimiters(mut self, delimiters: Delimiters) -> Self {
+        self.delimiters = Some(delimiters);
+        self
+    }
+
+    pub fn log_level(mut self, level: LogLevel) -> Self {
+        self.log_level = Some(level);
+        self
+    }
+
+    pub fn permissions(mut self, permissions: Permissions) -> Self {
+        self.permissions = Some(permissions);
+        self
+    }
+
+    pub fn fs(mut self, fs: Arc<dyn FileSystem>) -> Self {
+        self.fs = Some(fs);
+        self
+    }
+
+    pub fn output_sink(mut self, sink: OutputSink) -> Self {
+        self.output_sink = Some(sink);
+        self
+    }
+
+    pub fn cancel_check(mut self, check: CancelCheck) -> Self {
+        self.cancel_check = Some(check);
+        self
+    }
+
+    /// Add a statement hook, as with [`Evaluator::add_hook`].
+    pub fn hook(mut self, hook: Hook) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// Add a fallback for unset variables, as with
+    /// [`Evaluator::add_resolver`].
+    pub fn resolver(mut self, resolver: impl VarResolver + 'static) -> Self {
+        self.resolvers.push(Arc::new(resolver));
+        self
+    }
+
+    /// Start from saved variables, as with [`Evaluator::restore`]; `args`
+    /// and `var` are applied on top.
+    pub fn state(mut self, state: StateSnapshot) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Positional arguments, as for [`Evaluator::set_args`].
+    pub fn args(mut self, args: Vec<String>) -> Self {
+        self.args = Some(args);
+        self
+    }
+
+    /// Preset a variable; later calls for the same name win.
+    pub fn var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.vars.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn build(self) -> Evaluator {
+        let mut eval = Evaluator::new();
+        eval.base_dir = self.base_dir;
+        eval.strict = self.strict;
+        eval.max_steps = self.max_steps;
+        if let Some(delimiters) = self.delimiters {
+            eval.delimiters = delimiters;
         }
+        if let Some(level) = self.log_level {
+            eval.log_level = level;
+        }
+        if let Some(permissions) = self.permissions {
+            eval.permissions = permissions;
+        }
+        if let Some(fs) = self.fs {
+            eval.fs = fs;
+        }
+        eval.output_sink = self.output_sink;
+        eval.cancel_check = self.cancel_check;
+        eval.hooks = self.hooks;
+        eval.resolvers = self.resolvers;
+        if let Some(state) = &self.state {
+            eval.restore(state);
+        }
+        if let Some(args) = &self.args {
+            eval.set_args(args);
+        }
+        for (name, value) in self.vars {
+            eval.set_var(&name, value);
+        }
+        if self.stdlib {
+            crate::embed_stdlib(&mut eval);
+        }
+        crate::functions::register_all(&mut eval);
+        eval
     }
+}
 
+impl Evaluator {
     // -----------------------------------------------------------------------
     // Function registry
     // -----------------------------------------------------------------------
 
     pub fn register<F: BuclFunction + 'static>(&mut self, name: &str, func: F) {
-        self.functions.insert(name.to_string(), Arc::new(func));
+        Arc::make_mut(&mut self.functions).insert(name.to_string(), Arc::new(func));
+    }
+
+    /// Make a BUCL function available from source, as if it were
+    /// `functions/<name>.bucl`.  Embedded functions are found before the
+    /// filesystem is searched, and replace any earlier source for `name`.
+    ///
+    /// Use [`embed_bucl!`](crate::embed_bucl) to embed files at compile time.
+    pub fn embed_function(&mut self, name: &str, source: &str) {
+        Arc::make_mut(&mut self.embedded_functions).insert(name.to_string(), source.to_string());
+    }
+
+    /// Like [`embed_function`](Evaluator::embed_function), with `stmts`
+    /// already parsed from `source` with the default delimiters, so calls
+    /// use them instead of parsing it again.
+    #[cfg(feature = "stdlib")]
+    pub(crate) fn embed_parsed_function(
+        &mut self,
+        name: &str,
+        source: &str,
+        stmts: Arc<Vec<Statement>>,
+    ) {
+        self.embed_function(name, source);
+        let mut parsed = self.parsed_functions.lock().unwrap_or_else(|e| e.into_inner());
+        parsed.insert(
+            name.to_string(),
+            ParsedFunction {
+                source: source.to_string(),
+                delimiters: Delimiters::Braces,
+                stmts,
+            },
+        );
+    }
+
+    /// The names of the registered built-ins, sorted.
+    pub fn builtin_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.functions.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// The built-in registered as `name`.
+    pub fn builtin(&self, name: &str) -> Option<&dyn BuclFunction> {
+        self.functions.get(name).map(|f| f.as_ref())
+    }
+
+    /// Every `.bucl` function a call can load, sorted by name, with the file
+    /// it is read from (`None` when embedded).  Where a name exists in
+    /// several places, the one a call finds is listed.  Built-ins of the same
+    /// name still take precedence when called.
+    pub fn bucl_functions(&self) -> Vec<(String, Option<PathBuf>)> {
+        #[cfg_attr(target_arch = "wasm32", allow(unused_mut))]
+        let mut found: std::collections::BTreeMap<String, Option<PathBuf>> = self
+            .embedded_functions
+            .keys()
+            .map(|name| (name.clone(), None))
+            .collect();
+        #[cfg(not(target_arch = "wasm32"))]
+        for dir in self.function_dirs() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+                if path.extension().is_none_or(|ext| ext != "bucl") {
+                    continue;
+                }
+                if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                    found.entry(name.to_string()).or_insert(Some(path.clone()));
+                }
+            }
+        }
+        found.into_iter().collect()
+    }
+
+    /// Every function a call can reach, sorted by name, with where it comes
+    /// from.  A `.bucl` function shadowed by a built-in of the same name is
+    /// left out.
+    pub fn callable_functions(&self) -> Vec<(String, FunctionOrigin)> {
+        let mut all: Vec<(String, FunctionOrigin)> = self
+            .functions
+            .keys()
+            .map(|name| (name.clone(), FunctionOrigin::Builtin))
+            .collect();
+        for (name, path) in self.bucl_functions() {
+            if !self.functions.contains_key(&name) {
+                all.push((name, path.map_or(FunctionOrigin::Embedded, FunctionOrigin::File)));
+            }
+        }
+        all.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        all
+    }
+
+    /// The source of `.bucl` function `name`, found the way a call finds it.
+    pub fn bucl_function_source(&self, name: &str) -> Option<String> {
+        self.find_bucl_function(name).ok().flatten()
+    }
+
+    // -----------------------------------------------------------------------
+    // Output
+    // -----------------------------------------------------------------------
+
+    /// Emit one line of script output.
+    ///
+    /// The line is always appended to `output_buffer`.  It is then handed to
+    /// the `output_sink` if one is installed, or printed to stdout on native
+    /// targets.  Inside a `capture` block the line goes to the capture
+    /// instead and is not output at all.  Text left open by
+    /// [`emit_partial`](Evaluator::emit_partial) starts the line.
+    pub fn emit(&mut self, line: String) {
+        self.write_output(&line, true);
+    }
+
+    /// Emit `text` without ending the line (`print`, `echo … end:""`).
+    ///
+    /// Native stdout shows it at once.  The buffer, sink and captures only
+    /// deal in whole lines, so they get it with the next [`emit`] — or from
+    /// [`flush_output`](Evaluator::flush_output) if no line follows.
+    ///
+    /// [`emit`]: Evaluator::emit
+    pub fn emit_partial(&mut self, text: &str) {
+        self.write_output(text, false);
+    }
+
+    /// Hand text left open by [`emit_partial`](Evaluator::emit_partial) on
+    /// as a final line, without printing a newline.  Called when a run,
+    /// `capture` block or parallel iteration ends.
+    pub fn flush_output(&mut self) {
+        if !self.partial_line.is_empty() {
+            let line = std::mem::take(&mut self.partial_line);
+            self.finish_line(line);
+        }
+    }
+
+    fn write_output(&mut self, text: &str, end_line: bool) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.captures.is_empty() && self.output_sink.is_none() {
+            use std::io::Write;
+            if end_line {
+                println!("{}", text);
+            } else {
+                print!("{}", text);
+                let _ = std::io::stdout().flush();
+            }
+        }
+        self.partial_line.push_str(text);
+        if end_line {
+            let line = std::mem::take(&mut self.partial_line);
+            self.finish_line(line);
+        }
+    }
+
+    /// Deliver a complete line to the innermost capture, or to the sink and
+    /// `output_buffer`.
+    fn finish_line(&mut self, line: String) {
+        if let Some(captured) = self.captures.last_mut() {
+            captured.push(line);
+            return;
+        }
+        if let Some(sink) = &self.output_sink {
+            sink(&line);
+        }
+        self.output_buffer.push(line);
+    }
+
+    /// Report a `log` message at `level`, unless it is below
+    /// [`log_level`](Evaluator::log_level).  Goes to the
+    /// [`log_sink`](Evaluator::log_sink), or to stderr on native targets.
+    /// Unlike [`emit`](Evaluator::emit) it is never captured or buffered.
+    pub fn log(&self, level: LogLevel, message: &str) {
+        if level < self.log_level {
+            return;
+        }
+        if let Some(sink) = &self.log_sink {
+            sink(level, message);
+        } else {
+            #[cfg(not(target_arch = "wasm32"))]
+            eprintln!("[{}] {}", level, message);
+        }
     }
 
     // -----------------------------------------------------------------------
@@ -132,6 +879,135 @@ impl Evaluator {
         self.variables.insert(name.to_string(), value);
     }
 
+    /// Store a list of strings as an array variable.
+    ///
+    /// Follows the same layout as a multi-argument assignment: `{name}` holds
+    /// the concatenation, `{name/count}` the number of items, and each item is
+    /// stored explicitly under `{name/0}`, `{name/1}`, …  Indices left over
+    /// from a previous, longer array are removed.
+    pub fn set_array(&mut self, name: &str, items: Vec<String>) {
+        let old_count: usize = self
+            .variables
+            .get(&format!("{}/count", name))
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0);
+        for i in items.len()..old_count {
+            self.variables.remove(&format!("{}/{}", name, i));
+        }
+
+        self.set_var(name, items.concat());
+        self.variables
+            .insert(format!("{}/count", name), items.len().to_string());
+        for (i, item) in items.into_iter().enumerate() {
+            self.variables.insert(format!("{}/{}", name, i), item);
+        }
+    }
+
+    /// The items of an array variable, as stored by
+    /// [`set_array`](Evaluator::set_array) or a multi-value assignment.  A
+    /// plain single value is a one-item array; an unset variable is empty.
+    pub fn array_items(&self, name: &str) -> Vec<String> {
+        let Some(root) = self.variables.get(name) else {
+            return Vec::new();
+        };
+        let count: usize = self
+            .variables
+            .get(&format!("{}/count", name))
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(1);
+        if count == 1 && !self.variables.contains_key(&format!("{}/0", name)) {
+            return vec![root.clone()];
+        }
+        (0..count)
+            .map(|i| {
+                self.variables
+                    .get(&format!("{}/{}", name, i))
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Every sub-variable below `name` (at any depth), as the path after
+    /// `name/` and its value.
+    pub(crate) fn subtree(&self, name: &str) -> Vec<(String, String)> {
+        let prefix = format!("{}/", name);
+        self.variables
+            .iter()
+            .filter_map(|(k, v)| k.strip_prefix(&prefix).map(|rest| (rest.to_string(), v.clone())))
+            .collect()
+    }
+
+    /// Remove `name` and all of its sub-variables.
+    pub(crate) fn remove_tree(&mut self, name: &str) {
+        let prefix = format!("{}/", name);
+        self.variables
+            .retain(|k, _| k != name && !k.starts_with(&prefix));
+    }
+
+    /// Whether `name` is set at all — unlike [`resolve_var`](Evaluator::resolve_var),
+    /// which returns `""` both for unset variables and ones set to `""`.
+    /// Nested references in the name (`{row/{i}}`) are resolved first.
+    pub fn var_exists(&self, name: &str) -> bool {
+        let name = if name.contains('{') {
+            self.interpolate(name)
+        } else {
+            name.to_string()
+        };
+        self.variables.contains_key(&name) || self.ask_resolvers(&name).is_some()
+    }
+
+    /// Add a fallback for variables the script has not set, asked each time
+    /// such a variable is read; the answer is not stored, so a resolver may
+    /// answer differently next time.  `exists` sees resolved names too.
+    ///
+    /// ```ignore
+    /// eval.add_resolver(|name: &str| {
+    ///     std::env::var(name.strip_prefix("env/")?).ok()
+    /// });
+    /// // echo "home is {env/HOME}"
+    /// ```
+    ///
+    /// Resolvers are asked only after a normal lookup finds nothing, and
+    /// give single values: a resolved `{name}` is never expanded as an array
+    /// or struct.
+    pub fn add_resolver(&mut self, resolver: impl VarResolver + 'static) {
+        self.resolvers.push(Arc::new(resolver));
+    }
+
+    /// The first answer from [`add_resolver`](Evaluator::add_resolver)
+    /// resolvers for `name`.
+    fn ask_resolvers(&self, name: &str) -> Option<String> {
+        self.resolvers.iter().find_map(|r| r.resolve(name))
+    }
+
+    /// Every variable in the current scope as `(name, value)`, sorted by name.
+    pub fn variables_snapshot(&self) -> Vec<(String, String)> {
+        let mut vars: Vec<(String, String)> = self
+            .variables
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        vars.sort();
+        vars
+    }
+
+    /// Copy the variable store (and which variables are read-only) so that
+    /// [`restore`](Evaluator::restore) can return to this point.
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            variables: self.variables.clone().into_iter().collect(),
+            readonly: self.readonly.clone().into_iter().collect(),
+        }
+    }
+
+    /// Replace the variable store with the one `state` was taken from.
+    /// Output, functions and settings are left alone.
+    pub fn restore(&mut self, state: &StateSnapshot) {
+        self.variables = state.variables.clone().into_iter().collect();
+        self.readonly = state.readonly.clone().into_iter().collect();
+    }
+
     /// Resolve a variable name, with automatic index-based fallback.
     ///
     /// Lookup order for `"var/N"` (where N is a non-negative integer):
@@ -153,8 +1029,10 @@ impl Evaluator {
         // 0. If the name itself contains nested variable refs (e.g. "var/{key}"),
         //    resolve them first via interpolation, then look up the resulting name.
         if name.contains('{') {
-            let resolved = self.interpolate(name);
-            return self.resolve_var(&resolved);
+            return match self.expand_name(name) {
+                Some(resolved) => self.resolve_var(&resolved),
+                None => String::new(),
+            };
         }
 
         // 1. Direct lookup.
@@ -162,7 +1040,15 @@ impl Evaluator {
             return v.clone();
         }
 
-        // 2. Index fallback — only for numeric suffixes after the first '/'.
+        // 2. Slice — `items/1..3`: the elements or characters in the range.
+        if let Some(slice) = self.resolve_slice(name) {
+            return match slice {
+                Slice::Items(items) => items.join(" "),
+                Slice::Chars(text) => text,
+            };
+        }
+
+        // 3. Index fallback — only for numeric suffixes after the first '/'.
         if let Some(slash) = name.find('/') {
             let parent = &name[..slash];
             let index_str = &name[slash + 1..];
@@ -186,44 +1072,161 @@ impl Evaluator {
             }
         }
 
+        if let Some(value) = self.ask_resolvers(name) {
+            return value;
+        }
+        if self.strict {
+            self.note_undefined(name);
+        }
         String::new()
     }
 
+    /// Interpolate the references in a variable name until none are left.
+    /// A value can hold references too (`{a}` = `"{b}"`), so the name a
+    /// pass produces is interpolated again.  A name that comes back to one
+    /// it already was, nests or keeps expanding past [`MAX_NAME_DEPTH`], or
+    /// grows past [`MAX_NAME_LEN`], is noted for
+    /// [`check_names`](Evaluator::check_names) and gives `None`.
+    fn expand_name(&self, name: &str) -> Option<String> {
+        if self.name_depth.fetch_add(1, Ordering::Relaxed) >= MAX_NAME_DEPTH {
+            self.name_depth.fetch_sub(1, Ordering::Relaxed);
+            self.note_name_error(format!(
+                "variable references nested more than {} levels deep",
+                MAX_NAME_DEPTH
+            ));
+            return None;
+        }
+        let mut seen: Vec<String> = Vec::new();
+        let mut name = name.to_string();
+        let expanded = loop {
+            let parts = crate::parser::interpolation_parts(&name);
+            if !parts.iter().any(|part| matches!(part, TemplatePart::Var(_))) {
+                break Some(name);
+            }
+            if seen.contains(&name) {
+                seen.push(name);
+                self.note_name_error(format!(
+                    "variable reference refers to itself: {}",
+                    seen.join(" -> ")
+                ));
+                break None;
+            }
+            if seen.len() == MAX_NAME_DEPTH || (!seen.is_empty() && name.len() > MAX_NAME_LEN) {
+                self.note_name_error(format!(
+                    "variable reference {} keeps expanding: {} bytes after pass {}",
+                    seen[0],
+                    name.len(),
+                    seen.len()
+                ));
+                break None;
+            }
+            let next = self.render_parts(&parts);
+            seen.push(name);
+            name = next;
+        };
+        self.name_depth.fetch_sub(1, Ordering::Relaxed);
+        expanded
+    }
+
+    /// Remember the first runaway variable name since the last check.
+    fn note_name_error(&self, message: String) {
+        let mut error = self.name_error.lock().unwrap_or_else(|e| e.into_inner());
+        error.get_or_insert(message);
+    }
+
+    /// Fail with the runaway variable name [`expand_name`](Evaluator::expand_name)
+    /// met since the last check, if any.
+    fn check_names(&self) -> Result<()> {
+        match self.name_error.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            Some(message) => Err(BuclError::RuntimeError(message)),
+            None => Ok(()),
+        }
+    }
+
+    /// Remember `name` as read while unset, unless it has sub-variables (a
+    /// struct like `{db}` has no value of its own).
+    fn note_undefined(&self, name: &str) {
+        let prefix = format!("{}/", name);
+        if self.variables.keys().any(|k| k.starts_with(&prefix)) {
+            return;
+        }
+        let mut undefined = self.undefined_var.lock().unwrap_or_else(|e| e.into_inner());
+        undefined.get_or_insert_with(|| name.to_string());
+    }
+
+    /// Strict mode: fail if evaluating `params` reads a variable that was
+    /// never set.  A bare `{var}` is not read when the built-in takes
+    /// variable names (see [`BuclFunction::names_variables`]) or it follows
+    /// `exists`.
+    fn check_defined(&self, params: &[Param], names_variables: bool) -> Result<()> {
+        for (i, param) in params.iter().enumerate() {
+            if let Param::Variable(_) = param {
+                let after_exists = i > 0 && matches!(&params[i - 1], Param::Bare(kw) if kw == "exists");
+                if names_variables || after_exists {
+                    continue;
+                }
+            }
+            if let Err(name) = self.defined(|eval| eval.eval_params_with_names(std::slice::from_ref(param))) {
+                return Err(BuclError::RuntimeError(format!(
+                    "{{{}}} is not set (strict mode)",
+                    name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Run `read`, or give the first variable it read that was never set.
+    /// Only a strict evaluator notices unset variables.
+    pub(crate) fn defined<T>(&self, read: impl FnOnce(&Self) -> T) -> std::result::Result<T, String> {
+        *self.undefined_var.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        let value = read(self);
+        let undefined = self.undefined_var.lock().unwrap_or_else(|e| e.into_inner()).take();
+        match undefined {
+            Some(name) => Err(name),
+            None => Ok(value),
+        }
+    }
+
+    /// Resolve a slice reference `parent/start..end` (end exclusive; either
+    /// bound may be left out).  An array gives its items in the range, any
+    /// other value its characters.  Out-of-range bounds are clamped.
+    fn resolve_slice(&self, name: &str) -> Option<Slice> {
+        let (parent, range) = name.rsplit_once('/')?;
+        let (start, end) = range.split_once("..")?;
+        let bound = |s: &str, default: usize| -> Option<usize> {
+            if s.is_empty() { Some(default) } else { s.parse().ok() }
+        };
+        let (start, end) = (bound(start, 0)?, bound(end, usize::MAX)?);
+        let take = end.saturating_sub(start);
+        if self.variables.contains_key(&format!("{}/0", parent)) {
+            let items = self.array_items(parent);
+            return Some(Slice::Items(items.into_iter().skip(start).take(take).collect()));
+        }
+        let value = self.variables.get(parent).map(String::as_str).unwrap_or("");
+        Some(Slice::Chars(value.chars().skip(start).take(take).collect()))
+    }
+
     // -----------------------------------------------------------------------
     // String interpolation
     // -----------------------------------------------------------------------
 
     pub fn interpolate(&self, s: &str) -> String {
-        let mut result = String::with_capacity(s.len());
-        let mut chars = s.chars().peekable();
+        if !s.contains('{') {
+            return s.to_string();
+        }
+        self.render_parts(&crate::parser::interpolation_parts(s))
+    }
 
-        while let Some(c) = chars.next() {
-            if c != '{' {
-                result.push(c);
-                continue;
-            }
-            let mut var_name = String::new();
-            let mut closed = false;
-            let mut depth = 1usize;
-            for ch in chars.by_ref() {
-                match ch {
-                    '{' => { depth += 1; var_name.push('{'); }
-                    '}' => {
-                        depth -= 1;
-                        if depth == 0 { closed = true; break; }
-                        var_name.push('}');
-                    }
-                    _ => var_name.push(ch),
-                }
-            }
-            if closed {
-                result.push_str(&self.resolve_var_for_interpolation(&var_name));
-            } else {
-                result.push('{');
-                result.push_str(&var_name);
+    /// Join the parts of a quoted string, resolving its variable references.
+    fn render_parts(&self, parts: &[TemplatePart]) -> String {
+        let mut result = String::new();
+        for part in parts {
+            match part {
+                TemplatePart::Text(text) => result.push_str(text),
+                TemplatePart::Var(name) => result.push_str(&self.resolve_var_for_interpolation(name)),
             }
         }
-
         result
     }
 
@@ -241,7 +1244,10 @@ impl Evaluator {
         // First resolve any nested variable refs inside the name itself
         // (e.g. "parts/{i}" → "parts/2").
         let resolved_name = if name.contains('{') {
-            self.interpolate(name)
+            match self.expand_name(name) {
+                Some(resolved) => resolved,
+                None => return String::new(),
+            }
         } else {
             name.to_string()
         };
@@ -276,9 +1282,11 @@ impl Evaluator {
 
     pub fn eval_param(&self, param: &Param) -> String {
         match param {
-            Param::Quoted(s) => self.interpolate(s),
+            Param::Quoted(_, parts) => self.render_parts(parts),
             Param::Variable(name) => self.resolve_var(name),
             Param::Bare(s) => s.clone(),
+            Param::Named(_, value) => self.eval_param(value),
+            Param::Template(parts) => self.render_parts(parts),
         }
     }
 
@@ -310,7 +1318,7 @@ impl Evaluator {
     /// Used for **struct expansion**: when `{db}` is passed as an argument and
     /// `db/port`, `db/host` exist, those sub-variables are expanded as named
     /// parameters.
-    fn find_named_sub_vars(&self, parent: &str) -> Vec<(String, String)> {
+    pub(crate) fn find_named_sub_vars(&self, parent: &str) -> Vec<(String, String)> {
         let prefix = format!("{}/", parent);
         let mut result = Vec::new();
         for (key, value) in &self.variables {
@@ -335,6 +1343,17 @@ impl Evaluator {
         result
     }
 
+    /// The named sub-variables `{parent}` expands to when passed whole (see
+    /// [`eval_params_with_names`](Evaluator::eval_params_with_names)) or
+    /// walked with `each`.  None for an array (one with `{parent/0}`): named
+    /// helpers such as `{stdin/lines}` don't turn it into a struct.
+    pub(crate) fn expanded_fields(&self, parent: &str) -> Vec<(String, String)> {
+        if self.variables.contains_key(&format!("{}/0", parent)) {
+            return Vec::new();
+        }
+        self.find_named_sub_vars(parent)
+    }
+
     /// Evaluate parameters while preserving variable-name metadata.
     ///
     /// This is the name-aware version of [`eval_params`].  Each returned
@@ -350,6 +1369,9 @@ impl Evaluator {
     /// {db/host} = "myserver"
     /// {r} connect {db}          # expands to connect host:"myserver" port:"3308"
     /// ```
+    ///
+    /// An argument named at the call site (`port:"3308"`, `host:{h}`) is one
+    /// value under that name, whatever its value holds.
     pub fn eval_params_with_names(&self, params: &[Param]) -> Vec<ResolvedArg> {
         let mut result = Vec::new();
         for p in params {
@@ -365,7 +1387,7 @@ impl Evaluator {
                     // Only expand root-level variable names (no '/').
                     if !resolved_name.contains('/') {
                         // Check for struct expansion first: named sub-variables.
-                        let named_subs = self.find_named_sub_vars(&resolved_name);
+                        let named_subs = self.expanded_fields(&resolved_name);
                         if !named_subs.is_empty() {
                             for (suffix, value) in named_subs {
                                 result.push(ResolvedArg {
@@ -398,12 +1420,24 @@ impl Evaluator {
                         }
                     }
 
+                    // A slice of an array expands like the array itself.
+                    if let Some(Slice::Items(items)) = self.resolve_slice(&resolved_name) {
+                        result.extend(items.into_iter().map(|value| ResolvedArg { name: None, value }));
+                        continue;
+                    }
+
                     // Single value — carry the variable name.
                     result.push(ResolvedArg {
                         name: extract_param_name(&resolved_name),
                         value: self.resolve_var(name),
                     });
                 }
+                Param::Named(name, value) => {
+                    result.push(ResolvedArg {
+                        name: extract_param_name(name),
+                        value: self.eval_param(value),
+                    });
+                }
                 _ => {
                     result.push(ResolvedArg {
                         name: None,
@@ -419,19 +1453,167 @@ impl Evaluator {
     // Execution
     // -----------------------------------------------------------------------
 
+    /// Run a statement list — a script or a block.  Variables declared
+    /// `local` inside it get their previous values back when it finishes,
+    /// whether or not it succeeded.
     pub fn evaluate_statements(&mut self, stmts: &[Statement]) -> Result<()> {
-        for stmt in stmts {
-            self.evaluate_statement(stmt)?;
+        self.locals.push(Vec::new());
+        let result = stmts.iter().try_for_each(|stmt| self.evaluate_statement(stmt));
+        for local in self.locals.pop().unwrap_or_default().into_iter().rev() {
+            self.remove_tree(&local.name);
+            self.variables.extend(local.shadowed);
+        }
+        result
+    }
+
+    /// Run the block the caller wrote under this `.bucl` function's call
+    /// (`callblock`) in the caller's scope.  Each `(name, value)` pair is set
+    /// first, as `{target/name}` when the caller gave a target and as
+    /// `{name}` otherwise.
+    pub(crate) fn call_block(&mut self, vars: Vec<(String, String)>) -> Result<()> {
+        let Some(mut caller) = self.caller.take() else {
+            return Err(BuclError::RuntimeError(
+                "callblock: the function was called without a block".into(),
+            ));
+        };
+        std::mem::swap(&mut self.variables, &mut caller.variables);
+        std::mem::swap(&mut self.readonly, &mut caller.readonly);
+        let result = vars
+            .into_iter()
+            .try_for_each(|(name, value)| {
+                let name = match &caller.target {
+                    Some(target) => format!("{}/{}", target, name),
+                    None => name,
+                };
+                self.check_writable(&name)?;
+                self.set_var(&name, value);
+                Ok(())
+            })
+            .and_then(|()| self.evaluate_statements(&caller.block));
+        std::mem::swap(&mut self.variables, &mut caller.variables);
+        std::mem::swap(&mut self.readonly, &mut caller.readonly);
+        self.caller = Some(caller);
+        result
+    }
+
+    /// Fail if `name`, or a variable it is a sub-variable of, is read-only.
+    pub(crate) fn check_writable(&self, name: &str) -> Result<()> {
+        if self.readonly.is_empty() {
+            return Ok(());
+        }
+        let mut path = name;
+        loop {
+            if self.readonly.contains(path) {
+                return Err(BuclError::RuntimeError(format!(
+                    "cannot assign to '{}': '{}' is a constant",
+                    name, path
+                )));
+            }
+            match path.rsplit_once('/') {
+                Some((parent, _)) => path = parent,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Make `name` local to the innermost running statement list: save its
+    /// current value and sub-variables, then unset it.  Declaring the same
+    /// name twice in one list is a no-op.
+    pub(crate) fn declare_local(&mut self, name: &str) -> Result<()> {
+        self.check_writable(name)?;
+        let declared = self
+            .locals
+            .last()
+            .is_some_and(|frame| frame.iter().any(|l| l.name == name));
+        if declared {
+            return Ok(());
+        }
+        let prefix = format!("{}/", name);
+        let shadowed: Vec<(String, String)> = self
+            .variables
+            .iter()
+            .filter(|(k, _)| *k == name || k.starts_with(&prefix))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        self.remove_tree(name);
+        if let Some(frame) = self.locals.last_mut() {
+            frame.push(LocalVar {
+                name: name.to_string(),
+                shadowed,
+            });
         }
         Ok(())
     }
 
+    /// Run `hook` before and after every statement from now on, including
+    /// statements in blocks and `.bucl` functions.  Hooks can trace, count,
+    /// or refuse statements; returning an error stops the run with it:
+    ///
+    /// ```ignore
+    /// eval.add_hook(|point, stmt, _eval| match point {
+    ///     HookPoint::Before if stmt.function == "writefile" => {
+    ///         Err(BuclError::RuntimeError("writefile is not allowed here".into()))
+    ///     }
+    ///     _ => Ok(()),
+    /// });
+    /// ```
+    pub fn add_hook(
+        &mut self,
+        hook: impl Fn(HookPoint, &Statement, &Evaluator) -> Result<()> + Send + Sync + 'static,
+    ) {
+        self.hooks.push(Arc::new(hook));
+    }
+
     pub fn evaluate_statement(&mut self, stmt: &Statement) -> Result<()> {
+        if self.hooks.is_empty() {
+            return self.run_statement(stmt);
+        }
+        let hooks = self.hooks.clone();
+        for hook in &hooks {
+            hook(HookPoint::Before, stmt, self)?;
+        }
+        match self.run_statement(stmt) {
+            Ok(()) => hooks.iter().try_for_each(|hook| hook(HookPoint::After, stmt, self)),
+            Err(e) => {
+                for hook in &hooks {
+                    let _ = hook(HookPoint::Failed, stmt, self);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn run_statement(&mut self, stmt: &Statement) -> Result<()> {
+        if let Some(check) = &self.cancel_check {
+            if check() {
+                return Err(BuclError::Cancelled);
+            }
+        }
+        if let Some(max) = self.max_steps {
+            if self.steps.fetch_add(1, Ordering::Relaxed) >= max {
+                return Err(BuclError::RuntimeError(format!(
+                    "step limit exceeded: more than {} statements",
+                    max
+                )));
+            }
+        }
+        self.current_line = stmt.line;
+        if let Some(observer) = &self.observer {
+            observer.statement(stmt);
+        }
+
+        let builtin = self.functions.get(&stmt.function).cloned();
+        if self.strict {
+            let names_variables = builtin.as_ref().is_some_and(|f| f.names_variables());
+            self.check_defined(&stmt.args, names_variables)?;
+        }
         // Resolve args with names preserved.
         let resolved = self.eval_params_with_names(&stmt.args);
 
         // Check for duplicate named parameters.
-        check_duplicate_names(&resolved)?;
+        if !builtin.as_ref().is_some_and(|f| f.allows_duplicate_names()) {
+            check_duplicate_names(&resolved)?;
+        }
 
         // Extract flat values for built-in functions.
         let values: Vec<String> = resolved.iter().map(|a| a.value.clone()).collect();
@@ -443,21 +1625,37 @@ impl Evaluator {
             .filter_map(|a| a.name.as_ref().map(|n| (n.clone(), a.value.clone())))
             .collect();
         self.call_named_args = named;
+        self.call_params = stmt.args.clone();
+        self.call_arg_names = resolved.iter().map(|a| a.name.clone()).collect();
 
         // Resolve target name — supports nested variable refs like {var/{key}}.
         let resolved_target: Option<String> = stmt.target.as_ref().map(|t| {
             if t.contains('{') { self.interpolate(t) } else { t.clone() }
         });
 
+        self.check_names()?;
+        if let Some(target) = &resolved_target {
+            self.check_writable(target)?;
+        }
+
         // 1. Try built-in Rust functions first.
-        if let Some(func) = self.functions.get(&stmt.function).cloned() {
+        if let Some(func) = builtin {
+            let observer = self.observer.clone().filter(|_| stmt.block.is_some());
+            if let Some(observer) = &observer {
+                observer.enter_block(stmt);
+            }
             let result = func.call(
                 self,
                 resolved_target.as_deref(),
                 values,
                 stmt.block.as_deref(),
                 stmt.continuation.as_deref(),
-            )?;
+            );
+            if let Some(observer) = &observer {
+                observer.exit_block(stmt);
+            }
+            let result = result?;
+            self.check_names()?;
             self.call_named_args.clear();
             if let (Some(target), Some(value)) = (&resolved_target, result) {
                 self.set_var(target, value);
@@ -471,6 +1669,7 @@ impl Evaluator {
             &stmt.function.clone(),
             resolved_target.as_deref(),
             resolved,
+            stmt.block.as_deref(),
         )?;
         if let (Some(target), Some(value)) = (&resolved_target, result) {
             self.set_var(target, value);
@@ -488,30 +1687,146 @@ impl Evaluator {
     /// Lookup order:
     /// 1. `embedded_functions` map (used by WASM builds and for stdlib).
     /// 2. Filesystem: `functions/<name>.bucl` relative to `base_dir`, then CWD.
-    ///    (skipped when targeting `wasm32`).
-    fn find_bucl_function(&self, name: &str) -> Option<String> {
+    ///    (skipped when targeting `wasm32`), passed through
+    ///    [`function_check`](Evaluator::function_check).
+    fn find_bucl_function(&self, name: &str) -> Result<Option<String>> {
         // 1. Embedded (in-memory) registry — always checked first.
         if let Some(src) = self.embedded_functions.get(name) {
-            return Some(src.clone());
+            return Ok(Some(src.clone()));
         }
 
         // 2. Filesystem lookup — not available on WASM targets.
         #[cfg(not(target_arch = "wasm32"))]
         {
             let filename = format!("{}.bucl", name);
-            let mut candidates: Vec<PathBuf> = Vec::new();
-            if let Some(base) = &self.base_dir {
-                candidates.push(base.join("functions").join(&filename));
-            }
-            candidates.push(Path::new("functions").join(&filename));
-            for path in candidates {
+            for dir in self.function_dirs() {
+                let path = dir.join(&filename);
                 if let Ok(source) = std::fs::read_to_string(&path) {
-                    return Some(source);
+                    return match &self.function_check {
+                        Some(check) => check(&path, source).map(Some),
+                        None => Ok(Some(source)),
+                    };
                 }
             }
         }
 
-        None
+        Ok(None)
+    }
+
+    /// The directories `.bucl` functions are looked up in, in order:
+    /// `functions/` below `base_dir`, then below the CWD.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn function_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = Vec::new();
+        if let Some(base) = &self.base_dir {
+            dirs.push(base.join("functions"));
+        }
+        dirs.push(PathBuf::from("functions"));
+        dirs
+    }
+
+    /// A fresh evaluator with an empty variable scope that shares this one's
+    /// host hooks and settings (output sink, cancellation, function check,
+    /// statement hooks, variable resolvers, filesystem, permissions,
+    /// built-ins, embedded and parsed functions, catalogs, float tolerance,
+    /// step limit and count, delimiters).
+    fn child(&self) -> Evaluator {
+        let mut child = Evaluator::new();
+        child.base_dir = self.base_dir.clone();
+        child.output_sink = self.output_sink.clone();
+        child.log_level = self.log_level;
+        child.log_sink = self.log_sink.clone();
+        child.cancel_check = self.cancel_check.clone();
+        child.function_check = self.function_check.clone();
+        child.hooks = self.hooks.clone();
+        child.resolvers = self.resolvers.clone();
+        child.fs = self.fs.clone();
+        child.permissions = self.permissions.clone();
+        child.functions = self.functions.clone();
+        child.embedded_functions = self.embedded_functions.clone();
+        child.parsed_functions = self.parsed_functions.clone();
+        child.catalogs = self.catalogs.clone();
+        #[cfg(all(feature = "sqlite", not(target_arch = "wasm32")))]
+        {
+            child.databases = self.databases.clone();
+        }
+        #[cfg(all(feature = "net", not(target_arch = "wasm32")))]
+        {
+            child.connections = self.connections.clone();
+        }
+        child.float_tolerance = self.float_tolerance;
+        #[cfg(feature = "text")]
+        {
+            child.color = self.color;
+        }
+        child.max_steps = self.max_steps;
+        child.steps = self.steps.clone();
+        child.delimiters = self.delimiters.clone();
+        child
+    }
+
+    /// An evaluator for one iteration of `each … parallel:"N"`: a copy of
+    /// this one's variables and read-only marks, with its output
+    /// captured so the caller can emit it in item order.  It has no
+    /// observer or `rng`; the caller hands it a seed if needed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn parallel_child(&self) -> Evaluator {
+        let mut child = self.child();
+        child.variables = self.variables.clone();
+        child.strict = self.strict;
+        child.readonly = self.readonly.clone();
+        child.captures.push(Vec::new());
+        child
+    }
+
+    /// Set the positional arguments of a `.bucl` function call — or of the
+    /// script itself, when the CLI passes the words after the script path:
+    /// `{0}`, `{1}`, …, `{argc}`, and the array `{args}` (`{args/0}`, …,
+    /// `{args/count}`, `{args/length}`).
+    pub fn set_args(&mut self, values: &[String]) {
+        // Bypass set_var to avoid spurious output.
+        let argc = values.len();
+        self.variables.insert("argc".to_string(), argc.to_string());
+        for (i, val) in values.iter().enumerate() {
+            self.variables.insert(i.to_string(), val.clone());
+        }
+        // Also expose arguments as a structured {args} variable so that BUCL
+        // functions can use {args/{i}} for dynamic positional access without
+        // needing the `getvar` built-in.
+        self.variables.insert("args".to_string(), values.join(""));
+        self.variables
+            .insert("args/count".to_string(), argc.to_string());
+        let args_length: usize = values.iter().map(|s| s.chars().count()).sum();
+        self.variables
+            .insert("args/length".to_string(), args_length.to_string());
+        for (i, val) in values.iter().enumerate() {
+            self.variables.insert(format!("args/{}", i), val.clone());
+        }
+        self.call_args = values
+            .iter()
+            .map(|value| ResolvedArg { name: None, value: value.clone() })
+            .collect();
+    }
+
+    /// The statements of `.bucl` function `name`, parsed from `source` or
+    /// taken from an earlier call with the same source and delimiters.
+    fn parse_function(&self, name: &str, source: &str) -> Result<Arc<Vec<Statement>>> {
+        let lock = || self.parsed_functions.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(parsed) = lock().get(name) {
+            if parsed.source == source && parsed.delimiters == self.delimiters {
+                return Ok(parsed.stmts.clone());
+            }
+        }
+        let stmts = Arc::new(crate::parser::parse_with(source, &self.delimiters)?);
+        lock().insert(
+            name.to_string(),
+            ParsedFunction {
+                source: source.to_string(),
+                delimiters: self.delimiters.clone(),
+                stmts: stmts.clone(),
+            },
+        );
+        Ok(stmts)
     }
 
     /// Execute a `.bucl` function in an isolated child scope.
@@ -533,43 +1848,35 @@ impl Evaluator {
         name: &str,
         target: Option<&str>,
         resolved_args: Vec<ResolvedArg>,
+        block: Option<&[Statement]>,
     ) -> Result<Option<String>> {
         let source = self
-            .find_bucl_function(name)
+            .find_bucl_function(name)?
             .ok_or_else(|| BuclError::UnknownFunction(name.to_string()))?;
+        self.loaded_functions
+            .entry(name.to_string())
+            .or_insert_with(|| source.clone());
 
-        let stmts = crate::parser::parse(&source)?;
+        let stmts = self.parse_function(name, &source)?;
 
         // Build an isolated child evaluator that shares the function registry,
         // base_dir, and embedded_functions but has its own variable scope.
-        let mut child = Evaluator::new();
-        child.base_dir = self.base_dir.clone();
-        child.embedded_functions = self.embedded_functions.clone();
-        crate::functions::register_all(&mut child);
+        let mut child = self.child();
+        child.observer = self.observer.clone();
+        #[cfg(feature = "random")]
+        {
+            child.rng = self.rng.take();
+        }
+        // Inside a `capture` block the function's output is captured too.
+        if !self.captures.is_empty() {
+            child.captures.push(Vec::new());
+        }
+        child.partial_line = std::mem::take(&mut self.partial_line);
 
         // Extract string values for positional injection.
         let values: Vec<String> = resolved_args.iter().map(|a| a.value.clone()).collect();
 
-        // Inject call arguments — bypass set_var to avoid spurious output.
-        let argc = values.len();
-        child.variables.insert("argc".to_string(), argc.to_string());
-        for (i, val) in values.iter().enumerate() {
-            child.variables.insert(i.to_string(), val.clone());
-        }
-        // Also expose arguments as a structured {args} variable so that BUCL
-        // functions can use {args/{i}} for dynamic positional access without
-        // needing the `getvar` built-in.
-        child.variables.insert("args".to_string(), values.join(""));
-        child
-            .variables
-            .insert("args/count".to_string(), argc.to_string());
-        let args_length: usize = values.iter().map(|s| s.chars().count()).sum();
-        child
-            .variables
-            .insert("args/length".to_string(), args_length.to_string());
-        for (i, val) in values.iter().enumerate() {
-            child.variables.insert(format!("args/{}", i), val.clone());
-        }
+        child.set_args(&values);
 
         // Inject named parameters as variables in the child scope.
         for ra in &resolved_args {
@@ -577,16 +1884,67 @@ impl Evaluator {
                 child.variables.insert(param_name.clone(), ra.value.clone());
             }
         }
+        child.call_args = resolved_args;
 
         if let Some(t) = target {
             child.variables.insert("target".to_string(), t.to_string());
         }
 
-        child.evaluate_statements(&stmts)?;
+        // A block written under the call runs in this scope when the
+        // function invokes `callblock`, so hand the scope to the child for
+        // the duration of the call.
+        if let Some(block) = block {
+            child.caller = Some(Box::new(CallerScope {
+                block: block.to_vec(),
+                target: target.map(str::to_string),
+                variables: std::mem::take(&mut self.variables),
+                readonly: std::mem::take(&mut self.readonly),
+            }));
+        }
 
-        // Propagate any output the child produced into the parent buffer.
+        if let Some(observer) = &self.observer {
+            observer.enter_function(name);
+        }
+        let result = child.evaluate_statements(&stmts);
+        if let Some(observer) = &self.observer {
+            observer.exit_function(name);
+        }
+        if let Some(caller) = child.caller.take() {
+            self.variables = caller.variables;
+            self.readonly = caller.readonly;
+        }
+        #[cfg(feature = "random")]
+        {
+            self.rng = child.rng.take();
+        }
+        self.partial_line = std::mem::take(&mut child.partial_line);
+        self.loaded_functions.extend(child.loaded_functions.drain());
+        if let (Some(lines), Some(captured)) = (child.captures.pop(), self.captures.last_mut()) {
+            captured.extend(lines);
+        }
+        if result.is_err() {
+            self.error_trace = std::mem::take(&mut child.error_trace);
+            self.error_trace.push(StackFrame {
+                function: name.to_string(),
+                line: child.current_line,
+            });
+        }
+
+        // Propagate any output the child produced into the parent buffer —
+        // also when it stopped early, so output before an error or `exit`
+        // is kept.  (It has already been printed / sent to the sink by the
+        // child.)
         self.output_buffer.append(&mut child.output_buffer);
 
+        // Propagate assertion results, tagging failures with the function name
+        // so the report points at the right file.
+        self.asserts_passed += child.asserts_passed;
+        for mut failure in child.assert_failures.drain(..) {
+            failure.function.get_or_insert_with(|| name.to_string());
+            self.assert_failures.push(failure);
+        }
+        result?;
+
         // Extract the primary return value.
         let return_val = child.variables.get("return").cloned();
 
@@ -631,6 +1989,20 @@ impl Evaluator {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_slice_references() {
+        let mut eval = Evaluator::new();
+        eval.set_array("items", vec!["a".into(), "b".into(), "c".into(), "d".into()]);
+        eval.set_var("word", "hello".into());
+        assert_eq!(eval.resolve_var("items/1..3"), "b c");
+        assert_eq!(eval.resolve_var("items/2.."), "c d");
+        assert_eq!(eval.resolve_var("word/..4"), "hell");
+        assert_eq!(eval.resolve_var("word/3..99"), "lo");
+        assert_eq!(eval.resolve_var("word/3..1"), "");
+        let args = eval.eval_params(&[Param::Variable("items/0..2".into())]);
+        assert_eq!(args, vec!["a", "b"]);
+    }
+
     #[test]
     fn test_extract_param_name_root() {
         assert_eq!(extract_param_name("port"), Some("port".to_string()));
@@ -682,6 +2054,20 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn test_named_arguments_at_call_site() {
+        let mut eval = Evaluator::builder().build();
+        eval.embed_function("connect", "{return} = \"{host}:{port} ({0} {1}) {target}\"");
+        let source = "{p} = \"3308\"\n{r} connect host:\"db{p}\" port:{p} target:\"x\"\n";
+        eval.evaluate_statements(&crate::parser::parse(source).unwrap()).unwrap();
+        assert_eq!(eval.resolve_var("r"), "db3308:3308 (db3308 3308) r");
+
+        let err = eval
+            .evaluate_statements(&crate::parser::parse("{x} connect {p} p:\"1\"").unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("duplicate named parameter 'p'"), "{}", err);
+    }
+
     #[test]
     fn test_check_duplicate_names_ok() {
         let args = vec![
@@ -700,4 +2086,213 @@ mod tests {
         ];
         assert!(check_duplicate_names(&args).is_err());
     }
+
+    #[test]
+    fn test_emit_uses_output_sink() {
+        use std::sync::Mutex;
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink_seen = Arc::clone(&seen);
+        let mut eval = Evaluator::new();
+        eval.output_sink = Some(Arc::new(move |line: &str| {
+            sink_seen.lock().unwrap().push(line.to_string());
+        }));
+
+        eval.emit("one".to_string());
+        eval.emit("two".to_string());
+
+        assert_eq!(*seen.lock().unwrap(), vec!["one", "two"]);
+        assert_eq!(eval.output_buffer, vec!["one", "two"]);
+    }
+
+    #[test]
+    fn test_cancel_check_stops_run() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.cancel_check = Some(Arc::new(|| true));
+
+        let stmts = crate::parser::parse("{x} = \"1\"").unwrap();
+        let result = eval.evaluate_statements(&stmts);
+
+        assert!(matches!(result, Err(BuclError::Cancelled)));
+        assert!(!eval.variables.contains_key("x"));
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_function_check_sees_function_files() {
+        let dir = std::env::temp_dir().join(format!("bucl-function-check-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("functions")).unwrap();
+        std::fs::write(dir.join("functions/twice.bucl"), "{return} = \"{0}{0}\"\n").unwrap();
+
+        let mut eval = Evaluator::builder().base_dir(&dir).build();
+        crate::functions::register_all(&mut eval);
+        eval.function_check = Some(Arc::new(|path: &Path, source: String| {
+            if path.ends_with("functions/twice.bucl") && source.contains("{0}{0}") {
+                Err(BuclError::RuntimeError("not trusted".into()))
+            } else {
+                Ok(source)
+            }
+        }));
+        eval.embed_function("same", "{return} = {0}");
+
+        let stmts = crate::parser::parse("{x} same \"a\"\n{y} twice \"b\"").unwrap();
+        let err = eval.evaluate_statements(&stmts).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(err.to_string(), "Runtime error: not trusted");
+        assert_eq!(eval.resolve_var("x"), "a");
+    }
+
+    #[test]
+    fn test_builder_strict_and_max_steps() {
+        let run = |eval: &mut Evaluator, source: &str| {
+            eval.evaluate_statements(&crate::parser::parse(source).unwrap())
+        };
+
+        let mut eval = Evaluator::builder().strict(true).var("name", "Ann").build();
+        run(&mut eval, "{db/port} = \"80\"\nlocal {tmp}\nif exists {nope}\n    echo \"x\"\necho \"{name} {db}\"").unwrap();
+        let err = run(&mut eval, "echo \"{name} {nmae}\"").unwrap_err();
+        assert_eq!(err.to_string(), "Runtime error: {nmae} is not set (strict mode)");
+        assert!(run(&mut eval, "{x} = {row/{i}}").unwrap_err().to_string().contains("{i}"));
+
+        let mut eval = Evaluator::builder().max_steps(10).build();
+        run(&mut eval, "{r} repeat 9\n    {x} = \"1\"").unwrap();
+        let err = run(&mut eval, "echo \"one too many\"").unwrap_err();
+        assert_eq!(err.to_string(), "Runtime error: step limit exceeded: more than 10 statements");
+    }
+
+    #[test]
+    fn test_function_calls_share_registry() {
+        struct Shout;
+        impl BuclFunction for Shout {
+            fn call(
+                &self,
+                _evaluator: &mut Evaluator,
+                _target: Option<&str>,
+                args: Vec<String>,
+                _block: Option<&[Statement]>,
+                _continuation: Option<&Statement>,
+            ) -> Result<Option<String>> {
+                Ok(Some(args.concat().to_uppercase()))
+            }
+        }
+
+        let mut eval = Evaluator::builder().build();
+        eval.register("shout", Shout);
+        eval.embed_function("greet", "{return} shout \"hi \" {0}");
+        let child = eval.child();
+        assert!(Arc::ptr_eq(&eval.functions, &child.functions));
+        assert!(Arc::ptr_eq(&eval.embedded_functions, &child.embedded_functions));
+
+        let stmts = crate::parser::parse("{x} greet \"bob\"\necho {x}").unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.output_buffer, vec!["HI BOB"]);
+    }
+
+    #[test]
+    fn test_runaway_variable_names_fail() {
+        let run = |source: &str| {
+            let mut eval = Evaluator::builder().build();
+            let stmts = crate::parser::parse(source).unwrap();
+            eval.evaluate_statements(&stmts).map(|_| eval.output_buffer).map_err(|e| e.to_string())
+        };
+        let ok = run("{i} = \"1\"\n{p} = \"parts/\\{i\\}\"\n{parts} = \"a\" \"b\"\necho {{p}}\n");
+        assert_eq!(ok.unwrap(), vec!["b"]);
+
+        let err = run("{a} = \"\\{b\\}\"\n{b} = \"\\{a\\}\"\necho {{a}}\n").unwrap_err();
+        assert!(err.contains("refers to itself: {a} -> {b} -> {a}"), "{}", err);
+        let err = run("{a} = \"\\{a\\}\\{a\\}\"\necho \"{{a}}\"\n").unwrap_err();
+        assert!(err.contains("variable reference {a} keeps expanding"), "{}", err);
+        let deep = format!("{}x{}", "{".repeat(100), "}".repeat(100));
+        let err = run(&format!("echo {}\n", deep)).unwrap_err();
+        assert!(err.contains("nested more than 64 levels deep"), "{}", err);
+    }
+
+    #[test]
+    #[cfg(all(feature = "math", feature = "random"))]
+    fn test_reset_keeps_functions() {
+        let mut eval = Evaluator::builder().max_steps(5).build();
+        eval.embed_function("double", "{return} math \"{0} * 2\"");
+        let source = "{x} double 4\necho {x}\nrandomseed 7";
+        let stmts = crate::parser::parse(source).unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        let parsed = eval.parsed_functions.lock().unwrap()["double"].stmts.clone();
+
+        eval.reset();
+        assert!(eval.variables.is_empty() && eval.output_buffer.is_empty() && eval.rng.is_none());
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.output_buffer, vec!["8"]);
+        let cached = eval.parsed_functions.lock().unwrap()["double"].stmts.clone();
+        assert!(Arc::ptr_eq(&parsed, &cached));
+    }
+
+    #[test]
+    #[cfg(feature = "stdlib")]
+    fn test_stdlib_is_parsed_once_per_process() {
+        let stmts = crate::parser::parse("{p} strpos \"hello\" \"l\"\necho {p}").unwrap();
+        let mut first = Evaluator::builder().with_stdlib().build();
+        let mut second = Evaluator::builder().with_stdlib().build();
+        first.evaluate_statements(&stmts).unwrap();
+        second.evaluate_statements(&stmts).unwrap();
+        assert_eq!(second.output_buffer, vec!["2"]);
+        let parsed = |eval: &Evaluator| eval.parsed_functions.lock().unwrap()["strpos"].stmts.clone();
+        assert!(Arc::ptr_eq(&parsed(&first), &parsed(&second)));
+    }
+
+    #[test]
+    #[cfg(feature = "math")]
+    fn test_hooks_run_around_statements() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut eval = Evaluator::builder().build();
+        eval.embed_function("twice", "{return} math \"{0} * 2\"");
+        let log = events.clone();
+        eval.add_hook(move |point, stmt, eval| {
+            let x = eval.resolve_var("x");
+            log.lock().unwrap().push(format!("{:?} {} x={}", point, stmt.function, x));
+            match point {
+                HookPoint::Before if stmt.function == "writefile" => {
+                    Err(BuclError::RuntimeError("writefile is not allowed".into()))
+                }
+                _ => Ok(()),
+            }
+        });
+
+        let source = "{x} twice 2\nwritefile \"out.txt\" {x}\necho \"never\"";
+        let err = eval.evaluate_statements(&crate::parser::parse(source).unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "Runtime error: writefile is not allowed");
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "Before twice x=",
+                "Before math x=",
+                "After math x=",
+                "After twice x=4",
+                "Before writefile x=4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolver_fills_unset_variables() {
+        let mut eval = Evaluator::builder()
+            .strict(true)
+            .resolver(move |name: &str| {
+                let key = name.strip_prefix("secret/")?;
+                (key == "api_key").then(|| "s3cr3t".to_string())
+            })
+            .build();
+        eval.embed_function("show", "{return} = \"key={secret/api_key}\"");
+
+        let source = "{secret/region} = \"eu\"\n\
+            if exists {secret/api_key}\n    echo \"{secret/api_key} {secret/region}\"\n\
+            {r} show\necho {r}";
+        eval.evaluate_statements(&crate::parser::parse(source).unwrap()).unwrap();
+        assert_eq!(eval.output_buffer, vec!["s3cr3t eu", "key=s3cr3t"]);
+        assert!(!eval.variables.contains_key("secret/api_key"));
+
+        let err = eval
+            .evaluate_statements(&crate::parser::parse("echo {secret/other}").unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("{secret/other} is not set"), "{}", err);
+    }
 }
@@ -0,0 +1,352 @@
+//! File access used by the `readfile` / `writefile` / `eachline` built-ins.
+//!
+//! Built-ins never touch `std::fs` directly; they go through the evaluator's
+//! [`FileSystem`] so the same scripts work wherever the interpreter is
+//! embedded:
+//!
+//! - [`NativeFs`] — the real filesystem (default on native targets).
+//! - `HostFs` — forwards to the JavaScript host (default on WASM), which
+//!   can back it with an in-browser virtual filesystem.
+//! - [`MemoryFs`] — an in-memory map, for tests and sandboxed embedders.
+//! - [`JailFs`] — wraps another filesystem and confines every path to one
+//!   directory of it, like a chroot.
+
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, BufRead};
+use std::sync::{Arc, Mutex};
+
+/// A place `readfile` and `writefile` read from and write to.
+///
+/// Paths are `/`-separated strings exactly as the script wrote them.
+pub trait FileSystem: Send + Sync {
+    /// Return the whole file at `path` as UTF-8 text.
+    fn read_to_string(&self, path: &str) -> io::Result<String>;
+    /// Create or replace the file at `path` with `contents`.
+    fn write(&self, path: &str, contents: &str) -> io::Result<()>;
+
+    /// Open the file at `path` for reading piece by piece (`eachline`).
+    ///
+    /// The default reads the whole file with
+    /// [`read_to_string`](FileSystem::read_to_string); implementations
+    /// that can stream should override it.
+    fn open(&self, path: &str) -> io::Result<Box<dyn BufRead>> {
+        Ok(Box::new(io::Cursor::new(self.read_to_string(path)?)))
+    }
+
+    /// Whether a file or directory exists at `path`.
+    ///
+    /// The default tries to read it as a file.
+    fn exists(&self, path: &str) -> bool {
+        self.read_to_string(path).is_ok()
+    }
+
+    /// Names of the entries directly inside directory `path`, sorted.
+    ///
+    /// The default reports that listing is unsupported.
+    fn list(&self, path: &str) -> io::Result<Vec<String>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("cannot list '{}': not supported by this filesystem", path),
+        ))
+    }
+}
+
+/// The filesystem new evaluators start with: [`NativeFs`] on native targets,
+/// `HostFs` on WASM.
+pub fn default_fs() -> Arc<dyn FileSystem> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Arc::new(NativeFs)
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        Arc::new(HostFs)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Native
+// ---------------------------------------------------------------------------
+
+/// The operating system's filesystem; paths are relative to the CWD.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct NativeFs;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileSystem for NativeFs {
+    fn read_to_string(&self, path: &str) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn write(&self, path: &str, contents: &str) -> io::Result<()> {
+        std::fs::write(path, contents)
+    }
+
+    fn open(&self, path: &str) -> io::Result<Box<dyn BufRead>> {
+        Ok(Box::new(io::BufReader::new(std::fs::File::open(path)?)))
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        std::path::Path::new(path).exists()
+    }
+
+    fn list(&self, path: &str) -> io::Result<Vec<String>> {
+        let mut names = std::fs::read_dir(path)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<io::Result<Vec<_>>>()?;
+        names.sort();
+        Ok(names)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// WASM host
+// ---------------------------------------------------------------------------
+
+/// Files served by the JavaScript host through the imported
+/// `js_read_file(path_ptr, path_len) -> *mut u8` and
+/// `js_write_file(path_ptr, path_len, data_ptr, data_len) -> i32`.
+/// `js_read_file` replies like `js_call` (status 0 = contents, 1 = error
+/// message, 2 or null = no such file); `js_write_file` returns 0 on success.
+/// With the `wasm-bindgen` feature the same hooks come from `js/bucl-host.js`.
+///
+/// The demo's `worker.js` keeps the files in memory for the lifetime of the
+/// worker, so a file written by one run can be read by the next.
+#[cfg(target_arch = "wasm32")]
+pub struct HostFs;
+
+#[cfg(target_arch = "wasm32")]
+impl FileSystem for HostFs {
+    fn read_to_string(&self, path: &str) -> io::Result<String> {
+        use crate::host::{self, HostReply};
+
+        match host::read_file(path) {
+            HostReply::Ok(contents) => Ok(contents),
+            HostReply::Err(message) => Err(io::Error::other(message)),
+            HostReply::Missing => Err(not_found(path)),
+        }
+    }
+
+    fn write(&self, path: &str, contents: &str) -> io::Result<()> {
+        if crate::host::write_file(path, contents) {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("host refused to write '{}'", path)))
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// In-memory
+// ---------------------------------------------------------------------------
+
+/// Files kept in a map; nothing touches the disk.  Directories are implied
+/// by the paths of the files in them.
+#[derive(Default)]
+pub struct MemoryFs {
+    files: Mutex<HashMap<String, String>>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a file before (or between) runs.
+    pub fn insert(&self, path: &str, contents: &str) {
+        self.files.lock().unwrap().insert(path.to_string(), contents.to_string());
+    }
+
+    /// Current contents of `path`, if it exists.
+    pub fn get(&self, path: &str) -> Option<String> {
+        self.files.lock().unwrap().get(path).cloned()
+    }
+}
+
+impl FileSystem for MemoryFs {
+    fn read_to_string(&self, path: &str) -> io::Result<String> {
+        self.get(path).ok_or_else(|| not_found(path))
+    }
+
+    fn write(&self, path: &str, contents: &str) -> io::Result<()> {
+        self.insert(path, contents);
+        Ok(())
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        let dir = format!("{}/", path.trim_end_matches('/'));
+        self.files
+            .lock()
+            .unwrap()
+            .keys()
+            .any(|file| file == path || file.starts_with(&dir))
+    }
+
+    fn list(&self, path: &str) -> io::Result<Vec<String>> {
+        let dir = match path.trim_end_matches('/') {
+            "" | "." => String::new(),
+            dir => format!("{}/", dir),
+        };
+        let names: BTreeSet<String> = self
+            .files
+            .lock()
+            .unwrap()
+            .keys()
+            .filter_map(|file| file.strip_prefix(&dir))
+            .filter_map(|rest| rest.split('/').next())
+            .map(str::to_string)
+            .collect();
+        if names.is_empty() {
+            return Err(not_found(path));
+        }
+        Ok(names.into_iter().collect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Jail
+// ---------------------------------------------------------------------------
+
+/// Another filesystem seen from inside one of its directories.
+///
+/// Every path is taken relative to `root` (a leading `/` means `root`
+/// itself), and `..` cannot climb above it:
+///
+/// ```ignore
+/// eval.fs = Arc::new(JailFs::new("/srv/jobs/42", Arc::new(NativeFs)));
+/// // readfile "in.txt"           →  /srv/jobs/42/in.txt
+/// // readfile "/etc/passwd"      →  /srv/jobs/42/etc/passwd
+/// // readfile "../41/secret.txt" →  error: outside the jail
+/// ```
+///
+/// The jail is lexical: on a real filesystem, a symlink inside `root` still
+/// leads wherever it points.  Combine it with
+/// [`Permissions`](crate::permissions::Permissions) if that matters.
+pub struct JailFs {
+    root: String,
+    inner: Arc<dyn FileSystem>,
+}
+
+impl JailFs {
+    pub fn new(root: impl Into<String>, inner: Arc<dyn FileSystem>) -> Self {
+        Self {
+            root: root.into().trim_end_matches('/').to_string(),
+            inner,
+        }
+    }
+
+    /// Where `path` lives in the wrapped filesystem.
+    fn inner_path(&self, path: &str) -> io::Result<String> {
+        let mut parts = Vec::new();
+        for part in path.split('/') {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    if parts.pop().is_none() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::PermissionDenied,
+                            format!("'{}' is outside the jail", path),
+                        ));
+                    }
+                }
+                part => parts.push(part),
+            }
+        }
+        Ok(if parts.is_empty() {
+            self.root.clone()
+        } else if self.root.is_empty() {
+            parts.join("/")
+        } else {
+            format!("{}/{}", self.root, parts.join("/"))
+        })
+    }
+}
+
+impl FileSystem for JailFs {
+    fn read_to_string(&self, path: &str) -> io::Result<String> {
+        self.inner.read_to_string(&self.inner_path(path)?)
+    }
+
+    fn write(&self, path: &str, contents: &str) -> io::Result<()> {
+        self.inner.write(&self.inner_path(path)?, contents)
+    }
+
+    fn open(&self, path: &str) -> io::Result<Box<dyn BufRead>> {
+        self.inner.open(&self.inner_path(path)?)
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.inner_path(path).is_ok_and(|path| self.inner.exists(&path))
+    }
+
+    fn list(&self, path: &str) -> io::Result<Vec<String>> {
+        self.inner.list(&self.inner_path(path)?)
+    }
+}
+
+fn not_found(path: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("no such file: '{}'", path))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_readfile_and_writefile_use_evaluator_fs() {
+        use crate::evaluator::Evaluator;
+
+        let fs = Arc::new(MemoryFs::new());
+        fs.insert("in.txt", "hello");
+
+        let mut eval = Evaluator::new();
+        eval.fs = fs.clone();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{c} readfile \"in.txt\"\n",
+            "writefile \"out.txt\" {c} \" world\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+
+        assert_eq!(fs.get("out.txt").as_deref(), Some("hello world"));
+        let missing = crate::parser::parse("{c} readfile \"nope.txt\"\n").unwrap();
+        assert!(eval.evaluate_statements(&missing).is_err());
+    }
+
+    #[test]
+    fn test_memory_fs_exists_and_list() {
+        let fs = MemoryFs::new();
+        fs.insert("top.txt", "");
+        fs.insert("data/b.csv", "");
+        fs.insert("data/a.csv", "");
+        fs.insert("data/old/a.csv", "");
+
+        assert!(fs.exists("top.txt") && fs.exists("data") && fs.exists("data/old/"));
+        assert!(!fs.exists("dat") && !fs.exists("data/c.csv"));
+        assert_eq!(fs.list(".").unwrap(), ["data", "top.txt"]);
+        assert_eq!(fs.list("data/").unwrap(), ["a.csv", "b.csv", "old"]);
+        assert_eq!(fs.list("nope").unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_jail_fs_confines_paths() {
+        let inner = Arc::new(MemoryFs::new());
+        inner.insert("jobs/41/secret.txt", "no");
+        let jail = JailFs::new("jobs/42/", inner.clone());
+
+        jail.write("/out/result.txt", "ok").unwrap();
+        assert_eq!(inner.get("jobs/42/out/result.txt").as_deref(), Some("ok"));
+        assert_eq!(jail.read_to_string("out/../out/./result.txt").unwrap(), "ok");
+        assert_eq!(jail.list("/").unwrap(), ["out"]);
+
+        let escape = jail.read_to_string("../41/secret.txt").unwrap_err();
+        assert_eq!(escape.kind(), io::ErrorKind::PermissionDenied);
+        assert!(!jail.exists("out/../../41/secret.txt"));
+    }
+}
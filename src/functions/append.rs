@@ -0,0 +1,108 @@
+/// `append` — add to the end of an existing variable in place.
+///
+/// For a plain value the arguments are concatenated onto it, which avoids
+/// re-copying the whole string the way `{log} = "{log}…"` does:
+///
+/// ```bucl
+/// {log} = ""
+/// {log} append "step 1 done\n"
+/// {log} append "step 2 done\n"
+/// ```
+///
+/// When the target is an array (it has a `{target/0}` item, as after a
+/// multi-value assignment or `push`), each argument becomes a new item
+/// instead:
+///
+/// ```bucl
+/// {names} = "Ann" "Bob"
+/// {names} append "Cy"          # {names/2} = Cy, {names/count} = 3
+/// ```
+///
+/// `{target}`, `{target/count}` and `{target/length}` stay up to date.  An
+/// unset target is created as a plain value.
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct Append;
+
+impl BuclFunction for Append {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let name = target.ok_or_else(|| {
+            BuclError::RuntimeError("append: expected a target, e.g. {log} append \"text\"".into())
+        })?;
+        if !evaluator.variables.contains_key(name) {
+            evaluator.set_var(name, args.concat());
+            return Ok(None);
+        }
+
+        let is_array = evaluator.variables.contains_key(&format!("{}/0", name));
+        let added: usize = args.iter().map(|a| a.chars().count()).sum();
+        if is_array {
+            let count = evaluator.array_items(name).len();
+            for (i, item) in args.iter().enumerate() {
+                evaluator
+                    .variables
+                    .insert(format!("{}/{}", name, count + i), item.clone());
+            }
+            evaluator
+                .variables
+                .insert(format!("{}/count", name), (count + args.len()).to_string());
+        }
+        if let Some(value) = evaluator.variables.get_mut(name) {
+            for arg in &args {
+                value.push_str(arg);
+            }
+        }
+        // Only root variables carry a length (see `set_var`).
+        if let Some(length) = evaluator.variables.get_mut(&format!("{}/length", name)) {
+            let old: usize = length.parse().unwrap_or(0);
+            *length = (old + added).to_string();
+        }
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Add values to the end of a variable (or items to an array) in place: `append {log} \"line\"`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("append", Append);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_append_to_string_and_array() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{log} append \"a\"\n",
+            "{log} append \"bc\" \"d\"\n",
+            "{names} = \"Ann\" \"Bob\"\n",
+            "{names} append \"Cy\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("log"), "abcd");
+        assert_eq!(eval.resolve_var("log/length"), "4");
+        assert_eq!(eval.array_items("names"), vec!["Ann", "Bob", "Cy"]);
+        assert_eq!(eval.resolve_var("names"), "AnnBobCy");
+        assert_eq!(eval.resolve_var("names/length"), "8");
+    }
+}
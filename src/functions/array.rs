@@ -0,0 +1,220 @@
+/// `push` / `pop` / `shift` / `insert` — change an array variable in place.
+///
+/// The array is always the first argument, written as a variable.  Items
+/// are renumbered and `{list}`, `{list/count}` and `{list/length}` kept up
+/// to date, exactly as if the array had been assigned from scratch.
+///
+/// ```bucl
+/// {list} = "b" "c"
+/// push {list} "d" "e"          # b c d e
+/// insert {list} 0 "a"          # a b c d e
+/// {last} pop {list}            # {last} = e;  list: a b c d
+/// {first} shift {list}         # {first} = a; list: b c d
+/// ```
+///
+/// `pop` and `shift` fail on an empty array.  `insert` accepts indices from
+/// `0` to the current count; items pushed or inserted that are themselves
+/// arrays are added element by element.
+use crate::ast::{Param, Statement};
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+/// The array variable named by the first parameter of the current call, and
+/// the values of the remaining parameters.
+fn array_args(evaluator: &Evaluator, fname: &str) -> Result<(String, Vec<String>)> {
+    let Some((Param::Variable(name), rest)) = evaluator.call_params.split_first() else {
+        return Err(BuclError::RuntimeError(format!(
+            "{}: expected an array variable first, e.g. {} {{list}}",
+            fname, fname
+        )));
+    };
+    let name = if name.contains('{') {
+        evaluator.interpolate(name)
+    } else {
+        name.clone()
+    };
+    evaluator.check_writable(&name)?;
+    let values = evaluator
+        .eval_params_with_names(rest)
+        .into_iter()
+        .map(|a| a.value)
+        .collect();
+    Ok((name, values))
+}
+
+// ---------------------------------------------------------------------------
+// push / insert
+// ---------------------------------------------------------------------------
+
+pub struct Push;
+
+impl BuclFunction for Push {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        _args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (name, values) = array_args(evaluator, "push")?;
+        let mut items = evaluator.array_items(&name);
+        items.extend(values);
+        evaluator.set_array(&name, items);
+        Ok(None)
+    }
+
+    fn names_variables(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Add items to the end of an array variable: `push {list} \"a\" \"b\"`."
+    }
+}
+
+pub struct Insert;
+
+impl BuclFunction for Insert {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        _args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (name, mut values) = array_args(evaluator, "insert")?;
+        if values.is_empty() {
+            return Err(BuclError::RuntimeError(
+                "insert: expected 'insert {list} index item ...'".into(),
+            ));
+        }
+        let index_str = values.remove(0);
+        let mut items = evaluator.array_items(&name);
+        let index = index_str
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|&i| i <= items.len())
+            .ok_or_else(|| {
+                BuclError::RuntimeError(format!(
+                    "insert: index '{}' out of range 0..={}",
+                    index_str,
+                    items.len()
+                ))
+            })?;
+        items.splice(index..index, values);
+        evaluator.set_array(&name, items);
+        Ok(None)
+    }
+
+    fn names_variables(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Insert items into an array variable before an index: `insert {list} 0 \"first\"`."
+    }
+}
+
+// ---------------------------------------------------------------------------
+// pop / shift
+// ---------------------------------------------------------------------------
+
+pub struct Pop;
+
+impl BuclFunction for Pop {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        _args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (name, _) = array_args(evaluator, "pop")?;
+        let mut items = evaluator.array_items(&name);
+        let item = items
+            .pop()
+            .ok_or_else(|| BuclError::RuntimeError(format!("pop: '{}' is empty", name)))?;
+        evaluator.set_array(&name, items);
+        Ok(Some(item))
+    }
+
+    fn description(&self) -> &str {
+        "Remove the last item of an array variable and return it: `{x} pop {list}`."
+    }
+}
+
+pub struct Shift;
+
+impl BuclFunction for Shift {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        _args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (name, _) = array_args(evaluator, "shift")?;
+        let mut items = evaluator.array_items(&name);
+        if items.is_empty() {
+            return Err(BuclError::RuntimeError(format!("shift: '{}' is empty", name)));
+        }
+        let item = items.remove(0);
+        evaluator.set_array(&name, items);
+        Ok(Some(item))
+    }
+
+    fn description(&self) -> &str {
+        "Remove the first item of an array variable and return it: `{x} shift {list}`."
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Registration
+// ---------------------------------------------------------------------------
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("push", Push);
+    eval.register("pop", Pop);
+    eval.register("shift", Shift);
+    eval.register("insert", Insert);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_array_mutators_keep_metadata() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{list} = \"b\"\n",
+            "push {list} \"c\" \"d\"\n",
+            "insert {list} 0 \"a\"\n",
+            "{last} pop {list}\n",
+            "{first} shift {list}\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("last"), "d");
+        assert_eq!(eval.resolve_var("first"), "a");
+        assert_eq!(eval.array_items("list"), vec!["b", "c"]);
+        assert_eq!(eval.resolve_var("list"), "bc");
+        assert_eq!(eval.resolve_var("list/count"), "2");
+        assert_eq!(eval.resolve_var("list/length"), "2");
+        assert!(!eval.var_exists("list/2"));
+
+        let empty = crate::parser::parse("{x} pop {nothing}\n").unwrap();
+        assert!(eval.evaluate_statements(&empty).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+/// `assert` — check a condition and record a failure when it does not hold.
+///
+/// Condition syntax is the same as `if`: `<lhs> <op> <rhs>`, optionally
+/// followed by a message describing the expectation.
+///
+/// ```bucl
+/// {x} = "5"
+/// assert {x} = "5" "x should be five"
+/// assert {x} > "3"
+/// ```
+///
+/// A failed assertion does not stop the script.  It is recorded on the
+/// evaluator together with its line number, so `bucl test` can report every
+/// failure of a test file in one pass.  When a script is run directly, any
+/// recorded failures are printed to stderr and the exit code is 1.
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::{AssertFailure, Evaluator};
+use crate::functions::if_fn::{condition_rhs, evaluate_condition};
+use crate::functions::BuclFunction;
+
+pub struct Assert;
+
+impl BuclFunction for Assert {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (lhs, op, rhs, message) = match args.as_slice() {
+            [lhs, op, rhs] => (lhs, op, rhs, None),
+            [lhs, op, rhs, message] => (lhs, op, rhs, Some(message)),
+            _ => {
+                return Err(BuclError::RuntimeError(format!(
+                    "assert: expected '<lhs> <op> <rhs> [message]', got {} argument(s)",
+                    args.len()
+                )));
+            }
+        };
+
+        let rhs = &condition_rhs(evaluator, op, rhs);
+        if evaluate_condition(lhs, op, rhs, evaluator.float_tolerance)? {
+            evaluator.asserts_passed += 1;
+        } else {
+            let condition = format!("\"{}\" {} \"{}\"", lhs, op, rhs);
+            let message = match message {
+                Some(m) => format!("{} ({})", m, condition),
+                None => format!("assertion failed: {}", condition),
+            };
+            evaluator.assert_failures.push(AssertFailure {
+                line: evaluator.current_line,
+                function: None,
+                message,
+            });
+        }
+
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Check a condition like `if` and record a test failure when it does not hold: `assert {n} = 3 \"message\"`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("assert", Assert);
+}
@@ -74,6 +74,10 @@ impl BuclFunction for Assign {
         // We handled the store ourselves; tell the evaluator not to call set_var again.
         Ok(None)
     }
+
+    fn description(&self) -> &str {
+        "Store one or more values in a variable: `{name} = \"value\"`."
+    }
 }
 
 pub fn register(eval: &mut Evaluator) {
@@ -0,0 +1,96 @@
+/// `callblock` — run the block the caller wrote under a `.bucl` function call.
+///
+/// Any `.bucl` function call can own an indented block.  The block is not
+/// run by itself; the function runs it with `callblock`, as often as it
+/// likes, which makes control structures possible in BUCL itself.  The
+/// block runs in the caller's scope, so it sees and sets the caller's
+/// variables.
+///
+/// `callblock name value ...` first sets each `name` to `value` in the
+/// caller's scope: as `{target/name}` when the call had a target, like the
+/// `{r/index}` of `repeat`, and as `{name}` otherwise.
+///
+/// ```bucl
+/// # functions/times.bucl
+/// {r} repeat {0}
+///     callblock index {r/index}
+/// ```
+///
+/// ```bucl
+/// {t} times 3
+///     echo "round {t/index}"
+/// ```
+///
+/// It is an error to use `callblock` in a function called without a block.
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct CallBlock;
+
+impl BuclFunction for CallBlock {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        if !args.len().is_multiple_of(2) {
+            return Err(BuclError::RuntimeError(format!(
+                "callblock: expected 'name value' pairs, got {} argument(s)",
+                args.len()
+            )));
+        }
+        let mut args = args.into_iter();
+        let mut vars = Vec::new();
+        while let (Some(name), Some(value)) = (args.next(), args.next()) {
+            vars.push((name, value));
+        }
+        evaluator.call_block(vars)?;
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Run the block written under the current `.bucl` function call, in the caller's scope: `callblock name value`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("callblock", CallBlock);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_block_runs_in_caller_scope() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.embed_function(
+            "times",
+            "{secret} = \"function\"\n{r} repeat {0}\n    callblock index {r/index}\n{return} = \"done\"\n",
+        );
+        let stmts = crate::parser::parse(concat!(
+            "{secret} = \"caller\"\n",
+            "{seen} = \"\"\n",
+            "{t} times 3\n",
+            "    {seen} = \"{seen}{t/index}{secret};\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("seen"), "1caller;2caller;3caller;");
+        assert_eq!(eval.resolve_var("t"), "done");
+        assert_eq!(eval.resolve_var("t/index"), "3");
+
+        let no_block = crate::parser::parse("times 1\n").unwrap();
+        assert!(eval.evaluate_statements(&no_block).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+/// `capture` — collect the output of a block instead of printing it.
+///
+/// Every line the block outputs (with `echo`, or from `.bucl` functions it
+/// calls) is stored in the target as an array, one item per line, and is
+/// not written to the script's output:
+///
+/// - `{out/0}`, `{out/1}`, … — the captured lines.
+/// - `{out/count}` — number of lines.
+///
+/// ```bucl
+/// {out} capture
+///     echo "first"
+///     echo "second"
+/// {text} implode "\n" {out}
+/// ```
+///
+/// Without a target the output is discarded.  Captures can be nested; each
+/// line goes to the innermost one.
+use crate::ast::Statement;
+use crate::error::Result;
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct Capture;
+
+impl BuclFunction for Capture {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        target: Option<&str>,
+        _args: Vec<String>,
+        block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        evaluator.captures.push(Vec::new());
+        let result = match block {
+            Some(block) => evaluator.evaluate_statements(block),
+            None => Ok(()),
+        };
+        evaluator.flush_output();
+        let lines = evaluator.captures.pop().unwrap_or_default();
+        result?;
+
+        if let Some(prefix) = target {
+            evaluator.set_array(prefix, lines);
+        }
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Collect the output of the block into the target variable instead of printing it: `{out} capture`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("capture", Capture);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_collects_block_and_function_output() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.embed_function("greet", "echo \"hi {0}\"\n");
+        let stmts = crate::parser::parse(concat!(
+            "echo \"before\"\n",
+            "{out} capture\n",
+            "    echo \"one\"\n",
+            "    greet \"bob\"\n",
+            "    capture\n",
+            "        echo \"dropped\"\n",
+            "echo \"after {out/count}\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+
+        assert_eq!(eval.output_buffer, vec!["before", "after 2"]);
+        assert_eq!(eval.resolve_var("out/0"), "one");
+        assert_eq!(eval.resolve_var("out/1"), "hi bob");
+    }
+}
@@ -0,0 +1,160 @@
+/// `const` / `readonly` — variables that can't be changed.
+///
+/// `const {name} = value ...` assigns like `=` and then marks the variable
+/// read-only; `readonly {name} ...` marks variables that are already set.
+/// Any later assignment to a read-only variable or one of its
+/// sub-variables — `=`, a function result, `push`, `local`, … — is a
+/// runtime error.
+///
+/// ```bucl
+/// const {pi} = "3.14159"
+/// {limits/max} = "100"
+/// readonly {limits}
+/// {pi} = "3"            # error: cannot assign to 'pi'
+/// ```
+///
+/// Read-only marks belong to the script's scope; `.bucl` functions start
+/// with none.
+use crate::ast::{Param, Statement};
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+/// Resolve nested references in a variable parameter's name.
+fn variable_name(evaluator: &Evaluator, name: &str) -> String {
+    if name.contains('{') {
+        evaluator.interpolate(name)
+    } else {
+        name.to_string()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// const
+// ---------------------------------------------------------------------------
+
+pub struct Const;
+
+impl BuclFunction for Const {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        _args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let params = evaluator.call_params.clone();
+        let (name, values) = match params.as_slice() {
+            [Param::Variable(name), Param::Bare(eq), values @ ..] if eq == "=" => {
+                (variable_name(evaluator, name), values)
+            }
+            _ => {
+                return Err(BuclError::RuntimeError(
+                    "const: expected 'const {name} = value ...'".into(),
+                ))
+            }
+        };
+        evaluator.check_writable(&name)?;
+        let values: Vec<String> = evaluator
+            .eval_params_with_names(values)
+            .into_iter()
+            .map(|a| a.value)
+            .collect();
+        if values.len() > 1 {
+            evaluator.set_array(&name, values);
+        } else {
+            evaluator.set_var(&name, values.concat());
+        }
+        evaluator.readonly.insert(name);
+        Ok(None)
+    }
+
+    fn names_variables(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Assign a variable and make it read-only: `const {name} = value`."
+    }
+}
+
+// ---------------------------------------------------------------------------
+// readonly
+// ---------------------------------------------------------------------------
+
+pub struct Readonly;
+
+impl BuclFunction for Readonly {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        _args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let mut names = Vec::new();
+        for param in &evaluator.call_params {
+            let Param::Variable(name) = param else {
+                return Err(BuclError::RuntimeError(
+                    "readonly: expected variables, e.g. readonly {config}".into(),
+                ));
+            };
+            names.push(variable_name(evaluator, name));
+        }
+        evaluator.readonly.extend(names);
+        Ok(None)
+    }
+
+    fn allows_duplicate_names(&self) -> bool {
+        true
+    }
+
+    fn names_variables(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Make variables read-only; later assignments are errors: `readonly {name} ...`."
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Registration
+// ---------------------------------------------------------------------------
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("const", Const);
+    eval.register("readonly", Readonly);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    fn run(eval: &mut Evaluator, source: &str) -> crate::error::Result<()> {
+        eval.evaluate_statements(&crate::parser::parse(source).unwrap())
+    }
+
+    #[test]
+    fn test_constants_reject_assignment() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        run(&mut eval, "const {pi} = \"3.14159\"\n{cfg/port} = \"80\"\nreadonly {cfg}\n").unwrap();
+        assert_eq!(eval.resolve_var("pi"), "3.14159");
+
+        assert!(run(&mut eval, "{pi} = \"3\"\n").is_err());
+        assert!(run(&mut eval, "{pi} math \"1 + 1\"\n").is_err());
+        assert!(run(&mut eval, "{cfg/port} = \"8080\"\n").is_err());
+        assert!(run(&mut eval, "push {pi} \"x\"\n").is_err());
+        assert!(run(&mut eval, "const {pi} = \"3\"\n").is_err());
+        assert_eq!(eval.resolve_var("pi"), "3.14159");
+        assert_eq!(eval.resolve_var("cfg/port"), "80");
+        assert!(run(&mut eval, "each \"a\" \"b\" parallel:\"2\"\n    {pi} = \"3\"\n").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+/// `copyvar` — deep-copy a variable with all its sub-variables.
+///
+/// `{dst} = {src}` only copies the root value; `copyvar` also copies every
+/// `{src/...}` sub-variable (array items, struct fields, metadata) to
+/// `{dst/...}`.  Whatever `{dst}` held before, sub-variables included, is
+/// replaced.
+///
+/// ```bucl
+/// {db/host} = "localhost"
+/// {db/port} = "5432"
+/// {backup} copyvar {db}
+/// echo {backup/port}   # 5432
+/// ```
+use crate::ast::{Param, Statement};
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct CopyVar;
+
+impl BuclFunction for CopyVar {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        target: Option<&str>,
+        _args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let dst = target.ok_or_else(|| {
+            BuclError::RuntimeError("copyvar: expected a target, e.g. {dst} copyvar {src}".into())
+        })?;
+        let [Param::Variable(src)] = evaluator.call_params.as_slice() else {
+            return Err(BuclError::RuntimeError(
+                "copyvar: expected one variable to copy, e.g. {dst} copyvar {src}".into(),
+            ));
+        };
+        let src = if src.contains('{') {
+            evaluator.interpolate(src)
+        } else {
+            src.clone()
+        };
+
+        let subs = evaluator.subtree(&src);
+        let root = evaluator.variables.get(&src).cloned();
+        if root.is_none() && subs.is_empty() {
+            return Err(BuclError::RuntimeError(format!("copyvar: '{}' is not set", src)));
+        }
+
+        evaluator.remove_tree(dst);
+        evaluator.set_var(dst, root.unwrap_or_default());
+        for (rest, value) in subs {
+            evaluator.variables.insert(format!("{}/{}", dst, rest), value);
+        }
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Copy a variable with all its sub-variables: `{dst} copyvar {src}`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("copyvar", CopyVar);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_copyvar_copies_structure_and_replaces_target() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{list} = \"a\" \"b\" \"c\"\n",
+            "{list/meta/owner} = \"me\"\n",
+            "{copy/stale} = \"old\"\n",
+            "{copy} copyvar {list}\n",
+            "{list/1} = \"changed\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("copy"), "abc");
+        assert_eq!(eval.resolve_var("copy/count"), "3");
+        assert_eq!(eval.resolve_var("copy/1"), "b");
+        assert_eq!(eval.resolve_var("copy/meta/owner"), "me");
+        assert!(!eval.var_exists("copy/stale"));
+    }
+}
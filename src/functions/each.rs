@@ -19,11 +19,64 @@
 /// {output} = "total items: {e/count}"
 /// ```
 ///
+/// Passing a single struct-like variable (one with named sub-variables, such
+/// as `{db/host}` and `{db/port}`) iterates its fields in name order, and
+/// `{e/key}` additionally holds the current field name:
+///
+/// ```bucl
+/// {db/host} = "localhost"
+/// {db/port} = "5432"
+/// {f} each {db}
+///     echo "{f/key} = {f/value}"
+/// ```
+///
+/// A trailing `by:"char"` iterates the characters of the arguments instead,
+/// and `by:"line"` their lines:
+///
+/// ```bucl
+/// {c} each {word} by:"char"
+///     echo "{c/index}: {c/value}"
+/// {l} each {report} by:"line"
+///     echo "> {l/value}"
+/// ```
+///
+/// `each range from to [step]` iterates an inclusive integer range, with the
+/// same step rules as `repeat from to [step]`:
+///
+/// ```bucl
+/// {n} each range 1 10 3
+///     echo "{n/value}"        # 1, 4, 7, 10
+/// ```
+///
+/// `parallel:"N"` runs the block on up to N threads (native builds only; on
+/// WASM it runs sequentially).  Each iteration works on its own copy of the
+/// variables, so assignments made in the block are discarded when it ends;
+/// output and assertion results are kept, in item order.  Use it for
+/// independent, I/O-bound work:
+///
+/// ```bucl
+/// {f} each {files} parallel:"4"
+///     {text} readfile {f/value}
+///     echo "{f/value}: {text/length}"
+/// ```
+///
 /// If no target is given, the prefix defaults to `e`.
-use crate::ast::Statement;
-use crate::error::Result;
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+
+use crate::ast::{Param, Statement};
+use crate::error::{BuclError, Result};
 use crate::evaluator::Evaluator;
-use crate::functions::BuclFunction;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::evaluator::{AssertFailure, StackFrame};
+#[cfg(all(feature = "random", not(target_arch = "wasm32")))]
+use crate::functions::random::SeededRng;
+use crate::functions::repeat::Range;
+use crate::functions::{take_options, BuclFunction};
 
 pub struct Each;
 
@@ -37,6 +90,21 @@ impl BuclFunction for Each {
         _continuation: Option<&Statement>,
     ) -> Result<Option<String>> {
         let prefix = target.unwrap_or("e");
+        let mut args = args;
+        let (options, n_params) = parse_options(evaluator, &mut args)?;
+        let params = &evaluator.call_params[..n_params];
+        let is_range = matches!(params.first(), Some(Param::Bare(kw)) if kw == "range");
+        let (keys, args) = if is_range {
+            let range = Range::parse("each range", &args[1..])?;
+            (None, range.iter().map(|n| n.to_string()).collect())
+        } else if let Some(mode) = &options.by {
+            (None, split_items(&args, mode)?)
+        } else if let Some(fields) = struct_fields(evaluator, params) {
+            let (keys, values): (Vec<String>, Vec<String>) = fields.into_iter().unzip();
+            (Some(keys), values)
+        } else {
+            (None, args)
+        };
         let count = args.len();
 
         // Populate the target variable with all items before iterating so the
@@ -58,22 +126,316 @@ impl BuclFunction for Each {
                 .insert(format!("{}/{}", prefix, i), item.clone());
         }
 
-        if let Some(block) = block {
-            for (i, item) in args.iter().enumerate() {
-                evaluator
-                    .variables
-                    .insert(format!("{}/index", prefix), i.to_string());
-                evaluator
-                    .variables
-                    .insert(format!("{}/value", prefix), item.clone());
-                evaluator.evaluate_statements(block)?;
+        let Some(block) = block else {
+            return Ok(None);
+        };
+        match options.parallel {
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(workers) => {
+                run_parallel(evaluator, prefix, &args, keys.as_deref(), block, workers)?
+            }
+            _ => {
+                for (i, item) in args.iter().enumerate() {
+                    let key = keys.as_ref().map(|k| k[i].as_str());
+                    set_item(evaluator, prefix, i, item, key);
+                    evaluator.evaluate_statements(block)?;
+                }
             }
         }
 
         Ok(None) // Everything already stored directly.
     }
+
+    fn description(&self) -> &str {
+        "Run the block once for every argument, array item or struct field: `{e} each {items}`."
+    }
+}
+
+/// Set `{prefix/index}`, `{prefix/value}` and (for struct fields)
+/// `{prefix/key}` for item `i`.
+fn set_item(evaluator: &mut Evaluator, prefix: &str, i: usize, item: &str, key: Option<&str>) {
+    evaluator
+        .variables
+        .insert(format!("{}/index", prefix), i.to_string());
+    evaluator
+        .variables
+        .insert(format!("{}/value", prefix), item.to_string());
+    if let Some(key) = key {
+        evaluator
+            .variables
+            .insert(format!("{}/key", prefix), key.to_string());
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Options
+// ---------------------------------------------------------------------------
+
+/// Trailing `name:"value"` options of an `each` call.
+#[derive(Default)]
+struct Options {
+    /// `by:"char"` / `by:"line"`.
+    by: Option<String>,
+    /// `parallel:"N"` — number of worker threads.
+    parallel: Option<usize>,
+}
+
+/// Read the trailing options of the current call and drop them from `args`.
+/// Also returns how many parameters precede them.
+fn parse_options(evaluator: &Evaluator, args: &mut Vec<String>) -> Result<(Options, usize)> {
+    let mut options = Options::default();
+    let (found, n_params) = take_options(evaluator, args, &["by", "parallel"]);
+    for (name, value) in found {
+        if name == "by" {
+            options.by = Some(value);
+            continue;
+        }
+        let workers = value.parse().ok().filter(|&n: &usize| n > 0).ok_or_else(|| {
+            BuclError::RuntimeError(format!(
+                "each: parallel expects a positive number of workers, got '{}'",
+                value
+            ))
+        })?;
+        options.parallel = Some(workers);
+    }
+    Ok((options, n_params))
+}
+
+/// Split every argument into characters or lines, in order.
+fn split_items(args: &[String], mode: &str) -> Result<Vec<String>> {
+    match mode {
+        "char" => Ok(args.iter().flat_map(|a| a.chars()).map(String::from).collect()),
+        "line" => Ok(args.iter().flat_map(|a| a.lines()).map(String::from).collect()),
+        other => Err(BuclError::RuntimeError(format!(
+            "each: unknown mode 'by:{}' (expected \"char\" or \"line\")",
+            other
+        ))),
+    }
+}
+
+/// The `(name, value)` fields when the only argument is a struct-like
+/// variable, in name order.
+fn struct_fields(evaluator: &Evaluator, params: &[Param]) -> Option<Vec<(String, String)>> {
+    let [Param::Variable(name)] = params else {
+        return None;
+    };
+    let name = if name.contains('{') {
+        evaluator.interpolate(name)
+    } else {
+        name.clone()
+    };
+    if name.contains('/') {
+        return None;
+    }
+    let fields = evaluator.expanded_fields(&name);
+    (!fields.is_empty()).then_some(fields)
+}
+
+// ---------------------------------------------------------------------------
+// Parallel iteration
+// ---------------------------------------------------------------------------
+
+/// What a parallel iteration hands back to the parent.
+#[cfg(not(target_arch = "wasm32"))]
+struct Outcome {
+    output: Vec<String>,
+    loaded_functions: HashMap<String, String>,
+    asserts_passed: usize,
+    assert_failures: Vec<AssertFailure>,
+    error_trace: Vec<StackFrame>,
+    result: Result<()>,
+}
+
+/// Run `block` once per item on up to `workers` threads, each iteration in
+/// its own copy of the variable store (see [`Evaluator::parallel_child`]).
+///
+/// Output, assertion results and loaded functions are merged back in item
+/// order.  Variable assignments are not: they only live for their
+/// iteration.  After an error no new iterations start, and the error of the
+/// first failing item is returned once the running ones finish.
+#[cfg(not(target_arch = "wasm32"))]
+fn run_parallel(
+    evaluator: &mut Evaluator,
+    prefix: &str,
+    items: &[String],
+    keys: Option<&[String]>,
+    block: &[Statement],
+    workers: usize,
+) -> Result<()> {
+    // Seeded runs stay reproducible: every item gets its own seed, drawn in
+    // item order from the parent's generator.
+    #[cfg(feature = "random")]
+    let seeds: Vec<Option<u64>> = items
+        .iter()
+        .map(|_| evaluator.rng.as_mut().map(SeededRng::next_u64))
+        .collect();
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let parent: &Evaluator = evaluator;
+
+    let mut outcomes: Vec<(usize, Outcome)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers.min(items.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::SeqCst);
+                        if i >= items.len() || failed.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        let mut child = parent.parallel_child();
+                        #[cfg(feature = "random")]
+                        {
+                            child.rng = seeds[i].map(SeededRng::new);
+                        }
+                        set_item(&mut child, prefix, i, &items[i], keys.map(|k| k[i].as_str()));
+                        let result = child.evaluate_statements(block);
+                        child.flush_output();
+                        if result.is_err() {
+                            failed.store(true, Ordering::SeqCst);
+                        }
+                        done.push((
+                            i,
+                            Outcome {
+                                output: child.captures.pop().unwrap_or_default(),
+                                loaded_functions: child.loaded_functions,
+                                asserts_passed: child.asserts_passed,
+                                assert_failures: child.assert_failures,
+                                error_trace: child.error_trace,
+                                result,
+                            },
+                        ));
+                    }
+                    done
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("each: parallel worker panicked"))
+            .collect()
+    });
+    outcomes.sort_by_key(|(i, _)| *i);
+
+    for (_, outcome) in outcomes {
+        for line in outcome.output {
+            evaluator.emit(line);
+        }
+        evaluator.loaded_functions.extend(outcome.loaded_functions);
+        evaluator.asserts_passed += outcome.asserts_passed;
+        evaluator.assert_failures.extend(outcome.assert_failures);
+        if outcome.result.is_err() {
+            evaluator.error_trace = outcome.error_trace;
+            return outcome.result;
+        }
+    }
+    Ok(())
 }
 
 pub fn register(eval: &mut Evaluator) {
     eval.register("each", Each);
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_each_iterates_struct_fields() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{db/port} = \"5432\"\n",
+            "{db/host} = \"localhost\"\n",
+            "{out} = \"\"\n",
+            "{f} each {db}\n",
+            "    {out} = \"{out}{f/index}:{f/key}={f/value};\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("out"), "0:host=localhost;1:port=5432;");
+        assert_eq!(eval.resolve_var("f/count"), "2");
+    }
+
+    #[test]
+    fn test_each_walks_array_with_named_helper() {
+        // Like {stdin}, which has a {stdin/lines} helper next to its items.
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.set_array("input", vec!["a".into(), "b".into()]);
+        eval.variables.insert("input/lines".into(), "2".into());
+        let stmts = crate::parser::parse("{l} each {input}\n    {last} = {l/value}\n").unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("l/count"), "2");
+        assert_eq!(eval.resolve_var("last"), "b");
+    }
+
+    #[test]
+    fn test_each_by_char_and_line() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{word} = \"héj\"\n",
+            "{chars} = \"\"\n",
+            "{c} each {word} by:\"char\"\n",
+            "    {chars} = \"{chars}[{c/value}]\"\n",
+            "{text} = \"one\\ntwo\"\n",
+            "{l} each {text} by:\"line\"\n",
+            "    {last} = {l/value}\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("chars"), "[h][é][j]");
+        assert_eq!(eval.resolve_var("c/count"), "3");
+        assert_eq!(eval.resolve_var("l/count"), "2");
+        assert_eq!(eval.resolve_var("last"), "two");
+    }
+
+    #[test]
+    fn test_parallel_keeps_item_order_and_isolates_variables() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.output_sink = Some(std::sync::Arc::new(|_: &str| {}));
+        let stmts = crate::parser::parse(concat!(
+            "{x} = \"outer\"\n",
+            "{e} each range 1 20 parallel:\"4\"\n",
+            "    {x} = {e/value}\n",
+            "    echo {x}\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        let expected: Vec<String> = (1..=20).map(|n| n.to_string()).collect();
+        assert_eq!(eval.output_buffer, expected);
+        assert_eq!(eval.resolve_var("x"), "outer");
+
+        let failing = crate::parser::parse(concat!(
+            "each \"a\" \"b\" \"c\" parallel:\"2\"\n",
+            "    echo {e/value}\n",
+            "    if {e/value} = \"b\"\n",
+            "        nosuchfunction\n",
+        ))
+        .unwrap();
+        eval.output_buffer.clear();
+        assert!(eval.evaluate_statements(&failing).is_err());
+        assert_eq!(eval.output_buffer[..2], ["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn test_parallel_blocks_stay_strict() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.output_sink = Some(std::sync::Arc::new(|_: &str| {}));
+        eval.strict = true;
+        let stmts = crate::parser::parse(concat!(
+            "each \"a\" \"b\" parallel:\"2\"\n",
+            "    echo \"x{nmae}x\"\n",
+        ))
+        .unwrap();
+        let err = eval.evaluate_statements(&stmts).unwrap_err().to_string();
+        assert!(err.contains("{nmae} is not set"), "{}", err);
+    }
+}
@@ -0,0 +1,109 @@
+/// `eachline` — run a block for every line of a file, without reading the
+/// whole file into memory.
+///
+/// During each iteration:
+/// - `{l/index}` — 0-based line number.
+/// - `{l/value}` — the line, without its `\n` / `\r\n` ending.
+///
+/// Afterwards `{l}` and `{l/count}` hold the number of lines read.
+///
+/// ```bucl
+/// {errors} = "0"
+/// {l} eachline "app.log"
+///     if {l/value} like "*ERROR*"
+///         {errors} math "{errors} + 1"
+/// ```
+///
+/// On native builds the file is read through a buffered reader, so memory use
+/// stays flat however large it is.  Other [`FileSystem`](crate::fs::FileSystem)s
+/// may read it in one go.  Named param `{path}` may be used instead of the
+/// positional argument.  If no target is given, the prefix defaults to `l`.
+use crate::ast::Statement;
+use crate::error::Result;
+use crate::evaluator::Evaluator;
+use crate::functions::{bind_args, ArgSpec, BuclFunction};
+use crate::permissions::Permission;
+
+pub struct EachLine;
+
+impl BuclFunction for EachLine {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        target: Option<&str>,
+        args: Vec<String>,
+        block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let prefix = target.unwrap_or("l");
+        let [path] = bind_args(evaluator, "eachline", &args, [ArgSpec::required("path")])?;
+
+        evaluator.permissions.require(Permission::ReadFiles, "eachline", &path)?;
+        let mut reader = evaluator.fs.open(&path)?;
+        let mut line = String::new();
+        let mut count = 0usize;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            let value = line.strip_suffix('\n').unwrap_or(&line);
+            let value = value.strip_suffix('\r').unwrap_or(value);
+            evaluator
+                .variables
+                .insert(format!("{}/index", prefix), count.to_string());
+            evaluator
+                .variables
+                .insert(format!("{}/value", prefix), value.to_string());
+            count += 1;
+            if let Some(block) = block {
+                evaluator.evaluate_statements(block)?;
+            }
+        }
+
+        evaluator.set_var(prefix, count.to_string());
+        evaluator
+            .variables
+            .insert(format!("{}/count", prefix), count.to_string());
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Run the block for every line of a file without reading it all into memory: `{l} eachline \"log.txt\"`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("eachline", EachLine);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::evaluator::Evaluator;
+    use crate::fs::MemoryFs;
+
+    #[test]
+    fn test_eachline_visits_every_line() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.insert("app.log", "ok\r\nERROR one\nok\nERROR two");
+        let mut eval = Evaluator::new();
+        eval.fs = fs;
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{errors} = \"\"\n",
+            "{l} eachline \"app.log\"\n",
+            "    if {l/value} like \"ERROR*\"\n",
+            "        {errors} = \"{errors}{l/index};\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("errors"), "1;3;");
+        assert_eq!(eval.resolve_var("l/count"), "4");
+    }
+}
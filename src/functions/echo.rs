@@ -1,25 +1,40 @@
-// `echo` — print one or more values to standard output.
-//
-// All arguments are joined with a single space and emitted as one line.
-// On native: buffered in output_buffer and printed to stdout immediately.
-// On WASM:   each line is sent to the JS host via js_print, which streams
-//            it to the main thread in real time via postMessage.
-
-// WASM: imported from the Web Worker (see docs/demo/wasm/worker.js).
-#[cfg(target_arch = "wasm32")]
-extern "C" {
-    fn js_print(ptr: *const u8, len: usize);
-}
-
+/// `echo` — print one or more values to standard output.
+///
+/// All arguments are joined with a single space and emitted as one line via
+/// [`Evaluator::emit`]: buffered in `output_buffer`, then printed to stdout
+/// on native or forwarded to the host's output sink (see `bucl_run_streaming`).
+///
+/// ```bucl
+/// echo "Hello!"
+/// echo "x =" {x}
+/// ```
+///
+/// Trailing `sep:"…"` and `end:"…"` options replace the separator and the
+/// line ending.  An `end` without a newline leaves the line open, so the
+/// next output continues it:
+///
+/// ```bucl
+/// echo "a" "b" "c" sep:", "      # a, b, c
+/// echo "Loading" end:""
+/// echo "..."                     # Loading...
+/// ```
+///
+/// `print` is `echo` with `end:""`: it writes its arguments without ending
+/// the line, for progress indicators and output built up piece by piece.
+///
+/// ```bucl
+/// {i} repeat 3
+///     print "."
+/// echo " done"                   # ... done
+/// ```
 use crate::ast::Statement;
 use crate::error::Result;
 use crate::evaluator::Evaluator;
-use crate::functions::BuclFunction;
+use crate::functions::{take_options, BuclFunction};
 
 pub struct Echo;
 
 impl BuclFunction for Echo {
-    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
     fn call(
         &self,
         evaluator: &mut Evaluator,
@@ -28,20 +43,84 @@ impl BuclFunction for Echo {
         _block: Option<&[Statement]>,
         _continuation: Option<&Statement>,
     ) -> Result<Option<String>> {
-        let value = args.join(" ");
-        #[cfg(target_arch = "wasm32")]
-        unsafe {
-            js_print(value.as_ptr(), value.len());
-        }
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            evaluator.output_buffer.push(value.clone());
-            println!("{}", value);
-        }
+        write(evaluator, args, "\n");
         Ok(None)
     }
+
+    fn description(&self) -> &str {
+        "Print the arguments, joined with spaces, as one line: `echo \"hello\" {name}`."
+    }
+}
+
+pub struct Print;
+
+impl BuclFunction for Print {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        write(evaluator, args, "");
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Print the arguments without ending the line: `print \"loading...\"`."
+    }
+}
+
+/// Join `args` with the `sep` option (default a space) and write them
+/// followed by the `end` option (default `default_end`).  The line is only
+/// finished when `end` ends with a newline.
+fn write(evaluator: &mut Evaluator, mut args: Vec<String>, default_end: &str) {
+    let mut sep = " ".to_string();
+    let mut end = default_end.to_string();
+    for (name, value) in take_options(evaluator, &mut args, &["sep", "end"]).0 {
+        match name.as_str() {
+            "sep" => sep = value,
+            _ => end = value,
+        }
+    }
+    let text = args.join(&sep) + &end;
+    match text.strip_suffix('\n') {
+        Some(line) => evaluator.emit(line.to_string()),
+        None => evaluator.emit_partial(&text),
+    }
 }
 
 pub fn register(eval: &mut Evaluator) {
     eval.register("echo", Echo);
+    eval.register("print", Print);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_print_and_echo_options() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "echo \"a\" \"b\" \"c\" sep:\", \"\n",
+            "print \"x\"\n",
+            "print \"y\" \"z\" sep:\"-\"\n",
+            "echo \"!\"\n",
+            "{out} capture\n",
+            "    echo \"open\" end:\"\"\n",
+            "echo \"tab\" \"sep\" sep:\"\\t\" end:\";\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        eval.flush_output();
+        assert_eq!(eval.output_buffer, vec!["a, b, c", "xy-z!", "tab\tsep;"]);
+        assert_eq!(eval.resolve_var("out"), "open");
+    }
 }
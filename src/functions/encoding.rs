@@ -0,0 +1,317 @@
+/// `bytelen` / `encode` / `decode` / `isutf8` — byte-level views of text.
+///
+/// BUCL values are text, so bytes travel as uppercase hex (`"C3A9"`), the
+/// same digits `tohex` produces.
+///
+/// `bytelen` counts the bytes of its arguments (concatenated, like `=`) in
+/// an encoding — UTF-8 unless `encoding:"…"` says otherwise — for payloads
+/// with a length header:
+///
+/// ```bucl
+/// {body} = "héllo"
+/// {n} bytelen {body}                     # 6
+/// {n} bytelen {body} encoding:"latin1"   # 5
+/// echo "Content-Length: {n}"
+/// ```
+///
+/// `encode` turns text into hex bytes in an encoding, `decode` turns hex
+/// bytes back into text, and `isutf8` checks whether hex bytes are valid
+/// UTF-8 (`"1"` / `"0"`):
+///
+/// ```bucl
+/// {raw} encode "latin1" "café"     # 636166E9
+/// {text} decode "latin1" {raw}     # café
+/// {ok} isutf8 {raw}                # 0 — E9 alone is not UTF-8
+/// ```
+///
+/// Encodings: `utf-8`, `latin1` (ISO-8859-1), `utf-16le` and `utf-16be`;
+/// names ignore case and `-`/`_`, so `UTF8` and `iso-8859-1` work too.
+/// Text with a character the encoding can't represent (`€` in latin1),
+/// malformed hex, and bytes that don't decode are runtime errors.
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::{take_options, BuclFunction};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Utf8,
+    Latin1,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    fn parse(name: &str) -> Option<Self> {
+        let name: String = name
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_'))
+            .flat_map(char::to_lowercase)
+            .collect();
+        match name.as_str() {
+            "utf8" => Some(Encoding::Utf8),
+            "latin1" | "iso88591" => Some(Encoding::Latin1),
+            "utf16le" => Some(Encoding::Utf16Le),
+            "utf16be" => Some(Encoding::Utf16Be),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "utf-8",
+            Encoding::Latin1 => "latin1",
+            Encoding::Utf16Le => "utf-16le",
+            Encoding::Utf16Be => "utf-16be",
+        }
+    }
+
+    /// `text` as bytes, or the first character that has no encoding.
+    fn encode(self, text: &str) -> std::result::Result<Vec<u8>, char> {
+        match self {
+            Encoding::Utf8 => Ok(text.as_bytes().to_vec()),
+            Encoding::Latin1 => text
+                .chars()
+                .map(|c| u8::try_from(u32::from(c)).map_err(|_| c))
+                .collect(),
+            Encoding::Utf16Le => Ok(text.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+            Encoding::Utf16Be => Ok(text.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> std::result::Result<String, String> {
+        match self {
+            Encoding::Utf8 => String::from_utf8(bytes.to_vec()).map_err(|e| {
+                format!("not valid UTF-8 at byte {}", e.utf8_error().valid_up_to())
+            }),
+            Encoding::Latin1 => Ok(bytes.iter().map(|&b| char::from(b)).collect()),
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                if !bytes.len().is_multiple_of(2) {
+                    return Err(format!(
+                        "{} bytes is not a whole number of UTF-16 units",
+                        bytes.len()
+                    ));
+                }
+                let units = bytes.chunks(2).map(|pair| {
+                    let pair = [pair[0], pair[1]];
+                    if self == Encoding::Utf16Le {
+                        u16::from_le_bytes(pair)
+                    } else {
+                        u16::from_be_bytes(pair)
+                    }
+                });
+                char::decode_utf16(units)
+                    .collect::<std::result::Result<String, _>>()
+                    .map_err(|e| {
+                        format!("unpaired UTF-16 surrogate {:04X}", e.unpaired_surrogate())
+                    })
+            }
+        }
+    }
+}
+
+fn encoding_arg(fname: &str, name: &str) -> Result<Encoding> {
+    Encoding::parse(name).ok_or_else(|| {
+        BuclError::RuntimeError(format!(
+            "{}: unknown encoding '{}' (expected utf-8, latin1, utf-16le or utf-16be)",
+            fname, name
+        ))
+    })
+}
+
+fn encode(fname: &str, encoding: Encoding, text: &str) -> Result<Vec<u8>> {
+    encoding.encode(text).map_err(|c| {
+        BuclError::RuntimeError(format!(
+            "{}: '{}' can't be represented in {}",
+            fname,
+            c,
+            encoding.name()
+        ))
+    })
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+fn from_hex(fname: &str, hex: &str) -> Result<Vec<u8>> {
+    let digits: Vec<char> = hex.chars().filter(|c| !c.is_whitespace()).collect();
+    let invalid = || BuclError::RuntimeError(format!("{}: '{}' is not hex bytes", fname, hex));
+    if !digits.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let high = pair[0].to_digit(16).ok_or_else(invalid)?;
+            let low = pair[1].to_digit(16).ok_or_else(invalid)?;
+            Ok((high * 16 + low) as u8)
+        })
+        .collect()
+}
+
+/// The `[encoding, value]` arguments of `encode` / `decode`.
+fn two_args<'a>(fname: &str, args: &'a [String]) -> Result<(&'a str, &'a str)> {
+    match args {
+        [encoding, value] => Ok((encoding, value)),
+        _ => Err(BuclError::RuntimeError(format!(
+            "{}: expected an encoding and a value, got {} arguments",
+            fname,
+            args.len()
+        ))),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Built-ins
+// ---------------------------------------------------------------------------
+
+pub struct ByteLen;
+
+impl BuclFunction for ByteLen {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        mut args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let encoding = match take_options(evaluator, &mut args, &["encoding"]).0.pop() {
+            Some((_, name)) => encoding_arg("bytelen", &name)?,
+            None => Encoding::Utf8,
+        };
+        let text = evaluator
+            .named_arg("text")
+            .cloned()
+            .unwrap_or_else(|| args.concat());
+        Ok(Some(encode("bytelen", encoding, &text)?.len().to_string()))
+    }
+
+    fn description(&self) -> &str {
+        "The number of bytes a text takes in an encoding (UTF-8 by default): `{n} bytelen {body} encoding:\"latin1\"`."
+    }
+}
+
+pub struct Encode;
+
+impl BuclFunction for Encode {
+    fn call(
+        &self,
+        _evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (encoding, text) = two_args("encode", &args)?;
+        let encoding = encoding_arg("encode", encoding)?;
+        Ok(Some(to_hex(&encode("encode", encoding, text)?)))
+    }
+
+    fn description(&self) -> &str {
+        "Encode text as uppercase hex bytes: `{raw} encode \"latin1\" \"café\"`."
+    }
+}
+
+pub struct Decode;
+
+impl BuclFunction for Decode {
+    fn call(
+        &self,
+        _evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (encoding, hex) = two_args("decode", &args)?;
+        let encoding = encoding_arg("decode", encoding)?;
+        let bytes = from_hex("decode", hex)?;
+        encoding
+            .decode(&bytes)
+            .map(Some)
+            .map_err(|e| BuclError::RuntimeError(format!("decode: {}", e)))
+    }
+
+    fn description(&self) -> &str {
+        "Decode uppercase hex bytes back to text: `{text} decode \"latin1\" {raw}`."
+    }
+}
+
+pub struct IsUtf8;
+
+impl BuclFunction for IsUtf8 {
+    fn call(
+        &self,
+        _evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let bytes = from_hex("isutf8", &args.concat())?;
+        let valid = std::str::from_utf8(&bytes).is_ok();
+        Ok(Some(if valid { "1" } else { "0" }.to_string()))
+    }
+
+    fn description(&self) -> &str {
+        "Whether hex bytes are valid UTF-8 (`1` or `0`): `{ok} isutf8 \"C3A9\"`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("bytelen", ByteLen);
+    eval.register("encode", Encode);
+    eval.register("decode", Decode);
+    eval.register("isutf8", IsUtf8);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::run_script;
+
+    #[test]
+    fn test_byte_lengths_and_transcoding() {
+        let (eval, result) = run_script(concat!(
+            "{s} = \"héllo €\"\n",
+            "{utf8} bytelen {s}\n",
+            "{utf16} bytelen {s} encoding:\"UTF-16LE\"\n",
+            "{latin} bytelen \"café\" encoding:\"iso-8859-1\"\n",
+            "{raw} encode \"latin1\" \"café\"\n",
+            "{back} decode \"Latin-1\" {raw}\n",
+            "{be} encode \"utf-16be\" \"é€\"\n",
+            "{be_back} decode \"utf-16be\" {be}\n",
+            "{valid} isutf8 \"63 61 66 c3 a9\"\n",
+            "{invalid} isutf8 {raw}\n",
+        ));
+        result.unwrap();
+        for (name, value) in [
+            ("utf8", "10"),
+            ("utf16", "14"),
+            ("latin", "4"),
+            ("raw", "636166E9"),
+            ("back", "café"),
+            ("be", "00E920AC"),
+            ("be_back", "é€"),
+            ("valid", "1"),
+            ("invalid", "0"),
+        ] {
+            assert_eq!(eval.resolve_var(name), value, "{}", name);
+        }
+
+        for (source, message) in [
+            ("{n} bytelen \"€\" encoding:\"latin1\"", "bytelen: '€' can't be represented in latin1"),
+            ("{s} decode \"utf-8\" \"C3\"", "decode: not valid UTF-8 at byte 0"),
+            ("{s} decode \"utf-8\" \"C3A\"", "decode: 'C3A' is not hex bytes"),
+            ("{s} encode \"ebcdic\" \"x\"", "encode: unknown encoding 'ebcdic'"),
+        ] {
+            let err = run_script(source).1.unwrap_err().to_string();
+            assert!(err.contains(message), "{}: {}", source, err);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+/// `error` — abort the script with a custom error.
+///
+/// The arguments are joined with a single space, like `echo`, and become the
+/// message of a runtime error.  Library `.bucl` functions use it to reject
+/// bad input with a message the caller can act on.
+///
+/// ```bucl
+/// if {path} = ""
+///     error "config missing: {path}"
+/// ```
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct Error;
+
+impl BuclFunction for Error {
+    fn call(
+        &self,
+        _evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let message = if args.is_empty() {
+            "error raised by script".to_string()
+        } else {
+            args.join(" ")
+        };
+        Err(BuclError::RuntimeError(message))
+    }
+
+    fn description(&self) -> &str {
+        "Stop the script with a runtime error carrying the message: `error \"bad input\"`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("error", Error);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_error_aborts_with_message() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{path} = \"app.toml\"\n",
+            "error \"config missing:\" {path}\n",
+            "{after} = \"ran\"\n",
+        ))
+        .unwrap();
+        let err = eval.evaluate_statements(&stmts).unwrap_err();
+        assert_eq!(err.to_string(), "Runtime error: config missing: app.toml");
+        assert!(!eval.var_exists("after"));
+    }
+}
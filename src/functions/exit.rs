@@ -0,0 +1,70 @@
+/// `exit` — stop the script with an exit code.
+///
+/// Evaluation stops at once, as if the script had ended.  The code
+/// (default `0`) becomes the exit status of the `bucl` process; WASM hosts
+/// see it in the run result.  A non-zero code tells the caller the script
+/// failed.
+///
+/// ```bucl
+/// if {config/ok} = "false"
+///     echo "bad config"
+///     exit 3
+/// ```
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::{bind_args, ArgSpec, BuclFunction};
+
+pub struct Exit;
+
+impl BuclFunction for Exit {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        if args.len() > 1 {
+            return Err(BuclError::RuntimeError(format!(
+                "exit: expected at most one exit code, got {} arguments",
+                args.len()
+            )));
+        }
+        let [code] = bind_args(evaluator, "exit", &args, [ArgSpec::optional("code", "0")])?;
+        let code = code.trim().parse().map_err(|_| {
+            BuclError::RuntimeError(format!("exit: '{}' is not a valid exit code", code))
+        })?;
+        Err(BuclError::Exit(code))
+    }
+
+    fn description(&self) -> &str {
+        "Stop the script with an exit code (default 0): `exit 3`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("exit", Exit);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::error::BuclError;
+    use crate::functions::run_script;
+
+    #[test]
+    fn test_exit_stops_with_code() {
+        let (eval, result) = run_script("echo before\nif 1 = 1\n    exit 3\necho after\n");
+        assert!(matches!(result, Err(BuclError::Exit(3))));
+        assert_eq!(eval.output_buffer, vec!["before"]);
+
+        assert!(matches!(run_script("exit\n").1, Err(BuclError::Exit(0))));
+        assert!(matches!(run_script("exit code:\"4\"\n").1, Err(BuclError::Exit(4))));
+        assert!(matches!(run_script("exit x\n").1, Err(BuclError::RuntimeError(_))));
+    }
+}
@@ -0,0 +1,81 @@
+/// `functions` — the names of every function a script can call.
+///
+/// Returns the built-ins, the embedded `.bucl` functions and those found in
+/// `functions/` directories as one array in name order — the same list
+/// `bucl functions` prints.  A `.bucl` file shadowed by a built-in of the
+/// same name is listed once.
+///
+/// ```bucl
+/// {fns} functions
+/// echo {fns/count}
+/// {e} each {fns}
+///     echo {e}
+/// ```
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct Functions;
+
+impl BuclFunction for Functions {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        if !args.is_empty() {
+            return Err(BuclError::RuntimeError(
+                "functions: takes no arguments, e.g. {fns} functions".into(),
+            ));
+        }
+        let names: Vec<String> = evaluator
+            .callable_functions()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        match target {
+            Some(prefix) => {
+                evaluator.set_array(prefix, names);
+                Ok(None)
+            }
+            None => Ok(Some(names.concat())),
+        }
+    }
+
+    fn description(&self) -> &str {
+        "The names of every callable function, as an array: `{fns} functions`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("functions", Functions);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_functions_lists_builtins_and_bucl_functions() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.embed_function("greet", "echo \"hi\"\n");
+        eval.embed_function("reverse", "{return} = {0}\n");
+        let stmts = crate::parser::parse("{fns} functions\n").unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+
+        let fns = eval.array_items("fns");
+        assert!(fns.windows(2).all(|w| w[0] < w[1]), "{:?}", fns);
+        for name in ["echo", "functions", "greet", "reverse"] {
+            assert!(fns.iter().any(|f| f == name), "{} missing from {:?}", name, fns);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+/// `fuzzyfilter` — keep the items that fuzzily match a query, best first.
+///
+/// Arguments: one or more items, then the query as the last argument.
+/// An item matches when every character of the query appears in it in order
+/// (case-insensitive), like the file pickers of most editors.  Matches are
+/// ranked by score:
+/// - consecutive matched characters score higher than scattered ones;
+/// - matches at the start of the item or of a word (after space, `_`, `-`,
+///   `/`, `.`) get a bonus;
+/// - unmatched characters before the first match cost a little.
+///
+/// Items with equal scores keep their original order.  An empty query
+/// returns every item unchanged.
+///
+/// The target becomes an array of the matching items (`{t/count}`,
+/// `{t/0}`, `{t/1}`, …).
+///
+/// ```bucl
+/// {files} = "src/main.rs" "README.md" "src/evaluator.rs" "Makefile"
+/// {hits} fuzzyfilter {files} "mf"
+/// # {hits/count} = "1", {hits/0} = "Makefile"
+///
+/// {query} = "ev"
+/// {hits} fuzzyfilter {files} {query}
+/// ```
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct FuzzyFilter;
+
+impl BuclFunction for FuzzyFilter {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        target: Option<&str>,
+        mut args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        // Named param: {query} = "mf"; {hits} fuzzyfilter {files} {query}
+        let query = match evaluator.named_arg("query") {
+            Some(q) => {
+                let q = q.clone();
+                // The named query was passed positionally too; drop it.
+                if let Some(pos) = args.iter().rposition(|a| *a == q) {
+                    args.remove(pos);
+                }
+                q
+            }
+            None => args.pop().ok_or_else(|| {
+                BuclError::RuntimeError("fuzzyfilter: expected items followed by a query".into())
+            })?,
+        };
+
+        let mut scored: Vec<(i64, String)> = args
+            .into_iter()
+            .filter_map(|item| fuzzy_score(&item, &query).map(|s| (s, item)))
+            .collect();
+        // Stable sort: equal scores keep their input order.
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        let matches: Vec<String> = scored.into_iter().map(|(_, item)| item).collect();
+
+        match target {
+            Some(prefix) => {
+                evaluator.set_array(prefix, matches);
+                Ok(None)
+            }
+            None => Ok(Some(matches.concat())),
+        }
+    }
+
+    fn description(&self) -> &str {
+        "Keep the items that fuzzily match a query, best first: `{hits} fuzzyfilter {files} \"mf\"`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("fuzzyfilter", FuzzyFilter);
+}
+
+// ---------------------------------------------------------------------------
+// Scoring
+// ---------------------------------------------------------------------------
+
+const MATCH: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 24;
+const WORD_START_BONUS: i64 = 20;
+const LEADING_GAP_PENALTY: i64 = 1;
+
+/// Score `item` against `query`, or `None` if the query is not a
+/// (case-insensitive) subsequence of the item.
+fn fuzzy_score(item: &str, query: &str) -> Option<i64> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let mut score = 0i64;
+    let mut qi = 0;
+    let mut prev_matched = false;
+    let mut prev_char: Option<char> = None;
+    let mut first_match: Option<usize> = None;
+
+    for (i, c) in item.chars().enumerate() {
+        if qi < query.len() && c.to_lowercase().eq(std::iter::once(query[qi])) {
+            score += MATCH;
+            if prev_matched {
+                score += CONSECUTIVE_BONUS;
+            }
+            let word_start = match prev_char {
+                None => true,
+                Some(p) => matches!(p, ' ' | '_' | '-' | '/' | '.'),
+            };
+            if word_start {
+                score += WORD_START_BONUS;
+            }
+            first_match.get_or_insert(i);
+            qi += 1;
+            prev_matched = true;
+        } else {
+            prev_matched = false;
+        }
+        prev_char = Some(c);
+    }
+
+    if qi < query.len() {
+        return None;
+    }
+    Some(score - first_match.unwrap_or(0) as i64 * LEADING_GAP_PENALTY)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_subsequence_does_not_match() {
+        assert_eq!(fuzzy_score("Makefile", "xyz"), None);
+        assert_eq!(fuzzy_score("abc", "cba"), None);
+    }
+
+    #[test]
+    fn test_match_is_case_insensitive() {
+        assert!(fuzzy_score("Makefile", "MF").is_some());
+        assert!(fuzzy_score("makefile", "mf").is_some());
+    }
+
+    #[test]
+    fn test_consecutive_beats_scattered() {
+        let tight = fuzzy_score("evaluator", "eva").unwrap();
+        let loose = fuzzy_score("e_v_a", "eva").unwrap();
+        assert!(tight > loose);
+    }
+
+    #[test]
+    fn test_word_start_beats_mid_word() {
+        let start = fuzzy_score("src/main.rs", "m").unwrap();
+        let middle = fuzzy_score("README.md", "m").unwrap();
+        assert!(start > middle);
+    }
+}
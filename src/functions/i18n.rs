@@ -0,0 +1,510 @@
+/// `t` / `tload` — translated messages from per-locale catalogs.
+///
+/// `t "key"` looks `key` up in the catalog of the locale named by the
+/// `{locale}` variable (default `en`) and returns the translation with its
+/// `{placeholders}` filled in:
+///
+/// ```bucl
+/// # locales/de.json: { "greeting": "Hallo {name}!", "cart": { "items": "{0} Artikel" } }
+/// {locale} = "de"
+/// {name} = "Bob"
+/// {msg} t "greeting"              # Hallo Bob!
+/// {msg} t "cart.items" "3"        # 3 Artikel
+/// {msg} t "greeting" {user/name}  # named params win over variables
+/// ```
+///
+/// Placeholders are resolved from the named arguments first, then `{0}`,
+/// `{1}`, … from the arguments after the key, then from variables in scope.
+///
+/// A region-specific locale falls back to its language (`de-AT` → `de`),
+/// then to `en`; a key found nowhere is returned as-is, so untranslated
+/// scripts still print something readable.
+///
+/// Catalogs are loaded on first use from `locales/<locale>.json` or
+/// `locales/<locale>.toml` (relative to the script's directory, then the
+/// CWD), through the evaluator's filesystem and subject to its read
+/// permission.  A locale containing `/`, `\` or `..` is an error rather
+/// than a path.  Nested JSON objects and TOML tables become dotted keys.
+/// Where the host has no files to offer, a catalog can be loaded from text
+/// instead:
+///
+/// ```bucl
+/// {src} jscall "fetchText" "locales/fr.json"
+/// tload "fr" {src}
+/// ```
+///
+/// `tload` guesses the format from the first non-blank character (`{` means
+/// JSON, anything else TOML) and replaces any catalog already loaded for
+/// that locale.
+use std::collections::HashMap;
+
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+use crate::permissions::Permission;
+
+const DEFAULT_LOCALE: &str = "en";
+
+pub struct Translate;
+
+impl BuclFunction for Translate {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (key, positional) = args
+            .split_first()
+            .ok_or_else(|| BuclError::RuntimeError("t: expected a message key".into()))?;
+
+        let mut locale = evaluator.resolve_var("locale");
+        if locale.is_empty() {
+            locale = DEFAULT_LOCALE.to_string();
+        }
+
+        let mut template = None;
+        for candidate in fallback_chain(&locale) {
+            ensure_loaded(evaluator, &candidate)?;
+            if let Some(msg) = evaluator.catalogs.get(&candidate).and_then(|c| c.get(key)) {
+                template = Some(msg.clone());
+                break;
+            }
+        }
+
+        let template = template.unwrap_or_else(|| key.clone());
+        Ok(Some(fill_placeholders(evaluator, &template, positional)))
+    }
+
+    fn description(&self) -> &str {
+        "Look a message up in the catalog of `{locale}` and fill its placeholders: `{msg} t \"greeting\"`."
+    }
+}
+
+pub struct TLoad;
+
+impl BuclFunction for TLoad {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (locale, source) = match args.as_slice() {
+            [locale, rest @ ..] if !rest.is_empty() => (locale.clone(), rest.join("")),
+            _ => {
+                return Err(BuclError::RuntimeError(
+                    "tload: expected a locale and the catalog text".into(),
+                ))
+            }
+        };
+        let catalog = parse_catalog(&source)
+            .map_err(|e| BuclError::RuntimeError(format!("tload: {}: {}", locale, e)))?;
+        evaluator.catalogs.insert(locale, catalog);
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Load a locale's message catalog from JSON or TOML text: `tload \"fr\" {src}`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("t", Translate);
+    eval.register("tload", TLoad);
+}
+
+// ---------------------------------------------------------------------------
+// Lookup
+// ---------------------------------------------------------------------------
+
+/// `de-AT` → `["de-AT", "de", "en"]`, without duplicates.
+fn fallback_chain(locale: &str) -> Vec<String> {
+    let mut chain = vec![locale.to_string()];
+    if let Some((lang, _)) = locale.split_once(['-', '_']) {
+        chain.push(lang.to_string());
+    }
+    if !chain.iter().any(|l| l == DEFAULT_LOCALE) {
+        chain.push(DEFAULT_LOCALE.to_string());
+    }
+    chain
+}
+
+/// Load the catalog for `locale` from disk unless it is already present.
+///
+/// A missing file is not an error — the locale simply has no translations —
+/// but an unreadable or malformed one is.
+fn ensure_loaded(evaluator: &mut Evaluator, locale: &str) -> Result<()> {
+    if evaluator.catalogs.contains_key(locale) {
+        return Ok(());
+    }
+
+    let catalog = read_catalog_file(evaluator, locale)?;
+    evaluator.catalogs.insert(locale.to_string(), catalog);
+    Ok(())
+}
+
+/// Find and parse `locales/<locale>.json|toml`; empty if there is none.
+fn read_catalog_file(evaluator: &Evaluator, locale: &str) -> Result<Catalog> {
+    if locale.contains(['/', '\\']) || locale.contains("..") {
+        return Err(BuclError::RuntimeError(format!("t: invalid locale '{}'", locale)));
+    }
+
+    let mut dirs = Vec::new();
+    if let Some(base) = &evaluator.base_dir {
+        dirs.push(base.join("locales").to_string_lossy().into_owned());
+    }
+    dirs.push("locales".to_string());
+
+    for dir in dirs {
+        for ext in ["json", "toml"] {
+            let path = format!("{}/{}.{}", dir, locale, ext);
+            if !evaluator.fs.exists(&path) {
+                continue;
+            }
+            evaluator.permissions.require(Permission::ReadFiles, "t", &path)?;
+            let source = evaluator.fs.read_to_string(&path)?;
+            let parsed = if ext == "json" {
+                parse_json_catalog(&source)
+            } else {
+                parse_toml_catalog(&source)
+            };
+            return parsed.map_err(|e| BuclError::RuntimeError(format!("t: {}: {}", path, e)));
+        }
+    }
+    Ok(HashMap::new())
+}
+
+/// Replace `{name}` placeholders in a translated message.
+fn fill_placeholders(evaluator: &Evaluator, template: &str, positional: &[String]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = &rest[start + 1..start + len];
+        let value = match evaluator.named_arg(name) {
+            Some(v) => v.clone(),
+            None => match name.parse::<usize>() {
+                Ok(i) => positional.get(i).cloned().unwrap_or_default(),
+                Err(_) => evaluator.interpolate(&rest[start..=start + len]),
+            },
+        };
+        out.push_str(&value);
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Catalog parsing
+// ---------------------------------------------------------------------------
+
+type Catalog = HashMap<String, String>;
+
+fn parse_catalog(source: &str) -> std::result::Result<Catalog, String> {
+    if source.trim_start().starts_with('{') {
+        parse_json_catalog(source)
+    } else {
+        parse_toml_catalog(source)
+    }
+}
+
+/// Parse a JSON object of strings (nested objects allowed) into dotted keys.
+/// Numbers and booleans are kept as their literal text.
+fn parse_json_catalog(source: &str) -> std::result::Result<Catalog, String> {
+    let mut parser = JsonParser { chars: source.chars().collect(), pos: 0 };
+    let mut catalog = HashMap::new();
+    parser.skip_ws();
+    parser.object("", &mut catalog)?;
+    parser.skip_ws();
+    if parser.pos < parser.chars.len() {
+        return Err(parser.error("trailing characters after the catalog"));
+    }
+    Ok(catalog)
+}
+
+struct JsonParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl JsonParser {
+    fn error(&self, msg: &str) -> String {
+        format!("invalid JSON at character {}: {}", self.pos + 1, msg)
+    }
+
+    fn skip_ws(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> std::result::Result<(), String> {
+        self.skip_ws();
+        if self.chars.get(self.pos) == Some(&c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    fn object(&mut self, prefix: &str, out: &mut Catalog) -> std::result::Result<(), String> {
+        self.expect('{')?;
+        self.skip_ws();
+        if self.chars.get(self.pos) == Some(&'}') {
+            self.pos += 1;
+            return Ok(());
+        }
+        loop {
+            self.skip_ws();
+            let key = self.string()?;
+            let full_key = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+            self.expect(':')?;
+            self.skip_ws();
+            match self.chars.get(self.pos) {
+                Some('{') => self.object(&full_key, out)?,
+                Some('"') => {
+                    let value = self.string()?;
+                    out.insert(full_key, value);
+                }
+                Some(_) => {
+                    let start = self.pos;
+                    while self
+                        .chars
+                        .get(self.pos)
+                        .is_some_and(|c| c.is_ascii_alphanumeric() || "+-.".contains(*c))
+                    {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        return Err(self.error("expected a string, number, or object"));
+                    }
+                    out.insert(full_key, self.chars[start..self.pos].iter().collect());
+                }
+                None => return Err(self.error("unexpected end of input")),
+            }
+            self.skip_ws();
+            match self.chars.get(self.pos) {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> std::result::Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            let c = *self
+                .chars
+                .get(self.pos)
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += 1;
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let esc = *self
+                        .chars
+                        .get(self.pos)
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    match esc {
+                        'n' => s.push('\n'),
+                        't' => s.push('\t'),
+                        'r' => s.push('\r'),
+                        'b' => s.push('\u{8}'),
+                        'f' => s.push('\u{c}'),
+                        'u' => s.push(self.unicode_escape()?),
+                        other => s.push(other),
+                    }
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    /// Decode the hex digits after `\u`, combining surrogate pairs.
+    fn unicode_escape(&mut self) -> std::result::Result<char, String> {
+        let hex4 = |p: &mut Self| -> std::result::Result<u32, String> {
+            let digits: String = p.chars.get(p.pos..p.pos + 4).unwrap_or(&[]).iter().collect();
+            p.pos += 4;
+            u32::from_str_radix(&digits, 16).map_err(|_| p.error("bad \\u escape"))
+        };
+        let hi = hex4(self)?;
+        let code = if (0xD800..0xDC00).contains(&hi) && self.chars.get(self.pos..self.pos + 2)
+            == Some(&['\\', 'u'])
+        {
+            self.pos += 2;
+            let lo = hex4(self)?;
+            0x10000 + ((hi - 0xD800) << 10) + (lo.wrapping_sub(0xDC00) & 0x3FF)
+        } else {
+            hi
+        };
+        Ok(char::from_u32(code).unwrap_or('\u{FFFD}'))
+    }
+}
+
+/// Parse the TOML subset catalogs need: `key = "value"` lines, `[table]`
+/// headers (which prefix the following keys), and `#` comments.
+fn parse_toml_catalog(source: &str) -> std::result::Result<Catalog, String> {
+    let mut catalog = HashMap::new();
+    let mut table = String::new();
+    for (i, raw) in source.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let err = |msg: &str| format!("invalid TOML on line {}: {}", i + 1, msg);
+
+        if let Some(header) = line.strip_prefix('[') {
+            let name = header.strip_suffix(']').ok_or_else(|| err("unclosed table header"))?;
+            table = name.trim().to_string();
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or_else(|| err("expected key = \"value\""))?;
+        let key = key.trim().trim_matches('"');
+        let value = value.trim();
+        let value = if let Some(quoted) = value.strip_prefix('"') {
+            let end = closing_quote(quoted).ok_or_else(|| err("unterminated string"))?;
+            unescape_toml(&quoted[..end])
+        } else {
+            // Bare numbers/booleans, minus any trailing comment.
+            value.split('#').next().unwrap_or("").trim().to_string()
+        };
+        let full_key = if table.is_empty() { key.to_string() } else { format!("{}.{}", table, key) };
+        catalog.insert(full_key, value);
+    }
+    Ok(catalog)
+}
+
+/// Byte index of the first unescaped `"` in `s`.
+fn closing_quote(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+fn unescape_toml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_catalog_flattens_objects() {
+        let catalog = parse_json_catalog(
+            r#"{ "greeting": "Hallo {name}!", "cart": { "items": "{0} Artikel", "max": 9 },
+                 "quote": "\"ä\" 😀" }"#,
+        )
+        .unwrap();
+        assert_eq!(catalog["greeting"], "Hallo {name}!");
+        assert_eq!(catalog["cart.items"], "{0} Artikel");
+        assert_eq!(catalog["cart.max"], "9");
+        assert_eq!(catalog["quote"], "\"ä\" 😀");
+        assert!(parse_json_catalog(r#"{ "a": "b" "#).is_err());
+    }
+
+    #[test]
+    fn test_parse_toml_catalog() {
+        let catalog = parse_toml_catalog(
+            "# German\ngreeting = \"Hallo {name}!\"\n\n[cart]\nitems = \"{0} \\\"Artikel\\\"\" # note\n",
+        )
+        .unwrap();
+        assert_eq!(catalog["greeting"], "Hallo {name}!");
+        assert_eq!(catalog["cart.items"], "{0} \"Artikel\"");
+        assert!(parse_toml_catalog("just words").is_err());
+    }
+
+    #[test]
+    fn test_lookup_with_fallback_and_placeholders() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let catalog = |pairs: &[(&str, &str)]| {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+        eval.catalogs.insert("de".into(), catalog(&[("hi", "Hallo {name}, {0}!")]));
+        eval.catalogs.insert("en".into(), catalog(&[("bye", "Bye")]));
+        let stmts = crate::parser::parse(concat!(
+            "{locale} = \"de-AT\"\n",
+            "{name} = \"Bob\"\n",
+            "{a} t \"hi\" \"willkommen\"\n",
+            "{b} t \"bye\"\n",
+            "{c} t \"missing.key\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("a"), "Hallo Bob, willkommen!");
+        assert_eq!(eval.resolve_var("b"), "Bye");
+        assert_eq!(eval.resolve_var("c"), "missing.key");
+    }
+
+    #[test]
+    fn test_catalog_files_go_through_fs_and_permissions() {
+        use crate::fs::MemoryFs;
+        use crate::permissions::Permissions;
+        use std::sync::Arc;
+
+        let files = MemoryFs::new();
+        files.insert("locales/fr.toml", "hi = \"Salut\"\n");
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.fs = Arc::new(files);
+        let run = |eval: &mut Evaluator, source: &str| {
+            eval.evaluate_statements(&crate::parser::parse(source).unwrap())
+        };
+
+        run(&mut eval, "{locale} = \"fr\"\n{a} t \"hi\"\n").unwrap();
+        assert_eq!(eval.resolve_var("a"), "Salut");
+
+        let err = run(&mut eval, "{locale} = \"../../etc/x\"\n{b} t \"hi\"\n").unwrap_err();
+        assert!(err.to_string().contains("invalid locale"), "{}", err);
+
+        eval.catalogs.clear();
+        eval.permissions = Permissions::sandboxed();
+        let err = run(&mut eval, "{locale} = \"fr\"\n{c} t \"hi\"\n").unwrap_err();
+        assert!(err.to_string().contains("reading files is not permitted"), "{}", err);
+    }
+}
@@ -1,4 +1,4 @@
-/// `if` / `elseif` / `else` — conditional execution.
+/// `if` / `elseif` (or `elif`) / `else` — conditional execution.
 ///
 /// Condition syntax: `<lhs> <op> <rhs>`
 ///
@@ -9,11 +9,26 @@
 /// - `<`  — less than
 /// - `>=` — greater than or equal
 /// - `<=` — less than or equal
+/// - `~=` — numerically equal within a tolerance (`0.1+0.2 ~= 0.3`); string
+///   equality when either side isn't a number
+/// - `matches` — the left side matches a regular expression (feature `regex`)
+/// - `like` — the left side matches a glob, where `*` is any run of
+///   characters and `?` any single character
 ///
 /// For `>`, `<`, `>=`, `<=`: if both sides parse as numbers the comparison is
 /// numeric (integer or decimal); otherwise it falls back to lexicographic
 /// string comparison.
 ///
+/// A quoted `matches` pattern is not interpolated, so its braces are regex
+/// syntax (`"^[0-9]{4}$"`); pass `{pattern}` to use a variable's value.
+///
+/// `exists {var}` is true when the variable is set — even to `""` — and
+/// `not exists {var}` when it isn't.  A plain `{var} = ""` can't tell the
+/// two apart.
+///
+/// After `strictfloat off` every operator treats two numbers within the
+/// tolerance as equal, so `=` behaves like `~=` (see `strictfloat`).
+///
 /// ```bucl
 /// if {x} = "hello"
 ///     {output} = "Got hello"
@@ -23,10 +38,12 @@
 ///     {output} = "Got something else"
 /// ```
 ///
-/// `elseif` shares the same implementation as `if`.
-/// `else` simply runs its block unconditionally.
-use crate::ast::Statement;
-use crate::error::Result;
+/// `elseif` shares the same implementation as `if`; `elif` is another
+/// name for it.  `else` simply runs its block unconditionally.  Either
+/// must follow an `if` or `elseif` at the same indentation, or the script
+/// doesn't parse.
+use crate::ast::{Param, Statement};
+use crate::error::{BuclError, Result};
 use crate::evaluator::Evaluator;
 use crate::functions::BuclFunction;
 
@@ -34,18 +51,44 @@ use crate::functions::BuclFunction;
 // Condition evaluation
 // ---------------------------------------------------------------------------
 
-fn evaluate_condition(lhs: &str, op: &str, rhs: &str) -> bool {
-    match op {
-        "=" => lhs == rhs,
-        "!=" => lhs != rhs,
+/// Tolerance used by `~=`, and by every operator after `strictfloat off`,
+/// unless another is given.
+pub const DEFAULT_FLOAT_TOLERANCE: f64 = 1e-9;
+
+/// Evaluate `<lhs> <op> <rhs>`.  `tolerance` is the evaluator's
+/// [`float_tolerance`](Evaluator::float_tolerance): `None` compares numbers
+/// exactly (except for `~=`).  Fails only for an invalid `matches` pattern.
+pub(crate) fn evaluate_condition(
+    lhs: &str,
+    op: &str,
+    rhs: &str,
+    tolerance: Option<f64>,
+) -> Result<bool> {
+    let numbers = lhs.trim().parse::<f64>().ok().zip(rhs.trim().parse::<f64>().ok());
+    Ok(match op {
+        "~=" => match numbers {
+            Some((l, r)) => approx_eq(l, r, tolerance.unwrap_or(DEFAULT_FLOAT_TOLERANCE)),
+            None => lhs == rhs,
+        },
+        "=" | "!=" => {
+            let equal = match (numbers, tolerance) {
+                (Some((l, r)), Some(eps)) => approx_eq(l, r, eps),
+                _ => lhs == rhs,
+            };
+            equal == (op == "=")
+        }
         ">" | "<" | ">=" | "<=" => {
             // Prefer numeric comparison; fall back to lexicographic.
-            if let (Ok(l), Ok(r)) = (lhs.parse::<f64>(), rhs.parse::<f64>()) {
+            if let Some((l, r)) = numbers {
+                let ordering = match tolerance {
+                    Some(eps) if approx_eq(l, r, eps) => std::cmp::Ordering::Equal,
+                    _ => l.total_cmp(&r),
+                };
                 match op {
-                    ">"  => l > r,
-                    "<"  => l < r,
-                    ">=" => l >= r,
-                    "<=" => l <= r,
+                    ">"  => ordering.is_gt(),
+                    "<"  => ordering.is_lt(),
+                    ">=" => ordering.is_ge(),
+                    "<=" => ordering.is_le(),
                     _    => unreachable!(),
                 }
             } else {
@@ -58,7 +101,87 @@ fn evaluate_condition(lhs: &str, op: &str, rhs: &str) -> bool {
                 }
             }
         }
+        "matches" => regex_match(lhs, rhs)?,
+        "like" => glob_match(lhs, rhs),
         _ => false,
+    })
+}
+
+/// The right-hand side of the current `<lhs> <op> <rhs>` call: the evaluated
+/// `rhs`, except that a quoted `matches` pattern is taken uninterpolated.
+pub(crate) fn condition_rhs(evaluator: &Evaluator, op: &str, rhs: &str) -> String {
+    match evaluator.call_params.get(2) {
+        Some(Param::Quoted(raw, _)) if op == "matches" => raw.clone(),
+        _ => rhs.to_string(),
+    }
+}
+
+#[cfg(feature = "regex")]
+fn regex_match(value: &str, pattern: &str) -> Result<bool> {
+    let re = regex::Regex::new(pattern).map_err(|e| {
+        BuclError::RuntimeError(format!("matches: invalid pattern '{}': {}", pattern, e))
+    })?;
+    Ok(re.is_match(value))
+}
+
+#[cfg(not(feature = "regex"))]
+fn regex_match(_value: &str, _pattern: &str) -> Result<bool> {
+    Err(BuclError::RuntimeError(
+        "matches: regular expressions need the `regex` feature".into(),
+    ))
+}
+
+/// Whole-string glob match: `*` matches any run of characters (including
+/// none), `?` exactly one; everything else matches itself.
+fn glob_match(value: &str, pattern: &str) -> bool {
+    let value: Vec<char> = value.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    let (mut v, mut p) = (0, 0);
+    // Position of the last `*` seen and the value position it was tried at.
+    let mut star: Option<(usize, usize)> = None;
+    while v < value.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, v));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == value[v] => {
+                v += 1;
+                p += 1;
+            }
+            _ => match star {
+                // Let the last `*` swallow one more character and retry.
+                Some((star_p, star_v)) => {
+                    star = Some((star_p, star_v + 1));
+                    p = star_p + 1;
+                    v = star_v + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// `a` and `b` differ by at most `eps`, scaled up for magnitudes above 1.
+fn approx_eq(a: f64, b: f64, eps: f64) -> bool {
+    (a - b).abs() <= eps * a.abs().max(b.abs()).max(1.0)
+}
+
+/// `exists {var}` / `not exists {var}`, checked against the raw parameters of
+/// the current call since the evaluated argument is `""` either way.
+/// `None` when the condition has another form.
+fn exists_condition(evaluator: &Evaluator) -> Option<bool> {
+    match evaluator.call_params.as_slice() {
+        [Param::Bare(kw), Param::Variable(name)] if kw == "exists" => {
+            Some(evaluator.var_exists(name))
+        }
+        [Param::Bare(not), Param::Bare(kw), Param::Variable(name)]
+            if not == "not" && kw == "exists" =>
+        {
+            Some(!evaluator.var_exists(name))
+        }
+        _ => None,
     }
 }
 
@@ -77,8 +200,12 @@ impl BuclFunction for IfFn {
         block: Option<&[Statement]>,
         continuation: Option<&Statement>,
     ) -> Result<Option<String>> {
-        let condition = match args.as_slice() {
-            [lhs, op, rhs] => evaluate_condition(lhs, op, rhs),
+        let condition = match (exists_condition(evaluator), args.as_slice()) {
+            (Some(exists), _) => exists,
+            (None, [lhs, op, rhs]) => {
+                let rhs = condition_rhs(evaluator, op, rhs);
+                evaluate_condition(lhs, op, &rhs, evaluator.float_tolerance)?
+            }
             _ => false,
         };
 
@@ -92,6 +219,10 @@ impl BuclFunction for IfFn {
 
         Ok(None)
     }
+
+    fn description(&self) -> &str {
+        "Run the block when a condition holds; chain with `elseif` / `else`: `if {n} > 3`."
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -114,14 +245,143 @@ impl BuclFunction for ElseFn {
         }
         Ok(None)
     }
+
+    fn description(&self) -> &str {
+        "Run the block when no earlier `if` / `elseif` branch ran."
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Registration
 // ---------------------------------------------------------------------------
 
+/// `strictfloat on|off [tolerance]` — choose exact or tolerant number
+/// comparison for `if`, `elseif` and `assert`.
+///
+/// ```bucl
+/// {sum} math "0.1 + 0.2"
+/// strictfloat off          # numbers within 1e-9 (relative) compare equal
+/// if {sum} = "0.3"
+///     echo "equal"
+/// strictfloat off 0.01     # custom tolerance
+/// strictfloat on           # back to exact comparison (the default)
+/// ```
+///
+/// Embedders can set [`Evaluator::float_tolerance`] directly.  The setting
+/// applies to the script and the `.bucl` functions it calls.
+pub struct StrictFloat;
+
+impl BuclFunction for StrictFloat {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        evaluator.float_tolerance = match args.as_slice() {
+            [mode] if mode == "on" => None,
+            [mode] if mode == "off" => Some(DEFAULT_FLOAT_TOLERANCE),
+            [mode, eps] if mode == "off" => match eps.trim().parse::<f64>() {
+                Ok(eps) if eps >= 0.0 && eps.is_finite() => Some(eps),
+                _ => {
+                    return Err(BuclError::RuntimeError(format!(
+                        "strictfloat: tolerance must be a non-negative number, got '{}'",
+                        eps
+                    )))
+                }
+            },
+            _ => {
+                return Err(BuclError::RuntimeError(
+                    "strictfloat: expected 'on', 'off' or 'off <tolerance>'".into(),
+                ))
+            }
+        };
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Compare numbers exactly (`on`) or within a tolerance (`off [tolerance]`) in `if` and `assert`."
+    }
+}
+
 pub fn register(eval: &mut Evaluator) {
     eval.register("if", IfFn);
     eval.register("elseif", IfFn); // identical logic
+    eval.register("elif", IfFn);
     eval.register("else", ElseFn);
+    eval.register("strictfloat", StrictFloat);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tolerant_comparison() {
+        let sum = format!("{}", 0.1 + 0.2);
+        assert!(!evaluate_condition(&sum, "=", "0.3", None).unwrap());
+        assert!(evaluate_condition(&sum, "~=", "0.3", None).unwrap());
+        assert!(!evaluate_condition("0.31", "~=", "0.3", None).unwrap());
+        assert!(evaluate_condition("abc", "~=", "abc", None).unwrap());
+
+        let eps = Some(DEFAULT_FLOAT_TOLERANCE);
+        assert!(evaluate_condition(&sum, "=", "0.3", eps).unwrap());
+        assert!(!evaluate_condition(&sum, "!=", "0.3", eps).unwrap());
+        assert!(!evaluate_condition(&sum, ">", "0.3", eps).unwrap());
+        assert!(evaluate_condition(&sum, "<=", "0.3", eps).unwrap());
+        assert!(evaluate_condition("1e12", "~=", "1000000000000.0001", None).unwrap());
+    }
+
+    #[test]
+    fn test_exists_distinguishes_unset_from_empty() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{empty} = \"\"\n",
+            "{a} = \"no\"\n",
+            "{b} = \"no\"\n",
+            "if exists {empty}\n",
+            "    {a} = \"yes\"\n",
+            "if exists {missing}\n",
+            "    {b} = \"yes\"\n",
+            "elseif not exists {missing}\n",
+            "    {b} = \"unset\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("a"), "yes");
+        assert_eq!(eval.resolve_var("b"), "unset");
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("notes.txt", "*.txt"));
+        assert!(glob_match("a.txt", "?.txt"));
+        assert!(glob_match("abcbc", "a*bc"));
+        assert!(glob_match("", "*"));
+        assert!(!glob_match("notes.txt.bak", "*.txt"));
+        assert!(!glob_match("ab.txt", "?.txt"));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_matches_keeps_quoted_pattern_braces() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{year} = \"2024\"\n",
+            "{ok} = \"no\"\n",
+            "if {year} matches \"^[0-9]{4}$\"\n",
+            "    {ok} = \"yes\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("ok"), "yes");
+    }
 }
@@ -0,0 +1,179 @@
+/// `imageinfo` — read the dimensions and format of an image file.
+///
+/// Only the file header is read, so this is cheap even for large images.
+/// Recognised formats: PNG, JPEG, GIF, BMP, WebP, TIFF, ICO.
+///
+/// The target variable becomes a struct:
+/// - `{img}`        — `"<width>x<height>"`.
+/// - `{img/width}`  — width in pixels.
+/// - `{img/height}` — height in pixels.
+/// - `{img/format}` — lowercase format name (`png`, `jpeg`, …).
+///
+/// ```bucl
+/// {img} imageinfo "photo.jpg"
+/// echo "{img/format}: {img/width} by {img/height}"
+/// if {img/width} > "1920"
+///     echo "too wide"
+/// ```
+///
+/// The file is read through the evaluator's filesystem, so WASM builds read
+/// it from the host.  Unreadable files and unsupported or corrupted images
+/// are runtime errors.  Only available when built with the `imageinfo`
+/// cargo feature.
+use crate::evaluator::Evaluator;
+
+#[cfg(feature = "imageinfo")]
+mod enabled {
+    use std::io::{ErrorKind, Read};
+
+    use imagesize::{ImageError, ImageType};
+
+    use crate::ast::Statement;
+    use crate::error::{BuclError, Result};
+    use crate::evaluator::Evaluator;
+    use crate::functions::{bind_args, ArgSpec, BuclFunction};
+    use crate::permissions::Permission;
+
+    pub struct ImageInfo;
+
+    impl BuclFunction for ImageInfo {
+        fn call(
+            &self,
+            evaluator: &mut Evaluator,
+            target: Option<&str>,
+            args: Vec<String>,
+            _block: Option<&[Statement]>,
+            _continuation: Option<&Statement>,
+        ) -> Result<Option<String>> {
+            // Named param: {path} = "photo.jpg"; {img} imageinfo {path}
+            let [path] = bind_args(evaluator, "imageinfo", &args, [ArgSpec::required("path")])?;
+
+            // A header shorter than the format needs surfaces as an EOF I/O
+            // error; report it like any other unrecognised file.
+            let image_err = |e: ImageError| match e {
+                ImageError::IoError(io) if io.kind() != ErrorKind::UnexpectedEof => {
+                    BuclError::IoError(io)
+                }
+                ImageError::IoError(_) | ImageError::NotSupported => BuclError::RuntimeError(
+                    format!("imageinfo: '{}' is not a supported image", path),
+                ),
+                other => BuclError::RuntimeError(format!("imageinfo: '{}': {}", path, other)),
+            };
+
+            evaluator.permissions.require(Permission::ReadFiles, "imageinfo", &path)?;
+            let mut reader = evaluator.fs.open(&path)?;
+
+            // Read more of the file only while the header is cut short; some
+            // formats (JPEG with large metadata) put the size well past 4 KiB.
+            let mut header = Vec::new();
+            let mut want = 4096;
+            let (kind, size) = loop {
+                reader.by_ref().take((want - header.len()) as u64).read_to_end(&mut header)?;
+                let at_eof = header.len() < want;
+                let parsed = imagesize::image_type(&header)
+                    .and_then(|kind| Ok((kind, imagesize::blob_size(&header)?)));
+                match parsed {
+                    Err(ImageError::IoError(e))
+                        if e.kind() == ErrorKind::UnexpectedEof && !at_eof =>
+                    {
+                        want *= 2
+                    }
+                    parsed => break parsed.map_err(image_err)?,
+                }
+            };
+
+            let dims = format!("{}x{}", size.width, size.height);
+            let Some(prefix) = target else {
+                return Ok(Some(dims));
+            };
+            evaluator.set_var(prefix, dims);
+            evaluator
+                .variables
+                .insert(format!("{}/width", prefix), size.width.to_string());
+            evaluator
+                .variables
+                .insert(format!("{}/height", prefix), size.height.to_string());
+            evaluator
+                .variables
+                .insert(format!("{}/format", prefix), format_name(kind).to_string());
+
+            Ok(None) // Everything already stored directly.
+        }
+
+        fn description(&self) -> &str {
+            "Read the width, height and format of an image file: `{img} imageinfo \"photo.jpg\"`."
+        }
+    }
+
+    fn format_name(kind: ImageType) -> &'static str {
+        match kind {
+            ImageType::Png => "png",
+            ImageType::Jpeg => "jpeg",
+            ImageType::Gif => "gif",
+            ImageType::Bmp => "bmp",
+            ImageType::Webp => "webp",
+            ImageType::Tiff => "tiff",
+            ImageType::Ico => "ico",
+            _ => "unknown",
+        }
+    }
+
+    pub fn register(eval: &mut Evaluator) {
+        eval.register("imageinfo", ImageInfo);
+    }
+
+    // -----------------------------------------------------------------------
+    // Tests
+    // -----------------------------------------------------------------------
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::Arc;
+
+        use crate::evaluator::Evaluator;
+        use crate::fs::MemoryFs;
+        use crate::functions::run_script_in;
+        use crate::permissions::Permissions;
+
+        /// An evaluator whose filesystem holds a 3×2 GIF header and a text file.
+        fn with_files() -> Evaluator {
+            let fs = MemoryFs::new();
+            fs.insert("pics/a.gif", "GIF89a\x03\x00\x02\x00\x00\x00\x00");
+            fs.insert("notes.txt", "not an image");
+            let mut eval = Evaluator::new();
+            eval.fs = Arc::new(fs);
+            eval
+        }
+
+        #[test]
+        fn test_reads_header_through_evaluator_fs() {
+            let (eval, result) = run_script_in(with_files(), "{img} imageinfo \"pics/a.gif\"");
+            result.unwrap();
+            for (name, value) in [
+                ("img", "3x2"),
+                ("img/width", "3"),
+                ("img/height", "2"),
+                ("img/format", "gif"),
+            ] {
+                assert_eq!(eval.resolve_var(name), value, "{}", name);
+            }
+
+            let err = run_script_in(with_files(), "{img} imageinfo \"notes.txt\"").1.unwrap_err();
+            assert!(err.to_string().contains("imageinfo: 'notes.txt' is not a supported image"), "{}", err);
+        }
+
+        #[test]
+        fn test_sandbox_denies_reading() {
+            let mut eval = with_files();
+            eval.permissions = Permissions::sandboxed();
+            let err = run_script_in(eval, "{img} imageinfo \"pics/a.gif\"").1.unwrap_err();
+            assert!(err.to_string().contains("imageinfo: reading files is not permitted"), "{}", err);
+        }
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    #[cfg(feature = "imageinfo")]
+    enabled::register(eval);
+    let _ = eval; // suppress unused warning without the feature
+}
@@ -0,0 +1,123 @@
+/// `jscall` — call a function provided by the JavaScript host (WASM only).
+///
+/// The first argument names the host function; the remaining arguments are
+/// passed to it as strings.  Its return value becomes the result.
+///
+/// ```bucl
+/// {now} jscall "now"
+/// {page} jscall "fetchText" "https://example.com/data.txt"
+/// jscall "log" "hello from BUCL"
+/// ```
+///
+/// On WASM this goes through the imported
+/// `js_call(name_ptr, name_len, arg_ptr, arg_len) -> *mut u8`.  The
+/// arguments arrive as a UTF-8 JSON array of strings, so the host can simply
+/// `JSON.parse` them.  The host writes its reply into a buffer obtained from
+/// `bucl_alloc` and returns the pointer; the reply is laid out as
+/// (integers u32 little-endian):
+///
+/// ```text
+/// [status][len][len bytes of UTF-8]
+/// ```
+///
+/// where status 0 means the bytes are the result, 1 means the host function
+/// threw (the bytes are the message), and 2 means no such host function is
+/// registered.  A null pointer is treated as an empty result.  The buffer is
+/// freed here.  See `docs/demo/wasm/worker.js` for the demo's host
+/// functions, and `js/bucl-host.js` for wasm-bindgen builds.
+///
+/// On native builds there is no JavaScript host, so `jscall` is a runtime
+/// error.
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct JsCall;
+
+impl BuclFunction for JsCall {
+    fn call(
+        &self,
+        _evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (name, rest) = args.split_first().ok_or_else(|| {
+            BuclError::RuntimeError("jscall: expected a host function name".into())
+        })?;
+        call_host(name, &encode_args(rest)).map(Some)
+    }
+
+    fn description(&self) -> &str {
+        "Call a function provided by the JavaScript host (WASM only): `{r} jscall \"name\" args...`."
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn call_host(name: &str, args_json: &str) -> Result<String> {
+    use crate::host::{self, HostReply};
+
+    match host::call(name, args_json) {
+        HostReply::Ok(text) => Ok(text),
+        HostReply::Missing => Err(BuclError::RuntimeError(format!(
+            "jscall: no host function named '{}'",
+            name
+        ))),
+        HostReply::Err(msg) => Err(BuclError::RuntimeError(format!("jscall: {}: {}", name, msg))),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn call_host(name: &str, _args_json: &str) -> Result<String> {
+    Err(BuclError::RuntimeError(format!(
+        "jscall: cannot call '{}': host functions are only available in WASM builds",
+        name
+    )))
+}
+
+/// Encode `args` as a JSON array of strings.
+fn encode_args(args: &[String]) -> String {
+    let mut out = String::from("[");
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push('"');
+        for c in arg.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => out.push(c),
+            }
+        }
+        out.push('"');
+    }
+    out.push(']');
+    out
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("jscall", JsCall);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_args() {
+        assert_eq!(encode_args(&[]), "[]");
+        let args = vec!["a".to_string(), "say \"hi\"\\\n".to_string(), "\u{1}".to_string()];
+        assert_eq!(encode_args(&args), r#"["a","say \"hi\"\\\n","\u0001"]"#);
+    }
+}
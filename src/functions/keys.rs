@@ -0,0 +1,86 @@
+/// `keys` — the field names of a struct-like variable.
+///
+/// Returns the names of the variable's named sub-variables — not array
+/// indices, `count` / `length` metadata or deeper levels — as an array in
+/// name order.  A variable without fields gives an empty array.
+///
+/// ```bucl
+/// {db/host} = "localhost"
+/// {db/port} = "5432"
+/// {k} keys {db}
+/// echo {k/count}   # 2
+/// echo {k/0}       # host
+/// ```
+use crate::ast::{Param, Statement};
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct Keys;
+
+impl BuclFunction for Keys {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        target: Option<&str>,
+        _args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let [Param::Variable(name)] = evaluator.call_params.as_slice() else {
+            return Err(BuclError::RuntimeError(
+                "keys: expected one variable, e.g. {k} keys {db}".into(),
+            ));
+        };
+        let name = if name.contains('{') {
+            evaluator.interpolate(name)
+        } else {
+            name.clone()
+        };
+        let keys: Vec<String> = evaluator
+            .find_named_sub_vars(&name)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        match target {
+            Some(prefix) => {
+                evaluator.set_array(prefix, keys);
+                Ok(None)
+            }
+            None => Ok(Some(keys.concat())),
+        }
+    }
+
+    fn description(&self) -> &str {
+        "The field names of a struct-like variable, as an array: `{k} keys {config}`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("keys", Keys);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_keys_lists_named_fields_only() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{db} = \"x\" \"y\"\n",
+            "{db/port} = \"5432\"\n",
+            "{db/host} = \"localhost\"\n",
+            "{db/tls/cert} = \"a.pem\"\n",
+            "{k} keys {db}\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.array_items("k"), vec!["host", "port"]);
+    }
+}
@@ -0,0 +1,200 @@
+/// `map` / `filter` / `reduce` — transform a list with an indented block.
+///
+/// Each runs its block once per argument, in order, with `{t/index}`
+/// (0-based) and `{t/value}` set, where `t` is the target:
+///
+/// - `map` collects what the block stores in `{t/result}` into the target
+///   array (`""` if it stores nothing).
+/// - `filter` keeps the items for which the block sets `{t/result}` to
+///   anything but `""`, `0` or `false`.
+/// - `reduce` takes the initial accumulator as its first argument; the
+///   block updates `{t/acc}`, and the target ends up holding its final value.
+///
+/// ```bucl
+/// {nums} = "1" "2" "3" "4"
+/// {sq} map {nums}
+///     {sq/result} math "{sq/value} ^ 2"          # 1 4 9 16
+/// {odd} filter {nums}
+///     {odd/result} math "{odd/value} % 2"        # 1 3
+/// {sum} reduce 0 {nums}
+///     {sum/acc} math "{sum/acc} + {sum/value}"   # 10
+/// ```
+///
+/// The block runs in the script's scope, like `each`.
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+/// The target and block every list operation needs.
+fn target_and_block<'a>(
+    fname: &str,
+    target: Option<&'a str>,
+    block: Option<&'a [Statement]>,
+) -> Result<(&'a str, &'a [Statement])> {
+    let target = target.ok_or_else(|| {
+        BuclError::RuntimeError(format!("{}: expected a target, e.g. {{out}} {} ...", fname, fname))
+    })?;
+    let block = block.ok_or_else(|| {
+        BuclError::RuntimeError(format!("{}: expected an indented block", fname))
+    })?;
+    Ok((target, block))
+}
+
+/// Set `{prefix/index}` and `{prefix/value}`, clear `{prefix/result}` and run
+/// the block; returns what the block left in `{prefix/result}`.
+fn run_item(
+    evaluator: &mut Evaluator,
+    prefix: &str,
+    i: usize,
+    item: &str,
+    block: &[Statement],
+) -> Result<String> {
+    let result_key = format!("{}/result", prefix);
+    evaluator
+        .variables
+        .insert(format!("{}/index", prefix), i.to_string());
+    evaluator
+        .variables
+        .insert(format!("{}/value", prefix), item.to_string());
+    evaluator.variables.remove(&result_key);
+    evaluator.evaluate_statements(block)?;
+    Ok(evaluator.variables.remove(&result_key).unwrap_or_default())
+}
+
+fn is_truthy(value: &str) -> bool {
+    !matches!(value.trim(), "" | "0" | "false")
+}
+
+// ---------------------------------------------------------------------------
+// map / filter
+// ---------------------------------------------------------------------------
+
+pub struct Map;
+
+impl BuclFunction for Map {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        target: Option<&str>,
+        args: Vec<String>,
+        block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (prefix, block) = target_and_block("map", target, block)?;
+        let mut results = Vec::with_capacity(args.len());
+        for (i, item) in args.iter().enumerate() {
+            results.push(run_item(evaluator, prefix, i, item, block)?);
+        }
+        evaluator.set_array(prefix, results);
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Collect what the block stores in `{t/result}` for every argument: `{sq} map {nums}`."
+    }
+}
+
+pub struct Filter;
+
+impl BuclFunction for Filter {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        target: Option<&str>,
+        args: Vec<String>,
+        block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (prefix, block) = target_and_block("filter", target, block)?;
+        let mut kept = Vec::new();
+        for (i, item) in args.into_iter().enumerate() {
+            if is_truthy(&run_item(evaluator, prefix, i, &item, block)?) {
+                kept.push(item);
+            }
+        }
+        evaluator.set_array(prefix, kept);
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Keep the arguments for which the block sets `{t/result}` to a true value: `{odd} filter {nums}`."
+    }
+}
+
+// ---------------------------------------------------------------------------
+// reduce
+// ---------------------------------------------------------------------------
+
+pub struct Reduce;
+
+impl BuclFunction for Reduce {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        target: Option<&str>,
+        args: Vec<String>,
+        block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (prefix, block) = target_and_block("reduce", target, block)?;
+        let Some((initial, items)) = args.split_first() else {
+            return Err(BuclError::RuntimeError(
+                "reduce: expected an initial value, e.g. {sum} reduce 0 {nums}".into(),
+            ));
+        };
+        let acc_key = format!("{}/acc", prefix);
+        evaluator.variables.insert(acc_key.clone(), initial.clone());
+        for (i, item) in items.iter().enumerate() {
+            run_item(evaluator, prefix, i, item, block)?;
+        }
+        let acc = evaluator.variables.get(&acc_key).cloned().unwrap_or_default();
+        evaluator.set_var(prefix, acc);
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Fold the arguments into `{t/acc}`, starting from the first argument: `{sum} reduce 0 {nums}`."
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Registration
+// ---------------------------------------------------------------------------
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("map", Map);
+    eval.register("filter", Filter);
+    eval.register("reduce", Reduce);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(all(test, feature = "math"))]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_map_filter_reduce() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{nums} = \"1\" \"2\" \"3\" \"4\"\n",
+            "{sq} map {nums}\n",
+            "    {sq/result} math \"{sq/value} ^ 2\"\n",
+            "{even} filter {nums}\n",
+            "    {rem} math \"{even/value} % 2\"\n",
+            "    if {rem} = \"0\"\n",
+            "        {even/result} = \"yes\"\n",
+            "{sum} reduce 0 {nums}\n",
+            "    {sum/acc} math \"{sum/acc} + {sum/value}\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.array_items("sq"), vec!["1", "4", "9", "16"]);
+        assert_eq!(eval.array_items("even"), vec!["2", "4"]);
+        assert_eq!(eval.resolve_var("sum"), "10");
+    }
+}
@@ -0,0 +1,96 @@
+/// `local` — make variables local to the enclosing block.
+///
+/// There is one variable store per script (and per `.bucl` function call),
+/// so a variable set inside a `repeat`, `each` or `if` block is normally
+/// still set after it.  `local {name} ...` saves the current value of each
+/// variable — sub-variables included — and unsets it; when the block (or
+/// script) containing the `local` statement finishes, the saved values are
+/// put back.
+///
+/// ```bucl
+/// {tmp} = "outer"
+/// {r} repeat 3
+///     local {tmp}
+///     {tmp} math "{r/index} * 2"
+/// echo {tmp}   # outer
+/// ```
+use crate::ast::{Param, Statement};
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct Local;
+
+impl BuclFunction for Local {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        _args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let mut names = Vec::new();
+        for param in &evaluator.call_params {
+            let Param::Variable(name) = param else {
+                return Err(BuclError::RuntimeError(
+                    "local: expected variables, e.g. local {tmp} {i}".into(),
+                ));
+            };
+            names.push(if name.contains('{') {
+                evaluator.interpolate(name)
+            } else {
+                name.clone()
+            });
+        }
+        for name in names {
+            evaluator.declare_local(&name)?;
+        }
+        Ok(None)
+    }
+
+    fn allows_duplicate_names(&self) -> bool {
+        true
+    }
+
+    fn names_variables(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Make variables local to the enclosing block: `local {tmp}`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("local", Local);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_local_restores_shadowed_variables() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{tmp} = \"a\" \"b\"\n",
+            "{seen} = \"\"\n",
+            "{r} repeat 2\n",
+            "    local {tmp} {fresh}\n",
+            "    {seen} = \"{seen}[{tmp}]\"\n",
+            "    {tmp} = {r/index}\n",
+            "    {fresh} = \"x\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("seen"), "[][]");
+        assert_eq!(eval.array_items("tmp"), vec!["a", "b"]);
+        assert!(!eval.var_exists("fresh"));
+    }
+}
@@ -0,0 +1,121 @@
+/// `log` — write a diagnostic message at a level.
+///
+/// The first argument is the level (`debug`, `info`, `warn` or `error`); the
+/// rest are joined with a single space like `echo`.  Messages go to stderr
+/// on native builds — or to [`Evaluator::log_sink`] when one is installed —
+/// so they never mix with `echo` output.  Messages below
+/// [`Evaluator::log_level`] (default `info`; `bucl --log-level`) are dropped.
+///
+/// ```bucl
+/// log warn "low disk:" {free} "MB left"
+/// log debug "only shown with --log-level debug"
+/// ```
+use std::fmt;
+
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+/// Severity of a `log` message, lowest first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// Parse `debug`, `info`, `warn` or `error` (case-insensitive).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "debug" => Some(Self::Debug),
+            "info" => Some(Self::Info),
+            "warn" => Some(Self::Warn),
+            "error" => Some(Self::Error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warn => "warn",
+            Self::Error => "error",
+        })
+    }
+}
+
+pub struct Log;
+
+impl BuclFunction for Log {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let Some((level, message)) = args.split_first() else {
+            return Err(BuclError::RuntimeError(
+                "log: expected a level (debug, info, warn, error) and a message".into(),
+            ));
+        };
+        let level = LogLevel::parse(level).ok_or_else(|| {
+            BuclError::RuntimeError(format!(
+                "log: unknown level '{}'; expected debug, info, warn or error",
+                level
+            ))
+        })?;
+        evaluator.log(level, &message.join(" "));
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Write a diagnostic message at a level to stderr: `log warn \"disk almost full\"`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("log", Log);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_log_filters_by_level_and_skips_output() {
+        let logged: Arc<Mutex<Vec<String>>> = Arc::default();
+        let sink = logged.clone();
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.log_level = LogLevel::Warn;
+        eval.log_sink = Some(Arc::new(move |level, msg| {
+            sink.lock().unwrap().push(format!("{} {}", level, msg));
+        }));
+        let stmts = crate::parser::parse(concat!(
+            "log info \"hidden\"\n",
+            "log WARN \"low disk:\" 5 \"MB\"\n",
+            "log error \"failed\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(*logged.lock().unwrap(), vec!["warn low disk: 5 MB", "error failed"]);
+        assert!(eval.output_buffer.is_empty());
+
+        let bad = crate::parser::parse("log loud \"x\"\n").unwrap();
+        assert!(eval.evaluate_statements(&bad).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+/// `markdown` — render Markdown text to HTML.
+///
+/// All arguments are concatenated to form the Markdown source.  Tables,
+/// strikethrough, and task lists are enabled in addition to CommonMark.
+///
+/// ```bucl
+/// {html} markdown "# Report\n\nAll **green**."
+/// # {html} = "<h1>Report</h1>\n<p>All <strong>green</strong>.</p>\n"
+///
+/// {src} readfile "notes.md"
+/// {html} markdown {src}
+/// ```
+///
+/// Only available when built with the `markdown` cargo feature.
+use crate::evaluator::Evaluator;
+
+#[cfg(feature = "markdown")]
+mod enabled {
+    use pulldown_cmark::{html, Options, Parser};
+
+    use crate::ast::Statement;
+    use crate::error::Result;
+    use crate::evaluator::Evaluator;
+    use crate::functions::BuclFunction;
+
+    pub struct Markdown;
+
+    impl BuclFunction for Markdown {
+        fn call(
+            &self,
+            evaluator: &mut Evaluator,
+            _target: Option<&str>,
+            args: Vec<String>,
+            _block: Option<&[Statement]>,
+            _continuation: Option<&Statement>,
+        ) -> Result<Option<String>> {
+            // Named param: {text} = "# Title"; {html} markdown {text}
+            let source = evaluator
+                .named_arg("text")
+                .cloned()
+                .unwrap_or_else(|| args.join(""));
+
+            let options = Options::ENABLE_TABLES
+                | Options::ENABLE_STRIKETHROUGH
+                | Options::ENABLE_TASKLISTS;
+            let parser = Parser::new_ext(&source, options);
+            let mut out = String::with_capacity(source.len() * 3 / 2);
+            html::push_html(&mut out, parser);
+            Ok(Some(out))
+        }
+
+        fn description(&self) -> &str {
+            "Render Markdown text to HTML: `{html} markdown {text}`."
+        }
+    }
+
+    pub fn register(eval: &mut Evaluator) {
+        eval.register("markdown", Markdown);
+    }
+
+    // -----------------------------------------------------------------------
+    // Tests
+    // -----------------------------------------------------------------------
+
+    #[cfg(test)]
+    mod tests {
+        use crate::functions::run_script;
+
+        #[test]
+        fn test_renders_heading_list_and_inline_code() {
+            let (eval, result) = run_script(concat!(
+                "{src} = \"# Report\\n\\n- one\\n- `two`\\n\"\n",
+                "{html} markdown {src}\n",
+            ));
+            result.unwrap();
+            assert_eq!(
+                eval.resolve_var("html"),
+                "<h1>Report</h1>\n<ul>\n<li>one</li>\n<li><code>two</code></li>\n</ul>\n"
+            );
+        }
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    #[cfg(feature = "markdown")]
+    enabled::register(eval);
+    let _ = eval; // suppress unused warning without the feature
+}
@@ -1,11 +1,37 @@
 /// `math` — evaluate a basic arithmetic expression.
 ///
-/// Supports `+`, `-`, `*`, `/`, `%`, unary `-`, and parentheses.
+/// Supports `+`, `-`, `*`, `/`, `%`, `^` (power, right-associative and
+/// binding tighter than unary minus), unary `-`, parentheses, the bitwise
+/// operators `&`, `|`, `xor`, `<<` and `>>` on integers (lowest precedence,
+/// as in C: `|` < `xor` < `&` < shifts < `+`/`-`), and the
+/// functions `pow(x,y)`, `sqrt(x)`, `abs(x)`, `floor(x)`, `ceil(x)`,
+/// `round(x)`, `min(a,b,…)` and `max(a,b,…)`.
+///
+/// Integers are evaluated exactly (as 128-bit integers), so large values
+/// keep every digit and `%` on integers is integer remainder.  As soon as a
+/// fraction appears — a decimal literal, a division that doesn't come out
+/// even, `sqrt`, or an overflow — evaluation continues in floating point.
+/// The named argument `precision` rounds a fractional result to that many
+/// decimal places.
+///
+/// Variables can be used either interpolated (`"{x} * 2"`) or as bare
+/// identifiers (`"x * 2"`), which are looked up when the expression is
+/// evaluated; an unset or non-numeric variable is an error naming it.
 ///
 /// ```bucl
 /// {m} math "3+3"          # {m} = "6"
 /// {m} math "(10-2)*3"     # {m} = "24"
+/// {m} math "2^10"         # {m} = "1024"
+/// {m} math "sqrt(2)"      # {m} = "1.4142135623730951"
+/// {m} math "max(3, 5, 4)" # {m} = "5"
+/// {m} math "price * qty"  # uses {price} and {qty}
+/// {m} math "flags | 4"    # set bit 2
+/// {m} math "1 << 10"      # {m} = "1024"
+/// {m} math "2^100"        # {m} = "1267650600228229401496703205376"
+/// {precision} = 2
+/// {m} math "10 / 3" {precision}  # {m} = "3.33"
 /// ```
+use std::cmp::Ordering;
 use std::iter::Peekable;
 use std::str::Chars;
 
@@ -21,26 +47,42 @@ impl BuclFunction for Math {
         &self,
         evaluator: &mut Evaluator,
         _target: Option<&str>,
-        args: Vec<String>,
+        mut args: Vec<String>,
         _block: Option<&[Statement]>,
         _continuation: Option<&Statement>,
     ) -> Result<Option<String>> {
+        // Named param: {precision} = 2; {m} math "10/3" {precision}
+        let precision = match evaluator.named_arg("precision").cloned() {
+            Some(p) => {
+                // The named precision was passed positionally too; drop it.
+                if let Some(pos) = args.iter().rposition(|a| *a == p) {
+                    args.remove(pos);
+                }
+                let digits = p.trim().parse::<i32>().ok().filter(|d| (0..=15).contains(d));
+                Some(digits.ok_or_else(|| {
+                    BuclError::RuntimeError(format!(
+                        "math: precision must be a whole number from 0 to 15, got '{}'",
+                        p
+                    ))
+                })?)
+            }
+            None => None,
+        };
+
         // Named param: {expr} = "3+3"; {m} math {expr}
         let expr = evaluator
             .named_arg("expr")
             .cloned()
             .unwrap_or_else(|| args.join(""));
-        let value = eval_expr(&expr)
+        let lookup = |name: &str| evaluator.variables.get(name).cloned();
+        let value = eval_expr(&expr, &lookup)
             .map_err(|e| BuclError::RuntimeError(format!("math: {}", e)))?;
 
-        // Format as integer when there is no fractional part.
-        let s = if value.fract() == 0.0 && value.abs() < 1e15 {
-            format!("{}", value as i64)
-        } else {
-            format!("{}", value)
-        };
+        Ok(Some(format_num(value, precision)))
+    }
 
-        Ok(Some(s))
+    fn description(&self) -> &str {
+        "Evaluate an arithmetic expression: `{n} math \"{a} * 2 + 1\"`."
     }
 }
 
@@ -48,13 +90,126 @@ pub fn register(eval: &mut Evaluator) {
     eval.register("math", Math);
 }
 
+fn format_num(value: Num, precision: Option<i32>) -> String {
+    match value {
+        Num::Int(n) => n.to_string(),
+        Num::Float(mut f) => {
+            if let Some(digits) = precision {
+                let factor = 10f64.powi(digits);
+                f = (f * factor).round() / factor;
+            }
+            // Format as integer when there is no fractional part.
+            if f.fract() == 0.0 && f.abs() < 1e15 {
+                format!("{}", f as i64)
+            } else {
+                format!("{}", f)
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Numbers: exact integers with a floating-point fallback
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Num {
+    Int(i128),
+    Float(f64),
+}
+
+impl Num {
+    /// Parse a literal or variable value; integers that don't fit in `i128`
+    /// become floats.
+    fn parse(s: &str) -> Option<Num> {
+        let s = s.trim();
+        if !s.contains(['.', 'e', 'E']) {
+            if let Ok(n) = s.parse::<i128>() {
+                return Some(Num::Int(n));
+            }
+        }
+        s.parse::<f64>().ok().filter(|f| f.is_finite()).map(Num::Float)
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Num::Int(n) => n as f64,
+            Num::Float(f) => f,
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        self.as_f64() == 0.0
+    }
+
+    /// Apply `int` to two integers, falling back to `float` when either side
+    /// is a float or the integer operation overflows.
+    fn combine(
+        self,
+        other: Num,
+        int: fn(i128, i128) -> Option<i128>,
+        float: fn(f64, f64) -> f64,
+    ) -> Num {
+        if let (Num::Int(a), Num::Int(b)) = (self, other) {
+            if let Some(n) = int(a, b) {
+                return Num::Int(n);
+            }
+        }
+        Num::Float(float(self.as_f64(), other.as_f64()))
+    }
+
+    fn div(self, other: Num) -> Num {
+        self.combine(
+            other,
+            |a, b| if a % b == 0 { a.checked_div(b) } else { None },
+            |a, b| a / b,
+        )
+    }
+
+    fn pow(self, exponent: Num) -> std::result::Result<Num, String> {
+        if let (Num::Int(base), Num::Int(exp)) = (self, exponent) {
+            if let Some(n) = u32::try_from(exp).ok().and_then(|e| base.checked_pow(e)) {
+                return Ok(Num::Int(n));
+            }
+        }
+        finite(self.as_f64().powf(exponent.as_f64()), "^")
+    }
+
+    fn neg(self) -> Num {
+        match self {
+            Num::Int(n) => n.checked_neg().map_or(Num::Float(-(n as f64)), Num::Int),
+            Num::Float(f) => Num::Float(-f),
+        }
+    }
+
+    /// The value as an integer, for the bitwise operators; floats are accepted
+    /// when they are whole numbers.
+    fn as_int(self, op: &str) -> std::result::Result<i128, String> {
+        match self {
+            Num::Int(n) => Ok(n),
+            Num::Float(f) if f.fract() == 0.0 && f.abs() < 1e36 => Ok(f as i128),
+            Num::Float(f) => Err(format!("'{}' needs integers, got {}", op, f)),
+        }
+    }
+
+    fn compare(self, other: Num) -> Ordering {
+        match (self, other) {
+            (Num::Int(a), Num::Int(b)) => a.cmp(&b),
+            _ => self.as_f64().total_cmp(&other.as_f64()),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Recursive-descent expression evaluator
 // ---------------------------------------------------------------------------
 
-fn eval_expr(s: &str) -> std::result::Result<f64, String> {
+/// Looks up a bare identifier in the expression; `None` if it is unset.
+type Vars<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+fn eval_expr(s: &str, vars: Vars) -> std::result::Result<Num, String> {
     let mut chars = s.chars().peekable();
-    let result = parse_add_sub(&mut chars)?;
+    let result = parse_bit_or(&mut chars, vars)?;
     skip_ws(&mut chars);
     if let Some(c) = chars.peek() {
         return Err(format!("unexpected character '{}'", c));
@@ -63,23 +218,107 @@ fn eval_expr(s: &str) -> std::result::Result<f64, String> {
 }
 
 fn skip_ws(chars: &mut Peekable<Chars>) {
-    while chars.peek().map_or(false, |c| c.is_whitespace()) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+fn parse_bit_or(chars: &mut Peekable<Chars>, vars: Vars) -> std::result::Result<Num, String> {
+    let mut left = parse_xor(chars, vars)?;
+    loop {
+        skip_ws(chars);
+        if chars.peek() != Some(&'|') {
+            return Ok(left);
+        }
+        chars.next();
+        let right = parse_xor(chars, vars)?;
+        left = Num::Int(left.as_int("|")? | right.as_int("|")?);
+    }
+}
+
+fn parse_xor(chars: &mut Peekable<Chars>, vars: Vars) -> std::result::Result<Num, String> {
+    let mut left = parse_bit_and(chars, vars)?;
+    loop {
+        skip_ws(chars);
+        if !eat_keyword(chars, "xor") {
+            return Ok(left);
+        }
+        let right = parse_bit_and(chars, vars)?;
+        left = Num::Int(left.as_int("xor")? ^ right.as_int("xor")?);
+    }
+}
+
+fn parse_bit_and(chars: &mut Peekable<Chars>, vars: Vars) -> std::result::Result<Num, String> {
+    let mut left = parse_shift(chars, vars)?;
+    loop {
+        skip_ws(chars);
+        if chars.peek() != Some(&'&') {
+            return Ok(left);
+        }
         chars.next();
+        let right = parse_shift(chars, vars)?;
+        left = Num::Int(left.as_int("&")? & right.as_int("&")?);
     }
 }
 
-fn parse_add_sub(chars: &mut Peekable<Chars>) -> std::result::Result<f64, String> {
-    let mut left = parse_mul_div(chars)?;
+fn parse_shift(chars: &mut Peekable<Chars>, vars: Vars) -> std::result::Result<Num, String> {
+    let mut left = parse_add_sub(chars, vars)?;
+    loop {
+        skip_ws(chars);
+        let op = match chars.peek() {
+            Some('<') => "<<",
+            Some('>') => ">>",
+            _ => return Ok(left),
+        };
+        chars.next();
+        if chars.next() != op.chars().nth(1) {
+            return Err(format!("expected '{}'", op));
+        }
+        let right = parse_add_sub(chars, vars)?;
+        let (value, amount) = (left.as_int(op)?, right.as_int(op)?);
+        let amount = u32::try_from(amount)
+            .ok()
+            .filter(|&n| n < 128)
+            .ok_or_else(|| format!("shift amount must be 0..127, got {}", amount))?;
+        left = Num::Int(if op == "<<" {
+            let shifted = value << amount;
+            if shifted >> amount != value {
+                return Err(format!("{} << {} overflows", value, amount));
+            }
+            shifted
+        } else {
+            value >> amount
+        });
+    }
+}
+
+/// Consume `word` if it comes next and isn't the start of a longer identifier.
+fn eat_keyword(chars: &mut Peekable<Chars>, word: &str) -> bool {
+    let mut ahead = chars.clone();
+    if !word.chars().all(|c| ahead.next() == Some(c)) {
+        return false;
+    }
+    if ahead.peek().is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_') {
+        return false;
+    }
+    *chars = ahead;
+    true
+}
+
+fn parse_add_sub(chars: &mut Peekable<Chars>, vars: Vars) -> std::result::Result<Num, String> {
+    let mut left = parse_mul_div(chars, vars)?;
     loop {
         skip_ws(chars);
         match chars.peek() {
             Some('+') => {
                 chars.next();
-                left += parse_mul_div(chars)?;
+                let right = parse_mul_div(chars, vars)?;
+                left = left.combine(right, i128::checked_add, |a, b| a + b);
             }
             Some('-') => {
                 chars.next();
-                left -= parse_mul_div(chars)?;
+                let right = parse_mul_div(chars, vars)?;
+                left = left.combine(right, i128::checked_sub, |a, b| a - b);
             }
             _ => break,
         }
@@ -87,30 +326,31 @@ fn parse_add_sub(chars: &mut Peekable<Chars>) -> std::result::Result<f64, String
     Ok(left)
 }
 
-fn parse_mul_div(chars: &mut Peekable<Chars>) -> std::result::Result<f64, String> {
-    let mut left = parse_unary(chars)?;
+fn parse_mul_div(chars: &mut Peekable<Chars>, vars: Vars) -> std::result::Result<Num, String> {
+    let mut left = parse_unary(chars, vars)?;
     loop {
         skip_ws(chars);
         match chars.peek() {
             Some('*') => {
                 chars.next();
-                left *= parse_unary(chars)?;
+                let right = parse_unary(chars, vars)?;
+                left = left.combine(right, i128::checked_mul, |a, b| a * b);
             }
             Some('/') => {
                 chars.next();
-                let right = parse_unary(chars)?;
-                if right == 0.0 {
+                let right = parse_unary(chars, vars)?;
+                if right.is_zero() {
                     return Err("division by zero".to_string());
                 }
-                left /= right;
+                left = left.div(right);
             }
             Some('%') => {
                 chars.next();
-                let right = parse_unary(chars)?;
-                if right == 0.0 {
+                let right = parse_unary(chars, vars)?;
+                if right.is_zero() {
                     return Err("modulo by zero".to_string());
                 }
-                left %= right;
+                left = left.combine(right, i128::checked_rem, |a, b| a % b);
             }
             _ => break,
         }
@@ -118,23 +358,36 @@ fn parse_mul_div(chars: &mut Peekable<Chars>) -> std::result::Result<f64, String
     Ok(left)
 }
 
-fn parse_unary(chars: &mut Peekable<Chars>) -> std::result::Result<f64, String> {
+fn parse_unary(chars: &mut Peekable<Chars>, vars: Vars) -> std::result::Result<Num, String> {
     skip_ws(chars);
     if chars.peek() == Some(&'-') {
         chars.next();
-        return Ok(-parse_primary(chars)?);
+        return Ok(parse_power(chars, vars)?.neg());
     }
     if chars.peek() == Some(&'+') {
         chars.next();
     }
-    parse_primary(chars)
+    parse_power(chars, vars)
 }
 
-fn parse_primary(chars: &mut Peekable<Chars>) -> std::result::Result<f64, String> {
+/// `base ^ exponent`; the exponent may itself be signed (`2^-1`) or another
+/// power (`2^3^2` = `2^9`).
+fn parse_power(chars: &mut Peekable<Chars>, vars: Vars) -> std::result::Result<Num, String> {
+    let base = parse_primary(chars, vars)?;
+    skip_ws(chars);
+    if chars.peek() == Some(&'^') {
+        chars.next();
+        let exponent = parse_unary(chars, vars)?;
+        return base.pow(exponent);
+    }
+    Ok(base)
+}
+
+fn parse_primary(chars: &mut Peekable<Chars>, vars: Vars) -> std::result::Result<Num, String> {
     skip_ws(chars);
     if chars.peek() == Some(&'(') {
         chars.next();
-        let val = parse_add_sub(chars)?;
+        let val = parse_bit_or(chars, vars)?;
         skip_ws(chars);
         match chars.next() {
             Some(')') => return Ok(val),
@@ -142,6 +395,24 @@ fn parse_primary(chars: &mut Peekable<Chars>) -> std::result::Result<f64, String
         }
     }
 
+    if chars.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+        let mut name = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                name.push(c);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        skip_ws(chars);
+        if chars.peek() != Some(&'(') {
+            return variable_value(&name, vars);
+        }
+        let args = parse_call_args(chars, vars, &name)?;
+        return call_function(&name, &args);
+    }
+
     let mut num = String::new();
     while let Some(&c) = chars.peek() {
         if c.is_ascii_digit() || c == '.' {
@@ -159,6 +430,179 @@ fn parse_primary(chars: &mut Peekable<Chars>) -> std::result::Result<f64, String
         });
     }
 
-    num.parse()
-        .map_err(|_| format!("invalid number literal '{}'", num))
+    Num::parse(&num).ok_or_else(|| format!("invalid number literal '{}'", num))
+}
+
+/// Value of a bare identifier such as `x` in `math "x * 2"`.
+fn variable_value(name: &str, vars: Vars) -> std::result::Result<Num, String> {
+    let value = vars(name).ok_or_else(|| format!("unknown variable '{}'", name))?;
+    Num::parse(&value).ok_or_else(|| format!("variable '{}' is not a number: '{}'", name, value))
+}
+
+/// Parse the parenthesised, comma-separated argument list after a function name.
+fn parse_call_args(
+    chars: &mut Peekable<Chars>,
+    vars: Vars,
+    name: &str,
+) -> std::result::Result<Vec<Num>, String> {
+    skip_ws(chars);
+    if chars.next() != Some('(') {
+        return Err(format!("expected '(' after '{}'", name));
+    }
+    let mut args = Vec::new();
+    skip_ws(chars);
+    if chars.peek() == Some(&')') {
+        chars.next();
+        return Ok(args);
+    }
+    loop {
+        args.push(parse_bit_or(chars, vars)?);
+        skip_ws(chars);
+        match chars.next() {
+            Some(',') => continue,
+            Some(')') => return Ok(args),
+            other => return Err(format!("expected ',' or ')' in {}(), got {:?}", name, other)),
+        }
+    }
+}
+
+fn call_function(name: &str, args: &[Num]) -> std::result::Result<Num, String> {
+    let arity = |n: usize| {
+        if args.len() == n {
+            Ok(())
+        } else {
+            Err(format!("{}() takes {} argument(s), got {}", name, n, args.len()))
+        }
+    };
+    // floor / ceil / round leave integers alone.
+    let rounding = |f: fn(f64) -> f64| match args[0] {
+        Num::Int(n) => Num::Int(n),
+        Num::Float(x) => Num::Float(f(x)),
+    };
+    match name {
+        "pow" => {
+            arity(2)?;
+            args[0].pow(args[1])
+        }
+        "sqrt" => {
+            arity(1)?;
+            if args[0].as_f64() < 0.0 {
+                return Err("sqrt() of a negative number".to_string());
+            }
+            Ok(Num::Float(args[0].as_f64().sqrt()))
+        }
+        "abs" => arity(1).map(|_| match args[0] {
+            Num::Int(n) => n.checked_abs().map_or(Num::Float((n as f64).abs()), Num::Int),
+            Num::Float(x) => Num::Float(x.abs()),
+        }),
+        "floor" => arity(1).map(|_| rounding(f64::floor)),
+        "ceil" => arity(1).map(|_| rounding(f64::ceil)),
+        "round" => arity(1).map(|_| rounding(f64::round)),
+        "min" | "max" => {
+            if args.is_empty() {
+                return Err(format!("{}() needs at least one argument", name));
+            }
+            let wanted = if name == "min" { Ordering::Less } else { Ordering::Greater };
+            Ok(args[1..]
+                .iter()
+                .fold(args[0], |acc, &x| if x.compare(acc) == wanted { x } else { acc }))
+        }
+        _ => Err(format!("unknown function '{}'", name)),
+    }
+}
+
+/// Reject NaN / infinite results (e.g. `(-8)^0.5`) instead of printing them.
+fn finite(value: f64, op: &str) -> std::result::Result<Num, String> {
+    if value.is_finite() {
+        Ok(Num::Float(value))
+    } else {
+        Err(format!("{} result is not a finite number", op))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calc(expr: &str) -> std::result::Result<f64, String> {
+        eval_expr(expr, &|name| match name {
+            "x" => Some("4".to_string()),
+            "label" => Some("abc".to_string()),
+            _ => None,
+        })
+        .map(Num::as_f64)
+    }
+
+    fn show(expr: &str, precision: Option<i32>) -> String {
+        format_num(eval_expr(expr, &|_| None).unwrap(), precision)
+    }
+
+    #[test]
+    fn test_power_operator() {
+        assert_eq!(calc("2^10"), Ok(1024.0));
+        assert_eq!(calc("2^3^2"), Ok(512.0));
+        assert_eq!(calc("-2^2"), Ok(-4.0));
+        assert_eq!(calc("2^-1"), Ok(0.5));
+        assert_eq!(calc("3*2^2+1"), Ok(13.0));
+        assert!(calc("(-8)^0.5").is_err());
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(calc("sqrt(16)"), Ok(4.0));
+        assert_eq!(calc("pow(2, 8)"), Ok(256.0));
+        assert_eq!(calc("abs(-3) + floor(2.7) + ceil(2.1) + round(2.5)"), Ok(11.0));
+        assert_eq!(calc("min(3,5)"), Ok(3.0));
+        assert_eq!(calc("max(1, min(9, 4), 2)"), Ok(4.0));
+        assert!(calc("sqrt(-1)").is_err());
+        assert!(calc("pow(2)").is_err());
+        assert!(calc("nope(1)").is_err());
+    }
+
+    #[test]
+    fn test_bare_variables() {
+        assert_eq!(calc("x * 2 + 1"), Ok(9.0));
+        assert_eq!(calc("sqrt(x)"), Ok(2.0));
+        assert_eq!(calc("unset + 1"), Err("unknown variable 'unset'".to_string()));
+        assert_eq!(calc("label * 2"), Err("variable 'label' is not a number: 'abc'".to_string()));
+    }
+
+    #[test]
+    fn test_integers_stay_exact() {
+        assert_eq!(show("9007199254740993 + 0", None), "9007199254740993");
+        assert_eq!(show("2^100", None), "1267650600228229401496703205376");
+        assert_eq!(show("12345678901234567890 * 10", None), "123456789012345678900");
+        assert_eq!(show("-7 % 3", None), "-1");
+        assert_eq!(show("10 / 4", None), "2.5");
+        assert_eq!(show("10 / 5", None), "2");
+        // Overflowing i128 falls back to floating point instead of failing.
+        assert_eq!(show("2^200", None), format!("{}", 2f64.powi(200)));
+    }
+
+    #[test]
+    fn test_precision_rounds_fractions() {
+        assert_eq!(show("10 / 3", Some(2)), "3.33");
+        assert_eq!(show("2 / 3", Some(0)), "1");
+        assert_eq!(show("7 * 3", Some(2)), "21");
+    }
+
+    #[test]
+    fn test_bitwise_operators() {
+        assert_eq!(show("12 & 10", None), "8");
+        assert_eq!(show("12 | 3", None), "15");
+        assert_eq!(show("12 xor 10", None), "6");
+        assert_eq!(show("1 << 10", None), "1024");
+        assert_eq!(show("-16 >> 2", None), "-4");
+        // C precedence: shifts bind tighter than &, which binds tighter than |.
+        assert_eq!(show("1 | 2 & 3 << 1", None), "3");
+        assert_eq!(show("1 + 1 << 2", None), "8");
+        assert_eq!(calc("x xor 1"), Ok(5.0));
+        assert!(calc("1.5 & 1").is_err());
+        assert!(calc("1 << 200").is_err());
+        assert!(calc("1 < 2").is_err());
+    }
 }
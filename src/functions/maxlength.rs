@@ -0,0 +1,57 @@
+/// `maxlength` — the character count of the longest argument.
+///
+/// Returns `0` when called without arguments.  Replaces the looping
+/// `functions/maxlength.bucl`, which is kept as the reference implementation.
+///
+/// ```bucl
+/// {m} maxlength "ha" "llo"   # 3
+/// ```
+use crate::ast::Statement;
+use crate::error::Result;
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct MaxLength;
+
+impl BuclFunction for MaxLength {
+    fn call(
+        &self,
+        _evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let max = args.iter().map(|a| a.chars().count()).max().unwrap_or(0);
+        Ok(Some(max.to_string()))
+    }
+
+    fn description(&self) -> &str {
+        "The character count of the longest argument: `{m} maxlength \"ha\" \"llo\"`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("maxlength", MaxLength);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(all(test, feature = "math"))]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_matches_bucl_reference() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.embed_function("ref", include_str!("../../functions/maxlength.bucl"));
+        for args in [r#""ha" "llo""#, r#""""#, r#""héllo" "wö" "x""#, ""] {
+            let source = format!("{{a}} maxlength {}\n{{b}} ref {}\n", args, args);
+            eval.evaluate_statements(&crate::parser::parse(&source).unwrap()).unwrap();
+            assert_eq!(eval.resolve_var("a"), eval.resolve_var("b"), "{}", args);
+        }
+    }
+}
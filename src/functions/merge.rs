@@ -0,0 +1,111 @@
+/// `merge` — combine structs, later arguments overriding earlier ones.
+///
+/// Every sub-variable of each argument (at any depth) is copied into the
+/// target; where two arguments have the same field, the later one wins.
+/// Whatever the target held before is replaced, so an argument may be the
+/// target itself.
+///
+/// ```bucl
+/// {defaults/host} = "localhost"
+/// {defaults/port} = "5432"
+/// {user/port} = "6543"
+/// {cfg} merge {defaults} {user}
+/// echo "{cfg/host}:{cfg/port}"   # localhost:6543
+/// ```
+///
+/// `{target}` gets the root value of the last argument that has one; its
+/// `count` / `length` describe that value, as for any assignment.
+use crate::ast::{Param, Statement};
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct Merge;
+
+impl BuclFunction for Merge {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        target: Option<&str>,
+        _args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let dst = target.ok_or_else(|| {
+            BuclError::RuntimeError("merge: expected a target, e.g. {cfg} merge {a} {b}".into())
+        })?;
+
+        let mut root = None;
+        let mut fields = Vec::new();
+        for param in &evaluator.call_params {
+            let Param::Variable(name) = param else {
+                return Err(BuclError::RuntimeError(
+                    "merge: every argument must be a variable, e.g. {cfg} merge {a} {b}".into(),
+                ));
+            };
+            let name = if name.contains('{') {
+                evaluator.interpolate(name)
+            } else {
+                name.clone()
+            };
+            if let Some(value) = evaluator.variables.get(&name) {
+                root = Some(value.clone());
+            }
+            fields.extend(
+                evaluator
+                    .subtree(&name)
+                    .into_iter()
+                    .filter(|(rest, _)| rest != "count" && rest != "length"),
+            );
+        }
+
+        evaluator.remove_tree(dst);
+        evaluator.set_var(dst, root.unwrap_or_default());
+        // Later arguments were collected later, so they overwrite.
+        for (rest, value) in fields {
+            evaluator.variables.insert(format!("{}/{}", dst, rest), value);
+        }
+        Ok(None)
+    }
+
+    fn allows_duplicate_names(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Combine structs into the target, later ones winning: `{cfg} merge {defaults} {overrides}`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("merge", Merge);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_merge_later_arguments_win() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let stmts = crate::parser::parse(concat!(
+            "{defaults/host} = \"localhost\"\n",
+            "{defaults/port} = \"5432\"\n",
+            "{defaults/tls/verify} = \"yes\"\n",
+            "{user/port} = \"6543\"\n",
+            "{cfg/stale} = \"x\"\n",
+            "{cfg} merge {defaults} {user}\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("cfg/host"), "localhost");
+        assert_eq!(eval.resolve_var("cfg/port"), "6543");
+        assert_eq!(eval.resolve_var("cfg/tls/verify"), "yes");
+        assert!(!eval.var_exists("cfg/stale"));
+    }
+}
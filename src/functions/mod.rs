@@ -1,5 +1,5 @@
-use crate::ast::Statement;
-use crate::error::Result;
+use crate::ast::{Param, Statement};
+use crate::error::{BuclError, Result};
 use crate::evaluator::Evaluator;
 
 // ---------------------------------------------------------------------------
@@ -29,6 +29,142 @@ pub trait BuclFunction: Send + Sync {
         block: Option<&[Statement]>,
         continuation: Option<&Statement>,
     ) -> Result<Option<String>>;
+
+    /// Whether a call may pass the same named parameter more than once,
+    /// e.g. two structs with overlapping fields (`merge {a} {b}`).  When
+    /// `false` (the default) that is an error.
+    fn allows_duplicate_names(&self) -> bool {
+        false
+    }
+
+    /// Whether bare `{var}` parameters name variables to act on rather than
+    /// pass their values (`local {tmp}`), so strict mode doesn't require
+    /// them to be set.
+    fn names_variables(&self) -> bool {
+        false
+    }
+
+    /// What the built-in does, with a short example, for `bucl doc`.  Empty
+    /// (the default) leaves it undocumented.
+    fn description(&self) -> &str {
+        ""
+    }
+}
+
+/// One parameter of a built-in, for [`bind_args`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ArgSpec {
+    name: &'static str,
+    default: Option<&'static str>,
+}
+
+impl ArgSpec {
+    /// A parameter every call must pass.
+    pub(crate) const fn required(name: &'static str) -> Self {
+        ArgSpec { name, default: None }
+    }
+
+    /// A parameter that is `default` when a call doesn't pass it.
+    pub(crate) const fn optional(name: &'static str, default: &'static str) -> Self {
+        ArgSpec { name, default: Some(default) }
+    }
+}
+
+/// Bind the current call's arguments to the parameters in `spec`: each one
+/// takes the argument named after it (`{path}`, `path:"a.txt"`), else the
+/// next of the arguments not taken that way, else its default — as
+/// `params` does for `.bucl` functions.  A required one that gets nothing
+/// fails the call with `fname: missing argument 'name'`.
+///
+/// ```ignore
+/// let [text, format] = bind_args(evaluator, "qrcode", &args, [
+///     ArgSpec::required("text"),
+///     ArgSpec::optional("format", "unicode"),
+/// ])?;
+/// ```
+pub(crate) fn bind_args<const N: usize>(
+    evaluator: &Evaluator,
+    fname: &str,
+    args: &[String],
+    spec: [ArgSpec; N],
+) -> Result<[String; N]> {
+    let by_name = spec.map(|param| evaluator.named_arg(param.name));
+    let taken = |name: &str| {
+        spec.iter()
+            .zip(&by_name)
+            .any(|(param, value)| param.name == name && value.is_some())
+    };
+    let mut positional = args.iter().enumerate().filter_map(|(j, value)| {
+        match evaluator.call_arg_names.get(j) {
+            Some(Some(name)) if taken(name) => None,
+            _ => Some(value),
+        }
+    });
+    let mut missing = None;
+    let values = std::array::from_fn(|i| {
+        let param = spec[i];
+        by_name[i]
+            .or_else(|| positional.next())
+            .map(String::as_str)
+            .or(param.default)
+            .unwrap_or_else(|| {
+                missing.get_or_insert(param.name);
+                ""
+            })
+            .to_string()
+    });
+    match missing {
+        Some(name) => Err(BuclError::RuntimeError(format!(
+            "{}: missing argument '{}'",
+            fname, name
+        ))),
+        None => Ok(values),
+    }
+}
+
+/// Split the trailing `name:"value"` options whose name is in `names` off
+/// the current call (`each {x} by:"char"`, `echo "a" end:""`).  They are
+/// removed from `args` and returned in source order, with their quotes
+/// stripped and escapes such as `\t` resolved but not interpolated — a
+/// `name:{var}` option gives the variable's value; the second value is how
+/// many parameters precede them.
+pub(crate) fn take_options(
+    evaluator: &Evaluator,
+    args: &mut Vec<String>,
+    names: &[&str],
+) -> (Vec<(String, String)>, usize) {
+    let mut options = Vec::new();
+    let mut n_params = evaluator.call_params.len();
+    while let Some(param) = evaluator.call_params[..n_params].last() {
+        let (name, value) = match param {
+            Param::Named(name, value) => match value.as_ref() {
+                Param::Quoted(text, _) => (name.as_str(), text.clone()),
+                other => (name.as_str(), evaluator.eval_param(other)),
+            },
+            // `max:5`, without quotes, is still one bare word.
+            Param::Bare(option) => {
+                let Some((name, value)) = option.split_once(':') else {
+                    break;
+                };
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .unwrap_or(value);
+                let value = crate::lexer::resolve_escapes(value)
+                    .map_or_else(|_| value.to_string(), |(v, _)| v);
+                (name, value)
+            }
+            _ => break,
+        };
+        if !names.contains(&name) {
+            break;
+        }
+        options.push((name.to_string(), value));
+        args.pop();
+        n_params -= 1;
+    }
+    options.reverse();
+    (options, n_params)
 }
 
 // ---------------------------------------------------------------------------
@@ -37,15 +173,73 @@ pub trait BuclFunction: Send + Sync {
 // (control flow, OS I/O, arithmetic, or character-level string operations).
 // ---------------------------------------------------------------------------
 
+pub mod append;    // append — extend a variable in place
+pub mod array;     // push / pop / shift / insert — array mutation
+pub mod assert;    // assert — record test failures
 pub mod assign;    // =
+pub mod callblock; // callblock — run a .bucl function call's block
+pub mod capture;   // capture — collect block output into a variable
+pub mod const_fn;  // const / readonly — read-only variables
+pub mod copyvar;   // copyvar — deep-copy a structured variable
 pub mod each;      // each
+#[cfg(feature = "fs")]
+pub mod eachline;  // eachline — stream a file line by line
 pub mod echo;      // echo — print to output
-pub mod if_fn;     // if / elseif / else
-pub mod math;      // math
-pub mod random;    // random
+#[cfg(feature = "text")]
+pub mod encoding;  // bytelen / encode / decode / isutf8
+pub mod error_fn;  // error — abort with a custom error
+pub mod exit;      // exit — stop with an exit code
+pub mod functions_fn; // functions — list every callable function
+#[cfg(feature = "text")]
+pub mod fuzzyfilter; // fuzzyfilter — rank items by fuzzy match
+#[cfg(feature = "i18n")]
+pub mod i18n;      // t / tload — translated message catalogs
+pub mod if_fn;     // if / elseif / elif / else / strictfloat
+pub mod imageinfo; // imageinfo — image dimensions/format (feature: imageinfo)
+pub mod jscall;    // jscall — call JavaScript host functions (WASM)
+pub mod keys;      // keys — field names of a struct
+pub mod listops;   // map / filter / reduce
+pub mod local;     // local — block-scoped variables
+pub mod log;       // log — leveled diagnostics on stderr
+pub mod markdown;  // markdown — Markdown → HTML (feature: markdown)
+#[cfg(feature = "math")]
+pub mod math;      // math — arithmetic expressions
+#[cfg(feature = "text")]
+pub mod maxlength; // maxlength — longest argument length
+pub mod merge;     // merge — combine structs, later ones winning
+pub mod net;       // netsend / netrecv / netclose — TCP client (feature: net)
+#[cfg(feature = "text")]
+pub mod numwords;  // numtowords / wordstonum
+pub mod params;    // params — declare a .bucl function's parameters
+pub mod pass;      // pass — do nothing
+pub mod qrcode;    // qrcode — QR code rendering (feature: qrcode)
+#[cfg(feature = "random")]
+pub mod random;    // random / randomseed
+#[cfg(feature = "fs")]
 pub mod readfile;  // readfile
+#[cfg(feature = "fs")]
+pub mod render;    // render — fill a template file from a struct
 pub mod repeat;    // repeat
+#[cfg(feature = "text")]
+pub mod repeatstr; // repeatstr — repeat a string N times
+#[cfg(feature = "text")]
+pub mod reverse;   // reverse — reverse a string
+#[cfg(feature = "text")]
+pub mod similarity; // levenshtein / similar_text
+#[cfg(feature = "sleep")]
 pub mod sleep;     // sleep — pause execution
+#[cfg(feature = "text")]
+pub mod slice;     // slice — Python-style argument slices
+pub mod sqlite;    // sqlopen / sql / sqlclose — SQLite queries (feature: sqlite)
+#[cfg(feature = "fs")]
+pub mod store;     // store — key-value store kept between runs
+#[cfg(feature = "text")]
+pub mod style;     // style — ANSI colors for terminal output
+pub mod typecheck; // isnumber / isint / isset / isarray
+pub mod validate;  // validate — declarative field checks
+#[cfg(feature = "text")]
+pub mod wordwrap;  // wordwrap — wrap text to a column width
+#[cfg(feature = "fs")]
 pub mod writefile; // writefile
 
 // ---------------------------------------------------------------------------
@@ -54,18 +248,139 @@ pub mod writefile; // writefile
 
 /// Register every core built-in with the evaluator.
 ///
-/// Higher-level functions (`strpos`, `substr`, `explode`, `implode`,
-/// `reverse`, `maxlength`, `slice`, …) live in `functions/*.bucl` and are
-/// loaded automatically at runtime — no registration needed here.
+/// Groups of them are left out, modules and all, when their cargo feature
+/// (`fs`, `math`, `random`, `sleep`, `text`, `i18n`) is off.
+///
+/// Higher-level functions (`strpos`, `substr`, `explode`, `implode`, …) live
+/// in `functions/*.bucl` and are loaded automatically at runtime — no
+/// registration needed here.  `reverse`, `maxlength` and `slice` started out
+/// there too and were moved here for speed; their `.bucl` files remain as
+/// reference implementations.
 pub fn register_all(eval: &mut Evaluator) {
+    append::register(eval);
+    array::register(eval);
+    assert::register(eval);
     assign::register(eval);
+    callblock::register(eval);
+    capture::register(eval);
+    const_fn::register(eval);
+    copyvar::register(eval);
     each::register(eval);
+    #[cfg(feature = "fs")]
+    eachline::register(eval);
     echo::register(eval);
+    #[cfg(feature = "text")]
+    encoding::register(eval);
+    error_fn::register(eval);
+    exit::register(eval);
+    functions_fn::register(eval);
+    #[cfg(feature = "text")]
+    fuzzyfilter::register(eval);
+    #[cfg(feature = "i18n")]
+    i18n::register(eval);
     if_fn::register(eval);
+    imageinfo::register(eval);
+    jscall::register(eval);
+    keys::register(eval);
+    listops::register(eval);
+    local::register(eval);
+    log::register(eval);
+    markdown::register(eval);
+    #[cfg(feature = "math")]
     math::register(eval);
+    #[cfg(feature = "text")]
+    maxlength::register(eval);
+    merge::register(eval);
+    net::register(eval);
+    #[cfg(feature = "text")]
+    numwords::register(eval);
+    params::register(eval);
+    pass::register(eval);
+    qrcode::register(eval);
+    #[cfg(feature = "random")]
     random::register(eval);
+    #[cfg(feature = "fs")]
     readfile::register(eval);
+    #[cfg(feature = "fs")]
+    render::register(eval);
     repeat::register(eval);
+    #[cfg(feature = "text")]
+    repeatstr::register(eval);
+    #[cfg(feature = "text")]
+    reverse::register(eval);
+    #[cfg(feature = "text")]
+    similarity::register(eval);
+    #[cfg(feature = "sleep")]
     sleep::register(eval);
+    #[cfg(feature = "text")]
+    slice::register(eval);
+    sqlite::register(eval);
+    #[cfg(feature = "fs")]
+    store::register(eval);
+    #[cfg(feature = "text")]
+    style::register(eval);
+    typecheck::register(eval);
+    validate::register(eval);
+    #[cfg(feature = "text")]
+    wordwrap::register(eval);
+    #[cfg(feature = "fs")]
     writefile::register(eval);
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+/// Run `source` in a fresh evaluator with every built-in registered.  The
+/// built-ins' tests share it.
+#[cfg(test)]
+pub(crate) fn run_script(source: &str) -> (Evaluator, Result<()>) {
+    run_script_in(Evaluator::new(), source)
+}
+
+/// [`run_script`], in an evaluator the test has set up (a `MemoryFs`, …).
+#[cfg(test)]
+pub(crate) fn run_script_in(mut eval: Evaluator, source: &str) -> (Evaluator, Result<()>) {
+    register_all(&mut eval);
+    let result = eval.evaluate_statements(&crate::parser::parse(source).unwrap());
+    (eval, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_args_prefers_names_then_positions_then_defaults() {
+        let mut eval = Evaluator::new();
+        let spec = [ArgSpec::required("path"), ArgSpec::optional("mode", "r")];
+        let args = vec!["a.txt".to_string()];
+        let [path, mode] = bind_args(&eval, "open", &args, spec).unwrap();
+        assert_eq!((path.as_str(), mode.as_str()), ("a.txt", "r"));
+
+        eval.call_named_args.insert("mode".into(), "w".into());
+        let err = bind_args(&eval, "open", &[], spec).unwrap_err();
+        assert_eq!(err.to_string(), "Runtime error: open: missing argument 'path'");
+
+        let args = vec!["w".to_string(), "b.txt".to_string()];
+        eval.call_named_args.insert("path".into(), "b.txt".into());
+        let [path, mode] = bind_args(&eval, "open", &args, spec).unwrap();
+        assert_eq!((path.as_str(), mode.as_str()), ("b.txt", "w"));
+
+        // {mode} = "a"; open {mode} "c.txt" — the named argument doesn't
+        // take a position.
+        eval.call_named_args.clear();
+        eval.call_named_args.insert("mode".into(), "a".into());
+        eval.call_arg_names = vec![Some("mode".into()), None];
+        let args = vec!["a".to_string(), "c.txt".to_string()];
+        let [path, mode] = bind_args(&eval, "open", &args, spec).unwrap();
+        assert_eq!((path.as_str(), mode.as_str()), ("c.txt", "a"));
+
+        // A name that isn't a parameter is still positional.
+        eval.call_named_args.clear();
+        eval.call_named_args.insert("file".into(), "d.txt".into());
+        eval.call_arg_names = vec![Some("file".into())];
+        let [path, mode] = bind_args(&eval, "open", &["d.txt".to_string()], spec).unwrap();
+        assert_eq!((path.as_str(), mode.as_str()), ("d.txt", "r"));
+    }
+}
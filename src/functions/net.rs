@@ -0,0 +1,364 @@
+/// `netsend` / `netrecv` / `netclose` — talk to a TCP server.
+///
+/// Connections are kept open per `host:port` address: `netsend addr
+/// data...` connects if needed and sends the data (arguments concatenated),
+/// `netrecv addr` connects if needed and reads what the server sends, and
+/// `netclose addr` closes the connection.  Reading the banner of an SSH or
+/// SMTP server is a single `netrecv`:
+///
+/// ```bucl
+/// {addr} = "example.com:80"
+/// {s} netsend {addr} "HEAD / HTTP/1.0\r\n\r\n"
+/// {r} netrecv {addr} until:"\r\n" timeout:"2"
+/// if {r/ok} = "1"
+///     echo "status: {r}"
+/// else
+///     echo "probe failed: {r/error}"
+/// netclose {addr}
+/// ```
+///
+/// With a target, failures don't stop the script; they are stored next to
+/// the result, for health checks that report rather than abort:
+/// - `{t}`       — the data received (`netrecv`) or the number of bytes sent
+///   (`netsend`); `""` on failure.
+/// - `{t/ok}`    — `"1"` on success, `"0"` on failure.
+/// - `{t/error}` — what went wrong (`Connection refused …`, `timed out after
+///   2s`, …), `""` on success.
+///
+/// Without a target a failure is a runtime error.
+///
+/// `netrecv` reads until the server closes the connection, `max:"N"` bytes
+/// have arrived, or the text given with `until:"…"` has.  `timeout:"secs"`
+/// (default 5, fractions allowed) bounds connecting and each wait for data;
+/// running out of time after some data has arrived just ends the read.
+/// Received bytes that are not valid UTF-8 are replaced with `�`.
+///
+/// Needs network permission, which `bucl --sandbox` takes away and
+/// `--allow-net=<hosts>` grants for some hosts only (see
+/// [`crate::permissions`]).  Only available when built with the `net` cargo
+/// feature, and not in WASM builds.
+use crate::evaluator::Evaluator;
+
+#[cfg(all(feature = "net", not(target_arch = "wasm32")))]
+pub(crate) use native::Connections;
+
+#[cfg(all(feature = "net", not(target_arch = "wasm32")))]
+mod native {
+    use std::collections::HashMap;
+    use std::io::{self, ErrorKind, Read, Write};
+    use std::net::{TcpStream, ToSocketAddrs};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::ast::Statement;
+    use crate::error::{BuclError, Result};
+    use crate::evaluator::Evaluator;
+    use crate::functions::{take_options, BuclFunction};
+    use crate::permissions::Permission;
+
+    /// Open connections by address, shared with child evaluators.
+    pub(crate) type Connections = Arc<Mutex<HashMap<String, TcpStream>>>;
+
+    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Options of a `netsend` / `netrecv` call.
+    struct Options {
+        timeout: Duration,
+        until: Option<String>,
+        max: Option<usize>,
+    }
+
+    fn options(evaluator: &Evaluator, fname: &str, args: &mut Vec<String>) -> Result<Options> {
+        let mut options = Options {
+            timeout: DEFAULT_TIMEOUT,
+            until: None,
+            max: None,
+        };
+        let names: &[&str] = if fname == "netrecv" { &["timeout", "until", "max"] } else { &["timeout"] };
+        for (name, value) in take_options(evaluator, args, names).0 {
+            let invalid = |what: &str| {
+                BuclError::RuntimeError(format!("{}: {} must be {}, got '{}'", fname, name, what, value))
+            };
+            match name.as_str() {
+                "timeout" => {
+                    let secs: f64 = value.trim().parse().map_err(|_| invalid("a number of seconds"))?;
+                    options.timeout = Duration::try_from_secs_f64(secs)
+                        .ok()
+                        .filter(|d| !d.is_zero())
+                        .ok_or_else(|| invalid("a number of seconds"))?;
+                }
+                "until" if !value.is_empty() => options.until = Some(value),
+                "until" => return Err(invalid("non-empty text")),
+                _ => {
+                    options.max = Some(
+                        value
+                            .trim()
+                            .parse()
+                            .ok()
+                            .filter(|&n| n > 0)
+                            .ok_or_else(|| invalid("a positive number of bytes"))?,
+                    )
+                }
+            }
+        }
+        Ok(options)
+    }
+
+    /// The first argument: the `host:port` to talk to.
+    fn address(evaluator: &Evaluator, fname: &str, args: &[String]) -> Result<String> {
+        let addr = args.first().ok_or_else(|| {
+            BuclError::RuntimeError(format!("{}: missing host:port address", fname))
+        })?;
+        evaluator.permissions.require(Permission::Network, fname, addr)?;
+        Ok(addr.clone())
+    }
+
+    /// The open connection to `addr`, connecting first if there is none.
+    fn connection(evaluator: &Evaluator, addr: &str, timeout: Duration) -> io::Result<TcpStream> {
+        let mut connections = evaluator.connections.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(stream) = connections.get(addr) {
+            return stream.try_clone();
+        }
+        let mut last_err = io::Error::new(ErrorKind::NotFound, "no address found");
+        for socket in addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&socket, timeout) {
+                Ok(stream) => {
+                    connections.insert(addr.to_string(), stream.try_clone()?);
+                    return Ok(stream);
+                }
+                Err(e) => last_err = e,
+            }
+        }
+        Err(last_err)
+    }
+
+    fn forget(evaluator: &Evaluator, addr: &str) {
+        evaluator.connections.lock().unwrap_or_else(|e| e.into_inner()).remove(addr);
+    }
+
+    fn describe(e: &io::Error, timeout: Duration) -> String {
+        match e.kind() {
+            ErrorKind::WouldBlock | ErrorKind::TimedOut => {
+                format!("timed out after {}s", timeout.as_secs_f64())
+            }
+            _ => e.to_string(),
+        }
+    }
+
+    /// Store the outcome under `target` (value, `/ok`, `/error`), or turn a
+    /// failure into a runtime error when there is no target.
+    fn report(
+        evaluator: &mut Evaluator,
+        fname: &str,
+        target: Option<&str>,
+        outcome: std::result::Result<String, String>,
+    ) -> Result<Option<String>> {
+        let Some(prefix) = target else {
+            return outcome
+                .map(Some)
+                .map_err(|e| BuclError::RuntimeError(format!("{}: {}", fname, e)));
+        };
+        let (value, ok, error) = match outcome {
+            Ok(value) => (value, "1", String::new()),
+            Err(error) => (String::new(), "0", error),
+        };
+        evaluator.remove_tree(prefix);
+        evaluator.set_var(prefix, value);
+        evaluator.variables.insert(format!("{}/ok", prefix), ok.to_string());
+        evaluator.variables.insert(format!("{}/error", prefix), error);
+        Ok(None) // Everything already stored directly.
+    }
+
+    pub struct NetSend;
+
+    impl BuclFunction for NetSend {
+        fn call(
+            &self,
+            evaluator: &mut Evaluator,
+            target: Option<&str>,
+            mut args: Vec<String>,
+            _block: Option<&[Statement]>,
+            _continuation: Option<&Statement>,
+        ) -> Result<Option<String>> {
+            let options = options(evaluator, "netsend", &mut args)?;
+            let addr = address(evaluator, "netsend", &args)?;
+            let data = args[1..].concat();
+            let sent = connection(evaluator, &addr, options.timeout).and_then(|mut stream| {
+                stream.set_write_timeout(Some(options.timeout))?;
+                stream.write_all(data.as_bytes())?;
+                stream.flush()
+            });
+            let outcome = match sent {
+                Ok(()) => Ok(data.len().to_string()),
+                Err(e) => {
+                    forget(evaluator, &addr);
+                    Err(describe(&e, options.timeout))
+                }
+            };
+            report(evaluator, "netsend", target, outcome)
+        }
+
+        fn description(&self) -> &str {
+            "Send data to a TCP server, connecting if needed: `netsend \"host:port\" \"data\"`."
+        }
+    }
+
+    pub struct NetRecv;
+
+    impl BuclFunction for NetRecv {
+        fn call(
+            &self,
+            evaluator: &mut Evaluator,
+            target: Option<&str>,
+            mut args: Vec<String>,
+            _block: Option<&[Statement]>,
+            _continuation: Option<&Statement>,
+        ) -> Result<Option<String>> {
+            let options = options(evaluator, "netrecv", &mut args)?;
+            let addr = address(evaluator, "netrecv", &args)?;
+            let mut data: Vec<u8> = Vec::new();
+            let received = connection(evaluator, &addr, options.timeout).and_then(|mut stream| {
+                stream.set_read_timeout(Some(options.timeout))?;
+                let mut buf = [0u8; 4096];
+                loop {
+                    let want = match options.max {
+                        Some(max) if data.len() >= max => return Ok(()),
+                        Some(max) => (max - data.len()).min(buf.len()),
+                        None => buf.len(),
+                    };
+                    match stream.read(&mut buf[..want]) {
+                        Ok(0) => {
+                            forget(evaluator, &addr);
+                            return Ok(());
+                        }
+                        Ok(n) => data.extend_from_slice(&buf[..n]),
+                        Err(e)
+                            if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+                                && !data.is_empty() =>
+                        {
+                            return Ok(())
+                        }
+                        Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                        Err(e) => return Err(e),
+                    }
+                    if let Some(until) = &options.until {
+                        if data.windows(until.len()).any(|w| w == until.as_bytes()) {
+                            return Ok(());
+                        }
+                    }
+                }
+            });
+            let outcome = match received {
+                Ok(()) => Ok(String::from_utf8_lossy(&data).into_owned()),
+                Err(e) => {
+                    if !matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) {
+                        forget(evaluator, &addr);
+                    }
+                    Err(describe(&e, options.timeout))
+                }
+            };
+            report(evaluator, "netrecv", target, outcome)
+        }
+
+        fn description(&self) -> &str {
+            "Receive data from a TCP server: `{reply} netrecv \"host:port\"`."
+        }
+    }
+
+    pub struct NetClose;
+
+    impl BuclFunction for NetClose {
+        fn call(
+            &self,
+            evaluator: &mut Evaluator,
+            _target: Option<&str>,
+            args: Vec<String>,
+            _block: Option<&[Statement]>,
+            _continuation: Option<&Statement>,
+        ) -> Result<Option<String>> {
+            let addr = args.first().ok_or_else(|| {
+                BuclError::RuntimeError("netclose: missing host:port address".into())
+            })?;
+            let stream = evaluator.connections.lock().unwrap_or_else(|e| e.into_inner()).remove(addr);
+            if let Some(stream) = stream {
+                // The server may have gone already; closing is all that matters.
+                let _ = stream.shutdown(std::net::Shutdown::Both);
+            }
+            Ok(None)
+        }
+
+        fn description(&self) -> &str {
+            "Close the connection to a TCP server: `netclose \"host:port\"`."
+        }
+    }
+
+    pub fn register(eval: &mut Evaluator) {
+        eval.register("netsend", NetSend);
+        eval.register("netrecv", NetRecv);
+        eval.register("netclose", NetClose);
+    }
+
+    // -----------------------------------------------------------------------
+    // Tests
+    // -----------------------------------------------------------------------
+
+    #[cfg(test)]
+    mod tests {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        use crate::evaluator::Evaluator;
+
+        #[test]
+        fn test_send_and_receive() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap().to_string();
+            let server = std::thread::spawn(move || {
+                let (stream, _) = listener.accept().unwrap();
+                let mut writer = stream.try_clone().unwrap();
+                writer.write_all(b"HELLO\r\n").unwrap();
+                let mut line = String::new();
+                BufReader::new(stream).read_line(&mut line).unwrap();
+                writer.write_all(format!("echo: {}", line).as_bytes()).unwrap();
+            });
+
+            let mut eval = Evaluator::new();
+            crate::functions::register_all(&mut eval);
+            let source = format!(
+                concat!(
+                    "{{addr}} = \"{}\"\n",
+                    "{{banner}} netrecv {{addr}} until:\"\\r\\n\"\n",
+                    "{{s}} netsend {{addr}} \"ping\\n\"\n",
+                    "{{reply}} netrecv {{addr}}\n",
+                    "netclose {{addr}}\n",
+                    "{{refused}} netrecv {{addr}} timeout:\"1\"\n",
+                ),
+                addr
+            );
+            eval.evaluate_statements(&crate::parser::parse(&source).unwrap()).unwrap();
+            server.join().unwrap();
+            for (name, value) in [
+                ("banner", "HELLO\r\n"),
+                ("banner/ok", "1"),
+                ("s", "5"),
+                ("reply", "echo: ping\n"),
+                ("refused/ok", "0"),
+            ] {
+                assert_eq!(eval.resolve_var(name), value, "{}", name);
+            }
+            assert!(!eval.resolve_var("refused/error").is_empty());
+
+            eval.permissions = crate::permissions::Permissions::sandboxed();
+            let err = eval
+                .evaluate_statements(&crate::parser::parse("{r} netrecv \"localhost:1\"").unwrap())
+                .unwrap_err();
+            assert!(err.to_string().contains("netrecv: network access is not permitted"), "{}", err);
+        }
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    #[cfg(all(feature = "net", not(target_arch = "wasm32")))]
+    native::register(eval);
+    let _ = eval; // suppress unused warning without the feature / on wasm32
+}
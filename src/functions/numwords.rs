@@ -0,0 +1,313 @@
+/// `numtowords` / `wordstonum` — spell out numbers in English words and back.
+///
+/// `numtowords` accepts any integer in the `i64` range, optionally with a
+/// decimal part.  Digits after the decimal point are read one by one.
+///
+/// ```bucl
+/// {w} numtowords 342          # three hundred forty-two
+/// {w} numtowords -1005        # minus one thousand five
+/// {w} numtowords 12.50        # twelve point five zero
+/// ```
+///
+/// `wordstonum` is the best-effort inverse.  It ignores case, `and`, commas,
+/// and hyphens, and accepts `minus`/`negative` and `point`:
+///
+/// ```bucl
+/// {n} wordstonum "Three hundred and forty-two"   # 342
+/// {n} wordstonum "one million, two thousand"     # 1002000
+/// {n} wordstonum "zero point two five"           # 0.25
+/// ```
+///
+/// Unknown words are a runtime error.  Named param `{number}` / `{words}`
+/// may be used instead of positional arguments.
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::{bind_args, ArgSpec, BuclFunction};
+
+const ONES: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen",
+    "nineteen",
+];
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const SCALES: [(u64, &str); 6] = [
+    (1_000_000_000_000_000_000, "quintillion"),
+    (1_000_000_000_000_000, "quadrillion"),
+    (1_000_000_000_000, "trillion"),
+    (1_000_000_000, "billion"),
+    (1_000_000, "million"),
+    (1_000, "thousand"),
+];
+
+// ---------------------------------------------------------------------------
+// Number → words
+// ---------------------------------------------------------------------------
+
+/// Spell out a number below one thousand (`n` > 0).
+fn hundreds_to_words(n: u64, out: &mut Vec<String>) {
+    if n >= 100 {
+        out.push(ONES[(n / 100) as usize].to_string());
+        out.push("hundred".to_string());
+    }
+    let rest = n % 100;
+    if rest == 0 {
+        return;
+    }
+    if rest < 20 {
+        out.push(ONES[rest as usize].to_string());
+    } else if rest.is_multiple_of(10) {
+        out.push(TENS[(rest / 10) as usize].to_string());
+    } else {
+        out.push(format!("{}-{}", TENS[(rest / 10) as usize], ONES[(rest % 10) as usize]));
+    }
+}
+
+fn integer_to_words(mut n: u64) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+    let mut words = Vec::new();
+    for (scale, name) in SCALES {
+        if n >= scale {
+            hundreds_to_words(n / scale, &mut words);
+            words.push(name.to_string());
+            n %= scale;
+        }
+    }
+    if n > 0 {
+        hundreds_to_words(n, &mut words);
+    }
+    words.join(" ")
+}
+
+fn number_to_words(input: &str) -> std::result::Result<String, String> {
+    let trimmed = input.trim();
+    let (negative, digits) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    let (int_part, frac_part) = match digits.split_once('.') {
+        Some((i, f)) => (i, Some(f)),
+        None => (digits, None),
+    };
+
+    let valid = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if (int_part.is_empty() && frac_part.is_none_or(str::is_empty))
+        || !valid(int_part)
+        || !frac_part.is_none_or(valid)
+    {
+        return Err(format!("'{}' is not a number", input));
+    }
+
+    let int_value: u64 = if int_part.is_empty() {
+        0
+    } else {
+        int_part
+            .parse()
+            .map_err(|_| format!("'{}' is out of range", input))?
+    };
+    if int_value > i64::MAX as u64 {
+        return Err(format!("'{}' is out of range", input));
+    }
+
+    let mut words = integer_to_words(int_value);
+    if let Some(frac) = frac_part.filter(|f| !f.is_empty()) {
+        words.push_str(" point");
+        for d in frac.chars() {
+            words.push(' ');
+            words.push_str(ONES[d.to_digit(10).unwrap() as usize]);
+        }
+    }
+    if negative && words != "zero" {
+        words.insert_str(0, "minus ");
+    }
+    Ok(words)
+}
+
+// ---------------------------------------------------------------------------
+// Words → number
+// ---------------------------------------------------------------------------
+
+fn word_value(word: &str) -> Option<u64> {
+    if let Some(i) = ONES.iter().position(|w| *w == word) {
+        return Some(i as u64);
+    }
+    TENS.iter()
+        .position(|w| !w.is_empty() && *w == word)
+        .map(|i| i as u64 * 10)
+}
+
+fn words_to_number(input: &str) -> std::result::Result<String, String> {
+    let lowered = input.to_lowercase().replace([',', '-'], " ");
+    let mut tokens = lowered.split_whitespace().filter(|w| *w != "and").peekable();
+
+    let negative = matches!(tokens.peek(), Some(&("minus" | "negative")));
+    if negative {
+        tokens.next();
+    }
+
+    let mut total: u64 = 0;
+    let mut current: u64 = 0;
+    let mut seen_any = false;
+    let mut fraction = String::new();
+    let mut in_fraction = false;
+
+    let out_of_range = || format!("'{}' is out of range", input);
+    for word in tokens {
+        if in_fraction {
+            match word_value(word) {
+                Some(d) if d < 10 => fraction.push(char::from_digit(d as u32, 10).unwrap()),
+                _ => return Err(format!("unexpected word '{}' after 'point'", word)),
+            }
+            continue;
+        }
+        match word {
+            "point" => in_fraction = true,
+            "hundred" => current = current.max(1).checked_mul(100).ok_or_else(out_of_range)?,
+            _ => {
+                if let Some(&(scale, _)) = SCALES.iter().find(|(_, name)| *name == word) {
+                    total = current
+                        .max(1)
+                        .checked_mul(scale)
+                        .and_then(|v| total.checked_add(v))
+                        .ok_or_else(out_of_range)?;
+                    current = 0;
+                } else if let Some(v) = word_value(word) {
+                    current = current.checked_add(v).ok_or_else(out_of_range)?;
+                } else {
+                    return Err(format!("unknown number word '{}'", word));
+                }
+            }
+        }
+        seen_any = true;
+    }
+
+    if !seen_any {
+        return Err(format!("'{}' contains no number words", input));
+    }
+    if in_fraction && fraction.is_empty() {
+        return Err("expected digits after 'point'".to_string());
+    }
+
+    let value = total.checked_add(current).ok_or_else(out_of_range)?;
+    let mut result = if negative && (value != 0 || fraction.chars().any(|c| c != '0')) {
+        format!("-{}", value)
+    } else {
+        value.to_string()
+    };
+    if !fraction.is_empty() {
+        result.push('.');
+        result.push_str(&fraction);
+    }
+    Ok(result)
+}
+
+// ---------------------------------------------------------------------------
+// Built-ins
+// ---------------------------------------------------------------------------
+
+pub struct NumToWords;
+
+impl BuclFunction for NumToWords {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let [number] = bind_args(evaluator, "numtowords", &args, [ArgSpec::required("number")])?;
+        number_to_words(&number)
+            .map(Some)
+            .map_err(|e| BuclError::RuntimeError(format!("numtowords: {}", e)))
+    }
+
+    fn description(&self) -> &str {
+        "Spell out a number in English words: `{w} numtowords 42`."
+    }
+}
+
+pub struct WordsToNum;
+
+impl BuclFunction for WordsToNum {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        // All arguments are joined so both `wordstonum "forty two"` and
+        // `wordstonum forty two` work.
+        let words = evaluator
+            .named_arg("words")
+            .cloned()
+            .unwrap_or_else(|| args.join(" "));
+        words_to_number(&words)
+            .map(Some)
+            .map_err(|e| BuclError::RuntimeError(format!("wordstonum: {}", e)))
+    }
+
+    fn description(&self) -> &str {
+        "Turn English number words back into a number: `{n} wordstonum \"forty two\"`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("numtowords", NumToWords);
+    eval.register("wordstonum", WordsToNum);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_to_words() {
+        assert_eq!(number_to_words("0").unwrap(), "zero");
+        assert_eq!(number_to_words("15").unwrap(), "fifteen");
+        assert_eq!(number_to_words("40").unwrap(), "forty");
+        assert_eq!(number_to_words("342").unwrap(), "three hundred forty-two");
+        assert_eq!(number_to_words("-1005").unwrap(), "minus one thousand five");
+        assert_eq!(number_to_words("2000001").unwrap(), "two million one");
+        assert_eq!(number_to_words("12.50").unwrap(), "twelve point five zero");
+    }
+
+    #[test]
+    fn test_number_to_words_rejects_garbage() {
+        assert!(number_to_words("abc").is_err());
+        assert!(number_to_words("").is_err());
+        assert!(number_to_words("1.2.3").is_err());
+        assert!(number_to_words("99999999999999999999").is_err());
+    }
+
+    #[test]
+    fn test_words_to_number() {
+        assert_eq!(words_to_number("three hundred forty-two").unwrap(), "342");
+        assert_eq!(words_to_number("Three hundred and forty two").unwrap(), "342");
+        assert_eq!(words_to_number("one million, two thousand").unwrap(), "1002000");
+        assert_eq!(words_to_number("minus five").unwrap(), "-5");
+        assert_eq!(words_to_number("zero point two five").unwrap(), "0.25");
+        assert!(words_to_number("twelve bananas").is_err());
+
+        let huge = format!("one{}", " hundred".repeat(10));
+        assert_eq!(words_to_number(&huge), Err(format!("'{}' is out of range", huge)));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for n in [0u64, 7, 19, 20, 101, 999, 1_000, 123_456_789, i64::MAX as u64] {
+            let words = integer_to_words(n);
+            assert_eq!(words_to_number(&words).unwrap(), n.to_string(), "{}", words);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+/// `params` — declare the parameters of a `.bucl` function.
+///
+/// Written at the top of a function, `params` binds each argument to a
+/// variable, fills in defaults and fails the call when a required argument
+/// is missing:
+///
+/// ```bucl
+/// # functions/connect.bucl
+/// params host port timeout:"30"
+/// {return} = "{host}:{port} ({timeout}s)"
+/// ```
+///
+/// `connect "db1" "5432"`, `connect {host} {port}` and
+/// `connect port:"5432" host:"db1"` all bind `{host}` and `{port}`.
+/// Arguments whose name matches a parameter bind to it; the others fill the
+/// remaining parameters in order.  A parameter written `name:"default"` is
+/// optional: the default is interpolated after the arguments are bound, so
+/// it can use earlier parameters (`url:"http://{host}:{port}"`).
+///
+/// The last parameter may be written `name...` to collect the remaining
+/// arguments as the array `{name}` (`{name/count}`, `{name/0}`, …).  Without
+/// one, an unnamed argument left over is an error; named ones left over are
+/// ignored, so a struct with extra fields can still be passed.
+use crate::ast::{Param, Statement};
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct Params;
+
+/// One declared parameter.
+struct Declared {
+    name: String,
+    default: Option<Param>,
+}
+
+impl BuclFunction for Params {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        _args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (declared, rest) = declarations(&evaluator.call_params)?;
+
+        // Arguments named after a parameter go to it; the others are
+        // positional, whether or not they carry a name.
+        let mut bound: Vec<Option<String>> = vec![None; declared.len()];
+        let mut positional = Vec::new();
+        for arg in &evaluator.call_args {
+            let slot = arg
+                .name
+                .as_ref()
+                .and_then(|name| declared.iter().position(|d| &d.name == name));
+            match slot {
+                Some(i) if bound[i].is_none() => bound[i] = Some(arg.value.clone()),
+                _ => positional.push(arg),
+            }
+        }
+        let mut positional = positional.into_iter();
+        for slot in bound.iter_mut().filter(|slot| slot.is_none()) {
+            match positional.next() {
+                Some(arg) => *slot = Some(arg.value.clone()),
+                None => break,
+            }
+        }
+        let extra: Vec<_> = positional.collect();
+        if rest.is_none() && extra.iter().any(|arg| arg.name.is_none()) {
+            return Err(BuclError::RuntimeError(format!(
+                "params: too many arguments: expected {}, got {}",
+                declared.len(),
+                evaluator.call_args.len()
+            )));
+        }
+        let extra: Vec<String> = extra.into_iter().map(|arg| arg.value.clone()).collect();
+
+        for (param, value) in declared.iter().zip(&bound) {
+            if let Some(value) = value {
+                evaluator.set_var(&param.name, value.clone());
+            }
+        }
+        for (param, value) in declared.iter().zip(&bound) {
+            if value.is_some() {
+                continue;
+            }
+            let default = param.default.as_ref().ok_or_else(|| {
+                BuclError::RuntimeError(format!("params: missing argument '{}'", param.name))
+            })?;
+            let value = evaluator.eval_param(default);
+            evaluator.set_var(&param.name, value);
+        }
+        if let Some(rest) = rest {
+            evaluator.set_array(&rest, extra);
+        }
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Bind a `.bucl` function's arguments to named parameters, with defaults: `params host port timeout:\"30\"`."
+    }
+}
+
+/// The declared parameters, and the name of a trailing `name...` one.
+fn declarations(params: &[Param]) -> Result<(Vec<Declared>, Option<String>)> {
+    let mut declared = Vec::new();
+    let mut rest = None;
+    for (i, param) in params.iter().enumerate() {
+        let (name, default) = match param {
+            Param::Bare(word) => match word.strip_suffix("...") {
+                Some(name) if i + 1 == params.len() => {
+                    rest = Some(name.to_string());
+                    continue;
+                }
+                Some(name) => {
+                    return Err(BuclError::RuntimeError(format!(
+                        "params: {}... must be the last parameter",
+                        name
+                    )))
+                }
+                None => (word.clone(), None),
+            },
+            Param::Named(name, value) => (name.clone(), Some((**value).clone())),
+            _ => {
+                return Err(BuclError::RuntimeError(
+                    "params: expected parameter names, e.g. params host port timeout:\"30\"".into(),
+                ))
+            }
+        };
+        declared.push(Declared { name, default });
+    }
+    Ok((declared, rest))
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("params", Params);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    fn run(function: &str, source: &str) -> Result<Evaluator, String> {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.embed_function("f", function);
+        let stmts = crate::parser::parse(source).unwrap();
+        eval.evaluate_statements(&stmts).map_err(|e| e.to_string())?;
+        Ok(eval)
+    }
+
+    #[test]
+    fn test_params_bind_arguments() {
+        let f = concat!(
+            "params host port timeout:\"30\" url:\"http://{host}:{port}\"\n",
+            "{return} = \"{url} {timeout}\"\n",
+        );
+        let eval = run(
+            f,
+            concat!(
+                "{a} f \"db1\" \"5432\"\n",
+                "{port} = \"1\"\n",
+                "{b} f \"db2\" {port} timeout:\"5\"\n",
+                "{c} f port:\"2\" \"db3\"\n",
+            ),
+        )
+        .unwrap();
+        assert_eq!(eval.resolve_var("a"), "http://db1:5432 30");
+        assert_eq!(eval.resolve_var("b"), "http://db2:1 5");
+        assert_eq!(eval.resolve_var("c"), "http://db3:2 30");
+
+        let err = run(f, "{a} f \"db1\"\n").err().unwrap();
+        assert!(err.contains("missing argument 'port'"), "{}", err);
+        let err = run(f, "{a} f 1 2 3 4 5\n").err().unwrap();
+        assert!(err.contains("too many arguments: expected 4, got 5"), "{}", err);
+    }
+
+    #[test]
+    fn test_rest_parameter_collects_remaining_arguments() {
+        let f = concat!(
+            "params sep items...\n",
+            "{return} = \"{items/count}:{items/0}{sep}{items/1}\"\n",
+        );
+        let eval = run(f, "{words} = \"a\" \"b\"\n{r} f \",\" {words}\n").unwrap();
+        assert_eq!(eval.resolve_var("r"), "2:a,b");
+
+        let err = run("params items... sep\n", "f 1\n").err().unwrap();
+        assert!(err.contains("items... must be the last parameter"), "{}", err);
+    }
+}
@@ -0,0 +1,66 @@
+/// `pass` — do nothing.
+///
+/// Marks a branch that is empty on purpose, or one that is still to be
+/// written, so the intent is visible to the next reader:
+///
+/// ```bucl
+/// if {mode} = "dry-run"
+///     pass
+/// else
+///     writefile {path} {report}
+/// ```
+///
+/// A branch with no statements at all is also allowed; `pass` just says so.
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct Pass;
+
+impl BuclFunction for Pass {
+    fn call(
+        &self,
+        _evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        if !args.is_empty() {
+            return Err(BuclError::RuntimeError(format!(
+                "pass: takes no arguments, got {}",
+                args.len()
+            )));
+        }
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Do nothing; marks a branch that is empty on purpose."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("pass", Pass);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::run_script;
+
+    #[test]
+    fn test_pass_does_nothing() {
+        let source = "if 1 = 1\n    pass\nelse\n    echo no\nif 1 = 2\n    echo no\nelse\n    pass\necho done\n";
+        let (eval, result) = run_script(source);
+        result.unwrap();
+        assert_eq!(eval.output_buffer, vec!["done"]);
+
+        let (_, result) = run_script("pass 1\n");
+        assert!(result.unwrap_err().to_string().contains("pass: takes no arguments"));
+    }
+}
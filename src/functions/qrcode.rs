@@ -0,0 +1,125 @@
+/// `qrcode` — encode text as a QR code.
+///
+/// Arguments: the text to encode, then an optional output format:
+/// - `unicode` (default) — half-block characters for printing in a terminal.
+///   The colours are inverted so the code scans on dark terminal themes.
+/// - `svg` — a standalone `<svg>` document, e.g. for `writefile` or the web demo.
+///
+/// ```bucl
+/// {q} qrcode "https://example.com"
+/// echo {q}
+///
+/// {svg} qrcode "https://example.com" "svg"
+/// writefile "link.svg" {svg}
+/// ```
+///
+/// Named params `{text}` and `{format}` are also accepted.
+///
+/// Only available when built with the `qrcode` cargo feature.
+use crate::evaluator::Evaluator;
+
+#[cfg(feature = "qrcode")]
+mod enabled {
+    use qrcode::render::{svg, unicode};
+    use qrcode::QrCode;
+
+    use crate::ast::Statement;
+    use crate::error::{BuclError, Result};
+    use crate::evaluator::Evaluator;
+    use crate::functions::{bind_args, ArgSpec, BuclFunction};
+
+    pub struct QrCodeFn;
+
+    impl BuclFunction for QrCodeFn {
+        fn call(
+            &self,
+            evaluator: &mut Evaluator,
+            _target: Option<&str>,
+            args: Vec<String>,
+            _block: Option<&[Statement]>,
+            _continuation: Option<&Statement>,
+        ) -> Result<Option<String>> {
+            let [text, format] = bind_args(
+                evaluator,
+                "qrcode",
+                &args,
+                [ArgSpec::required("text"), ArgSpec::optional("format", "unicode")],
+            )?;
+
+            let code = QrCode::new(text.as_bytes())
+                .map_err(|e| BuclError::RuntimeError(format!("qrcode: {}", e)))?;
+
+            let rendered = match format.as_str() {
+                "unicode" => code
+                    .render::<unicode::Dense1x2>()
+                    .dark_color(unicode::Dense1x2::Light)
+                    .light_color(unicode::Dense1x2::Dark)
+                    .build(),
+                "svg" => code.render::<svg::Color>().min_dimensions(200, 200).build(),
+                other => {
+                    return Err(BuclError::RuntimeError(format!(
+                        "qrcode: unknown format '{}' (expected 'unicode' or 'svg')",
+                        other
+                    )));
+                }
+            };
+            Ok(Some(rendered))
+        }
+
+        fn description(&self) -> &str {
+            "Encode text as a QR code, as terminal characters or SVG: `{svg} qrcode \"https://example.com\" \"svg\"`."
+        }
+    }
+
+    pub fn register(eval: &mut Evaluator) {
+        eval.register("qrcode", QrCodeFn);
+    }
+
+    // -----------------------------------------------------------------------
+    // Tests
+    // -----------------------------------------------------------------------
+
+    #[cfg(test)]
+    mod tests {
+        use crate::functions::run_script;
+
+        #[test]
+        fn test_unicode_and_svg_output() {
+            let (eval, result) = run_script(concat!(
+                "{q} qrcode \"hello\"\n",
+                "{svg} qrcode \"hello\" \"svg\"\n",
+            ));
+            result.unwrap();
+            let q = eval.resolve_var("q");
+            assert!(q.lines().count() > 10, "{}", q);
+            assert!(q.chars().all(|c| " \u{2580}\u{2584}\u{2588}\n".contains(c)), "{}", q);
+            let svg = eval.resolve_var("svg");
+            assert!(svg.starts_with("<?xml") && svg.contains("<svg"), "{}", svg);
+
+            let err = run_script("{q} qrcode \"hello\" \"png\"").1.unwrap_err();
+            assert!(err.to_string().contains("qrcode: unknown format 'png'"), "{}", err);
+        }
+
+        #[test]
+        fn test_named_text_and_format() {
+            let (eval, result) = run_script(concat!(
+                "{plain} qrcode \"hello\" \"svg\"\n",
+                "{format} = \"svg\"\n",
+                "{by_variable} qrcode {format} \"hello\"\n",
+                "{by_name} qrcode format:\"svg\" text:\"hello\"\n",
+                "{other} qrcode \"svg\" \"svg\"\n",
+            ));
+            result.unwrap();
+            let plain = eval.resolve_var("plain");
+            assert_eq!(eval.resolve_var("by_variable"), plain);
+            assert_eq!(eval.resolve_var("by_name"), plain);
+            assert_ne!(eval.resolve_var("other"), plain);
+        }
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    #[cfg(feature = "qrcode")]
+    enabled::register(eval);
+    let _ = eval; // suppress unused warning without the feature
+}
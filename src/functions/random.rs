@@ -1,25 +1,23 @@
-/// `random` — generate a random integer.
-///
-/// ```bucl
-/// {r} random           # 0 .. i64::MAX
-/// {r} random 10        # 0 .. 10  (inclusive)
-/// {r} random 1 6       # 1 .. 6   (inclusive, like a die)
-/// ```
-///
-/// On native targets this uses `rand::thread_rng`.
-/// On WASM targets it imports `js_math_random` from the host (provided by the
-/// demo's JS glue as `() => Math.random()`).
+//! `random` — generate a random integer.
+//!
+//! ```bucl
+//! {r} random           # 0 .. i64::MAX
+//! {r} random 10        # 0 .. 10  (inclusive)
+//! {r} random 1 6       # 1 .. 6   (inclusive, like a die)
+//! ```
+//!
+//! On native targets this uses `rand::thread_rng`.
+//! On WASM targets it imports `js_math_random` from the host (provided by the
+//! demo's JS glue as `() => Math.random()`).
+//!
+//! After `randomseed N` (or when a `seed` named argument is passed) numbers
+//! come from a [`SeededRng`] stored on the evaluator instead, so the sequence
+//! is the same on every run and on every target.
 
 // Native: pull in the rand crate.
 #[cfg(not(target_arch = "wasm32"))]
 use rand::Rng;
 
-// WASM: import Math.random() from the JavaScript host.
-#[cfg(target_arch = "wasm32")]
-extern "C" {
-    fn js_math_random() -> f64;
-}
-
 fn random_in_range(min: i64, max: i64) -> i64 {
     #[cfg(not(target_arch = "wasm32"))]
     {
@@ -27,13 +25,43 @@ fn random_in_range(min: i64, max: i64) -> i64 {
     }
     #[cfg(target_arch = "wasm32")]
     {
-        let f = unsafe { js_math_random() };
+        let f = crate::host::math_random();
         // Map [0, 1) float to [min, max] integer.
         let range = (max - min).saturating_add(1) as f64;
         min + (f * range) as i64
     }
 }
 
+/// Deterministic generator behind `randomseed` (SplitMix64).
+///
+/// Small, dependency-free and identical on every target; not suitable for
+/// anything security-related.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform integer in `min..=max` (requires `min <= max`).
+    pub fn in_range(&mut self, min: i64, max: i64) -> i64 {
+        let span = (max as i128 - min as i128 + 1) as u128;
+        let offset = (self.next_u64() as u128 * span) >> 64;
+        (min as i128 + offset as i128) as i64
+    }
+}
+
 use crate::ast::Statement;
 use crate::error::{BuclError, Result};
 use crate::evaluator::Evaluator;
@@ -77,10 +105,97 @@ impl BuclFunction for Random {
             )));
         }
 
-        Ok(Some(random_in_range(min, max).to_string()))
+        if let Some(seed) = evaluator.named_arg("seed").cloned() {
+            evaluator.rng = Some(SeededRng::new(parse_seed("random", &seed)?));
+        }
+        let value = match evaluator.rng.as_mut() {
+            Some(rng) => rng.in_range(min, max),
+            None => random_in_range(min, max),
+        };
+        Ok(Some(value.to_string()))
+    }
+
+    fn description(&self) -> &str {
+        "A random integer between min and max, inclusive: `{n} random 1 6`."
+    }
+}
+
+/// `randomseed N` — make `random` deterministic from here on.
+/// `randomseed` with no argument goes back to system randomness.
+///
+/// ```bucl
+/// randomseed 42
+/// {a} random 1 6   # same value on every run
+/// ```
+pub struct RandomSeed;
+
+impl BuclFunction for RandomSeed {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        evaluator.rng = match args.first() {
+            Some(seed) => Some(SeededRng::new(parse_seed("randomseed", seed)?)),
+            None => None,
+        };
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Make `random` deterministic from here on, or random again without a seed: `randomseed 42`."
     }
 }
 
+/// Seeds are integers; negative ones are accepted and reinterpreted as `u64`.
+fn parse_seed(fname: &str, s: &str) -> Result<u64> {
+    let s = s.trim();
+    s.parse::<u64>()
+        .or_else(|_| s.parse::<i64>().map(|n| n as u64))
+        .map_err(|_| BuclError::RuntimeError(format!("{}: '{}' is not a valid integer seed", fname, s)))
+}
+
 pub fn register(eval: &mut Evaluator) {
     eval.register("random", Random);
+    eval.register("randomseed", RandomSeed);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(eval: &mut Evaluator, src: &str) -> Vec<String> {
+        eval.output_buffer.clear();
+        let stmts = crate::parser::parse(src).unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        eval.output_buffer.clone()
+    }
+
+    #[test]
+    fn test_randomseed_repeats_sequence() {
+        let script = "randomseed 42\nrepeat 5\n    {r} random 1 6\n    echo {r}\n";
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let first = run(&mut eval, script);
+        let second = run(&mut eval, script);
+        assert_eq!(first, second);
+        assert!(first.iter().all(|v| (1..=6).contains(&v.parse::<i64>().unwrap())));
+    }
+
+    #[test]
+    fn test_seeded_range_extremes() {
+        let mut rng = SeededRng::new(7);
+        for _ in 0..100 {
+            assert_eq!(rng.in_range(3, 3), 3);
+            let full = rng.in_range(i64::MIN, i64::MAX);
+            assert!((i64::MIN..=i64::MAX).contains(&full));
+        }
+    }
 }
@@ -4,49 +4,37 @@
 /// {contents} readfile "hello.txt"
 /// ```
 ///
-/// Not available in WASM builds (no filesystem access).
+/// Reads through the evaluator's [`FileSystem`](crate::fs::FileSystem): the
+/// real filesystem on native builds, the host's virtual filesystem on WASM.
+use crate::ast::Statement;
+use crate::error::Result;
 use crate::evaluator::Evaluator;
+use crate::functions::{bind_args, ArgSpec, BuclFunction};
+use crate::permissions::Permission;
 
-#[cfg(not(target_arch = "wasm32"))]
-mod native {
-    use std::fs;
+pub struct ReadFile;
 
-    use crate::ast::Statement;
-    use crate::error::{BuclError, Result};
-    use crate::evaluator::Evaluator;
-    use crate::functions::BuclFunction;
-
-    pub struct ReadFile;
-
-    impl BuclFunction for ReadFile {
-        fn call(
-            &self,
-            evaluator: &mut Evaluator,
-            _target: Option<&str>,
-            args: Vec<String>,
-            _block: Option<&[Statement]>,
-            _continuation: Option<&Statement>,
-        ) -> Result<Option<String>> {
-            // Named param: {path} = "hello.txt"; {c} readfile {path}
-            let path = evaluator
-                .named_arg("path")
-                .cloned()
-                .or_else(|| args.first().cloned())
-                .ok_or_else(|| {
-                    BuclError::RuntimeError("readfile: missing path argument".into())
-                })?;
-            let contents = fs::read_to_string(&path)?;
-            Ok(Some(contents))
-        }
+impl BuclFunction for ReadFile {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        // Named param: {path} = "hello.txt"; {c} readfile {path}
+        let [path] = bind_args(evaluator, "readfile", &args, [ArgSpec::required("path")])?;
+        evaluator.permissions.require(Permission::ReadFiles, "readfile", &path)?;
+        let contents = evaluator.fs.read_to_string(&path)?;
+        Ok(Some(contents))
     }
 
-    pub fn register(eval: &mut Evaluator) {
-        eval.register("readfile", ReadFile);
+    fn description(&self) -> &str {
+        "Read the whole contents of a file: `{text} readfile \"hello.txt\"`."
     }
 }
 
 pub fn register(eval: &mut Evaluator) {
-    #[cfg(not(target_arch = "wasm32"))]
-    native::register(eval);
-    let _ = eval; // suppress unused warning on wasm32
+    eval.register("readfile", ReadFile);
 }
@@ -0,0 +1,165 @@
+/// `render` — fill the placeholders of a template file from a struct.
+///
+/// Arguments: the template path, then one or more struct variables.  The
+/// template sees only their fields — not the script's other variables — so
+/// what ends up in a generated config file or email is exactly what was
+/// passed in:
+///
+/// ```bucl
+/// {mail/name} = "Ada"
+/// {mail/items/count} = "3"
+/// {body} render "templates/welcome.txt" {mail}
+/// ```
+///
+/// with `templates/welcome.txt`:
+///
+/// ```text
+/// Hello {name},
+/// your order of {items/count} items has shipped.
+/// ```
+///
+/// Placeholders are interpolated like quoted strings, including nested
+/// fields (`{items/count}`) and computed names (`{row/{i}}`).  With several
+/// structs, a field in a later one wins.  A placeholder that matches no
+/// field is an error, so a typo can't silently become an empty string.
+///
+/// Templates full of literal braces (JSON, CSS) can use other markers with
+/// `delimiters:"…"`, in the form of `# bucl-delimiters:`:
+///
+/// ```bucl
+/// {conf} render "app.json.tmpl" {settings} delimiters:"${ }"
+/// ```
+///
+/// The file is read through the evaluator's [`FileSystem`](crate::fs::FileSystem)
+/// and is subject to the same read permissions as `readfile`.
+use crate::ast::{Param, Statement};
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::{take_options, BuclFunction};
+use crate::parser::{self, Delimiters};
+use crate::permissions::Permission;
+
+pub struct Render;
+
+impl BuclFunction for Render {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        mut args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (options, n_params) = take_options(evaluator, &mut args, &["delimiters"]);
+        let delimiters = match options.into_iter().next() {
+            Some((_, spec)) => Delimiters::from_spec(&spec)
+                .map_err(|e| BuclError::RuntimeError(format!("render: {}", e)))?,
+            None => Delimiters::Braces,
+        };
+        let params = &evaluator.call_params[..n_params];
+        let Some((path, structs)) = params.split_first().filter(|(_, s)| !s.is_empty()) else {
+            return Err(BuclError::RuntimeError(
+                "render: expected a template path followed by struct variables, e.g. render \"mail.txt\" {vars}".into(),
+            ));
+        };
+        let path = evaluator.eval_param(path);
+
+        // The template's scope: the fields of the structs, nothing else.
+        let mut scope = Evaluator::new();
+        scope.strict = true;
+        for param in structs {
+            let Param::Variable(name) = param else {
+                return Err(BuclError::RuntimeError(format!(
+                    "render: expected struct variables after the path, got '{}'",
+                    evaluator.eval_param(param)
+                )));
+            };
+            let name = if name.contains('{') { evaluator.interpolate(name) } else { name.clone() };
+            if evaluator.find_named_sub_vars(&name).is_empty() {
+                return Err(BuclError::RuntimeError(format!(
+                    "render: {{{}}} is not a struct (it has no named fields)",
+                    name
+                )));
+            }
+            let prefix = format!("{}/", name);
+            for (key, value) in &evaluator.variables {
+                if let Some(field) = key.strip_prefix(&prefix) {
+                    scope.variables.insert(field.to_string(), value.clone());
+                }
+            }
+        }
+
+        evaluator.permissions.require(Permission::ReadFiles, "render", &path)?;
+        let template = evaluator.fs.read_to_string(&path)?;
+        let template = parser::template_param(&template, &delimiters);
+        scope
+            .defined(|scope| scope.eval_param(&template))
+            .map(Some)
+            .map_err(|name| {
+                BuclError::RuntimeError(format!("render: {}: {{{}}} is not set", path, name))
+            })
+    }
+
+    fn allows_duplicate_names(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Fill the placeholders of a template file from structs: `{body} render \"welcome.txt\" {mail}`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("render", Render);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::evaluator::Evaluator;
+    use crate::fs::MemoryFs;
+    use crate::functions::run_script_in;
+
+    fn run(source: &str) -> (Evaluator, crate::error::Result<()>) {
+        let fs = Arc::new(MemoryFs::new());
+        fs.insert("mail.txt", "Hi {name}, {items/count} items: {items}. {greeting/{lang}}");
+        fs.insert("app.json", "{\"port\": ${port}, \"host\": \"${host}\"}");
+        fs.insert("typo.txt", "Hi {nmae}");
+        let mut eval = Evaluator::new();
+        eval.fs = fs;
+        run_script_in(eval, source)
+    }
+
+    #[test]
+    fn test_render_uses_only_the_given_structs() {
+        let (eval, result) = run(concat!(
+            "{secret} = \"hidden\"\n",
+            "{mail/name} = \"Ada\"\n",
+            "{mail/items} = \"tea\" \"cake\"\n",
+            "{mail/lang} = \"en\"\n",
+            "{mail/greeting/en} = \"Cheers\"\n",
+            "{body} render \"mail.txt\" {mail}\n",
+            "{defaults/host} = \"localhost\"\n",
+            "{defaults/port} = \"80\"\n",
+            "{site/port} = \"8080\"\n",
+            "{conf} render \"app.json\" {defaults} {site} delimiters:\"${ }\"\n",
+        ));
+        result.unwrap();
+        assert_eq!(eval.resolve_var("body"), "Hi Ada, 2 items: tea cake. Cheers");
+        assert_eq!(eval.resolve_var("conf"), "{\"port\": 8080, \"host\": \"localhost\"}");
+
+        for (source, message) in [
+            ("{m/name} = \"x\"\n{t} render \"typo.txt\" {m}", "render: typo.txt: {nmae} is not set"),
+            ("{secret} = \"x\"\n{t} render \"mail.txt\" {secret}", "render: {secret} is not a struct"),
+            ("{t} render \"mail.txt\"", "render: expected a template path"),
+        ] {
+            let err = run(source).1.unwrap_err().to_string();
+            assert!(err.contains(message), "{}: {}", source, err);
+        }
+    }
+}
@@ -14,11 +14,21 @@
 ///     {output} = "Iteration {r/index} of {r/count}"
 /// ```
 ///
+/// With two or three arguments, `repeat from to [step]` counts through an
+/// inclusive integer range instead, and `{r/value}` holds the current number.
+/// The step defaults to `1`, or `-1` when `to` is below `from`; `{r}` is the
+/// number of iterations.
+///
+/// ```bucl
+/// {r} repeat 10 0 -2
+///     echo "{r/value}"        # 10, 8, 6, 4, 2, 0
+/// ```
+///
 /// If no target is given, the prefix defaults to `r`.
 use crate::ast::Statement;
 use crate::error::{BuclError, Result};
 use crate::evaluator::Evaluator;
-use crate::functions::BuclFunction;
+use crate::functions::{bind_args, ArgSpec, BuclFunction};
 
 pub struct Repeat;
 
@@ -32,13 +42,12 @@ impl BuclFunction for Repeat {
         _continuation: Option<&Statement>,
     ) -> Result<Option<String>> {
         let prefix = target.unwrap_or("r");
+        if args.len() >= 2 {
+            return repeat_range(evaluator, prefix, &args, block);
+        }
 
         // Named param: {count} = 5; {r} repeat {count}
-        let count_str = evaluator
-            .named_arg("count")
-            .cloned()
-            .or_else(|| args.first().cloned())
-            .ok_or_else(|| BuclError::RuntimeError("repeat: missing count argument".into()))?;
+        let [count_str] = bind_args(evaluator, "repeat", &args, [ArgSpec::required("count")])?;
 
         let count: usize = count_str.parse().map_err(|_| {
             BuclError::RuntimeError(format!("repeat: '{}' is not a valid count", &count_str))
@@ -62,8 +71,113 @@ impl BuclFunction for Repeat {
 
         Ok(None)
     }
+
+    fn description(&self) -> &str {
+        "Run the block a number of times, or over a range: `{r} repeat 5`, `{r} repeat 10 0 -2`."
+    }
+}
+
+fn repeat_range(
+    evaluator: &mut Evaluator,
+    prefix: &str,
+    args: &[String],
+    block: Option<&[Statement]>,
+) -> Result<Option<String>> {
+    let range = Range::parse("repeat", args)?;
+    let count = range.len();
+    evaluator.set_var(prefix, count.to_string());
+    evaluator
+        .variables
+        .insert(format!("{}/count", prefix), count.to_string());
+
+    if let Some(block) = block {
+        for (i, value) in range.iter().enumerate() {
+            evaluator
+                .variables
+                .insert(format!("{}/index", prefix), (i + 1).to_string());
+            evaluator
+                .variables
+                .insert(format!("{}/value", prefix), value.to_string());
+            evaluator.evaluate_statements(block)?;
+        }
+    }
+    Ok(None)
+}
+
+/// An inclusive integer range `from..=to` in steps of `step`, shared by
+/// `repeat from to [step]` and `each range from to [step]`.
+pub(crate) struct Range {
+    from: i64,
+    to: i64,
+    step: i64,
+}
+
+impl Range {
+    /// Parse `from to [step]`; `fname` prefixes error messages.
+    pub(crate) fn parse(fname: &str, args: &[String]) -> Result<Self> {
+        let number = |s: &String| -> Result<i64> {
+            s.trim().parse().map_err(|_| {
+                BuclError::RuntimeError(format!("{}: '{}' is not an integer", fname, s))
+            })
+        };
+        let (from, to, step) = match args {
+            [from, to] => {
+                let (from, to) = (number(from)?, number(to)?);
+                (from, to, if to < from { -1 } else { 1 })
+            }
+            [from, to, step] => (number(from)?, number(to)?, number(step)?),
+            _ => {
+                return Err(BuclError::RuntimeError(format!(
+                    "{}: expected 'from to [step]', got {} argument(s)",
+                    fname,
+                    args.len()
+                )))
+            }
+        };
+        if step == 0 {
+            return Err(BuclError::RuntimeError(format!("{}: step must not be 0", fname)));
+        }
+        Ok(Self { from, to, step })
+    }
+
+    /// Number of values; `0` when `step` points away from `to`.
+    pub(crate) fn len(&self) -> usize {
+        let span = self.to as i128 - self.from as i128;
+        if span != 0 && (span < 0) != (self.step < 0) {
+            return 0;
+        }
+        (span / self.step as i128 + 1) as usize
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = i64> + '_ {
+        (0..self.len()).map(|i| (self.from as i128 + i as i128 * self.step as i128) as i64)
+    }
 }
 
 pub fn register(eval: &mut Evaluator) {
     eval.register("repeat", Repeat);
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(args: &[&str]) -> Vec<i64> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        Range::parse("repeat", &args).unwrap().iter().collect()
+    }
+
+    #[test]
+    fn test_range_values() {
+        assert_eq!(values(&["1", "5"]), vec![1, 2, 3, 4, 5]);
+        assert_eq!(values(&["0", "10", "3"]), vec![0, 3, 6, 9]);
+        assert_eq!(values(&["3", "1"]), vec![3, 2, 1]);
+        assert_eq!(values(&["10", "0", "-5"]), vec![10, 5, 0]);
+        assert_eq!(values(&["1", "5", "-1"]), Vec::<i64>::new());
+        assert!(Range::parse("repeat", &["1".into(), "5".into(), "0".into()]).is_err());
+    }
+}
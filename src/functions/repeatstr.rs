@@ -0,0 +1,108 @@
+/// `repeatstr` — repeat a string a number of times.
+///
+/// Builds separators and padding in one step instead of a `repeat` loop
+/// with `append`:
+///
+/// ```bucl
+/// {line} repeatstr "-" 40          # ----------------------------------------
+/// {indent} repeatstr "  " {depth}
+/// {none} repeatstr "ab" 0          # (empty)
+/// ```
+///
+/// The count must be a whole number ≥ 0, and the result may be at most
+/// 256 MiB; a larger one is a runtime error rather than an allocation that
+/// takes down the host.
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+/// Largest result `repeatstr` builds, in bytes.
+const MAX_LEN: usize = 256 * 1024 * 1024;
+
+pub struct RepeatStr;
+
+impl BuclFunction for RepeatStr {
+    fn call(
+        &self,
+        _evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let [text, count] = args.as_slice() else {
+            return Err(BuclError::RuntimeError(format!(
+                "repeatstr: expected a string and a count, got {} arguments",
+                args.len()
+            )));
+        };
+        let count: usize = count.trim().parse().map_err(|_| {
+            BuclError::RuntimeError(format!(
+                "repeatstr: count must be a whole number >= 0, got '{}'",
+                count
+            ))
+        })?;
+        let too_large = || {
+            BuclError::RuntimeError(format!(
+                "repeatstr: {} copies of a {}-byte string is too large",
+                count,
+                text.len()
+            ))
+        };
+        let len = text
+            .len()
+            .checked_mul(count)
+            .filter(|&len| len <= MAX_LEN)
+            .ok_or_else(too_large)?;
+        let mut out = String::new();
+        out.try_reserve_exact(len).map_err(|_| too_large())?;
+        for _ in 0..count {
+            out.push_str(text);
+        }
+        Ok(Some(out))
+    }
+
+    fn description(&self) -> &str {
+        "Repeat a string a number of times: `{line} repeatstr \"-\" 40`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("repeatstr", RepeatStr);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::run_script;
+
+    #[test]
+    fn test_repeatstr() {
+        let (eval, result) = run_script(concat!(
+            "{n} = \"3\"\n",
+            "{line} repeatstr \"-\" 5\n",
+            "{pad} repeatstr \"é \" {n}\n",
+            "{none} repeatstr \"ab\" 0\n",
+        ));
+        result.unwrap();
+        assert_eq!(eval.resolve_var("line"), "-----");
+        assert_eq!(eval.resolve_var("pad"), "é é é ");
+        assert_eq!(eval.resolve_var("none"), "");
+
+        for (source, message) in [
+            ("{x} repeatstr \"-\" -1", "repeatstr: count must be a whole number >= 0, got '-1'"),
+            ("{x} repeatstr \"-\"", "repeatstr: expected a string and a count, got 1 arguments"),
+            (
+                "{w} repeatstr \"ab\" 99999999999",
+                "repeatstr: 99999999999 copies of a 2-byte string is too large",
+            ),
+        ] {
+            let err = run_script(source).1.unwrap_err().to_string();
+            assert!(err.contains(message), "{}: {}", source, err);
+        }
+    }
+}
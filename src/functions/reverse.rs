@@ -0,0 +1,58 @@
+/// `reverse` — reverse the characters of a string.
+///
+/// All arguments are concatenated before reversing.  Replaces the
+/// character-by-character `functions/reverse.bucl`, which is kept as the
+/// reference implementation.
+///
+/// ```bucl
+/// {res} reverse "Hello"            # olleH
+/// {res} reverse "Hello" " World"   # dlroW olleH
+/// ```
+use crate::ast::Statement;
+use crate::error::Result;
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct Reverse;
+
+impl BuclFunction for Reverse {
+    fn call(
+        &self,
+        _evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        Ok(Some(args.concat().chars().rev().collect()))
+    }
+
+    fn description(&self) -> &str {
+        "Reverse the characters of a string: `{r} reverse \"abc\"`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("reverse", Reverse);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(all(test, feature = "math"))]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_matches_bucl_reference() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.embed_function("ref", include_str!("../../functions/reverse.bucl"));
+        for args in [r#""Hello""#, r#""Hello" " World""#, r#""""#, r#""héllo wörld""#] {
+            let source = format!("{{a}} reverse {}\n{{b}} ref {}\n", args, args);
+            eval.evaluate_statements(&crate::parser::parse(&source).unwrap()).unwrap();
+            assert_eq!(eval.resolve_var("a"), eval.resolve_var("b"), "{}", args);
+        }
+    }
+}
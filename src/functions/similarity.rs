@@ -0,0 +1,151 @@
+/// `levenshtein` / `similar_text` — how close two strings are.
+///
+/// `levenshtein a b` is the edit distance: the fewest single-character
+/// insertions, deletions and substitutions that turn `a` into `b`.
+/// `similar_text a b` turns that into a percentage, `100` for equal strings
+/// down to `0` for nothing in common, rounded to two decimals.  Both count
+/// characters, not bytes, and are case-sensitive.
+///
+/// Together with `each` they give "did you mean?" suggestions:
+///
+/// ```bucl
+/// {best} = ""
+/// {best_score} = "0"
+/// {c} each "build" "bench" "clean" "test"
+///     {score} similar_text {c/value} {input}
+///     if {score} > {best_score}
+///         {best} = {c/value}
+///         {best_score} = {score}
+/// if {best_score} >= "60"
+///     echo "unknown command '{input}', did you mean '{best}'?"
+/// ```
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+/// Edit distance between `a` and `b`, one row of the matrix at a time.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Similarity of `a` and `b` as a percentage with at most two decimals.
+fn similarity(a: &str, b: &str) -> String {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return "100".into();
+    }
+    let ratio = 1.0 - levenshtein(a, b) as f64 / longest as f64;
+    let percent = (ratio * 10_000.0).round() / 100.0;
+    format!("{}", percent)
+}
+
+/// The two strings to compare.
+fn pair<'a>(fname: &str, args: &'a [String]) -> Result<(&'a str, &'a str)> {
+    match args {
+        [a, b] => Ok((a, b)),
+        _ => Err(BuclError::RuntimeError(format!(
+            "{}: expected two strings, got {} arguments",
+            fname,
+            args.len()
+        ))),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Built-ins
+// ---------------------------------------------------------------------------
+
+pub struct Levenshtein;
+
+impl BuclFunction for Levenshtein {
+    fn call(
+        &self,
+        _evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (a, b) = pair("levenshtein", &args)?;
+        Ok(Some(levenshtein(a, b).to_string()))
+    }
+
+    fn description(&self) -> &str {
+        "The edit distance between two strings: `{d} levenshtein \"kitten\" \"sitting\"`."
+    }
+}
+
+pub struct SimilarText;
+
+impl BuclFunction for SimilarText {
+    fn call(
+        &self,
+        _evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let (a, b) = pair("similar_text", &args)?;
+        Ok(Some(similarity(a, b)))
+    }
+
+    fn description(&self) -> &str {
+        "How similar two strings are, as a percentage: `{score} similar_text \"World\" \"Word\"`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("levenshtein", Levenshtein);
+    eval.register("similar_text", SimilarText);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::run_script;
+
+    #[test]
+    fn test_distance_and_similarity() {
+        let (eval, result) = run_script(concat!(
+            "{d1} levenshtein \"kitten\" \"sitting\"\n",
+            "{d2} levenshtein \"\" \"abc\"\n",
+            "{d3} levenshtein \"héllo\" \"hello\"\n",
+            "{s1} similar_text \"build\" \"biuld\"\n",
+            "{s2} similar_text \"kitten\" \"sitting\"\n",
+            "{s3} similar_text \"\" \"\"\n",
+            "{s4} similar_text \"abc\" \"xyz\"\n",
+        ));
+        result.unwrap();
+        for (name, value) in [
+            ("d1", "3"),
+            ("d2", "3"),
+            ("d3", "1"),
+            ("s1", "60"),
+            ("s2", "57.14"),
+            ("s3", "100"),
+            ("s4", "0"),
+        ] {
+            assert_eq!(eval.resolve_var(name), value, "{}", name);
+        }
+
+        let err = run_script("{d} levenshtein \"a\"").1.unwrap_err().to_string();
+        assert!(err.contains("levenshtein: expected two strings, got 1 arguments"), "{}", err);
+    }
+}
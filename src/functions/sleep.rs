@@ -8,16 +8,10 @@
 // The host implements it as a Date.now() spin-loop so that the synchronous
 // evaluator can block without requiring an async runtime.
 
-// WASM: import a host-provided busy-wait from JavaScript.
-#[cfg(target_arch = "wasm32")]
-extern "C" {
-    fn js_sleep(ms: f64);
-}
-
 use crate::ast::Statement;
 use crate::error::{BuclError, Result};
 use crate::evaluator::Evaluator;
-use crate::functions::BuclFunction;
+use crate::functions::{bind_args, ArgSpec, BuclFunction};
 
 pub struct Sleep;
 
@@ -31,13 +25,7 @@ impl BuclFunction for Sleep {
         _continuation: Option<&Statement>,
     ) -> Result<Option<String>> {
         // Named param: {seconds} = 0.5; sleep {seconds}
-        let secs_str = evaluator
-            .named_arg("seconds")
-            .cloned()
-            .or_else(|| args.first().cloned())
-            .ok_or_else(|| {
-                BuclError::RuntimeError("sleep: expected a number of seconds".into())
-            })?;
+        let [secs_str] = bind_args(evaluator, "sleep", &args, [ArgSpec::required("seconds")])?;
 
         let secs: f64 = secs_str.parse().map_err(|_| {
             BuclError::RuntimeError(format!(
@@ -57,12 +45,14 @@ impl BuclFunction for Sleep {
         std::thread::sleep(std::time::Duration::from_secs_f64(secs));
 
         #[cfg(target_arch = "wasm32")]
-        unsafe {
-            js_sleep(secs * 1000.0);
-        }
+        crate::host::sleep_ms(secs * 1000.0);
 
         Ok(None)
     }
+
+    fn description(&self) -> &str {
+        "Pause for a number of seconds (fractions allowed): `sleep 0.5`."
+    }
 }
 
 pub fn register(eval: &mut Evaluator) {
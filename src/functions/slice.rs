@@ -0,0 +1,101 @@
+/// `slice` — select a range of arguments, Python style.
+///
+/// The first two arguments are the start (inclusive) and end (exclusive)
+/// indices into the items that follow; negative indices count from the end.
+/// The selected items are joined with spaces.  Named params `{start}` and
+/// `{end}` take precedence over the positional indices when non-empty.
+///
+/// ```bucl
+/// {res} slice 1 -1 "cut" "keep1" "keep2" "cut"   # keep1 keep2
+/// ```
+///
+/// Replaces the looping `functions/slice.bucl`, which is kept as the
+/// reference implementation.
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct Slice;
+
+impl BuclFunction for Slice {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let items = args.get(2..).unwrap_or_default();
+        let count = items.len() as i64;
+        let index = |name: &str, position: usize| -> Result<i64> {
+            let value = match evaluator.call_named_args.get(name) {
+                Some(v) if !v.is_empty() => v.as_str(),
+                _ => args.get(position).map_or("", String::as_str),
+            };
+            let n: i64 = value.trim().parse().map_err(|_| {
+                BuclError::RuntimeError(format!(
+                    "slice: {} must be an integer, got '{}'",
+                    name, value
+                ))
+            })?;
+            Ok(if n < 0 { count + n } else { n })
+        };
+        let (start, end) = (index("start", 0)?, index("end", 1)?);
+
+        // Same joining as the .bucl version: an empty result so far is
+        // replaced rather than extended, so leading empty items vanish.
+        let mut result = String::new();
+        for (i, item) in items.iter().enumerate() {
+            let i = i as i64;
+            if i < start || i >= end {
+                continue;
+            }
+            if !result.is_empty() {
+                result.push(' ');
+            }
+            result.push_str(item);
+        }
+        Ok(Some(result))
+    }
+
+    fn description(&self) -> &str {
+        "Select items by start and end index, Python style: `{s} slice 1 -1 {items}`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("slice", Slice);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(all(test, feature = "math"))]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_matches_bucl_reference() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.embed_function("ref", include_str!("../../functions/slice.bucl"));
+        let cases = [
+            r#"1 -1 "cut" "keep1" "keep2" "cut""#,
+            r#"0 2 "a" "b" "c""#,
+            r#"-2 10 "a" "b" "c""#,
+            r#"2 1 "a" "b" "c""#,
+            r#"0 3 "" "b" "" "d""#,
+            r#"{start} {end} "a" "b" "c" "d""#,
+        ];
+        let setup = crate::parser::parse("{start} = \"1\"\n{end} = \"-1\"\n").unwrap();
+        eval.evaluate_statements(&setup).unwrap();
+        for args in cases {
+            let source = format!("{{a}} slice {}\n{{b}} ref {}\n", args, args);
+            eval.evaluate_statements(&crate::parser::parse(&source).unwrap()).unwrap();
+            assert_eq!(eval.resolve_var("a"), eval.resolve_var("b"), "{}", args);
+        }
+    }
+}
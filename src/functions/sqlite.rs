@@ -0,0 +1,344 @@
+/// `sqlopen` / `sql` / `sqlclose` — query SQLite databases.
+///
+/// `sqlopen path` opens (or creates) a database and returns its handle,
+/// which is the path itself; `":memory:"` gives a private in-memory one.
+/// `sql handle query args...` runs one statement with `args` bound to its
+/// `?` placeholders, always as text, so values never need quoting.
+///
+/// A query's rows land in the target as an array of structs, one field per
+/// column:
+///
+/// ```bucl
+/// {db} sqlopen "app.db"
+/// {rows} sql {db} "SELECT id, name FROM users WHERE age > ?" {min_age}
+/// echo "{rows/count} users"
+/// {row} each {rows}
+///     echo "{rows/{row/index}/id}: {rows/{row/index}/name}"
+/// sqlclose {db}
+/// ```
+///
+/// `{rows/0}`, `{rows/1}`, … hold each row's first column, so the result of
+/// a single-column query is a plain array to pass on or loop over with `each`.
+/// `NULL` becomes `""` and blobs uppercase hex, as `encode` gives them.
+///
+/// A statement without result columns (`INSERT`, `UPDATE`, `CREATE`, …)
+/// returns the number of rows it changed.  Without `args`, the query may
+/// hold several statements separated by `;` — a whole schema, say.
+///
+/// Opening a file needs permission to read and write it (see
+/// [`crate::permissions`]); `":memory:"` is always allowed.  The same goes
+/// for `ATTACH` and `VACUUM INTO` inside `sql`, and `file:` URIs need
+/// unrestricted file access, since they can name any path.  Only available
+/// when built with the `sqlite` cargo feature, and not in WASM builds.
+use crate::evaluator::Evaluator;
+
+#[cfg(all(feature = "sqlite", not(target_arch = "wasm32")))]
+pub(crate) use native::Databases;
+
+#[cfg(all(feature = "sqlite", not(target_arch = "wasm32")))]
+mod native {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use rusqlite::hooks::{AuthAction, AuthContext, Authorization};
+    use rusqlite::types::ValueRef;
+    use rusqlite::{ffi, Connection};
+
+    use crate::ast::Statement;
+    use crate::error::{BuclError, Result};
+    use crate::evaluator::Evaluator;
+    use crate::functions::{bind_args, ArgSpec, BuclFunction};
+    use crate::permissions::{Access, Permission, Permissions};
+
+    /// Open connections by handle, shared with child evaluators.
+    pub(crate) type Databases = Arc<Mutex<HashMap<String, Connection>>>;
+
+    const MEMORY: &str = ":memory:";
+
+    fn sql_err(fname: &str, e: rusqlite::Error) -> BuclError {
+        BuclError::RuntimeError(format!("{}: {}", fname, e))
+    }
+
+    fn not_open(fname: &str, handle: &str) -> BuclError {
+        BuclError::RuntimeError(format!(
+            "{}: database '{}' is not open (use sqlopen first)",
+            fname, handle
+        ))
+    }
+
+    /// SQLite is built to read `file:` names as URIs, whose path our
+    /// permission checks cannot see.
+    fn is_uri(path: &str) -> bool {
+        path.get(..5).is_some_and(|scheme| scheme.eq_ignore_ascii_case("file:"))
+    }
+
+    fn unrestricted(permissions: &Permissions) -> bool {
+        permissions.read == Access::Allow && permissions.write == Access::Allow
+    }
+
+    /// Whether `permissions` let a database be opened or attached at `path`.
+    fn may_open(permissions: &Permissions, path: &str) -> bool {
+        if path.is_empty() || path == MEMORY {
+            return true;
+        }
+        if is_uri(path) {
+            return unrestricted(permissions);
+        }
+        permissions.read.allows(path) && permissions.write.allows(path)
+    }
+
+    /// Refuse `ATTACH` (which `VACUUM INTO` runs too) of files outside
+    /// `permissions`, and of names that are not plain string literals.
+    fn authorize(permissions: Permissions) -> impl FnMut(AuthContext<'_>) -> Authorization + Send {
+        move |ctx| match ctx.action {
+            AuthAction::Attach { filename } if !may_open(&permissions, filename) => {
+                Authorization::Deny
+            }
+            AuthAction::Unknown { code: ffi::SQLITE_ATTACH, .. } => Authorization::Deny,
+            _ => Authorization::Allow,
+        }
+    }
+
+    fn text(value: ValueRef<'_>) -> String {
+        match value {
+            ValueRef::Null => String::new(),
+            ValueRef::Integer(n) => n.to_string(),
+            ValueRef::Real(f) => f.to_string(),
+            ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned(),
+            ValueRef::Blob(b) => b.iter().map(|b| format!("{:02X}", b)).collect(),
+        }
+    }
+
+    pub struct SqlOpen;
+
+    impl BuclFunction for SqlOpen {
+        fn call(
+            &self,
+            evaluator: &mut Evaluator,
+            _target: Option<&str>,
+            args: Vec<String>,
+            _block: Option<&[Statement]>,
+            _continuation: Option<&Statement>,
+        ) -> Result<Option<String>> {
+            // Named param: {path} = "app.db"; {db} sqlopen {path}
+            let [path] = bind_args(evaluator, "sqlopen", &args, [ArgSpec::required("path")])?;
+            if is_uri(&path) && !unrestricted(&evaluator.permissions) {
+                return Err(BuclError::RuntimeError(format!(
+                    "sqlopen: '{}' is a URI, which needs unrestricted file access",
+                    path
+                )));
+            }
+            if path != MEMORY {
+                evaluator.permissions.require(Permission::ReadFiles, "sqlopen", &path)?;
+                evaluator.permissions.require(Permission::WriteFiles, "sqlopen", &path)?;
+            }
+            let conn = Connection::open(&path).map_err(|e| sql_err("sqlopen", e))?;
+            conn.authorizer(Some(authorize(evaluator.permissions.clone())));
+            evaluator
+                .databases
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(path.clone(), conn);
+            Ok(Some(path))
+        }
+
+        fn description(&self) -> &str {
+            "Open (or create) an SQLite database and return its handle: `{db} sqlopen \"app.db\"`."
+        }
+    }
+
+    pub struct Sql;
+
+    impl BuclFunction for Sql {
+        fn call(
+            &self,
+            evaluator: &mut Evaluator,
+            target: Option<&str>,
+            args: Vec<String>,
+            _block: Option<&[Statement]>,
+            _continuation: Option<&Statement>,
+        ) -> Result<Option<String>> {
+            let [handle, query, params @ ..] = args.as_slice() else {
+                return Err(BuclError::RuntimeError(
+                    "sql: expected a database handle and a query".into(),
+                ));
+            };
+            let databases = evaluator.databases.clone();
+            let databases = databases.lock().unwrap_or_else(|e| e.into_inner());
+            let conn = databases.get(handle).ok_or_else(|| not_open("sql", handle))?;
+
+            let mut stmt = match conn.prepare(query) {
+                Err(rusqlite::Error::MultipleStatement) if params.is_empty() => {
+                    conn.execute_batch(query).map_err(|e| sql_err("sql", e))?;
+                    return Ok(Some(conn.changes().to_string()));
+                }
+                prepared => prepared.map_err(|e| sql_err("sql", e))?,
+            };
+            let params = rusqlite::params_from_iter(params.iter());
+            if stmt.column_count() == 0 {
+                let changed = stmt.execute(params).map_err(|e| sql_err("sql", e))?;
+                return Ok(Some(changed.to_string()));
+            }
+
+            let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+            let mut rows = stmt.query(params).map_err(|e| sql_err("sql", e))?;
+            let mut table: Vec<Vec<String>> = Vec::new();
+            while let Some(row) = rows.next().map_err(|e| sql_err("sql", e))? {
+                table.push(
+                    (0..columns.len())
+                        .map(|i| row.get_ref(i).map(text))
+                        .collect::<rusqlite::Result<_>>()
+                        .map_err(|e| sql_err("sql", e))?,
+                );
+            }
+
+            let first_column: Vec<String> = table.iter().map(|row| row[0].clone()).collect();
+            let Some(prefix) = target else {
+                return Ok(Some(first_column.concat()));
+            };
+            evaluator.remove_tree(prefix);
+            evaluator.set_array(prefix, first_column);
+            for (i, row) in table.into_iter().enumerate() {
+                for (column, value) in columns.iter().zip(row) {
+                    evaluator
+                        .variables
+                        .insert(format!("{}/{}/{}", prefix, i, column), value);
+                }
+            }
+            Ok(None) // Everything already stored directly.
+        }
+
+        fn description(&self) -> &str {
+            "Run an SQL statement on an open database: `{rows} sql {db} \"SELECT name FROM users WHERE id = ?\" {id}`."
+        }
+    }
+
+    pub struct SqlClose;
+
+    impl BuclFunction for SqlClose {
+        fn call(
+            &self,
+            evaluator: &mut Evaluator,
+            _target: Option<&str>,
+            args: Vec<String>,
+            _block: Option<&[Statement]>,
+            _continuation: Option<&Statement>,
+        ) -> Result<Option<String>> {
+            let handle = args.first().ok_or_else(|| {
+                BuclError::RuntimeError("sqlclose: missing database handle".into())
+            })?;
+            let conn = evaluator
+                .databases
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(handle)
+                .ok_or_else(|| not_open("sqlclose", handle))?;
+            conn.close().map_err(|(_, e)| sql_err("sqlclose", e))?;
+            Ok(None)
+        }
+
+        fn description(&self) -> &str {
+            "Close an open SQLite database: `sqlclose {db}`."
+        }
+    }
+
+    pub fn register(eval: &mut Evaluator) {
+        eval.register("sqlopen", SqlOpen);
+        eval.register("sql", Sql);
+        eval.register("sqlclose", SqlClose);
+    }
+
+    // -----------------------------------------------------------------------
+    // Tests
+    // -----------------------------------------------------------------------
+
+    #[cfg(test)]
+    mod tests {
+        use crate::evaluator::Evaluator;
+
+        #[test]
+        fn test_rows_become_structs() {
+            let mut eval = Evaluator::new();
+            crate::functions::register_all(&mut eval);
+            let stmts = crate::parser::parse(concat!(
+                "{db} sqlopen \":memory:\"\n",
+                "{r} sql {db} \"CREATE TABLE users (id INTEGER, name TEXT, age INTEGER, note TEXT); CREATE INDEX by_age ON users (age)\"\n",
+                "{added} sql {db} \"INSERT INTO users VALUES (1, ?, 36, NULL), (2, ?, 29, ?)\" \"Ada\" \"Bob's\" \"x\"\n",
+                "{min} = \"30\"\n",
+                "{old} sql {db} \"SELECT name, note FROM users WHERE age > ?\" {min}\n",
+                "{names} sql {db} \"SELECT name FROM users ORDER BY id\"\n",
+                "{none} sql {db} \"SELECT id FROM users WHERE age > 100\"\n",
+                "sqlclose {db}\n",
+            ))
+            .unwrap();
+            eval.evaluate_statements(&stmts).unwrap();
+            for (name, value) in [
+                ("added", "2"),
+                ("old/count", "1"),
+                ("old/0", "Ada"),
+                ("old/0/name", "Ada"),
+                ("old/0/note", ""),
+                ("names/count", "2"),
+                ("names/1", "Bob's"),
+                ("none/count", "0"),
+            ] {
+                assert_eq!(eval.resolve_var(name), value, "{}", name);
+            }
+
+            let err = eval
+                .evaluate_statements(&crate::parser::parse("{x} sql \":memory:\" \"SELECT 1\"").unwrap())
+                .unwrap_err();
+            assert!(err.to_string().contains("sql: database ':memory:' is not open"), "{}", err);
+        }
+
+        #[test]
+        fn test_sandbox_limits_attach_and_uris() {
+            use crate::functions::run_script_in;
+            use crate::permissions::{Access, Permissions};
+
+            let dir = std::env::temp_dir().join(format!("bucl-sqlite-sandbox-{}", std::process::id()));
+            std::fs::create_dir_all(dir.join("data")).unwrap();
+            let outside = dir.join("secret.db");
+            let _ = std::fs::remove_file(&outside);
+            let sandboxed = || {
+                let mut eval = Evaluator::new();
+                eval.permissions = Permissions::sandboxed();
+                eval.permissions.read = Access::within([dir.join("data")]);
+                eval.permissions.write = Access::within([dir.join("data")]);
+                eval
+            };
+
+            for query in [
+                format!("ATTACH DATABASE '{}' AS x", outside.display()),
+                "ATTACH DATABASE '/tmp' || '/x.db' AS x".to_string(),
+                format!("VACUUM INTO '{}'", outside.display()),
+            ] {
+                let source = format!("{{db}} sqlopen \":memory:\"\n{{r}} sql {{db}} \"{}\"", query);
+                let (_, result) = run_script_in(sandboxed(), &source);
+                let err = result.unwrap_err().to_string();
+                assert!(err.contains("authoriz"), "{}: {}", query, err);
+            }
+            assert!(!outside.exists());
+
+            let inside = dir.join("data/ok.db");
+            let source = format!(
+                "{{db}} sqlopen \":memory:\"\n{{r}} sql {{db}} \"ATTACH DATABASE '{}' AS x\"\n{{r}} sql {{db}} \"VACUUM\"",
+                inside.display()
+            );
+            run_script_in(sandboxed(), &source).1.unwrap();
+
+            let source = format!("{{db}} sqlopen \"file:{}\"", outside.display());
+            let err = run_script_in(sandboxed(), &source).1.unwrap_err().to_string();
+            assert!(err.contains("is a URI, which needs unrestricted file access"), "{}", err);
+            assert!(!outside.exists());
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    #[cfg(all(feature = "sqlite", not(target_arch = "wasm32")))]
+    native::register(eval);
+    let _ = eval; // suppress unused warning without the feature / on wasm32
+}
@@ -0,0 +1,222 @@
+/// `store` — a small key-value store kept in a file between runs.
+///
+/// ```bucl
+/// {last} store get "lastrun" "never"
+/// echo "last run: {last}"
+/// store set "lastrun" {now}
+/// ```
+///
+/// Subcommands:
+/// - `store set key value ...` — save the value (arguments concatenated,
+///   like `=`).
+/// - `{v} store get key [default]` — the saved value; `default` (or `""`)
+///   when the key was never set.
+/// - `{ok} store has key` — `"1"` if the key is set, else `"0"`.
+/// - `store delete key` — forget the key; deleting a missing key is fine.
+/// - `{k} store keys` — every key, in order, as an array.
+///
+/// The store lives in `.bucl-store` beside the script (in the current
+/// directory when there is no script), or in the file given with
+/// `file:"…"`.  It is plain text in the `name = value` format of
+/// `bucl --save-vars`, read on every call and rewritten on every change, so
+/// it is meant for a handful of small values rather than as a database.
+/// Keys can't be empty or contain a newline or an `=` next to a space.
+///
+/// Reads and writes go through the evaluator's
+/// [`FileSystem`](crate::fs::FileSystem) and its read/write permissions.
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::{take_options, BuclFunction};
+use crate::permissions::Permission;
+use crate::snapshot::Snapshot;
+
+const DEFAULT_FILE: &str = ".bucl-store";
+
+/// Where the store lives: `file:"…"`, or [`DEFAULT_FILE`] beside the script.
+fn store_path(evaluator: &Evaluator, file: Option<String>) -> String {
+    match (file, &evaluator.base_dir) {
+        (Some(file), _) => file,
+        (None, Some(base)) => base.join(DEFAULT_FILE).to_string_lossy().into_owned(),
+        (None, None) => DEFAULT_FILE.to_string(),
+    }
+}
+
+fn load(evaluator: &Evaluator, path: &str) -> Result<Snapshot> {
+    evaluator.permissions.require(Permission::ReadFiles, "store", path)?;
+    if !evaluator.fs.exists(path) {
+        return Ok(Snapshot::default());
+    }
+    let text = evaluator.fs.read_to_string(path)?;
+    Snapshot::parse(&text).map_err(|e| BuclError::RuntimeError(format!("store: {}: {}", path, e)))
+}
+
+fn save(evaluator: &Evaluator, path: &str, store: &Snapshot) -> Result<()> {
+    evaluator.permissions.require(Permission::WriteFiles, "store", path)?;
+    evaluator.fs.write(path, &store.to_text())?;
+    Ok(())
+}
+
+fn check_key(key: &str) -> Result<()> {
+    // A key ending in " =" or starting with "= " would split differently
+    // when the `name = value` line is read back.
+    if key.is_empty() || key.contains('\n') || key.contains(" =") || key.contains("= ") {
+        return Err(BuclError::RuntimeError(format!(
+            "store: invalid key '{}' (keys can't be empty or contain a newline or '=' next to a space)",
+            key
+        )));
+    }
+    Ok(())
+}
+
+pub struct Store;
+
+impl BuclFunction for Store {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        target: Option<&str>,
+        mut args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let file = take_options(evaluator, &mut args, &["file"]).0.pop().map(|(_, f)| f);
+        let path = store_path(evaluator, file);
+        let Some((command, rest)) = args.split_first() else {
+            return Err(BuclError::RuntimeError(
+                "store: expected get, set, has, delete or keys".into(),
+            ));
+        };
+        let key = || -> Result<&String> {
+            let key = rest.first().ok_or_else(|| {
+                BuclError::RuntimeError(format!("store {}: missing key", command))
+            })?;
+            check_key(key)?;
+            Ok(key)
+        };
+
+        match command.as_str() {
+            "get" => {
+                let key = key()?;
+                let mut store = load(evaluator, &path)?;
+                let value = store.vars.remove(key);
+                Ok(Some(value.or_else(|| rest.get(1).cloned()).unwrap_or_default()))
+            }
+            "has" => {
+                let key = key()?;
+                let store = load(evaluator, &path)?;
+                Ok(Some(if store.vars.contains_key(key) { "1" } else { "0" }.to_string()))
+            }
+            "set" => {
+                let key = key()?;
+                let mut store = load(evaluator, &path)?;
+                store.vars.insert(key.clone(), rest[1..].concat());
+                save(evaluator, &path, &store)?;
+                Ok(None)
+            }
+            "delete" => {
+                let key = key()?;
+                let mut store = load(evaluator, &path)?;
+                if store.vars.remove(key).is_some() {
+                    save(evaluator, &path, &store)?;
+                }
+                Ok(None)
+            }
+            "keys" => {
+                let store = load(evaluator, &path)?;
+                let keys: Vec<String> = store.vars.into_keys().collect();
+                match target {
+                    Some(prefix) => {
+                        evaluator.set_array(prefix, keys);
+                        Ok(None)
+                    }
+                    None => Ok(Some(keys.concat())),
+                }
+            }
+            other => Err(BuclError::RuntimeError(format!(
+                "store: unknown subcommand '{}' (expected get, set, has, delete or keys)",
+                other
+            ))),
+        }
+    }
+
+    fn description(&self) -> &str {
+        "Get, set or delete keys in a key-value file kept between runs: `{v} store get \"key\" \"default\"`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("store", Store);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::evaluator::Evaluator;
+    use crate::fs::MemoryFs;
+    use crate::functions::run_script_in;
+
+    #[test]
+    fn test_values_survive_between_runs() {
+        let fs = Arc::new(MemoryFs::new());
+        let run = |source: &str| {
+            let mut eval = Evaluator::new();
+            eval.fs = fs.clone();
+            let (eval, result) = run_script_in(eval, source);
+            result.unwrap();
+            eval
+        };
+
+        run(concat!(
+            "{ts} = \"2026-10-16\"\n",
+            "store set \"lastrun\" {ts}\n",
+            "store set \"notes\" \"two\\nlines\"\n",
+            "store set \"tmp\" \"x\"\n",
+            "store delete \"tmp\"\n",
+            "store set \"other\" \"1\" file:\"other.store\"\n",
+            "store set \"crlf\" \"x\\r\" file:\"other.store\"\n",
+        ));
+        assert_eq!(
+            fs.get(".bucl-store").as_deref(),
+            Some("lastrun = 2026-10-16\nnotes = two\\nlines\n")
+        );
+
+        let eval = run(concat!(
+            "{last} store get \"lastrun\"\n",
+            "{notes} store get \"notes\"\n",
+            "{count} store get \"count\" \"0\"\n",
+            "{has} store has \"tmp\"\n",
+            "{keys} store keys\n",
+            "{other} store get \"other\" file:\"other.store\"\n",
+            "{crlf} store get \"crlf\" file:\"other.store\"\n",
+        ));
+        for (name, value) in [
+            ("last", "2026-10-16"),
+            ("notes", "two\nlines"),
+            ("count", "0"),
+            ("has", "0"),
+            ("keys/count", "2"),
+            ("keys/1", "notes"),
+            ("other", "1"),
+            ("crlf", "x\r"),
+        ] {
+            assert_eq!(eval.resolve_var(name), value, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_keys_that_would_not_round_trip_are_rejected() {
+        for key in ["", "x =", "= x", "a = b"] {
+            let mut eval = Evaluator::new();
+            eval.fs = Arc::new(MemoryFs::new());
+            let source = format!("store set \"{}\" \"v\"", key);
+            let err = run_script_in(eval, &source).1.unwrap_err();
+            assert!(err.to_string().contains("store: invalid key"), "{:?}: {}", key, err);
+        }
+    }
+}
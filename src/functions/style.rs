@@ -0,0 +1,127 @@
+/// `style` — wrap text in ANSI colors and styles for terminal output.
+///
+/// Every argument but the last names a style; the last is the text:
+///
+/// ```bucl
+/// {s} style "red" "bold" "Error!"
+/// echo {s} "something went wrong"
+/// ```
+///
+/// Styles: `bold`, `dim`, `italic`, `underline`, `reverse`, the colors
+/// `black`, `red`, `green`, `yellow`, `blue`, `magenta`, `cyan`, `white`,
+/// their `bright-` variants (`bright-red`), and `bg-` background colors
+/// (`bg-blue`, `bg-bright-blue`).
+///
+/// When [`Evaluator::color`] is off — stdout is not a terminal, `NO_COLOR`
+/// is set, or the script runs in WASM — the text is returned unchanged, so
+/// piped output stays clean.  Unknown style names are an error either way.
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+const COLORS: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+/// The SGR code for a style name.
+fn code(name: &str) -> Option<u8> {
+    let (base, name) = match name.strip_prefix("bg-") {
+        Some(rest) => (40, rest),
+        None => (30, name),
+    };
+    let (base, name) = match name.strip_prefix("bright-") {
+        Some(rest) => (base + 60, rest),
+        None => (base, name),
+    };
+    if let Some(i) = COLORS.iter().position(|&c| c == name) {
+        return Some(base + i as u8);
+    }
+    if base != 30 {
+        return None;
+    }
+    match name {
+        "bold" => Some(1),
+        "dim" => Some(2),
+        "italic" => Some(3),
+        "underline" => Some(4),
+        "reverse" => Some(7),
+        _ => None,
+    }
+}
+
+pub struct Style;
+
+impl BuclFunction for Style {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let Some((text, styles)) = args.split_last() else {
+            return Err(BuclError::RuntimeError(
+                "style: expected style names followed by the text".into(),
+            ));
+        };
+        let codes = styles
+            .iter()
+            .map(|s| {
+                code(&s.to_ascii_lowercase()).map(|c| c.to_string()).ok_or_else(|| {
+                    BuclError::RuntimeError(format!("style: unknown style '{}'", s))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if !evaluator.color || codes.is_empty() {
+            return Ok(Some(text.clone()));
+        }
+        Ok(Some(format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)))
+    }
+
+    fn description(&self) -> &str {
+        "Wrap text in ANSI colors and styles: `{s} style \"red\" \"bold\" \"Error!\"`."
+    }
+}
+
+/// Whether to emit colors by default: stdout is a terminal and `NO_COLOR`
+/// is unset or empty (<https://no-color.org>).  Always `false` on WASM.
+pub fn color_default() -> bool {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use std::io::IsTerminal;
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        !no_color && std::io::stdout().is_terminal()
+    }
+    #[cfg(target_arch = "wasm32")]
+    false
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("style", Style);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(color: bool, source: &str) -> Result<String> {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.color = color;
+        eval.evaluate_statements(&crate::parser::parse(source).unwrap())?;
+        Ok(eval.resolve_var("s"))
+    }
+
+    #[test]
+    fn test_style_codes_and_suppression() {
+        let source = "{s} style \"red\" \"Bold\" \"bg-bright-blue\" \"Error!\"\n";
+        assert_eq!(style(true, source).unwrap(), "\x1b[31;1;104mError!\x1b[0m");
+        assert_eq!(style(false, source).unwrap(), "Error!");
+        assert!(style(false, "{s} style \"bg-bold\" \"x\"\n").is_err());
+        assert!(style(true, "{s} style \"purple\" \"x\"\n").is_err());
+    }
+}
@@ -0,0 +1,233 @@
+/// `isnumber` / `isint` / `isset` / `isarray` — inspect values before using
+/// them.
+///
+/// Each returns `"1"` or `"0"`, so a script can check its input and print a
+/// helpful message instead of stopping at a runtime error in `math` or
+/// `repeat`:
+///
+/// ```bucl
+/// {ok} isint {count}
+/// if {ok} = "0"
+///     error "count must be a whole number, got '{count}'"
+/// {r} repeat {count}
+///     …
+/// ```
+///
+/// - `isnumber value ...` — every value is a finite number (`3`, `-0.5`,
+///   `1e3`; surrounding spaces are ignored).
+/// - `isint value ...` — every value is a whole number in the 64-bit range.
+/// - `isset {var} ...` — every variable is set, even to `""` (like
+///   `if exists {var}`).
+/// - `isarray {var} ...` — every variable holds an array, i.e. was set with
+///   several values or by a built-in returning a list (`{var/0}`, …).
+///
+/// `isset` and `isarray` take the variables themselves, not their values,
+/// so they work in strict mode too.
+use crate::ast::{Param, Statement};
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+fn flag(value: bool) -> Option<String> {
+    Some(if value { "1" } else { "0" }.to_string())
+}
+
+fn is_number(value: &str) -> bool {
+    value.trim().parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+fn is_int(value: &str) -> bool {
+    value.trim().parse::<i64>().is_ok()
+}
+
+/// The variable names passed to `isset` / `isarray`, with nested references
+/// such as `{row/{i}}` resolved.
+fn variable_names(evaluator: &Evaluator, fname: &str) -> Result<Vec<String>> {
+    if evaluator.call_params.is_empty() {
+        return Err(BuclError::RuntimeError(format!(
+            "{}: expected variables, e.g. {} {{name}}",
+            fname, fname
+        )));
+    }
+    evaluator
+        .call_params
+        .iter()
+        .map(|param| match param {
+            Param::Variable(name) if name.contains('{') => Ok(evaluator.interpolate(name)),
+            Param::Variable(name) => Ok(name.clone()),
+            _ => Err(BuclError::RuntimeError(format!(
+                "{}: expected variables, e.g. {} {{name}}",
+                fname, fname
+            ))),
+        })
+        .collect()
+}
+
+/// Whether `name` holds an array: an item count other than one, or an
+/// explicit `{name/0}` item.
+fn is_array(evaluator: &Evaluator, name: &str) -> bool {
+    let vars = &evaluator.variables;
+    if !vars.contains_key(name) {
+        return false;
+    }
+    match vars.get(&format!("{}/count", name)) {
+        Some(count) => count != "1" || vars.contains_key(&format!("{}/0", name)),
+        None => false,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Built-ins
+// ---------------------------------------------------------------------------
+
+/// `isnumber` and `isint`: every argument passes `check`.
+pub struct ValueCheck {
+    name: &'static str,
+    check: fn(&str) -> bool,
+}
+
+impl BuclFunction for ValueCheck {
+    fn call(
+        &self,
+        _evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        if args.is_empty() {
+            return Err(BuclError::RuntimeError(format!("{}: missing value argument", self.name)));
+        }
+        Ok(flag(args.iter().all(|a| (self.check)(a))))
+    }
+
+    fn description(&self) -> &str {
+        match self.name {
+            "isint" => "Whether every value is a whole number (`1` or `0`): `{ok} isint {count}`.",
+            _ => "Whether every value is a finite number (`1` or `0`): `{ok} isnumber {price}`.",
+        }
+    }
+}
+
+pub struct IsSet;
+
+impl BuclFunction for IsSet {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        _args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let names = variable_names(evaluator, "isset")?;
+        Ok(flag(names.iter().all(|name| evaluator.var_exists(name))))
+    }
+
+    fn allows_duplicate_names(&self) -> bool {
+        true
+    }
+
+    fn names_variables(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Whether every variable is set, even to `\"\"` (`1` or `0`): `{ok} isset {name}`."
+    }
+}
+
+pub struct IsArray;
+
+impl BuclFunction for IsArray {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        _args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let names = variable_names(evaluator, "isarray")?;
+        Ok(flag(names.iter().all(|name| is_array(evaluator, name))))
+    }
+
+    fn allows_duplicate_names(&self) -> bool {
+        true
+    }
+
+    fn names_variables(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Whether every variable holds an array (`1` or `0`): `{ok} isarray {items}`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("isnumber", ValueCheck { name: "isnumber", check: is_number });
+    eval.register("isint", ValueCheck { name: "isint", check: is_int });
+    eval.register("isset", IsSet);
+    eval.register("isarray", IsArray);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(all(test, feature = "math"))]
+mod tests {
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_type_predicates() {
+        let mut eval = Evaluator::builder().strict(true).build();
+        let stmts = crate::parser::parse(concat!(
+            "{one} = \"x\"\n",
+            "{empty} = \"\"\n",
+            "{list} = \"a\" \"b\"\n",
+            "{parts} explode \",\" \"a\"\n",
+            "{none} = \"\"\n",
+            "{none/count} = \"0\"\n",
+            "{i} = \"1\"\n",
+            "{n1} isnumber \" -0.5 \"\n",
+            "{n2} isnumber \"1e3\" \"7\"\n",
+            "{n3} isnumber \"inf\"\n",
+            "{n4} isnumber \"12px\"\n",
+            "{i1} isint \"42\" \"-7\"\n",
+            "{i2} isint \"4.2\"\n",
+            "{s1} isset {one} {empty}\n",
+            "{s2} isset {one} {missing}\n",
+            "{s3} isset {list/{i}}\n",
+            "{a1} isarray {list}\n",
+            "{a2} isarray {one}\n",
+            "{a3} isarray {missing}\n",
+            "{a4} isarray {none}\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        for (name, value) in [
+            ("n1", "1"),
+            ("n2", "1"),
+            ("n3", "0"),
+            ("n4", "0"),
+            ("i1", "1"),
+            ("i2", "0"),
+            ("s1", "1"),
+            ("s2", "0"),
+            ("s3", "1"),
+            ("a1", "1"),
+            ("a2", "0"),
+            ("a3", "0"),
+            ("a4", "1"),
+        ] {
+            assert_eq!(eval.resolve_var(name), value, "{}", name);
+        }
+
+        let err = eval
+            .evaluate_statements(&crate::parser::parse("{x} isset \"one\"").unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("isset: expected variables"), "{}", err);
+    }
+}
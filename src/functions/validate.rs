@@ -0,0 +1,272 @@
+/// `validate` — check the fields of a struct against declarative rules.
+///
+/// The struct is passed as the argument (its fields arrive through struct
+/// expansion); each line of the indented block names a field followed by its
+/// rules:
+///
+/// ```bucl
+/// {form/name} = "alice"
+/// {form/age}  = "abc"
+/// {errors} validate {form}
+///     name required matches:"^[a-z]+$"
+///     age  required numeric min:"1" max:"120"
+/// # {errors} = "1", {errors/age} = "age must be a number"
+/// ```
+///
+/// | Rule | Passes when the value… |
+/// |---|---|
+/// | `required` | is not empty or whitespace |
+/// | `numeric` | is a number |
+/// | `integer` | is a whole number |
+/// | `min:"N"` / `max:"N"` | is a number `>= N` / `<= N` |
+/// | `minlength:"N"` / `maxlength:"N"` | has at least / at most N characters |
+/// | `matches:"RE"` | matches the regular expression (feature `regex`) |
+///
+/// An empty value only fails `required`; the other rules are skipped so
+/// optional fields can be left blank.  Only the first failing rule of each
+/// field is reported.
+///
+/// The target receives the number of invalid fields (`"0"` when everything
+/// passed) and one `{target/field}` message per invalid field; messages left
+/// from an earlier run for fields that now pass are removed.  Without a
+/// target the prefix defaults to `errors`.
+///
+/// Rule values are interpolated (`max:{limit}`), except `matches` patterns,
+/// whose braces are regex syntax.
+use crate::ast::{Param, Statement};
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::BuclFunction;
+
+pub struct Validate;
+
+impl BuclFunction for Validate {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        target: Option<&str>,
+        _args: Vec<String>,
+        block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let prefix = target.unwrap_or("errors");
+        let block = block.ok_or_else(|| {
+            BuclError::RuntimeError("validate: expected an indented block of field rules".into())
+        })?;
+
+        let mut results = Vec::with_capacity(block.len());
+        for stmt in block {
+            if stmt.target.is_some() {
+                return Err(BuclError::RuntimeError(format!(
+                    "validate: line {}: rule lines start with a field name",
+                    stmt.line
+                )));
+            }
+            let field = &stmt.function;
+            let value = evaluator.named_arg(field).cloned().unwrap_or_default();
+            let mut error = None;
+            for param in &stmt.args {
+                let rule = parse_rule(evaluator, param);
+                if let Some(msg) = check_rule(field, &value, &rule)? {
+                    error = Some(msg);
+                    break;
+                }
+            }
+            results.push((field.clone(), error));
+        }
+
+        let invalid = results.iter().filter(|(_, e)| e.is_some()).count();
+        evaluator.set_var(prefix, invalid.to_string());
+        for (field, error) in results {
+            let key = format!("{}/{}", prefix, field);
+            match error {
+                Some(msg) => evaluator.variables.insert(key, msg),
+                None => evaluator.variables.remove(&key),
+            };
+        }
+        Ok(None)
+    }
+
+    fn description(&self) -> &str {
+        "Check the fields of a struct against the rules in the block: `{errors} validate {form}`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("validate", Validate);
+}
+
+// ---------------------------------------------------------------------------
+// Rules
+// ---------------------------------------------------------------------------
+
+/// A rule as written in the block: `required` or `min:"1"`.
+struct Rule {
+    name: String,
+    arg: Option<String>,
+}
+
+fn parse_rule(evaluator: &Evaluator, param: &Param) -> Rule {
+    if let Param::Named(name, value) = param {
+        let arg = match value.as_ref() {
+            // Braces in a pattern are quantifiers, not variables.
+            Param::Quoted(pattern, _) if name == "matches" => pattern.clone(),
+            other => evaluator.eval_param(other),
+        };
+        return Rule { name: name.clone(), arg: Some(arg) };
+    }
+    let text = match param {
+        Param::Bare(s) => s.clone(),
+        other => evaluator.eval_param(other),
+    };
+    match text.split_once(':') {
+        Some((name, raw)) => {
+            let unquoted = raw
+                .strip_prefix('"')
+                .and_then(|r| r.strip_suffix('"'))
+                .unwrap_or(raw);
+            let arg = if name == "matches" {
+                unquoted.to_string()
+            } else {
+                evaluator.interpolate(unquoted)
+            };
+            Rule { name: name.to_string(), arg: Some(arg) }
+        }
+        None => Rule { name: text, arg: None },
+    }
+}
+
+/// Check one rule, returning the error message if `value` fails it.
+fn check_rule(field: &str, value: &str, rule: &Rule) -> Result<Option<String>> {
+    let trimmed = value.trim();
+    if rule.name == "required" {
+        return Ok(trimmed.is_empty().then(|| format!("{} is required", field)));
+    }
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+
+    let arg = || {
+        rule.arg.as_deref().ok_or_else(|| {
+            BuclError::RuntimeError(format!(
+                "validate: rule '{}' needs a value, e.g. {}:\"…\"",
+                rule.name, rule.name
+            ))
+        })
+    };
+    let number_arg = || {
+        let a = arg()?;
+        a.trim().parse::<f64>().map_err(|_| {
+            BuclError::RuntimeError(format!(
+                "validate: rule '{}' expects a number, got '{}'",
+                rule.name, a
+            ))
+        })
+    };
+    let not_a_number = || format!("{} must be a number", field);
+
+    let failure = match rule.name.as_str() {
+        "numeric" => trimmed.parse::<f64>().is_err().then(not_a_number),
+        "integer" => trimmed
+            .parse::<i64>()
+            .is_err()
+            .then(|| format!("{} must be a whole number", field)),
+        "min" | "max" => {
+            let bound = number_arg()?;
+            match trimmed.parse::<f64>() {
+                Err(_) => Some(not_a_number()),
+                Ok(n) if rule.name == "min" && n < bound => {
+                    Some(format!("{} must be at least {}", field, arg()?))
+                }
+                Ok(n) if rule.name == "max" && n > bound => {
+                    Some(format!("{} must be at most {}", field, arg()?))
+                }
+                Ok(_) => None,
+            }
+        }
+        "minlength" | "maxlength" => {
+            let bound = number_arg()?;
+            let len = value.chars().count() as f64;
+            if rule.name == "minlength" && len < bound {
+                Some(format!("{} must be at least {} characters", field, arg()?))
+            } else if rule.name == "maxlength" && len > bound {
+                Some(format!("{} must be at most {} characters", field, arg()?))
+            } else {
+                None
+            }
+        }
+        "matches" => (!matches_pattern(value, arg()?)?)
+            .then(|| format!("{} has an invalid format", field)),
+        other => {
+            return Err(BuclError::RuntimeError(format!(
+                "validate: unknown rule '{}'",
+                other
+            )))
+        }
+    };
+    Ok(failure)
+}
+
+#[cfg(feature = "regex")]
+fn matches_pattern(value: &str, pattern: &str) -> Result<bool> {
+    let re = regex::Regex::new(pattern).map_err(|e| {
+        BuclError::RuntimeError(format!("validate: invalid pattern '{}': {}", pattern, e))
+    })?;
+    Ok(re.is_match(value))
+}
+
+#[cfg(not(feature = "regex"))]
+fn matches_pattern(_value: &str, _pattern: &str) -> Result<bool> {
+    Err(BuclError::RuntimeError(
+        "validate: 'matches' rules need the `regex` feature".into(),
+    ))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::run_script;
+
+    #[test]
+    fn test_reports_first_failing_rule_per_field() {
+        let (eval, result) = run_script(concat!(
+            "{form/name} = \"\"\n",
+            "{form/age} = \"abc\"\n",
+            "{form/city} = \"Oslo\"\n",
+            "{errors} validate {form}\n",
+            "    name required minlength:\"2\"\n",
+            "    age required numeric min:\"1\"\n",
+            "    city required maxlength:\"10\"\n",
+        ));
+        result.unwrap();
+        assert_eq!(eval.resolve_var("errors"), "2");
+        assert_eq!(eval.resolve_var("errors/name"), "name is required");
+        assert_eq!(eval.resolve_var("errors/age"), "age must be a number");
+        assert!(!eval.variables.contains_key("errors/city"));
+    }
+
+    #[test]
+    fn test_bounds_and_optional_fields() {
+        let (eval, result) = run_script(concat!(
+            "{limit} = \"120\"\n",
+            "{form/age} = \"150\"\n",
+            "{form/nick} = \"\"\n",
+            "{errors} validate {form}\n",
+            "    age integer min:\"1\" max:{limit}\n",
+            "    nick minlength:\"3\"\n",
+        ));
+        result.unwrap();
+        assert_eq!(eval.resolve_var("errors"), "1");
+        assert_eq!(eval.resolve_var("errors/age"), "age must be at most 120");
+    }
+
+    #[test]
+    fn test_unknown_rule_is_an_error() {
+        let rule = Rule { name: "shiny".into(), arg: None };
+        assert!(check_rule("x", "1", &rule).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+/// `wordwrap` — wrap text to a column width at word boundaries.
+///
+/// The first argument is the width in characters; the rest is the text,
+/// concatenated like `=`.  Lines break between words, so a word longer than
+/// the width gets a line of its own rather than being split:
+///
+/// ```bucl
+/// {help} wordwrap 30 "Copy files from the source directory to the target, skipping existing ones."
+/// echo {help}
+/// # Copy files from the source
+/// # directory to the target,
+/// # skipping existing ones.
+/// ```
+///
+/// `indent:"…"` is put in front of every line after the first, for
+/// hanging indents in help text; it counts towards the width:
+///
+/// ```bucl
+/// {desc} wordwrap 40 "  -v, --verbose  print each file as it is copied" indent:"                 "
+/// ```
+///
+/// Newlines already in the text are kept (each line is wrapped on its own),
+/// and runs of spaces between words collapse to one.
+use crate::ast::Statement;
+use crate::error::{BuclError, Result};
+use crate::evaluator::Evaluator;
+use crate::functions::{take_options, BuclFunction};
+
+/// Wrap every line of `text` to `width` characters, starting each line
+/// after the first with `indent`.
+fn wrap(text: &str, width: usize, indent: &str) -> String {
+    let indent_width = indent.chars().count();
+    let mut lines: Vec<String> = Vec::new();
+    for paragraph in text.split('\n') {
+        let lead = if lines.is_empty() { "" } else { indent };
+        let mut line = lead.to_string();
+        let mut line_width = lead.chars().count();
+        let mut has_word = false;
+        for word in paragraph.split_whitespace() {
+            let word_width = word.chars().count();
+            if has_word && line_width + 1 + word_width > width {
+                lines.push(std::mem::replace(&mut line, indent.to_string()));
+                line_width = indent_width;
+                has_word = false;
+            }
+            if has_word {
+                line.push(' ');
+                line_width += 1;
+            }
+            line.push_str(word);
+            line_width += word_width;
+            has_word = true;
+        }
+        lines.push(if has_word { line } else { String::new() });
+    }
+    lines.join("\n")
+}
+
+pub struct WordWrap;
+
+impl BuclFunction for WordWrap {
+    fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        _target: Option<&str>,
+        mut args: Vec<String>,
+        _block: Option<&[Statement]>,
+        _continuation: Option<&Statement>,
+    ) -> Result<Option<String>> {
+        let indent = match take_options(evaluator, &mut args, &["indent"]).0.pop() {
+            Some((_, indent)) => indent,
+            None => String::new(),
+        };
+        let Some((width, text)) = args.split_first() else {
+            return Err(BuclError::RuntimeError(
+                "wordwrap: expected a width followed by the text".into(),
+            ));
+        };
+        let width = match width.trim().parse::<usize>() {
+            Ok(width) if width > 0 => width,
+            _ => {
+                return Err(BuclError::RuntimeError(format!(
+                    "wordwrap: width must be a positive whole number, got '{}'",
+                    width
+                )))
+            }
+        };
+        Ok(Some(wrap(&text.concat(), width, &indent)))
+    }
+
+    fn description(&self) -> &str {
+        "Wrap text to a column width at word boundaries: `{w} wordwrap 40 {text}`."
+    }
+}
+
+pub fn register(eval: &mut Evaluator) {
+    eval.register("wordwrap", WordWrap);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::run_script;
+
+    #[test]
+    fn test_wordwrap() {
+        let (eval, result) = run_script(concat!(
+            "{w} = \"12\"\n",
+            "{plain} wordwrap {w} \"the quick  brown fox jumps\"\n",
+            "{long} wordwrap 5 \"a abcdefgh b\"\n",
+            "{hang} wordwrap 14 \"-v  print every file\" indent:\"    \"\n",
+            "{paras} wordwrap 10 \"one two three\\n\\nfour\"\n",
+        ));
+        result.unwrap();
+        assert_eq!(eval.resolve_var("plain"), "the quick\nbrown fox\njumps");
+        assert_eq!(eval.resolve_var("long"), "a\nabcdefgh\nb");
+        assert_eq!(eval.resolve_var("hang"), "-v print every\n    file");
+        assert_eq!(eval.resolve_var("paras"), "one two\nthree\n\nfour");
+
+        let err = run_script("{x} wordwrap 0 \"text\"").1.unwrap_err().to_string();
+        assert!(err.contains("wordwrap: width must be a positive whole number, got '0'"), "{}", err);
+    }
+}
@@ -0,0 +1,186 @@
+//! Functions the WASM build imports from its JavaScript host.
+//!
+//! Every host import lives here behind a small safe wrapper, so built-ins
+//! don't care which ABI the module was built with:
+//!
+//! - **Raw C-ABI** (default): plain `extern "C"` imports from the `env`
+//!   module.  Strings cross the boundary as `(ptr, len)` pairs and replies
+//!   come back in buffers the host allocates with `bucl_alloc` (see
+//!   [`take_host_reply`]).  `docs/demo/wasm/worker.js` implements these.
+//! - **wasm-bindgen** (feature `wasm-bindgen`): the same hooks imported from
+//!   `js/bucl-host.js` with ordinary JS strings, so bundler users get working
+//!   defaults they can override through `globalThis.buclHost`.
+//!
+//! Only compiled for `wasm32`; native builds use the OS directly.
+
+/// Result of a host call that can succeed, fail, or find nothing.
+pub(crate) enum HostReply {
+    /// The call returned this text.
+    Ok(String),
+    /// The host reported an error with this message.
+    Err(String),
+    /// Nothing by that name (host function or file) exists.
+    Missing,
+}
+
+// ---------------------------------------------------------------------------
+// Raw C-ABI
+// ---------------------------------------------------------------------------
+
+#[cfg(not(feature = "wasm-bindgen"))]
+mod imports {
+    extern "C" {
+        pub fn js_math_random() -> f64;
+        pub fn js_sleep(ms: f64);
+        pub fn js_emit(ptr: *const u8, len: usize);
+        pub fn js_should_cancel() -> i32;
+        pub fn js_call(name_ptr: *const u8, name_len: usize, arg_ptr: *const u8, arg_len: usize)
+            -> *mut u8;
+        pub fn js_read_file(path_ptr: *const u8, path_len: usize) -> *mut u8;
+        pub fn js_write_file(
+            path_ptr: *const u8,
+            path_len: usize,
+            data_ptr: *const u8,
+            data_len: usize,
+        ) -> i32;
+    }
+}
+
+/// Read and free a reply buffer the host allocated with `bucl_alloc`.
+///
+/// Host replies (`js_call`, `js_read_file`) share one layout:
+/// `[u32-le status][u32-le len][len bytes of UTF-8]` with status 0 = result,
+/// 1 = error message, 2 = missing.  A null pointer reads as `None`.
+///
+/// # Safety
+/// `ptr` must be null or a buffer of that layout obtained from `bucl_alloc`.
+#[cfg(not(feature = "wasm-bindgen"))]
+unsafe fn take_host_reply(ptr: *mut u8) -> Option<HostReply> {
+    if ptr.is_null() {
+        return None;
+    }
+    unsafe {
+        let header = std::slice::from_raw_parts(ptr, 8);
+        let status = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        let len = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+        let text = String::from_utf8_lossy(std::slice::from_raw_parts(ptr.add(8), len)).into_owned();
+        crate::bucl_free(ptr, 8 + len);
+        Some(match status {
+            0 => HostReply::Ok(text),
+            2 => HostReply::Missing,
+            _ => HostReply::Err(text),
+        })
+    }
+}
+
+#[cfg(not(feature = "wasm-bindgen"))]
+pub(crate) fn math_random() -> f64 {
+    unsafe { imports::js_math_random() }
+}
+
+#[cfg(not(feature = "wasm-bindgen"))]
+pub(crate) fn sleep_ms(ms: f64) {
+    unsafe { imports::js_sleep(ms) }
+}
+
+#[cfg(not(feature = "wasm-bindgen"))]
+pub(crate) fn emit(line: &str) {
+    unsafe { imports::js_emit(line.as_ptr(), line.len()) }
+}
+
+#[cfg(not(feature = "wasm-bindgen"))]
+pub(crate) fn should_cancel() -> bool {
+    unsafe { imports::js_should_cancel() != 0 }
+}
+
+/// Call a host function; `args_json` is a JSON array of strings.  A null
+/// reply counts as an empty result.
+#[cfg(not(feature = "wasm-bindgen"))]
+pub(crate) fn call(name: &str, args_json: &str) -> HostReply {
+    unsafe {
+        let ptr = imports::js_call(name.as_ptr(), name.len(), args_json.as_ptr(), args_json.len());
+        take_host_reply(ptr).unwrap_or(HostReply::Ok(String::new()))
+    }
+}
+
+#[cfg(not(feature = "wasm-bindgen"))]
+pub(crate) fn read_file(path: &str) -> HostReply {
+    unsafe {
+        let ptr = imports::js_read_file(path.as_ptr(), path.len());
+        take_host_reply(ptr).unwrap_or(HostReply::Missing)
+    }
+}
+
+/// Returns `false` if the host refused the write.
+#[cfg(not(feature = "wasm-bindgen"))]
+pub(crate) fn write_file(path: &str, contents: &str) -> bool {
+    unsafe {
+        imports::js_write_file(path.as_ptr(), path.len(), contents.as_ptr(), contents.len()) == 0
+    }
+}
+
+// ---------------------------------------------------------------------------
+// wasm-bindgen
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "wasm-bindgen")]
+mod imports {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(module = "/js/bucl-host.js")]
+    extern "C" {
+        pub fn js_math_random() -> f64;
+        pub fn js_sleep(ms: f64);
+        pub fn js_emit(line: &str);
+        pub fn js_should_cancel() -> bool;
+        #[wasm_bindgen(catch)]
+        pub fn js_call(name: &str, args_json: &str) -> Result<Option<String>, JsValue>;
+        #[wasm_bindgen(catch)]
+        pub fn js_read_file(path: &str) -> Result<Option<String>, JsValue>;
+        pub fn js_write_file(path: &str, contents: &str) -> bool;
+    }
+}
+
+#[cfg(feature = "wasm-bindgen")]
+fn to_reply(result: Result<Option<String>, wasm_bindgen::JsValue>) -> HostReply {
+    match result {
+        Ok(Some(text)) => HostReply::Ok(text),
+        Ok(None) => HostReply::Missing,
+        Err(e) => HostReply::Err(e.as_string().unwrap_or_else(|| format!("{:?}", e))),
+    }
+}
+
+#[cfg(feature = "wasm-bindgen")]
+pub(crate) fn math_random() -> f64 {
+    imports::js_math_random()
+}
+
+#[cfg(feature = "wasm-bindgen")]
+pub(crate) fn sleep_ms(ms: f64) {
+    imports::js_sleep(ms)
+}
+
+#[cfg(feature = "wasm-bindgen")]
+pub(crate) fn emit(line: &str) {
+    imports::js_emit(line)
+}
+
+#[cfg(feature = "wasm-bindgen")]
+pub(crate) fn should_cancel() -> bool {
+    imports::js_should_cancel()
+}
+
+#[cfg(feature = "wasm-bindgen")]
+pub(crate) fn call(name: &str, args_json: &str) -> HostReply {
+    to_reply(imports::js_call(name, args_json))
+}
+
+#[cfg(feature = "wasm-bindgen")]
+pub(crate) fn read_file(path: &str) -> HostReply {
+    to_reply(imports::js_read_file(path))
+}
+
+#[cfg(feature = "wasm-bindgen")]
+pub(crate) fn write_file(path: &str, contents: &str) -> bool {
+    imports::js_write_file(path, contents)
+}
@@ -1,3 +1,6 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
 use crate::error::{BuclError, Result};
 
 /// A single token on a BUCL line.
@@ -5,33 +8,71 @@ use crate::error::{BuclError, Result};
 pub enum Token {
     /// `{name}` — a variable reference.
     Variable(String),
-    /// `"..."` — a quoted string (escape sequences already resolved).
-    Quoted(String),
+    /// `"..."` — a quoted string (escape sequences already resolved), with
+    /// the byte offsets of braces written as `\{` or `\}`, which are literal
+    /// rather than interpolation markers.
+    Quoted(String, Vec<usize>),
+    /// `r"..."` — a raw string: no escapes, no interpolation.
+    Raw(String),
     /// Any bare word, number, or operator (`=`, `-1`, …).
     Bare(String),
+    /// `name:"value"`, `name:r"value"` or `name:{var}` — an argument named
+    /// at the call site, with the token holding its value.
+    Named(String, Box<Token>),
 }
 
-/// A successfully tokenized non-empty, non-comment line.
+/// A successfully tokenized statement: a non-empty, non-comment line, or
+/// one of the `;`-separated statements on it.
 #[derive(Debug, Clone)]
 pub struct Line {
+    /// 1-based line number in the original source.
+    pub lineno: usize,
     /// Number of leading whitespace characters (used as indent level).
     pub indent: usize,
+    /// The leading whitespace itself, to catch tabs mixed with spaces.
+    pub indent_text: String,
     pub tokens: Vec<Token>,
+    /// 1-based column (in characters) where each token starts, for error
+    /// messages.
+    pub columns: Vec<usize>,
+}
+
+/// Tokenize one raw source line into its statements: one, several when
+/// they are separated by `;` (`{x} = "1"; echo {x}`), or none for blank
+/// lines and pure-comment lines — which includes a `#!/usr/bin/env bucl`
+/// shebang line.  Every statement gets the line's indentation.
+/// The returned lines' `lineno` is left at 0; [`tokenize`] fills it in.
+pub fn tokenize_line(line: &str) -> Result<Vec<Line>> {
+    lex_line(line).map_err(|(_, e)| e)
 }
 
-/// Tokenize one raw source line.
-/// Returns `None` for blank lines and pure-comment lines.
-pub fn tokenize_line(line: &str) -> Result<Option<Line>> {
+/// [`tokenize_line`], with the column an error was found at.
+fn lex_line(line: &str) -> std::result::Result<Vec<Line>, (usize, BuclError)> {
     // Measure indent before stripping
-    let indent = line.len() - line.trim_start_matches(|c: char| c == ' ' || c == '\t').len();
+    let indent_text = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+    let indent = indent_text.len();
     let content = line.trim();
 
     if content.is_empty() || content.starts_with('#') {
-        return Ok(None);
+        return Ok(Vec::new());
     }
 
+    let mut lines: Vec<Line> = Vec::new();
     let mut tokens: Vec<Token> = Vec::new();
+    let mut columns: Vec<usize> = Vec::new();
+    let content_len = content.chars().count();
     let mut chars = content.chars().peekable();
+    let mut finish = |tokens: &mut Vec<Token>, columns: &mut Vec<usize>| {
+        if !tokens.is_empty() {
+            lines.push(Line {
+                lineno: 0,
+                indent,
+                indent_text: indent_text.to_string(),
+                tokens: std::mem::take(tokens),
+                columns: std::mem::take(columns),
+            });
+        }
+    };
 
     while let Some(&c) = chars.peek() {
         if c.is_whitespace() {
@@ -39,76 +80,423 @@ pub fn tokenize_line(line: &str) -> Result<Option<Line>> {
             continue;
         }
 
-        if c == '{' {
-            chars.next(); // consume opening '{'
-            let mut name = String::new();
-            let mut depth = 1usize;
-            loop {
-                match chars.next() {
-                    None => break,
-                    Some('{') => { depth += 1; name.push('{'); }
-                    Some('}') => {
-                        depth -= 1;
-                        if depth == 0 { break; }
-                        name.push('}');
-                    }
-                    Some(ch) => name.push(ch),
+        // A `#` where a token would start begins an end-of-line comment.
+        if c == '#' {
+            break;
+        }
+
+        // So does a `;` the next statement.
+        if c == ';' {
+            chars.next();
+            finish(&mut tokens, &mut columns);
+            continue;
+        }
+
+        let column = indent + content_len - chars.clone().count() + 1;
+        columns.push(column);
+        let at = |e| (column, e);
+
+        tokens.push(lex_token(&mut chars).map_err(at)?);
+    }
+
+    finish(&mut tokens, &mut columns);
+    Ok(lines)
+}
+
+/// Lex the token starting at `chars`.
+fn lex_token(chars: &mut Peekable<Chars>) -> Result<Token> {
+    let c = *chars.peek().expect("lex_token called at the end of the line");
+    if c == '{' {
+        chars.next(); // consume opening '{'
+        let mut name = String::new();
+        let mut depth = 1usize;
+        loop {
+            match chars.next() {
+                None => break,
+                Some('{') => { depth += 1; name.push('{'); }
+                Some('}') => {
+                    depth -= 1;
+                    if depth == 0 { break; }
+                    name.push('}');
                 }
+                Some(ch) => name.push(ch),
+            }
+        }
+        Ok(Token::Variable(name))
+    } else if c == 'r' && chars.clone().nth(1) == Some('"') {
+        chars.nth(1); // consume opening 'r"'
+        let raw: String = chars.by_ref().take_while(|&ch| ch != '"').collect();
+        Ok(Token::Raw(raw))
+    } else if c == '"' && chars.clone().take(3).eq("\"\"\"".chars()) {
+        chars.nth(2); // consume opening '"""'
+        let mut raw = String::new();
+        loop {
+            if chars.clone().take(3).eq("\"\"\"".chars()) {
+                chars.nth(2);
+                break;
             }
-            tokens.push(Token::Variable(name));
-        } else if c == '"' {
-            chars.next(); // consume opening '"'
-            let mut s = String::new();
-            loop {
-                match chars.next() {
-                    None | Some('"') => break,
-                    Some('\\') => match chars.next() {
-                        Some('"') => s.push('"'),
-                        Some('n') => s.push('\n'),
-                        Some('t') => s.push('\t'),
-                        Some('\\') => s.push('\\'),
-                        Some(nc) => {
-                            s.push('\\');
-                            s.push(nc);
-                        }
-                        None => break,
-                    },
-                    Some(ch) => s.push(ch),
+            match chars.next() {
+                None => return Err(BuclError::ParseError("unterminated \"\"\" string".into())),
+                Some('\\') => {
+                    raw.push('\\');
+                    raw.extend(chars.next());
                 }
+                Some(ch) => raw.push(ch),
             }
-            tokens.push(Token::Quoted(s));
-        } else {
-            let mut word = String::new();
-            while let Some(&ch) = chars.peek() {
-                if ch.is_whitespace() {
-                    break;
+        }
+        let (s, braces) = resolve_escapes(&dedent(&raw))?;
+        Ok(Token::Quoted(s, braces))
+    } else if c == '"' {
+        chars.next(); // consume opening '"'
+        let mut raw = String::new();
+        loop {
+            match chars.next() {
+                None | Some('"') => break,
+                Some('\\') => {
+                    raw.push('\\');
+                    raw.extend(chars.next());
+                }
+                Some(ch) => raw.push(ch),
+            }
+        }
+        let (s, braces) = resolve_escapes(&raw)?;
+        Ok(Token::Quoted(s, braces))
+    } else {
+        let mut word = String::new();
+        while let Some(&ch) = chars.peek() {
+            // `echo hi; echo there`: a `;` ending a word separates
+            // statements, one inside it (`a;b`) doesn't.
+            let ends_word = |next: Option<char>| next.is_none_or(char::is_whitespace);
+            if ch.is_whitespace() || (ch == ';' && ends_word(chars.clone().nth(1))) {
+                break;
+            }
+            // `name:"value"`, `name:r"value"` or `name:{var}` names an
+            // argument; `host:8080` stays one word.
+            if ch == ':' && is_arg_name(&word) {
+                let mut value = chars.clone();
+                value.next();
+                let starts_value = match value.peek() {
+                    Some('"' | '{') => true,
+                    Some('r') => value.clone().nth(1) == Some('"'),
+                    _ => false,
+                };
+                if starts_value {
+                    *chars = value;
+                    return Ok(Token::Named(word, Box::new(lex_token(chars)?)));
+                }
+            }
+            word.push(ch);
+            chars.next();
+            // The quoted value of a `name:"value"` option may hold spaces.
+            if ch == '"' && word.ends_with(":\"") {
+                while let Some(ch) = chars.next() {
+                    word.push(ch);
+                    match ch {
+                        '"' => break,
+                        '\\' => word.extend(chars.next()),
+                        _ => {}
+                    }
                 }
-                word.push(ch);
-                chars.next();
             }
-            tokens.push(Token::Bare(word));
         }
+        Ok(Token::Bare(word))
     }
+}
 
-    if tokens.is_empty() {
-        return Ok(None);
+/// Whether `word` can name an argument: a letter or `_`, then letters,
+/// digits, `_` or `-`.
+fn is_arg_name(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Strip the layout of a `"""` string: a newline straight after the opening
+/// quotes, the whitespace-only line holding the closing quotes, and the
+/// indentation common to every non-blank line.
+fn dedent(raw: &str) -> String {
+    let body = raw.strip_prefix('\n').unwrap_or(raw);
+    let body = match body.rfind('\n') {
+        Some(i) if body[i + 1..].trim().is_empty() => &body[..i],
+        _ => body,
+    };
+    let indent = body
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start_matches([' ', '\t']).len())
+        .min()
+        .unwrap_or(0);
+    body.split('\n')
+        .map(|l| l.get(indent..).unwrap_or("").trim_end_matches('\r'))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Resolve the escape sequences allowed in quoted strings: `\"`, `\\`,
+/// `\n`, `\t`, `\r`, `\0`, `\u{XXXX}` and the literal braces `\{` / `\}`.
+/// Unknown escapes are kept as written.  Also returns the byte offsets of the
+/// escaped braces.
+pub(crate) fn resolve_escapes(raw: &str) -> Result<(String, Vec<usize>)> {
+    let mut s = String::new();
+    let mut braces = Vec::new();
+    let mut chars = raw.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            s.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('"') => s.push('"'),
+            Some('n') => s.push('\n'),
+            Some('t') => s.push('\t'),
+            Some('r') => s.push('\r'),
+            Some('0') => s.push('\0'),
+            Some('\\') => s.push('\\'),
+            Some(brace @ ('{' | '}')) => {
+                braces.push(s.len());
+                s.push(brace);
+            }
+            Some('u') => s.push(unicode_escape(&mut chars)?),
+            Some(nc) => {
+                s.push('\\');
+                s.push(nc);
+            }
+            None => s.push('\\'),
+        }
     }
+    Ok((s, braces))
+}
 
-    Ok(Some(Line { indent, tokens }))
+/// Read the `{XXXX}` after `\u` and return the character it names.
+fn unicode_escape(chars: &mut std::str::Chars) -> Result<char> {
+    let invalid = |hex: &str| BuclError::ParseError(format!("invalid unicode escape '\\u{}'", hex));
+    let rest = chars.as_str();
+    let Some(end) = rest.strip_prefix('{').and_then(|r| r.find('}')) else {
+        return Err(invalid(""));
+    };
+    let hex = &rest[1..end + 1];
+    let c = u32::from_str_radix(hex, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or_else(|| invalid(&format!("{{{}}}", hex)))?;
+    chars.nth(end + 1);
+    Ok(c)
+}
+
+/// True when `line` opens a `"""` string that it doesn't close, so the
+/// following source lines belong to the same statement.
+fn opens_multiline(line: &str) -> bool {
+    let line = line.trim_start();
+    if line.starts_with('#') {
+        return false;
+    }
+    let mut chars = line.chars().peekable();
+    let mut in_quote = false;
+    let mut in_triple = false;
+    let mut prev = ' ';
+    while let Some(c) = chars.next() {
+        let raw = prev.is_whitespace() && c == 'r' && chars.peek() == Some(&'"');
+        let comment = prev.is_whitespace() && c == '#';
+        prev = c;
+        match c {
+            _ if comment && !in_quote && !in_triple => break,
+            _ if raw && !in_quote && !in_triple => {
+                chars.next();
+                chars.by_ref().find(|&ch| ch == '"');
+            }
+            '\\' if in_quote || in_triple => {
+                chars.next();
+            }
+            '"' if !in_quote && chars.clone().take(2).eq("\"\"".chars()) => {
+                chars.nth(1);
+                in_triple = !in_triple;
+            }
+            '"' if !in_triple => in_quote = !in_quote,
+            _ => {}
+        }
+    }
+    in_triple
 }
 
 /// Tokenize an entire BUCL source string into a sequence of lines.
+///
+/// A line that opens a `"""` string is joined with the following lines up to
+/// the one that closes it; the statement keeps the first line's number.
+/// Every line that fails to tokenize is reported, not just the first.
 pub fn tokenize(source: &str) -> Result<Vec<Line>> {
+    let (lines, errors) = tokenize_recovering(source);
+    if errors.is_empty() {
+        Ok(lines)
+    } else {
+        Err(BuclError::parse_errors(errors))
+    }
+}
+
+/// [`tokenize`], carrying on past bad lines.  Each one is reported in the
+/// returned errors and stands in the lines as a `Line` with no tokens, so
+/// the parser still sees the indentation structure around it.
+pub(crate) fn tokenize_recovering(source: &str) -> (Vec<Line>, Vec<BuclError>) {
     let mut lines = Vec::new();
-    for (lineno, raw) in source.lines().enumerate() {
-        match tokenize_line(raw) {
-            Ok(Some(line)) => lines.push(line),
-            Ok(None) => {}
-            Err(BuclError::ParseError(msg)) => {
-                return Err(BuclError::ParseError(format!("line {}: {}", lineno + 1, msg)));
+    let mut errors = Vec::new();
+    let mut raw_lines = source.lines().enumerate();
+    while let Some((lineno, raw)) = raw_lines.next() {
+        let mut joined = raw.to_string();
+        if opens_multiline(raw) {
+            for (_, next) in raw_lines.by_ref() {
+                joined.push('\n');
+                joined.push_str(next);
+                if next.contains("\"\"\"") {
+                    break;
+                }
+            }
+        }
+        match lex_line(&joined) {
+            Ok(statements) => {
+                for mut line in statements {
+                    line.lineno = lineno + 1;
+                    lines.push(line);
+                }
+            }
+            Err((column, e)) => {
+                let msg = match e {
+                    BuclError::ParseError(msg) => msg,
+                    other => other.to_string(),
+                };
+                errors.push(BuclError::parse_at(source, lineno + 1, column, &msg));
+                let indent_text = &raw[..raw.len() - raw.trim_start_matches([' ', '\t']).len()];
+                lines.push(Line {
+                    lineno: lineno + 1,
+                    indent: indent_text.len(),
+                    indent_text: indent_text.to_string(),
+                    tokens: Vec::new(),
+                    columns: Vec::new(),
+                });
             }
-            Err(e) => return Err(e),
         }
     }
-    Ok(lines)
+    (lines, errors)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quoted(source: &str) -> Vec<String> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .flat_map(|line| line.tokens)
+            .filter_map(|t| match t {
+                Token::Quoted(s, _) => Some(s),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_triple_quoted_strings() {
+        let source = "{sql} = \"\"\"\n    SELECT *\n\n      FROM \"t\"\\n\n    \"\"\"\necho \"\"\"a \"b\" c\"\"\" \"d\"\n";
+        assert_eq!(quoted(source), vec!["SELECT *\n\n  FROM \"t\"\n", "a \"b\" c", "d"]);
+
+        let lines = tokenize(source).unwrap();
+        assert_eq!((lines[0].lineno, lines[1].lineno), (1, 6));
+        assert!(tokenize("echo \"\"\"\nnever closed\n").is_err());
+    }
+
+    #[test]
+    fn test_raw_strings() {
+        let line = tokenize_line(r##"echo r"\d+ {x} \n" r"" """x""""##).unwrap().remove(0);
+        let raw: Vec<&str> = line
+            .tokens
+            .iter()
+            .filter_map(|t| match t {
+                Token::Raw(s) => Some(s.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(raw, vec![r"\d+ {x} \n", ""]);
+        assert!(!opens_multiline(r##"echo r"\" """x""""##));
+    }
+
+    #[test]
+    fn test_named_arguments() {
+        let line = tokenize_line(r#"echo {a} sep:", " end:"" to:{dir/x} raw:r"\d" x:y 12:"a b""#)
+            .unwrap()
+            .remove(0);
+        let named: Vec<String> = line
+            .tokens
+            .iter()
+            .map(|t| match t {
+                Token::Named(name, value) => format!("{}={:?}", name, value),
+                other => format!("{:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            named,
+            vec![
+                r#"Bare("echo")"#,
+                r#"Variable("a")"#,
+                r#"sep=Quoted(", ", [])"#,
+                r#"end=Quoted("", [])"#,
+                r#"to=Variable("dir/x")"#,
+                r#"raw=Raw("\\d")"#,
+                r#"Bare("x:y")"#,
+                r#"Bare("12:\"a b\"")"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_semicolons_separate_statements() {
+        let lines = tokenize("{x} = \"a;b\"; echo {x};echo x;y hi;\n  ; ;\n").unwrap();
+        let words: Vec<Vec<String>> = lines
+            .iter()
+            .map(|line| {
+                line.tokens
+                    .iter()
+                    .map(|t| match t {
+                        Token::Variable(v) => format!("{{{}}}", v),
+                        Token::Quoted(s, _) | Token::Raw(s) | Token::Bare(s) => s.clone(),
+                        Token::Named(name, _) => format!("{}:", name),
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            words,
+            vec![vec!["{x}", "=", "a;b"], vec!["echo", "{x}"], vec!["echo", "x;y", "hi"]]
+        );
+        assert!(lines.iter().all(|l| l.lineno == 1 && l.indent == 0));
+        assert_eq!(lines[1].columns, vec![14, 19]);
+    }
+
+    #[test]
+    fn test_shebang_line_is_skipped() {
+        let lines = tokenize("#!/usr/bin/env bucl\necho hi\n").unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].lineno, 2);
+    }
+
+    #[test]
+    fn test_trailing_comments() {
+        let line = tokenize_line(r#"{x} = "a # b" 5 # note "x""#).unwrap().remove(0);
+        assert_eq!(line.tokens.len(), 4);
+        assert!(matches!(&line.tokens[2], Token::Quoted(s, _) if s == "a # b"));
+        assert!(!opens_multiline(r#"echo 1 # not a """ string"#));
+        assert!(tokenize_line("   # only a comment").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_escape_sequences() {
+        let (s, braces) = resolve_escapes(r"a\r\0\u{1F600}\u{e9} \{x\} \q\\").unwrap();
+        assert_eq!(s, "a\r\0\u{1F600}\u{e9} {x} \\q\\");
+        assert_eq!(braces, vec![10, 12]);
+        assert!(resolve_escapes(r"\u{110000}").is_err());
+        assert!(resolve_escapes(r"\u{zz}").is_err());
+        assert!(resolve_escapes(r"\u1F600").is_err());
+    }
 }
@@ -1,32 +1,98 @@
-/// WASM library entry point for BUCL.
-///
-/// Exposes three C-ABI functions that JavaScript can call directly after
-/// instantiating the `.wasm` module:
+//! WASM library entry point for BUCL.
+//!
+//! Exposes these C-ABI functions that JavaScript can call directly after
+//! instantiating the `.wasm` module:
+//!
+//! | Function | Description |
+//! |---|---|
+//! | `bucl_alloc(size) -> *mut u8` | Allocate `size` bytes; JS writes source here |
+//! | `bucl_free(ptr, size)` | Free a buffer previously returned by this module |
+//! | `bucl_run(src_ptr, src_len) -> *mut u8` | Run BUCL; returns `[u32-le len][utf-8 bytes]` |
+//! | `bucl_run_v2(src_ptr, src_len) -> *mut u8` | Run BUCL; returns status, output, and error as separate sections |
+//! | `bucl_run_streaming(src_ptr, src_len) -> *mut u8` | Run BUCL, sending each output line to `js_emit`; returns only the error |
+//! | `bucl_parse(src_ptr, src_len) -> *mut u8` | Syntax-check only; returns `ok` or the parse error with its line |
+//!
+//! The standard library BUCL functions (`strpos`, `substr`, `explode`,
+//! `implode`, `tohex`, `urlencode`) are embedded at compile time via
+//! `include_str!` so they are available without a filesystem, and parsed
+//! once per process rather than once per run.  Builds without the `text`
+//! feature embed `reverse`, `maxlength` and `slice` the same way, in place
+//! of their Rust versions.
+//!
+//! On WASM the `random` function needs a `js_math_random` import, the
+//! `sleep` function needs a `js_sleep` import, `jscall` needs a `js_call`
+//! import, `readfile`/`writefile` need `js_read_file`/`js_write_file` imports
+//! (see `fs::HostFs`), and `bucl_run_streaming` needs a `js_emit` import
+//! from the host.  Every run also polls the
+//! imported `js_should_cancel() -> i32` before each statement; returning
+//! non-zero stops the script with "Execution cancelled" (see
+//! `docs/demo/wasm/worker.js` for the JS glue).
+//!
+//! The same functions are exported from the native `cdylib`, so C, C++ or
+//! Go programs can link the library directly; `capi.rs` adds
+//! `bucl_run_with` for runs with a base directory and sandbox, and
+//! `include/bucl.h` declares it all.
+//!
+//! With the `wasm-bindgen` feature the crate additionally exports a
+//! string-based API (`run`, `runDetailed`, `checkSyntax`; see `bindgen.rs`),
+//! and the host hooks above are imported from `js/bucl-host.js` instead.
+//! The `python` and `napi` features build the same library as a Python
+//! extension module or a Node.js addon (see `python.rs` and `node.rs`).
+
+/// Embed `.bucl` function files into the binary at compile time.
 ///
-/// | Function | Description |
-/// |---|---|
-/// | `bucl_alloc(size) -> *mut u8` | Allocate `size` bytes; JS writes source here |
-/// | `bucl_free(ptr, size)` | Free a buffer previously returned by this module |
-/// | `bucl_run(src_ptr, src_len) -> *mut u8` | Run BUCL; returns `[u32-le len][utf-8 bytes]` |
+/// Each `"name" => "path"` pair is read with `include_str!` (so `path` is
+/// relative to the file containing the macro call) and registered with
+/// [`Evaluator::embed_function`](evaluator::Evaluator::embed_function).
+/// Scripts can then call `name` without any filesystem lookup — the same
+/// mechanism [`embed_stdlib`] uses for the standard library.
 ///
-/// The standard library BUCL functions (`strpos`, `substr`, `reverse`,
-/// `explode`, `implode`, `maxlength`, `slice`, `tohex`, `urlencode`) are
-/// embedded at compile time via `include_str!` so they are available without
-/// a filesystem.
+/// ```ignore
+/// use bucl_wasm::{embed_bucl, evaluator::Evaluator, functions};
 ///
-/// On WASM the `random` function needs a `js_math_random` import and the
-/// `sleep` function needs a `js_sleep` import from the host
-/// (see `demo/index.html` for the JS glue).
+/// let mut eval = Evaluator::new();
+/// functions::register_all(&mut eval);
+/// embed_bucl!(eval, {
+///     "greet"  => "../bucl/greet.bucl",
+///     "report" => "../bucl/report.bucl",
+/// });
+/// ```
+#[macro_export]
+macro_rules! embed_bucl {
+    ($eval:expr, { $($name:literal => $path:literal),* $(,)? }) => {{
+        $( $eval.embed_function($name, include_str!($path)); )*
+    }};
+}
+
+pub mod ast;
+pub mod error;
+pub mod evaluator;
+pub mod fs;
+pub mod functions;
+pub mod lexer;
+pub mod parser;
+pub mod permissions;
+pub mod snapshot;
+pub mod trace_graph;
 
-mod ast;
-mod error;
-mod evaluator;
-mod functions;
-mod lexer;
-mod parser;
+#[cfg(feature = "wasm-bindgen")]
+pub mod bindgen;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod capi;
+#[cfg(all(feature = "napi", not(target_arch = "wasm32")))]
+pub mod node;
+#[cfg(all(feature = "python", not(target_arch = "wasm32")))]
+pub mod python;
+#[cfg(feature = "signing")]
+pub mod signing;
+#[cfg(target_arch = "wasm32")]
+mod host;
 
 use std::alloc::{alloc, dealloc, Layout};
+#[cfg(feature = "stdlib")]
+use std::sync::{Arc, OnceLock};
 
+use error::BuclError;
 use evaluator::Evaluator;
 
 // ---------------------------------------------------------------------------
@@ -41,9 +107,15 @@ pub extern "C" fn bucl_alloc(size: usize) -> *mut u8 {
     unsafe { alloc(layout) }
 }
 
-/// Free a buffer previously returned by `bucl_alloc` or `bucl_run`.
+/// Free a buffer previously returned by `bucl_alloc` or one of the
+/// `bucl_run*` functions.
+///
+/// # Safety
+/// `ptr` must have been returned by `bucl_alloc(size)` or one of the run
+/// functions (with the matching total size) and must not be used after
+/// this call.
 #[no_mangle]
-pub extern "C" fn bucl_free(ptr: *mut u8, size: usize) {
+pub unsafe extern "C" fn bucl_free(ptr: *mut u8, size: usize) {
     if ptr.is_null() || size == 0 {
         return;
     }
@@ -51,72 +123,364 @@ pub extern "C" fn bucl_free(ptr: *mut u8, size: usize) {
     unsafe { dealloc(ptr, layout) };
 }
 
-/// Run a BUCL script.
+/// Run a BUCL script and return its buffered output.
 ///
 /// * `src_ptr` — pointer to UTF-8 encoded source (allocated by `bucl_alloc`).
 /// * `src_len` — byte length of the source.
 ///
 /// Returns a pointer to a buffer with layout:
-/// ```
+/// ```text
 /// [4 bytes little-endian u32 = output_len][output_len bytes of UTF-8]
 /// ```
-/// The caller must free the returned pointer with `bucl_free(ptr, 4 + output_len)`.
+/// Output is only returned, never printed.  The caller must free the
+/// returned pointer with `bucl_free(ptr, 4 + output_len)`.
+///
+/// # Safety
+/// `src_ptr` must point to `src_len` readable bytes.
 #[no_mangle]
-pub extern "C" fn bucl_run(src_ptr: *const u8, src_len: usize) -> *mut u8 {
-    let source = unsafe {
-        let slice = std::slice::from_raw_parts(src_ptr, src_len);
-        std::str::from_utf8(slice).unwrap_or("")
+pub unsafe extern "C" fn bucl_run(src_ptr: *const u8, src_len: usize) -> *mut u8 {
+    let source = unsafe { read_source(src_ptr, src_len) };
+    let mut eval = new_evaluator();
+    let output = match execute(&mut eval, source) {
+        Ok(()) => eval.output_buffer.join("\n"),
+        Err(failure) => failure.legacy_message(),
     };
+    encode_output(&output)
+}
 
-    let output = run_internal(source);
-    let out_bytes = output.as_bytes();
-    let total = 4 + out_bytes.len();
+/// Run status reported by [`bucl_run_v2`]: the script finished normally.
+pub const BUCL_STATUS_OK: u32 = 0;
+/// Run status reported by [`bucl_run_v2`]: the source failed to parse.
+pub const BUCL_STATUS_PARSE_ERROR: u32 = 1;
+/// Run status reported by [`bucl_run_v2`]: the script stopped with a runtime error.
+pub const BUCL_STATUS_RUNTIME_ERROR: u32 = 2;
+/// Run status reported by [`bucl_run_v2`]: the host cancelled the run.
+pub const BUCL_STATUS_CANCELLED: u32 = 3;
+/// Run status reported by [`bucl_run_v2`]: the script called `exit` with a
+/// non-zero code, which the error section holds in decimal.  (`exit 0`
+/// reports [`BUCL_STATUS_OK`].)
+pub const BUCL_STATUS_EXIT: u32 = 4;
 
-    let layout = Layout::from_size_align(total, 1).expect("invalid layout");
-    let ptr = unsafe { alloc(layout) };
+/// Run a BUCL script and return a structured result that keeps output and
+/// errors apart.
+///
+/// Returns a pointer to a buffer with layout (all integers u32 little-endian):
+/// ```text
+/// [status][output_len][error_len][output_len bytes of UTF-8][error_len bytes of UTF-8]
+/// ```
+/// `status` is one of the `BUCL_STATUS_*` constants.  The output section
+/// holds every line produced before the run ended — including before an
+/// error — and the error section is empty on success, or holds the exit code
+/// for [`BUCL_STATUS_EXIT`].  Output is only returned, never printed.
+///
+/// The caller must free the returned pointer with
+/// `bucl_free(ptr, 12 + output_len + error_len)`.
+///
+/// # Safety
+/// `src_ptr` must point to `src_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bucl_run_v2(src_ptr: *const u8, src_len: usize) -> *mut u8 {
+    let source = unsafe { read_source(src_ptr, src_len) };
+    let mut eval = new_evaluator();
+    let (status, error) = match execute(&mut eval, source) {
+        Ok(()) => (BUCL_STATUS_OK, String::new()),
+        Err(failure) => (failure.status, failure.detail()),
+    };
+    encode_result(status, &eval.output_buffer.join("\n"), &error)
+}
 
-    let len_bytes = (out_bytes.len() as u32).to_le_bytes();
-    unsafe {
-        std::ptr::copy_nonoverlapping(len_bytes.as_ptr(), ptr, 4);
-        std::ptr::copy_nonoverlapping(out_bytes.as_ptr(), ptr.add(4), out_bytes.len());
+/// Run a BUCL script, streaming each output line to the host as it is
+/// produced instead of returning it at the end.
+///
+/// On WASM every line is passed to the imported `js_emit(ptr, len)`; on
+/// native targets lines go to stdout.  The returned buffer has the same
+/// layout as [`bucl_run`] but holds only the error message (empty on
+/// success), since the output has already been delivered.
+///
+/// # Safety
+/// `src_ptr` must point to `src_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bucl_run_streaming(src_ptr: *const u8, src_len: usize) -> *mut u8 {
+    let source = unsafe { read_source(src_ptr, src_len) };
+    let mut eval = new_evaluator();
+    #[cfg(target_arch = "wasm32")]
+    {
+        eval.output_sink = Some(std::sync::Arc::new(host::emit));
+    }
+    // Without a sink, native evaluators print each line to stdout.
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        eval.output_sink = None;
     }
+    let error = match execute(&mut eval, source) {
+        Ok(()) => String::new(),
+        Err(failure) => failure.legacy_message(),
+    };
+    encode_output(&error)
+}
 
-    ptr
+/// Check a BUCL script for syntax errors without running it.
+///
+/// Returns a `[u32-le len][utf-8 bytes]` buffer (like `bucl_run`) holding
+/// `ok` when the source parses, or the parse error — e.g.
+/// `Parse error: line 3: expected function name after '{x}'` — otherwise.
+/// Nothing is evaluated, so it is cheap enough to call on every keystroke.
+///
+/// The caller must free the returned pointer with `bucl_free(ptr, 4 + len)`.
+///
+/// # Safety
+/// `src_ptr` must point to `src_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bucl_parse(src_ptr: *const u8, src_len: usize) -> *mut u8 {
+    let source = unsafe { read_source(src_ptr, src_len) };
+    match parser::parse(source) {
+        Ok(_) => encode_output("ok"),
+        Err(e) => encode_output(&e.to_string()),
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Internal engine
 // ---------------------------------------------------------------------------
 
-fn run_internal(source: &str) -> String {
-    let mut eval = Evaluator::new();
-    embed_stdlib(&mut eval);
-    functions::register_all(&mut eval);
+/// Borrow the UTF-8 source passed in by the host (invalid UTF-8 → empty).
+unsafe fn read_source<'a>(src_ptr: *const u8, src_len: usize) -> &'a str {
+    let slice = unsafe { std::slice::from_raw_parts(src_ptr, src_len) };
+    std::str::from_utf8(slice).unwrap_or("")
+}
+
+/// Encode `output` as a `[u32-le len][utf-8 bytes]` host buffer.
+fn encode_output(output: &str) -> *mut u8 {
+    let mut buf = Vec::with_capacity(4 + output.len());
+    buf.extend_from_slice(&(output.len() as u32).to_le_bytes());
+    buf.extend_from_slice(output.as_bytes());
+    into_host_buffer(&buf)
+}
 
-    match parser::parse(source) {
-        Ok(stmts) => match eval.evaluate_statements(&stmts) {
-            Ok(()) => eval.output_buffer.join("\n"),
-            Err(e) => format!("[error] {}", e),
-        },
-        Err(e) => format!("[parse error] {}", e),
+/// Encode a `[status][output_len][error_len][output][error]` host buffer,
+/// as returned by `bucl_run_v2`.
+fn encode_result(status: u32, output: &str, error: &str) -> *mut u8 {
+    let mut buf = Vec::with_capacity(12 + output.len() + error.len());
+    buf.extend_from_slice(&status.to_le_bytes());
+    buf.extend_from_slice(&(output.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&(error.len() as u32).to_le_bytes());
+    buf.extend_from_slice(output.as_bytes());
+    buf.extend_from_slice(error.as_bytes());
+    into_host_buffer(&buf)
+}
+
+/// Copy `bytes` into a freshly allocated buffer the host frees with `bucl_free`.
+fn into_host_buffer(bytes: &[u8]) -> *mut u8 {
+    let layout = Layout::from_size_align(bytes.len(), 1).expect("invalid layout");
+    let ptr = unsafe { alloc(layout) };
+    unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len()) };
+    ptr
+}
+
+/// Build an evaluator with the embedded stdlib and every built-in registered.
+/// Output is only collected in `output_buffer`, never printed, since the
+/// buffered exports hand it back to the host.  On WASM the run can be
+/// cancelled by the host via `js_should_cancel`.
+fn new_evaluator() -> Evaluator {
+    let builder = Evaluator::builder()
+        .with_stdlib()
+        .output_sink(std::sync::Arc::new(|_: &str| {}));
+    #[cfg(target_arch = "wasm32")]
+    let builder = builder.cancel_check(std::sync::Arc::new(host::should_cancel));
+    builder.build()
+}
+
+/// Why a run did not finish normally.
+struct Failure {
+    /// One of the `BUCL_STATUS_*` constants.
+    status: u32,
+    error: BuclError,
+}
+
+impl Failure {
+    /// The error section of a structured result: the message, or the code
+    /// for [`BUCL_STATUS_EXIT`].
+    fn detail(&self) -> String {
+        match self.error {
+            BuclError::Exit(code) => code.to_string(),
+            _ => self.error.to_string(),
+        }
     }
+
+    /// The single-string form used by `bucl_run` and `bucl_run_streaming`.
+    fn legacy_message(&self) -> String {
+        if self.status == BUCL_STATUS_PARSE_ERROR {
+            format!("[parse error] {}", self.error)
+        } else {
+            format!("[error] {}", self.error)
+        }
+    }
+}
+
+/// Parse and run `source`.
+fn execute(eval: &mut Evaluator, source: &str) -> Result<(), Failure> {
+    let stmts = parser::parse(source).map_err(|error| Failure {
+        status: BUCL_STATUS_PARSE_ERROR,
+        error,
+    })?;
+    let result = eval.evaluate_statements(&stmts);
+    eval.flush_output();
+    match result {
+        Err(BuclError::Exit(0)) => Ok(()),
+        result => result.map_err(|error| Failure {
+            status: match error {
+                BuclError::Cancelled => BUCL_STATUS_CANCELLED,
+                BuclError::Exit(_) => BUCL_STATUS_EXIT,
+                _ => BUCL_STATUS_RUNTIME_ERROR,
+            },
+            error,
+        }),
+    }
+}
+
+/// The standard library's `.bucl` sources, by function name.  `reverse`,
+/// `maxlength` and `slice` are builtins with the `text` feature.
+#[cfg(feature = "stdlib")]
+const STDLIB: &[(&str, &str)] = &[
+    ("substr", include_str!("../functions/substr.bucl")),
+    ("strpos", include_str!("../functions/strpos.bucl")),
+    ("explode", include_str!("../functions/explode.bucl")),
+    ("implode", include_str!("../functions/implode.bucl")),
+    ("tohex", include_str!("../functions/tohex.bucl")),
+    ("urlencode", include_str!("../functions/urlencode.bucl")),
+    #[cfg(not(feature = "text"))]
+    ("reverse", include_str!("../functions/reverse.bucl")),
+    #[cfg(not(feature = "text"))]
+    ("maxlength", include_str!("../functions/maxlength.bucl")),
+    #[cfg(not(feature = "text"))]
+    ("slice", include_str!("../functions/slice.bucl")),
+];
+
+/// Name, source and statements of a standard library function.
+#[cfg(feature = "stdlib")]
+type ParsedStdlibFunction = (&'static str, &'static str, Arc<Vec<ast::Statement>>);
+
+/// [`STDLIB`], parsed the first time an evaluator embeds it.  Every later
+/// evaluator shares these statements, so a fresh evaluator per run (as the
+/// WASM exports use) doesn't re-parse `substr`, `strpos`, … on its first
+/// call to them.
+#[cfg(feature = "stdlib")]
+fn parsed_stdlib() -> &'static [ParsedStdlibFunction] {
+    static PARSED: OnceLock<Vec<ParsedStdlibFunction>> = OnceLock::new();
+    PARSED.get_or_init(|| {
+        STDLIB
+            .iter()
+            .map(|&(name, source)| {
+                let stmts = parser::parse(source)
+                    .unwrap_or_else(|e| panic!("functions/{}.bucl: {}", name, e));
+                (name, source, Arc::new(stmts))
+            })
+            .collect()
+    })
 }
 
-/// Pre-load the standard BUCL library into the evaluator so they are
+/// Pre-load the standard BUCL library into the evaluator so it is
 /// available without a filesystem (essential for WASM builds).
-fn embed_stdlib(eval: &mut Evaluator) {
-    let stdlib: &[(&str, &str)] = &[
-        ("substr",    include_str!("../functions/substr.bucl")),
-        ("strpos",    include_str!("../functions/strpos.bucl")),
-        ("reverse",   include_str!("../functions/reverse.bucl")),
-        ("explode",   include_str!("../functions/explode.bucl")),
-        ("implode",   include_str!("../functions/implode.bucl")),
-        ("maxlength", include_str!("../functions/maxlength.bucl")),
-        ("slice",     include_str!("../functions/slice.bucl")),
-        ("tohex",     include_str!("../functions/tohex.bucl")),
-        ("urlencode", include_str!("../functions/urlencode.bucl")),
-    ];
-    for (name, src) in stdlib {
-        eval.embedded_functions.insert(name.to_string(), src.to_string());
+///
+/// Native embedders can call this too, so scripts find the standard
+/// library without a `functions/` directory next to them.  The functions
+/// are parsed once per process and shared by every evaluator.
+///
+/// Embeds nothing when built without the `stdlib` feature, which the
+/// library's `.bucl` sources need `math` for.
+pub fn embed_stdlib(eval: &mut Evaluator) {
+    #[cfg(feature = "stdlib")]
+    for (name, source, stmts) in parsed_stdlib() {
+        eval.embed_parsed_function(name, source, stmts.clone());
+    }
+    let _ = eval; // suppress unused warning without the feature
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `source` through `bucl_run_v2` and decode the result buffer.
+    fn run_v2(source: &str) -> (u32, String, String) {
+        let word =
+            |buf: &[u8], i: usize| u32::from_le_bytes(buf[i * 4..i * 4 + 4].try_into().unwrap());
+        unsafe {
+            let ptr = bucl_run_v2(source.as_ptr(), source.len());
+            let header = std::slice::from_raw_parts(ptr, 12);
+            let (status, out_len, err_len) =
+                (word(header, 0), word(header, 1) as usize, word(header, 2) as usize);
+            let body = std::slice::from_raw_parts(ptr.add(12), out_len + err_len);
+            let output = String::from_utf8(body[..out_len].to_vec()).unwrap();
+            let error = String::from_utf8(body[out_len..].to_vec()).unwrap();
+            bucl_free(ptr, 12 + out_len + err_len);
+            (status, output, error)
+        }
+    }
+
+    #[test]
+    fn test_parse_reports_line_of_error() {
+        let check = |source: &str| unsafe {
+            let ptr = bucl_parse(source.as_ptr(), source.len());
+            let len = u32::from_le_bytes(std::slice::from_raw_parts(ptr, 4).try_into().unwrap());
+            let text = String::from_utf8(
+                std::slice::from_raw_parts(ptr.add(4), len as usize).to_vec(),
+            )
+            .unwrap();
+            bucl_free(ptr, 4 + len as usize);
+            text
+        };
+        assert_eq!(check("echo hi\nnosuchfunction\n"), "ok");
+        assert_eq!(
+            check("echo hi\n\n{x}\n"),
+            "Parse error: line 3: expected function name after '{x}'\n 3 | {x}\n   |    ^"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "math")]
+    fn test_embed_bucl_registers_functions() {
+        let mut eval = Evaluator::new();
+        functions::register_all(&mut eval);
+        embed_bucl!(eval, { "backwards" => "../functions/reverse.bucl" });
+        let stmts = parser::parse("{r} backwards \"abc\"\n").unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("r"), "cba");
+    }
+
+    #[test]
+    #[cfg(feature = "stdlib")]
+    fn test_stdlib_has_text_functions_with_or_without_text_feature() {
+        let mut eval = Evaluator::new();
+        functions::register_all(&mut eval);
+        embed_stdlib(&mut eval);
+        let stmts = parser::parse(concat!(
+            "{r} reverse \"abc\"\n",
+            "{m} maxlength \"a\" \"abc\" \"ab\"\n",
+            "{s} slice 1 3 \"a\" \"b\" \"c\" \"d\"\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+        assert_eq!(eval.resolve_var("r"), "cba");
+        assert_eq!(eval.resolve_var("m"), "3");
+        assert_eq!(eval.resolve_var("s"), "b c");
+    }
+
+    #[test]
+    fn test_run_v2_separates_output_and_error() {
+        assert_eq!(
+            run_v2("echo hi\n"),
+            (BUCL_STATUS_OK, "hi".to_string(), String::new())
+        );
+
+        let (status, output, error) = run_v2("echo before\nnosuchfunction\n");
+        assert_eq!(status, BUCL_STATUS_RUNTIME_ERROR);
+        assert_eq!(output, "before");
+        assert!(error.contains("nosuchfunction"), "{}", error);
+
+        assert_eq!(
+            run_v2("echo bye\nexit 3\necho unreachable\n"),
+            (BUCL_STATUS_EXIT, "bye".to_string(), "3".to_string())
+        );
+        assert_eq!(run_v2("exit 0\n").0, BUCL_STATUS_OK);
     }
 }
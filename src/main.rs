@@ -1,21 +1,323 @@
-mod ast;
-mod error;
-mod evaluator;
-mod functions;
-mod lexer;
-mod parser;
+mod cli;
 
 use std::env;
 use std::fs;
-use std::io::{self, Read};
-use std::path::PathBuf;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use bucl_wasm::ast::{Param, Statement, TemplatePart};
+use bucl_wasm::error::{BuclError, Result};
+use bucl_wasm::functions::log::LogLevel;
+use bucl_wasm::snapshot::{self, Snapshot, StateSnapshot};
+use bucl_wasm::trace_graph::TraceGraph;
+use bucl_wasm::permissions::{Access, Permissions};
+use bucl_wasm::evaluator::{Evaluator, FunctionCheck, OutputSink};
+use bucl_wasm::parser;
+
+/// Leading options that take a value.
+const VALUE_FLAGS: &[&str] = &[
+    "--verify",
+    "--redact",
+    "--diff-vars",
+    "--save-vars",
+    "--emit-trace-graph",
+    "--delimiters",
+    "--log-level",
+    "--var",
+    "--output",
+    "--output-file",
+    "--max-steps",
+    "--resume",
+    "-e",
+];
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    let (source, base_dir) = if args.len() > 1 {
-        let path = PathBuf::from(&args[1]);
-        let source = match fs::read_to_string(&path) {
+    // `bucl test [--snapshot] [--update] [dir]` — run every *_test.bucl file.
+    if args.get(1).map(String::as_str) == Some("test") {
+        std::process::exit(cli::test::main(&args[2..]));
+    }
+
+    // `bucl doc [--markdown] [name]` — document the callable functions.
+    if args.get(1).map(String::as_str) == Some("doc") {
+        std::process::exit(cli::doc::main(&args[2..]));
+    }
+
+    // `bucl functions` — list the callable functions and where they live.
+    if args.get(1).map(String::as_str) == Some("functions") {
+        std::process::exit(cli::functions::main(&args[2..]));
+    }
+
+    // `bucl serve [--host <addr>] [--port <n>] [--route <path>=<script>] [dir]`.
+    if args.get(1).map(String::as_str) == Some("serve") {
+        std::process::exit(cli::serve::main(&args[2..]));
+    }
+
+    // `bucl keygen <name>` / `bucl sign --key <file> [--detached] <script>`.
+    if let Some(cmd @ ("keygen" | "sign")) = args.get(1).map(String::as_str) {
+        #[cfg(feature = "signing")]
+        std::process::exit(if cmd == "keygen" {
+            cli::sign::keygen(&args[2..])
+        } else {
+            cli::sign::sign(&args[2..])
+        });
+        #[cfg(not(feature = "signing"))]
+        {
+            eprintln!("'bucl {}' requires a build with the `signing` feature", cmd);
+            std::process::exit(1);
+        }
+    }
+
+    // Leading options:
+    //   --verify <key.pub>   (repeatable) refuse unsigned or modified scripts
+    //   --report             write a crash report bundle if the run fails
+    //   --redact <name>      (repeatable) also redact matching variables in it
+    //   --diff-vars <file>   compare the final variables with a saved snapshot
+    //   --save-vars <file>   save the final variables as a snapshot
+    //   --emit-trace-graph <file>  write the executed call/block graph
+    //                        (Mermaid for .mmd/.mermaid, Graphviz DOT otherwise)
+    //   --delimiters "<open> <close>"|none  interpolation delimiters for
+    //                        files without a `# bucl-delimiters:` line
+    //   --log-level debug|info|warn|error  least severe `log` level shown
+    //   --var <name>=<value> (repeatable) preset a variable; also accepted
+    //                        after the script path
+    //   --output text|json   print output lines as they come (default), or
+    //                        one JSON result object at the end
+    //   --strict             reading a variable that was never set is an error
+    //   --stdin              read piped input into {stdin} even when the
+    //                        script doesn't name it directly
+    //   --max-steps <n>      fail the run after <n> statements
+    //   --resume <file>      start from the variables saved in <file> (if it
+    //                        exists) and save them there when the run ends
+    //   --quiet              don't print output lines (they are still
+    //                        collected, e.g. for --output json)
+    //   --output-file <file> append output lines to <file> instead of
+    //                        printing them
+    //   --sandbox            deny file and network access to built-ins
+    //                        (see bucl_wasm::permissions)
+    //   --allow-read[=<paths>], --allow-write[=<paths>]  (repeatable) sandbox,
+    //                        but allow reading / writing everywhere or only
+    //                        below the comma-separated paths
+    //   --allow-net[=<hosts>]  (repeatable) sandbox, but allow network
+    //                        connections to any host or only to the
+    //                        comma-separated host[:port]s
+    //   -e <code>            run <code> instead of a script file (statements
+    //                        may be separated by `;`); every word after it
+    //                        is a script argument
+    //   --watch              re-run the script whenever it or its functions/
+    //                        change (only --var, --delimiters, --log-level,
+    //                        --quiet, --output-file and the sandbox flags
+    //                        apply)
+    let mut verify_keys = Vec::new();
+    let mut report = cli::report::ReportOptions::default();
+    let mut diff_vars: Option<PathBuf> = None;
+    let mut save_vars: Option<PathBuf> = None;
+    let mut trace_graph: Option<PathBuf> = None;
+    let mut delimiters = parser::Delimiters::Braces;
+    let mut log_level = LogLevel::default();
+    let mut preset_vars = Vec::new();
+    let mut strict = false;
+    let mut read_stdin = false;
+    let mut max_steps: Option<u64> = None;
+    let mut json_output = false;
+    let mut resume: Option<PathBuf> = None;
+    let mut quiet = false;
+    let mut output_file: Option<PathBuf> = None;
+    let mut sandbox = false;
+    let mut allow_read: Option<Access> = None;
+    let mut allow_write: Option<Access> = None;
+    let mut allow_net: Option<Access> = None;
+    let mut watch = false;
+    let mut inline: Option<String> = None;
+    let mut rest = &args[1..];
+    loop {
+        match rest {
+            [flag, key, tail @ ..] if flag == "--verify" => {
+                verify_keys.push(key.clone());
+                rest = tail;
+            }
+            [flag, name, tail @ ..] if flag == "--redact" => {
+                report.redact.push(name.clone());
+                rest = tail;
+            }
+            [flag, file, tail @ ..] if flag == "--diff-vars" => {
+                diff_vars = Some(PathBuf::from(file));
+                rest = tail;
+            }
+            [flag, file, tail @ ..] if flag == "--save-vars" => {
+                save_vars = Some(PathBuf::from(file));
+                rest = tail;
+            }
+            [flag, file, tail @ ..] if flag == "--emit-trace-graph" => {
+                trace_graph = Some(PathBuf::from(file));
+                rest = tail;
+            }
+            [flag, spec, tail @ ..] if flag == "--delimiters" => {
+                delimiters = parser::Delimiters::from_spec(spec).unwrap_or_else(|e| {
+                    eprintln!("--delimiters: {}", e);
+                    std::process::exit(1);
+                });
+                rest = tail;
+            }
+            [flag, level, tail @ ..] if flag == "--log-level" => {
+                log_level = LogLevel::parse(level).unwrap_or_else(|| {
+                    eprintln!("--log-level: expected debug, info, warn or error, got '{}'", level);
+                    std::process::exit(1);
+                });
+                rest = tail;
+            }
+            [flag, format, tail @ ..] if flag == "--output" => {
+                json_output = match format.as_str() {
+                    "json" => true,
+                    "text" => false,
+                    _ => {
+                        eprintln!("--output: expected text or json, got '{}'", format);
+                        std::process::exit(1);
+                    }
+                };
+                rest = tail;
+            }
+            [flag, file, tail @ ..] if flag == "--output-file" => {
+                output_file = Some(PathBuf::from(file));
+                rest = tail;
+            }
+            [flag, tail @ ..] if flag == "--strict" => {
+                strict = true;
+                rest = tail;
+            }
+            [flag, tail @ ..] if flag == "--stdin" => {
+                read_stdin = true;
+                rest = tail;
+            }
+            [flag, n, tail @ ..] if flag == "--max-steps" => {
+                max_steps = Some(n.parse().unwrap_or_else(|_| {
+                    eprintln!("--max-steps: expected a number of statements, got '{}'", n);
+                    std::process::exit(1);
+                }));
+                rest = tail;
+            }
+            [flag, file, tail @ ..] if flag == "--resume" => {
+                resume = Some(PathBuf::from(file));
+                rest = tail;
+            }
+            [flag, tail @ ..] if flag == "--quiet" => {
+                quiet = true;
+                rest = tail;
+            }
+            [flag, pair, tail @ ..] if flag == "--var" => {
+                preset_vars.push(parse_var_flag(pair));
+                rest = tail;
+            }
+            [flag, tail @ ..] if flag == "--sandbox" => {
+                sandbox = true;
+                rest = tail;
+            }
+            [flag, tail @ ..]
+                if matches!(
+                    flag.split('=').next(),
+                    Some("--allow-read" | "--allow-write" | "--allow-net")
+                ) =>
+            {
+                let (name, spec) = match flag.split_once('=') {
+                    Some((name, spec)) => (name, Some(spec)),
+                    None => (flag.as_str(), None),
+                };
+                let list = spec.map(|list| list.split(',').filter(|p| !p.is_empty()));
+                let (access, granted) = match name {
+                    "--allow-net" => (&mut allow_net, list.map_or(Access::Allow, Access::hosts)),
+                    "--allow-read" => (&mut allow_read, list.map_or(Access::Allow, Access::within)),
+                    _ => (&mut allow_write, list.map_or(Access::Allow, Access::within)),
+                };
+                grant(access, granted);
+                rest = tail;
+            }
+            [flag, code, tail @ ..] if flag == "-e" => {
+                inline = Some(code.clone());
+                rest = tail;
+            }
+            [flag, tail @ ..] if flag == "--watch" => {
+                watch = true;
+                rest = tail;
+            }
+            [flag, tail @ ..] if flag == "--report" => {
+                report.enabled = true;
+                rest = tail;
+            }
+            [flag] if VALUE_FLAGS.contains(&flag.as_str()) => {
+                eprintln!("{} expects a value", flag);
+                std::process::exit(1);
+            }
+            _ => break,
+        }
+    }
+
+    // Words after the script path are the script's own arguments, so an
+    // executable script (`#!/usr/bin/env bucl`) sees `./tool.bucl a b` as
+    // {0} = a, {1} = b, like a `.bucl` function call.  Without any, none of
+    // those variables are set.  With -e there is no script path.
+    let (script, script_words) = match inline {
+        Some(_) => (None, rest),
+        None => (rest.first().map(PathBuf::from), rest.get(1..).unwrap_or_default()),
+    };
+    let script_args = take_trailing_vars(script_words, &mut preset_vars);
+    // Output lines always end up in output_buffer; a sink replaces printing
+    // them, to write them to a file or (quiet, JSON) drop them.
+    let output_sink: Option<OutputSink> = match &output_file {
+        Some(path) => Some(file_sink(path)),
+        None if quiet || json_output => Some(Arc::new(|_: &str| {})),
+        None => None,
+    };
+    let permissions = Permissions::from_flags(sandbox, allow_read, allow_write, allow_net);
+    let state = resume.as_deref().and_then(load_state);
+    let new_evaluator = |base_dir: Option<PathBuf>| {
+        let mut builder = Evaluator::builder()
+            .delimiters(delimiters.clone())
+            .log_level(log_level)
+            .permissions(permissions.clone())
+            .strict(strict);
+        if let Some(dir) = base_dir {
+            builder = builder.base_dir(dir);
+        }
+        if let Some(max) = max_steps {
+            builder = builder.max_steps(max);
+        }
+        if let Some(state) = &state {
+            builder = builder.state(state.clone());
+        }
+        if let Some(sink) = &output_sink {
+            builder = builder.output_sink(sink.clone());
+        }
+        if !script_args.is_empty() {
+            builder = builder.args(script_args.clone());
+        }
+        for (name, value) in &preset_vars {
+            builder = builder.var(name, value);
+        }
+        builder.build()
+    };
+
+    if watch {
+        let Some(script) = script.clone() else {
+            eprintln!("--watch needs a script file");
+            std::process::exit(1);
+        };
+        if !verify_keys.is_empty() || json_output || resume.is_some() {
+            eprintln!("--watch cannot be combined with --verify, --output json or --resume");
+            std::process::exit(1);
+        }
+        let base_dir = script
+            .canonicalize()
+            .ok()
+            .and_then(|p| p.parent().map(|d| d.to_path_buf()));
+        cli::watch::run(&script, || new_evaluator(base_dir.clone()));
+    }
+
+    let (source, base_dir) = if let Some(code) = &inline {
+        (code.clone(), None)
+    } else if let Some(path) = &script {
+        let source = match fs::read_to_string(path) {
             Ok(s) => s,
             Err(e) => {
                 eprintln!("Error reading '{}': {}", path.display(), e);
@@ -37,21 +339,380 @@ fn main() {
         }
         (buf, None)
     };
+    let (source, function_check) = if verify_keys.is_empty() {
+        (source, None)
+    } else {
+        let (body, check) = verify_source(&verify_keys, script.clone(), source);
+        (body, Some(check))
+    };
 
-    let mut eval = evaluator::Evaluator::new();
-    eval.base_dir = base_dir;
-    functions::register_all(&mut eval);
-
-    let stmts = match parser::parse(&source) {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("{}", e);
+    let mut eval = new_evaluator(base_dir);
+    eval.function_check = function_check;
+    // A script file (or -e code) run with piped input reads it as {stdin},
+    // so it can be used as a filter (`cat log | bucl count.bucl`).  Only a
+    // script that reads a {stdin…} variable, or is run with --stdin, waits
+    // for it: cron and CI often leave stdin open without ever closing it.
+    let wants_stdin = (script.is_some() || inline.is_some())
+        && (read_stdin
+            || parser::parse_with(&source, &eval.delimiters).is_ok_and(|stmts| reads_stdin(&stmts)));
+    if wants_stdin && !io::stdin().is_terminal() {
+        let mut input = String::new();
+        if let Err(e) = io::stdin().read_to_string(&mut input) {
+            eprintln!("Error reading stdin: {}", e);
             std::process::exit(1);
         }
+        // The lines are array items ({stdin/0}, …, {stdin/count}), so
+        // `each {stdin}` walks them; {stdin} itself is the text as read.
+        let lines: Vec<String> = input.lines().map(String::from).collect();
+        let count = lines.len();
+        eval.set_array("stdin", lines);
+        eval.set_var("stdin", input);
+        eval.set_var("stdin/count", count.to_string());
+        eval.set_var("stdin/lines", count.to_string());
+    }
+
+    let graph = trace_graph.as_ref().map(|_| Arc::new(TraceGraph::new()));
+    if let Some(graph) = &graph {
+        eval.observer = Some(graph.clone());
+    }
+
+    let result = if report.enabled {
+        cli::report::capture(&mut eval, script.as_deref(), &source, &report, |eval| {
+            execute(eval, &source)
+        })
+    } else {
+        execute(&mut eval, &source)
+    };
+    // Saved even when the run failed, so the next run resumes from there.
+    if let Some(path) = &resume {
+        save_state(path, &eval);
+    }
+    // Written even when the run failed: the graph shows how far it got.
+    if let (Some(path), Some(graph)) = (&trace_graph, &graph) {
+        let mermaid = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("mmd" | "mermaid")
+        );
+        let text = if mermaid { graph.to_mermaid() } else { graph.to_dot() };
+        if let Err(e) = fs::write(path, text) {
+            eprintln!("Error writing '{}': {}", path.display(), e);
+        }
+    }
+    // `exit N` ends the run early but cleanly; N becomes the exit status.
+    let (mut exit_code, mut error) = match result {
+        Ok(()) => (0, None),
+        Err(BuclError::Exit(code)) => (code, None),
+        Err(e) => (1, Some(e.to_string())),
     };
 
-    if let Err(e) = eval.evaluate_statements(&stmts) {
+    if error.is_none() {
+        let vars = Snapshot::capture(&eval);
+        let vars_differ = diff_vars.is_some_and(|path| report_var_diff(&path, &vars));
+        if let Some(path) = save_vars {
+            if let Err(e) = fs::write(&path, vars.to_text()) {
+                eprintln!("Error writing '{}': {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+        if vars_differ {
+            exit_code = 1;
+        } else if !eval.assert_failures.is_empty() {
+            // Failed assertions don't abort the run, but they do fail it.
+            let failures: Vec<String> =
+                eval.assert_failures.iter().map(ToString::to_string).collect();
+            error = Some(failures.join("\n"));
+            exit_code = 1;
+        }
+    }
+
+    if json_output {
+        let result = cli::json::RunResult {
+            output: &eval.output_buffer,
+            error,
+            exit_code,
+        };
+        println!("{}", result.to_json());
+    } else if let Some(error) = error {
+        eprintln!("{}", error);
+    }
+    std::process::exit(exit_code);
+}
+
+/// An output sink appending each line to `path`, exiting if the file can't
+/// be opened.  Lines are written unbuffered, so they survive `exit`.
+fn file_sink(path: &Path) -> OutputSink {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .unwrap_or_else(|e| {
+            eprintln!("Error opening '{}': {}", path.display(), e);
+            std::process::exit(1);
+        });
+    let path = path.to_path_buf();
+    Arc::new(move |line: &str| {
+        if let Err(e) = writeln!(&file, "{}", line) {
+            eprintln!("Error writing '{}': {}", path.display(), e);
+        }
+    })
+}
+
+/// Add an `--allow-read` / `--allow-write` / `--allow-net` grant to
+/// `access`: no value allows everything, `=a,b` adds those paths or hosts to
+/// the ones already allowed.
+fn grant(access: &mut Option<Access>, granted: Access) {
+    *access = Some(match (access.take(), granted) {
+        (Some(Access::Within(mut paths)), Access::Within(more)) => {
+            paths.extend(more);
+            Access::Within(paths)
+        }
+        (Some(Access::Hosts(mut hosts)), Access::Hosts(more)) => {
+            hosts.extend(more);
+            Access::Hosts(hosts)
+        }
+        (Some(Access::Allow), _) => Access::Allow,
+        (_, granted) => granted,
+    });
+}
+
+/// Split a `--var name=value` argument, exiting if it has no `=` or name.
+fn parse_var_flag(pair: &str) -> (String, String) {
+    split_var(pair).unwrap_or_else(|e| {
         eprintln!("{}", e);
         std::process::exit(1);
+    })
+}
+
+/// Split `name=value` at the first `=`, so the value may contain more.
+fn split_var(pair: &str) -> std::result::Result<(String, String), String> {
+    match pair.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("--var expects name=value, got '{}'", pair)),
+    }
+}
+
+/// Pull `--var name=value` pairs out of the words after the script path
+/// into `vars`; the rest are the script's arguments.  Everything after a
+/// `--` is an argument.
+fn take_trailing_vars(words: &[String], vars: &mut Vec<(String, String)>) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut words = words.iter();
+    while let Some(word) = words.next() {
+        match word.as_str() {
+            "--" => {
+                args.extend(words.cloned());
+                break;
+            }
+            "--var" => match words.next() {
+                Some(pair) => vars.push(parse_var_flag(pair)),
+                None => {
+                    eprintln!("--var expects a value");
+                    std::process::exit(1);
+                }
+            },
+            _ => args.push(word.clone()),
+        }
+    }
+    args
+}
+
+/// Print how `vars` differs from the snapshot saved at `path`; returns
+/// `true` if anything differs (or the snapshot can't be read).
+fn report_var_diff(path: &Path, vars: &Snapshot) -> bool {
+    let saved = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| Snapshot::parse(&text));
+    let saved = match saved {
+        Ok(saved) => saved,
+        Err(e) => {
+            eprintln!("Error reading snapshot '{}': {}", path.display(), e);
+            return true;
+        }
+    };
+    let changes = snapshot::diff(&saved, vars);
+    if changes.is_empty() {
+        return false;
+    }
+    eprintln!("Variables differ from {}:", path.display());
+    for change in &changes {
+        eprintln!("  {}", change);
+    }
+    true
+}
+
+/// Whether any statement reads `{stdin}` or one of its sub-variables.
+fn reads_stdin(stmts: &[Statement]) -> bool {
+    fn is_stdin(name: &str) -> bool {
+        name == "stdin" || name.starts_with("stdin/")
+    }
+    fn param_reads_stdin(param: &Param) -> bool {
+        match param {
+            Param::Variable(name) => is_stdin(name),
+            Param::Quoted(_, parts) => parts.iter().any(part_reads_stdin),
+            Param::Template(parts) => parts.iter().any(part_reads_stdin),
+            Param::Named(_, inner) => param_reads_stdin(inner),
+            Param::Bare(_) => false,
+        }
+    }
+    fn part_reads_stdin(part: &TemplatePart) -> bool {
+        matches!(part, TemplatePart::Var(name) if is_stdin(name))
+    }
+    stmts.iter().any(|stmt| {
+        stmt.args.iter().any(param_reads_stdin)
+            || stmt.block.as_deref().is_some_and(reads_stdin)
+            || stmt
+                .continuation
+                .as_deref()
+                .is_some_and(|next| reads_stdin(std::slice::from_ref(next)))
+    })
+}
+
+fn execute(eval: &mut Evaluator, source: &str) -> Result<()> {
+    let stmts = parser::parse_with(source, &eval.delimiters)?;
+    let result = eval.evaluate_statements(&stmts);
+    eval.flush_output();
+    result
+}
+
+/// Read the `--resume` state file; `None` if it doesn't exist yet.
+#[cfg(feature = "serde")]
+fn load_state(path: &Path) -> Option<StateSnapshot> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            eprintln!("Error reading '{}': {}", path.display(), e);
+            std::process::exit(1);
+        }
+    };
+    match StateSnapshot::from_json(&text) {
+        Ok(state) => Some(state),
+        Err(e) => {
+            eprintln!("--resume: '{}': {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Save the final variables to the `--resume` state file.
+#[cfg(feature = "serde")]
+fn save_state(path: &Path, eval: &Evaluator) {
+    if let Err(e) = fs::write(path, eval.snapshot().to_json() + "\n") {
+        eprintln!("Error writing '{}': {}", path.display(), e);
+    }
+}
+
+/// Without the `serde` feature the state could not be saved, so refuse to
+/// run rather than silently losing the run's progress.
+#[cfg(not(feature = "serde"))]
+fn load_state(_path: &Path) -> Option<StateSnapshot> {
+    eprintln!("--resume requires a build with the `serde` feature");
+    std::process::exit(1);
+}
+
+#[cfg(not(feature = "serde"))]
+fn save_state(_path: &Path, _eval: &Evaluator) {}
+
+/// Check `source` against the `--verify` keys, exiting if it is unsigned or
+/// doesn't match.  Returns the signed body and the check the `.bucl`
+/// functions it calls must pass.
+#[cfg(feature = "signing")]
+fn verify_source(
+    keys: &[String],
+    script: Option<PathBuf>,
+    source: String,
+) -> (String, FunctionCheck) {
+    let verified = cli::sign::load_policy(keys).and_then(|policy| {
+        let body = cli::sign::verify(&policy, script.as_deref(), &source)?;
+        Ok((body, cli::sign::function_check(policy)))
+    });
+    match verified {
+        Ok(verified) => verified,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Without the `signing` feature nothing can be verified, so refuse to run
+/// rather than silently ignoring `--verify`.
+#[cfg(not(feature = "signing"))]
+fn verify_source(
+    _keys: &[String],
+    _script: Option<PathBuf>,
+    _source: String,
+) -> (String, FunctionCheck) {
+    eprintln!("--verify requires a build with the `signing` feature");
+    std::process::exit(1);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reads(source: &str) -> bool {
+        reads_stdin(&parser::parse(source).unwrap())
+    }
+
+    #[test]
+    fn test_reads_stdin_finds_every_kind_of_reference() {
+        for source in [
+            "echo {stdin/count}",
+            "echo \"{stdin} lines\"",
+            "echo \"\\{literal\\} {stdin/0}\"",
+            "echo text:{stdin}",
+            "{l} each range 1 3\n    echo {stdin/{l/value}}",
+            "if \"a\" = \"b\"\n    echo \"no\"\nelseif \"a\" = \"a\"\n    echo {stdin}",
+        ] {
+            assert!(reads(source), "{}", source);
+        }
+        for source in [
+            "# reads stdin? no\necho \"stdin\"",
+            "echo {stdinx}",
+            "{stdin} = \"x\"",
+        ] {
+            assert!(!reads(source), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_split_var() {
+        let pair = |name: &str, value: &str| Ok((name.to_string(), value.to_string()));
+        assert_eq!(split_var("a=b"), pair("a", "b"));
+        assert_eq!(split_var("a="), pair("a", ""));
+        assert_eq!(split_var("url=x?a=1&b=2"), pair("url", "x?a=1&b=2"));
+        assert_eq!(split_var("db/port=5432"), pair("db/port", "5432"));
+        for bad in ["ab", "=b", ""] {
+            assert_eq!(split_var(bad), Err(format!("--var expects name=value, got '{}'", bad)));
+        }
+    }
+
+    #[test]
+    fn test_take_trailing_vars() {
+        let words: Vec<String> = ["x", "--var", "db/port=5432", "--", "--var", "y"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let mut vars = Vec::new();
+        assert_eq!(take_trailing_vars(&words, &mut vars), ["x", "--var", "y"]);
+        assert_eq!(vars, [("db/port".to_string(), "5432".to_string())]);
+    }
+
+    #[test]
+    fn test_output_file_appends_across_runs() {
+        let path = env::temp_dir().join(format!("bucl-output-file-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        for source in ["echo \"one\"\nprint \"two\" \"three\"", "print \"four\"\necho \"!\""] {
+            let mut eval = Evaluator::builder().output_sink(file_sink(&path)).build();
+            execute(&mut eval, source).unwrap();
+        }
+        let written = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(written, "one\ntwo three\nfour!\n");
     }
 }
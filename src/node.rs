@@ -0,0 +1,199 @@
+//! Node.js bindings (feature `napi`).
+//!
+//! Built with the feature, the native library is also a Node-API addon:
+//! copy it to `bucl.node` (`make node` does) and `require` it.  Strings
+//! are marshalled for you, and `run` returns a promise and executes the
+//! script on libuv's thread pool, so it doesn't block the event loop:
+//!
+//! ```js
+//! const bucl = require('./bucl.node');
+//!
+//! const r = await bucl.run('echo "hi {name}"', { vars: { name: 'Ada' }, sandbox: true });
+//! if (r.ok) console.log(r.output);    // "hi Ada"
+//! else console.error(r.error);        // r.status, r.exitCode, r.vars
+//! ```
+//!
+//! The result has the fields of `runDetailed` in the wasm-bindgen build,
+//! plus the variables the script ended with.  The options mirror the CLI:
+//! `baseDir`, `sandbox`, `allowRead` / `allowWrite` (path lists; an empty
+//! list allows everywhere), `allowNet`, `strict`, `maxSteps` and preset
+//! `vars`.  Each call uses a fresh evaluator with the standard library
+//! embedded; output is returned, not printed.
+
+use std::collections::HashMap;
+
+use napi::bindgen_prelude::AsyncTask;
+use napi::{Env, Task};
+use napi_derive::napi;
+
+use crate::error::BuclError;
+use crate::evaluator::Evaluator;
+use crate::permissions::{Access, Permissions};
+use crate::{execute, BUCL_STATUS_OK};
+
+/// How [`run`] runs a script; every field is optional.
+#[napi(object)]
+#[derive(Debug, Default, Clone)]
+pub struct RunOptions {
+    /// Directory `functions/<name>.bucl` is looked up in.
+    pub base_dir: Option<String>,
+    /// Deny file and network access, like `bucl --sandbox`.
+    pub sandbox: Option<bool>,
+    /// Sandbox, but allow reading below these paths (`[]` for anywhere).
+    pub allow_read: Option<Vec<String>>,
+    /// Sandbox, but allow writing below these paths (`[]` for anywhere).
+    pub allow_write: Option<Vec<String>>,
+    /// Sandbox, but allow network connections.
+    pub allow_net: Option<bool>,
+    /// Reading an unset variable is an error.
+    pub strict: Option<bool>,
+    /// Fail the run after this many statements.
+    pub max_steps: Option<i64>,
+    /// Variables to preset, by name.
+    pub vars: Option<HashMap<String, String>>,
+}
+
+/// What [`run`] resolves to.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct RunResult {
+    /// One of the `BUCL_STATUS_*` codes: 0 ok, 1 parse error, 2 runtime
+    /// error, 3 cancelled, 4 exited with a non-zero code.
+    pub status: u32,
+    /// `true` when the script finished without an error.
+    pub ok: bool,
+    /// Output lines joined with `\n`, including any produced before an error.
+    pub output: String,
+    /// The error message, or an empty string on success.
+    pub error: String,
+    /// The code the script passed to `exit`, or 0.
+    pub exit_code: i32,
+    /// Every variable the script ended with, by name.
+    pub vars: HashMap<String, String>,
+}
+
+/// `[]` grants everywhere, otherwise the listed paths.
+fn grant(paths: Vec<String>) -> Access {
+    if paths.is_empty() {
+        Access::Allow
+    } else {
+        Access::within(paths)
+    }
+}
+
+fn evaluator_for(options: RunOptions) -> Evaluator {
+    let permissions = Permissions::from_flags(
+        options.sandbox.unwrap_or(false),
+        options.allow_read.map(grant),
+        options.allow_write.map(grant),
+        options.allow_net.unwrap_or(false).then_some(Access::Allow),
+    );
+    let mut builder = Evaluator::builder()
+        .with_stdlib()
+        .permissions(permissions)
+        .strict(options.strict.unwrap_or(false))
+        .output_sink(std::sync::Arc::new(|_: &str| {}));
+    if let Some(dir) = options.base_dir {
+        builder = builder.base_dir(dir);
+    }
+    if let Some(max) = options.max_steps.filter(|&max| max > 0) {
+        builder = builder.max_steps(max as u64);
+    }
+    for (name, value) in options.vars.unwrap_or_default() {
+        builder = builder.var(name, value);
+    }
+    builder.build()
+}
+
+/// A script run on the thread pool.
+pub struct RunTask {
+    source: String,
+    options: RunOptions,
+}
+
+impl Task for RunTask {
+    type Output = RunResult;
+    type JsValue = RunResult;
+
+    fn compute(&mut self) -> napi::Result<RunResult> {
+        let mut eval = evaluator_for(std::mem::take(&mut self.options));
+        let (status, error, exit_code) = match execute(&mut eval, &self.source) {
+            Ok(()) => (BUCL_STATUS_OK, String::new(), 0),
+            Err(failure) => {
+                let exit_code = match failure.error {
+                    BuclError::Exit(code) => code,
+                    _ => 0,
+                };
+                (failure.status, failure.detail(), exit_code)
+            }
+        };
+        Ok(RunResult {
+            status,
+            ok: status == BUCL_STATUS_OK,
+            output: eval.output_buffer.join("\n"),
+            error,
+            exit_code,
+            vars: eval.variables_snapshot().into_iter().collect(),
+        })
+    }
+
+    fn resolve(&mut self, _env: Env, output: RunResult) -> napi::Result<RunResult> {
+        Ok(output)
+    }
+}
+
+/// `run(source, options?)` — run a script; resolves to a [`RunResult`]
+/// (script errors are reported in it, not by rejecting).
+#[napi(ts_return_type = "Promise<RunResult>")]
+pub fn run(source: String, options: Option<RunOptions>) -> AsyncTask<RunTask> {
+    AsyncTask::new(RunTask {
+        source,
+        options: options.unwrap_or_default(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BUCL_STATUS_RUNTIME_ERROR;
+
+    fn compute(source: &str, options: RunOptions) -> RunResult {
+        RunTask {
+            source: source.into(),
+            options,
+        }
+        .compute()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_task_applies_options() {
+        let vars = HashMap::from([("name".to_string(), "Ada".to_string())]);
+        let r = compute(
+            "{greeting} = \"hi {name}\"\necho {greeting}\n",
+            RunOptions {
+                vars: Some(vars),
+                ..RunOptions::default()
+            },
+        );
+        assert_eq!((r.ok, r.output.as_str()), (true, "hi Ada"));
+        assert_eq!(r.vars["greeting"], "hi Ada");
+
+        let r = compute(
+            "echo \"a\"\n{t} readfile \"Cargo.toml\"\n",
+            RunOptions {
+                sandbox: Some(true),
+                ..RunOptions::default()
+            },
+        );
+        assert_eq!((r.status, r.output.as_str()), (BUCL_STATUS_RUNTIME_ERROR, "a"));
+        assert!(r.error.contains("reading files is not permitted"), "{}", r.error);
+
+        let r = compute("exit 3\n", RunOptions::default());
+        assert_eq!((r.ok, r.exit_code), (false, 3));
+    }
+}
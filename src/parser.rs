@@ -1,42 +1,183 @@
-use crate::ast::{Param, Statement};
+use crate::ast::{Param, Statement, TemplatePart};
 use crate::error::{BuclError, Result};
 use crate::lexer::{self, Line, Token};
 
+/// Comment directive that sets a file's interpolation delimiters.  It must
+/// appear before the first statement:
+///
+/// ```text
+/// # bucl-delimiters: ${ }
+/// echo "{\"name\": \"${name}\"}"
+/// ```
+///
+/// `# bucl-delimiters: none` turns interpolation off for the file.
+pub const DELIMITERS_DIRECTIVE: &str = "# bucl-delimiters:";
+
+/// How variables are interpolated into quoted strings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Delimiters {
+    /// `{name}` — the default.
+    #[default]
+    Braces,
+    /// Custom markers such as `${` and `}`; plain braces become literal.
+    Custom { open: String, close: String },
+    /// No interpolation: quoted strings are taken literally.
+    None,
+}
+
+impl Delimiters {
+    /// Parse `"<open> <close>"`, `"{ }"` or `"none"`, as written after
+    /// [`DELIMITERS_DIRECTIVE`] or passed to `bucl --delimiters`.
+    pub fn from_spec(spec: &str) -> std::result::Result<Self, String> {
+        let parts: Vec<&str> = spec.split_whitespace().collect();
+        match parts.as_slice() {
+            ["none"] => Ok(Delimiters::None),
+            ["{", "}"] => Ok(Delimiters::Braces),
+            [open, close] => Ok(Delimiters::Custom {
+                open: open.to_string(),
+                close: close.to_string(),
+            }),
+            _ => Err(format!(
+                "expected '<open> <close>' or 'none' as interpolation delimiters, got '{}'",
+                spec.trim()
+            )),
+        }
+    }
+}
+
 /// Parse a full BUCL source string into a list of top-level statements.
+///
+/// The parser carries on after an error, skipping the bad line (and the
+/// block under it), so the error lists every problem it found, one after
+/// another.  Only tabs mixed with spaces stop it at once, since the
+/// indentation after them can't be trusted.
 pub fn parse(source: &str) -> Result<Vec<Statement>> {
-    let lines = lexer::tokenize(source)?;
-    let mut p = Parser { lines, cursor: 0 };
-    p.parse_block(0)
+    parse_with(source, &Delimiters::Braces)
+}
+
+/// Like [`parse`], with `delimiters` for quoted strings unless the source
+/// sets its own with a [`DELIMITERS_DIRECTIVE`] line.
+pub fn parse_with(source: &str, delimiters: &Delimiters) -> Result<Vec<Statement>> {
+    let delimiters = directive_delimiters(source)?.unwrap_or_else(|| delimiters.clone());
+    let (lines, errors) = lexer::tokenize_recovering(source);
+    let mut p = Parser {
+        source,
+        lines,
+        cursor: 0,
+        delimiters,
+        errors,
+    };
+    let stmts = p.parse_block(0);
+    if p.errors.is_empty() {
+        if let Ok(stmts) = stmts {
+            return Ok(stmts);
+        }
+    }
+    p.errors.sort_by_key(|e| match e {
+        BuclError::ParseError(msg) => error_line(msg),
+        _ => 0,
+    });
+    Err(BuclError::parse_errors(p.errors))
+}
+
+/// The line number at the start of a `line N: …` message.
+fn error_line(msg: &str) -> usize {
+    msg.strip_prefix("line ")
+        .and_then(|rest| rest.split(':').next())
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(0)
+}
+
+/// The delimiters set by a directive in the file's leading comments, if any.
+fn directive_delimiters(source: &str) -> Result<Option<Delimiters>> {
+    for (i, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if let Some(spec) = line.strip_prefix(DELIMITERS_DIRECTIVE) {
+            let column = source.lines().nth(i).map_or(1, |raw| {
+                raw.chars().count() - spec.trim_start().chars().count() + 1
+            });
+            return Delimiters::from_spec(spec)
+                .map(Some)
+                .map_err(|e| BuclError::parse_at(source, i + 1, column, &e));
+        }
+        if !line.is_empty() && !line.starts_with('#') {
+            break;
+        }
+    }
+    Ok(None)
 }
 
 // ---------------------------------------------------------------------------
 // Internal parser state
 // ---------------------------------------------------------------------------
 
-struct Parser {
+struct Parser<'a> {
+    /// The text being parsed, quoted in error messages.
+    source: &'a str,
     lines: Vec<Line>,
     cursor: usize,
+    delimiters: Delimiters,
+    /// Every error found so far, including the lexer's.
+    errors: Vec<BuclError>,
 }
 
-impl Parser {
+/// Parsing gave up: the reason is the last entry of `Parser::errors`.
+struct Abort;
+
+type Parsed<T> = std::result::Result<T, Abort>;
+
+impl Parser<'_> {
     // -----------------------------------------------------------------------
     // Helpers
     // -----------------------------------------------------------------------
 
+    /// Record a parse error pointing at `column` of `line`.
+    fn error_at(&mut self, lineno: usize, column: usize, msg: &str) {
+        self.errors.push(BuclError::parse_at(self.source, lineno, column, msg));
+    }
+
     fn current_indent(&self) -> Option<usize> {
         self.lines.get(self.cursor).map(|l| l.indent)
     }
 
-    /// Returns true when the line at `idx` is `elseif` or `else`.
+    /// The keyword when the line at `idx` is `elseif`, `elif` or `else`.
     /// These are handled as continuations of an `if`/`elseif` statement and
     /// must never be consumed as standalone top-level statements.
+    fn continuation_at(&self, idx: usize) -> Option<&str> {
+        match self.lines.get(idx)?.tokens.first()? {
+            Token::Bare(name) if is_continuation(name) => Some(name),
+            _ => None,
+        }
+    }
+
     fn is_continuation_at(&self, idx: usize) -> bool {
-        if let Some(line) = self.lines.get(idx) {
-            if let Some(Token::Bare(name)) = line.tokens.first() {
-                return name == "elseif" || name == "else";
-            }
+        self.continuation_at(idx).is_some()
+    }
+
+    /// Indentation only nests when one line's leading whitespace is a prefix
+    /// of the next one's.  Counting characters alone would make a tab and a
+    /// space the same depth, so report tabs mixed with spaces here instead of
+    /// as a puzzling "unexpected indentation" further on.
+    fn check_indent_text(&mut self) -> Parsed<()> {
+        let (Some(prev), Some(line)) = (
+            self.cursor.checked_sub(1).and_then(|i| self.lines.get(i)),
+            self.lines.get(self.cursor),
+        ) else {
+            return Ok(());
+        };
+        let (a, b) = (&prev.indent_text, &line.indent_text);
+        if a.starts_with(b.as_str()) || b.starts_with(a.as_str()) {
+            return Ok(());
         }
-        false
+        let msg = format!(
+            "inconsistent indentation: {} here but {} on line {}; \
+             indent with either tabs or spaces, not both",
+            describe_indent(b),
+            describe_indent(a),
+            prev.lineno
+        );
+        self.error_at(line.lineno, 1, &msg);
+        Err(Abort)
     }
 
     // -----------------------------------------------------------------------
@@ -44,31 +185,43 @@ impl Parser {
     // -----------------------------------------------------------------------
 
     /// Parse all consecutive statements at exactly `expected_indent`.
-    /// Stops (without consuming) when indentation drops below `expected_indent`
-    /// or when an `elseif`/`else` keyword is seen (handled by parent).
-    fn parse_block(&mut self, expected_indent: usize) -> Result<Vec<Statement>> {
+    /// Stops (without consuming) when indentation drops below
+    /// `expected_indent`.  An `elseif`/`else` at this level has no `if` to
+    /// belong to and is reported.  Statements with errors are left out.
+    fn parse_block(&mut self, expected_indent: usize) -> Parsed<Vec<Statement>> {
         let mut stmts = Vec::new();
 
         loop {
+            self.check_indent_text()?;
             match self.current_indent() {
                 None => break,
                 Some(i) if i < expected_indent => break,
                 Some(i) if i > expected_indent => {
-                    return Err(BuclError::ParseError(format!(
+                    let msg = format!(
                         "unexpected indentation: expected {} spaces/tabs, got {}",
                         expected_indent, i
-                    )));
+                    );
+                    self.error_at(self.lines[self.cursor].lineno, i + 1, &msg);
+                    // Skip the whole over-indented run.
+                    while self.current_indent().is_some_and(|i| i > expected_indent) {
+                        self.cursor += 1;
+                    }
+                    continue;
                 }
                 _ => {}
             }
 
-            // Leave elseif/else for the parent if/elseif to consume.
-            if self.is_continuation_at(self.cursor) {
-                break;
+            // An if/elseif consumes the elseif/else after it at its own
+            // indentation, so one reaching the block itself has no if.
+            if let Some(keyword) = self.continuation_at(self.cursor) {
+                let msg = format!("{} without matching if", keyword);
+                let line = &self.lines[self.cursor];
+                self.error_at(line.lineno, line.indent + 1, &msg);
+                self.parse_statement(expected_indent)?;
+                continue;
             }
 
-            let stmt = self.parse_statement(expected_indent)?;
-            stmts.push(stmt);
+            stmts.extend(self.parse_statement(expected_indent)?);
         }
 
         Ok(stmts)
@@ -78,11 +231,20 @@ impl Parser {
     // Statement parser
     // -----------------------------------------------------------------------
 
-    fn parse_statement(&mut self, current_indent: usize) -> Result<Statement> {
+    /// Parse the statement at the cursor with its block and `elseif`/`else`
+    /// continuation; `None` if its line has an error (which is recorded).
+    fn parse_statement(&mut self, current_indent: usize) -> Parsed<Option<Statement>> {
         let line = self.lines[self.cursor].clone();
         self.cursor += 1;
 
-        let (target, function, args) = extract_parts(&line.tokens)?;
+        // A line without tokens failed in the lexer, which reported it.
+        let parts = if line.tokens.is_empty() {
+            None
+        } else {
+            extract_parts(&line, &self.delimiters)
+                .map_err(|(column, msg)| self.error_at(line.lineno, column, &msg))
+                .ok()
+        };
 
         // Collect a deeper-indented block that belongs to this statement.
         let block = match self.current_indent() {
@@ -93,12 +255,22 @@ impl Parser {
             _ => None,
         };
 
+        let Some((target, function, args)) = parts else {
+            // Any elseif/else after it still gets checked.
+            while self.is_continuation_at(self.cursor)
+                && self.current_indent() == Some(current_indent)
+            {
+                self.parse_statement(current_indent)?;
+            }
+            return Ok(None);
+        };
+
         // Collect elseif / else as a continuation (only for if / elseif).
-        let continuation = if function == "if" || function == "elseif" {
+        let continuation = if function == "if" || function == "elseif" || function == "elif" {
             if self.is_continuation_at(self.cursor)
                 && self.current_indent() == Some(current_indent)
             {
-                Some(Box::new(self.parse_statement(current_indent)?))
+                self.parse_statement(current_indent)?.map(Box::new)
             } else {
                 None
             }
@@ -106,13 +278,14 @@ impl Parser {
             None
         };
 
-        Ok(Statement {
+        Ok(Some(Statement {
+            line: line.lineno,
             target,
             function,
             args,
             block,
             continuation,
-        })
+        }))
     }
 }
 
@@ -120,20 +293,52 @@ impl Parser {
 // Token-level helpers
 // ---------------------------------------------------------------------------
 
-/// Decompose a tokenised line into `(target, function_name, args)`.
+/// Whether `name` continues an `if`: `elseif` (or its alias `elif`) or
+/// `else`.
+fn is_continuation(name: &str) -> bool {
+    matches!(name, "elseif" | "elif" | "else")
+}
+
+/// `"1 tab"`, `"4 spaces"`, `"1 tab then 2 spaces"` …
+fn describe_indent(ws: &str) -> String {
+    let mut runs: Vec<(char, usize)> = Vec::new();
+    for c in ws.chars() {
+        match runs.last_mut() {
+            Some((last, n)) if *last == c => *n += 1,
+            _ => runs.push((c, 1)),
+        }
+    }
+    runs.iter()
+        .map(|&(c, n)| {
+            let name = if c == '\t' { "tab" } else { "space" };
+            format!("{} {}{}", n, name, if n == 1 { "" } else { "s" })
+        })
+        .collect::<Vec<_>>()
+        .join(" then ")
+}
+
+/// A statement line split into target, function name and arguments.
+type Parts = (Option<String>, String, Vec<Param>);
+
+/// Decompose a tokenised line into `(target, function_name, args)`, or an
+/// error message and the column it points at.
 ///
 /// Grammar:
 /// ```text
 /// line = ( '{' IDENT '}' ) BARE param*
 ///      | BARE param*
-/// param = '{' IDENT '}' | '"' … '"' | BARE
+/// param = '{' IDENT '}' | '"' … '"' | 'r"' … '"' | BARE
 /// ```
-fn extract_parts(tokens: &[Token]) -> Result<(Option<String>, String, Vec<Param>)> {
-    if tokens.is_empty() {
-        return Err(BuclError::ParseError("empty line".to_string()));
+fn extract_parts(
+    line: &Line,
+    delimiters: &Delimiters,
+) -> std::result::Result<Parts, (usize, String)> {
+    let column = |i: usize| line.columns.get(i).copied().unwrap_or(line.indent + 1);
+    if line.tokens.is_empty() {
+        return Err((column(0), "empty line".to_string()));
     }
 
-    let mut iter = tokens.iter();
+    let mut iter = line.tokens.iter();
     let first = iter.next().unwrap();
 
     // Determine target and function name.
@@ -143,36 +348,279 @@ fn extract_parts(tokens: &[Token]) -> Result<(Option<String>, String, Vec<Param>
             match iter.next() {
                 Some(Token::Bare(f)) => (Some(name.clone()), f.clone()),
                 Some(other) => {
-                    return Err(BuclError::ParseError(format!(
-                        "expected function name after '{{{}}}', got {:?}",
-                        name, other
-                    )));
+                    return Err((
+                        column(1),
+                        format!("expected function name after '{{{}}}', got {:?}", name, other),
+                    ));
                 }
                 None => {
-                    return Err(BuclError::ParseError(format!(
-                        "expected function name after '{{{}}}'",
-                        name
-                    )));
+                    // Point just past the `{name}`.
+                    let end = column(0) + name.chars().count() + 2;
+                    return Err((end, format!("expected function name after '{{{}}}'", name)));
                 }
             }
         }
         Token::Bare(name) => (None, name.clone()),
-        Token::Quoted(s) => {
-            return Err(BuclError::ParseError(format!(
-                "a line cannot start with a string literal: \"{}\"",
-                s
-            )));
+        Token::Quoted(s, _) | Token::Raw(s) => {
+            return Err((
+                column(0),
+                format!("a line cannot start with a string literal: \"{}\"", s),
+            ));
+        }
+        Token::Named(name, _) => {
+            return Err((
+                column(0),
+                format!("a line cannot start with a named argument: {}:…", name),
+            ));
         }
     };
 
     // Remaining tokens are arguments.
-    let args = iter
-        .map(|t| match t {
-            Token::Quoted(s) => Param::Quoted(s.clone()),
-            Token::Variable(n) => Param::Variable(n.clone()),
-            Token::Bare(s) => Param::Bare(s.clone()),
-        })
-        .collect();
+    let args = iter.map(|t| token_param(t, delimiters)).collect();
 
     Ok((target, function, args))
 }
+
+/// The argument an argument token stands for.
+fn token_param(token: &Token, delimiters: &Delimiters) -> Param {
+    match token {
+        Token::Quoted(s, braces) => quoted_param(s, braces, delimiters),
+        Token::Raw(s) => Param::Template(vec![TemplatePart::Text(s.clone())]),
+        Token::Variable(n) => Param::Variable(n.clone()),
+        Token::Bare(s) => Param::Bare(s.clone()),
+        Token::Named(name, value) => {
+            Param::Named(name.clone(), Box::new(token_param(value, delimiters)))
+        }
+    }
+}
+
+/// Turn template text — a quoted string without the quotes, or a file
+/// rendered by `render` — into a parameter according to `delimiters`.
+#[cfg(feature = "fs")]
+pub(crate) fn template_param(s: &str, delimiters: &Delimiters) -> Param {
+    quoted_param(s, &[], delimiters)
+}
+
+/// Turn a quoted string into a parameter according to `delimiters`.  The
+/// escaped braces at byte offsets `braces` are always literal text.
+fn quoted_param(s: &str, braces: &[usize], delimiters: &Delimiters) -> Param {
+    let (open, close) = match delimiters {
+        Delimiters::Braces if braces.is_empty() => {
+            return Param::Quoted(s.to_string(), interpolation_parts(s).into())
+        }
+        Delimiters::None => return Param::Template(vec![TemplatePart::Text(s.to_string())]),
+        Delimiters::Braces => ("{", "}"),
+        Delimiters::Custom { open, close } => (open.as_str(), close.as_str()),
+    };
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for &at in braces.iter().chain([&s.len()]) {
+        for part in split_template(&s[start..at], open, close) {
+            match (parts.last_mut(), part) {
+                (Some(TemplatePart::Text(text)), TemplatePart::Text(more)) => text.push_str(&more),
+                (_, part) => parts.push(part),
+            }
+        }
+        if at < s.len() {
+            match parts.last_mut() {
+                Some(TemplatePart::Text(text)) => text.push_str(&s[at..at + 1]),
+                _ => parts.push(TemplatePart::Text(s[at..at + 1].to_string())),
+            }
+        }
+        start = at + 1;
+    }
+    parts.retain(|p| p != &TemplatePart::Text(String::new()));
+    if parts.is_empty() {
+        parts.push(TemplatePart::Text(String::new()));
+    }
+    Param::Template(parts)
+}
+
+/// Split `s` at `{…}` references, the way [`Evaluator::interpolate`]
+/// reads it.
+///
+/// [`Evaluator::interpolate`]: crate::evaluator::Evaluator::interpolate
+pub(crate) fn interpolation_parts(s: &str) -> Vec<TemplatePart> {
+    split_template(s, "{", "}")
+}
+
+/// Split `s` at `open … close` markers.  A `{…}` nested inside a reference
+/// (`${parts/{i}}`) doesn't end it; an unclosed marker is literal text.
+fn split_template(s: &str, open: &str, close: &str) -> Vec<TemplatePart> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find(open) {
+        let after = &rest[start + open.len()..];
+        let mut depth = 0usize;
+        let mut end = None;
+        for (i, c) in after.char_indices() {
+            if depth == 0 && after[i..].starts_with(close) {
+                end = Some(i);
+                break;
+            }
+            match c {
+                '{' => depth += 1,
+                '}' if depth > 0 => depth -= 1,
+                _ => {}
+            }
+        }
+        let Some(end) = end else { break };
+        text.push_str(&rest[..start]);
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+        }
+        parts.push(TemplatePart::Var(after[..end].to_string()));
+        rest = &after[end + close.len()..];
+    }
+    text.push_str(rest);
+    if !text.is_empty() || parts.is_empty() {
+        parts.push(TemplatePart::Text(text));
+    }
+    parts
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_arg(source: &str, delimiters: &Delimiters) -> Param {
+        parse_with(source, delimiters).unwrap()[0].args[0].clone()
+    }
+
+    #[test]
+    fn test_custom_delimiters_split_template() {
+        let dollar = Delimiters::from_spec("${ }").unwrap();
+        let Param::Template(parts) = first_arg("echo \"{\\\"a\\\": ${x/{i}}} ${y\"", &dollar) else {
+            panic!("expected a template");
+        };
+        assert_eq!(
+            parts,
+            vec![
+                TemplatePart::Text("{\"a\": ".into()),
+                TemplatePart::Var("x/{i}".into()),
+                TemplatePart::Text("} ${y".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_directive_overrides_option() {
+        let source = "# bucl-delimiters: none\necho \"{x}\"\n";
+        let Param::Template(parts) = first_arg(source, &Delimiters::Braces) else {
+            panic!("expected a template");
+        };
+        assert_eq!(parts, vec![TemplatePart::Text("{x}".into())]);
+
+        // A directive after the first statement is just a comment.
+        let late = "echo \"{x}\"\n# bucl-delimiters: none\n";
+        assert!(matches!(first_arg(late, &Delimiters::Braces), Param::Quoted(..)));
+        assert!(parse("# bucl-delimiters: $\necho 1\n").is_err());
+    }
+
+    #[test]
+    fn test_mixed_indentation_is_reported() {
+        let err = parse("if 1 = 1\n    echo a\n\techo b\n").unwrap_err().to_string();
+        assert!(err.contains("line 3: inconsistent indentation: 1 tab here but 4 spaces on line 2"), "{}", err);
+        let err = parse("if 1 = 1\n\t  echo a\n  \techo b\n").unwrap_err().to_string();
+        assert!(err.contains("2 spaces then 1 tab here but 1 tab then 2 spaces"), "{}", err);
+        assert!(parse("if 1 = 1\n\tif 2 = 2\n\t\techo a\n\techo b\necho c\n").is_ok());
+    }
+
+    #[test]
+    fn test_errors_quote_the_line() {
+        let err = parse("if 1 = 1\n        echo a\n    echo b\n").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Parse error: line 3: unexpected indentation: expected 0 spaces/tabs, got 4\n \
+             3 |     echo b\n   |     ^"
+        );
+        let err = parse("echo 1\n\t{x}   \"y\"\n").unwrap_err().to_string();
+        assert!(err.ends_with("\n 2 | \t{x}   \"y\"\n   | \t      ^"), "{}", err);
+        let err = parse("echo \"\\u{zz}\"\n").unwrap_err().to_string();
+        assert!(err.ends_with("\n 1 | echo \"\\u{zz}\"\n   |      ^"), "{}", err);
+    }
+
+    #[test]
+    fn test_all_errors_are_reported() {
+        let source = concat!(
+            "{x}\n",
+            "if 1 = 1\n",
+            "    \"oops\"\n",
+            "        echo \"\\u{zz}\"\n",
+            "    echo ok\n",
+            "else\n",
+            "        echo deep\n",
+            "      echo deeper\n",
+            "    {y} = \"fine\"\n",
+            "echo \"\"\"\n",
+        );
+        let err = parse(source).unwrap_err().to_string();
+        let lines: Vec<&str> = err.lines().filter(|l| !l.contains(" | ")).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Parse error: line 1: expected function name after '{x}'",
+                "line 3: a line cannot start with a string literal: \"oops\"",
+                "line 4: invalid unicode escape '\\u{zz}'",
+                "line 8: unexpected indentation: expected 0 spaces/tabs, got 6",
+                "line 10: unterminated \"\"\" string",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_elif_and_orphan_else() {
+        let stmts = parse("if 1 = 2\n    echo a\nelif 1 = 1\n    echo b\nelse\n    echo c\n").unwrap();
+        let elif = stmts[0].continuation.as_ref().unwrap();
+        assert_eq!(elif.function, "elif");
+        assert_eq!(elif.continuation.as_ref().unwrap().function, "else");
+
+        let err = parse("echo a\nelse\n    echo b\necho c\n    elif 1 = 1\n").unwrap_err().to_string();
+        let lines: Vec<&str> = err.lines().filter(|l| !l.contains(" | ")).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Parse error: line 2: else without matching if",
+                "line 5: elif without matching if",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escaped_braces_are_literal() {
+        let Param::Template(parts) = first_arg(r#"echo "\{x\} = {x}\}""#, &Delimiters::Braces) else {
+            panic!("expected a template");
+        };
+        assert_eq!(
+            parts,
+            vec![
+                TemplatePart::Text("{x} = ".into()),
+                TemplatePart::Var("x".into()),
+                TemplatePart::Text("}".into()),
+            ]
+        );
+        assert!(matches!(first_arg(r#"echo "{x}""#, &Delimiters::Braces), Param::Quoted(..)));
+    }
+
+    #[test]
+    fn test_quoted_string_is_split_at_parse_time() {
+        let Param::Quoted(text, parts) = first_arg(r#"echo "a {x/{i}} {y""#, &Delimiters::Braces)
+        else {
+            panic!("expected a quoted string");
+        };
+        assert_eq!(text, "a {x/{i}} {y");
+        assert_eq!(
+            &parts[..],
+            [
+                TemplatePart::Text("a ".into()),
+                TemplatePart::Var("x/{i}".into()),
+                TemplatePart::Text(" {y".into()),
+            ]
+        );
+    }
+}
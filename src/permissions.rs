@@ -0,0 +1,317 @@
+//! What a script may do outside the interpreter.
+//!
+//! Every evaluator carries a [`Permissions`] value (see
+//! [`Evaluator::permissions`](crate::evaluator::Evaluator::permissions)).
+//! Built-ins that touch the filesystem or the network ask it before doing
+//! so and fail with a runtime error naming the denied capability:
+//!
+//! ```ignore
+//! let mut eval = Evaluator::new();
+//! eval.permissions = Permissions::sandboxed();
+//! eval.permissions.read = Access::within(["data"]);
+//! // readfile "data/in.txt"  →  ok
+//! // readfile "secret.txt"   →  readfile: 'secret.txt' is outside the paths allowed for reading files
+//! // writefile "x.txt" "…"   →  writefile: writing files is not permitted (sandbox)
+//! ```
+//!
+//! `.bucl` function files beside the script are part of the program rather
+//! than its data and are still loaded in a sandbox; `locales/` catalogs are
+//! read like any other file.
+
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::{BuclError, Result};
+
+/// A capability a built-in can require.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Read files: `readfile`, `eachline`, `imageinfo`, `render`, `store`,
+    /// `sqlopen`, and `t` catalogs.
+    ReadFiles,
+    /// Create or replace files: `writefile`, `store`, `sqlopen`.
+    WriteFiles,
+    /// Open network connections: `netsend`, `netrecv`.
+    Network,
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Permission::ReadFiles => "reading files",
+            Permission::WriteFiles => "writing files",
+            Permission::Network => "network access",
+        })
+    }
+}
+
+/// How far one [`Permission`] is granted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Access {
+    /// Anywhere.
+    Allow,
+    /// Nowhere.
+    Deny,
+    /// Only at or below these paths (absolute, normalized; build them with
+    /// [`Access::within`]).
+    Within(Vec<PathBuf>),
+    /// Only to these hosts, for [`Permission::Network`] (lowercase `host`
+    /// or `host:port`; build them with [`Access::hosts`]).
+    Hosts(Vec<String>),
+}
+
+impl Access {
+    /// Access limited to `paths` and everything below them.  Relative
+    /// paths are taken from the current directory, like file built-ins do.
+    pub fn within<I, P>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        Access::Within(paths.into_iter().map(|p| normalize(p.as_ref())).collect())
+    }
+
+    /// Access limited to `hosts`.  A bare host allows any port on it, a
+    /// `host:port` just that port.
+    pub fn hosts<I, S>(hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Access::Hosts(hosts.into_iter().map(|h| h.as_ref().to_ascii_lowercase()).collect())
+    }
+
+    /// Whether `path` — for the network, a `host:port` address — is covered.
+    pub fn allows(&self, path: &str) -> bool {
+        match self {
+            Access::Allow => true,
+            Access::Deny => false,
+            Access::Within(roots) => {
+                let path = normalize(Path::new(path));
+                roots.iter().any(|root| path.starts_with(root))
+            }
+            Access::Hosts(hosts) => {
+                let addr = path.to_ascii_lowercase();
+                let host = match addr.rsplit_once(':') {
+                    Some((host, port)) if port.bytes().all(|b| b.is_ascii_digit()) => host,
+                    _ => addr.as_str(),
+                };
+                let unbracket = |h: &str| h.trim_matches(['[', ']']).to_string();
+                let host = unbracket(host);
+                hosts.iter().any(|allowed| *allowed == addr || unbracket(allowed) == host)
+            }
+        }
+    }
+}
+
+/// The capabilities granted to a run.  The default allows everything;
+/// [`Permissions::sandboxed`] allows nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permissions {
+    pub read: Access,
+    pub write: Access,
+    /// Network connections: [`Access::Allow`], [`Access::Deny`] or
+    /// [`Access::Hosts`].
+    pub network: Access,
+}
+
+impl Permissions {
+    /// No filesystem or network access — what `bucl --sandbox` runs with.
+    pub fn sandboxed() -> Self {
+        Self {
+            read: Access::Deny,
+            write: Access::Deny,
+            network: Access::Deny,
+        }
+    }
+
+    /// What the CLI's sandbox flags describe: everything when none is given,
+    /// otherwise [`sandboxed`](Permissions::sandboxed) plus the `read` and
+    /// `write` grants (`--allow-read`, `--allow-write`) and the `network`
+    /// one (`--allow-net`).  Embedding APIs take the same options.
+    pub fn from_flags(
+        sandbox: bool,
+        read: Option<Access>,
+        write: Option<Access>,
+        network: Option<Access>,
+    ) -> Self {
+        if !(sandbox || read.is_some() || write.is_some() || network.is_some()) {
+            return Self::default();
+        }
+        let mut permissions = Self::sandboxed();
+        if let Some(access) = read {
+            permissions.read = access;
+        }
+        if let Some(access) = write {
+            permissions.write = access;
+        }
+        if let Some(access) = network {
+            permissions.network = access;
+        }
+        permissions
+    }
+
+    /// The scope granted for `permission`.
+    pub fn access(&self, permission: Permission) -> &Access {
+        match permission {
+            Permission::ReadFiles => &self.read,
+            Permission::WriteFiles => &self.write,
+            Permission::Network => &self.network,
+        }
+    }
+
+    /// `Ok` if `permission` is granted for `path`, otherwise an error for
+    /// built-in `fname`.
+    pub fn require(&self, permission: Permission, fname: &str, path: &str) -> Result<()> {
+        let access = self.access(permission);
+        if access.allows(path) {
+            return Ok(());
+        }
+        Err(BuclError::RuntimeError(match access {
+            Access::Within(_) => format!(
+                "{}: '{}' is outside the paths allowed for {}",
+                fname, path, permission
+            ),
+            Access::Hosts(_) => format!(
+                "{}: '{}' is not a host allowed for {}",
+                fname, path, permission
+            ),
+            _ => format!("{}: {} is not permitted (sandbox)", fname, permission),
+        }))
+    }
+}
+
+impl Default for Permissions {
+    fn default() -> Self {
+        Self {
+            read: Access::Allow,
+            write: Access::Allow,
+            network: Access::Allow,
+        }
+    }
+}
+
+/// Make `path` absolute and remove `.` and `..` without touching the
+/// filesystem, then resolve symlinks in the part of it that exists, so a
+/// link inside an allowed directory cannot lead outside it.
+fn normalize(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        match std::env::current_dir() {
+            Ok(dir) => dir.join(path),
+            Err(_) => path.to_path_buf(),
+        }
+    };
+    let mut lexical = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                lexical.pop();
+            }
+            other => lexical.push(other),
+        }
+    }
+
+    let mut existing = lexical.as_path();
+    let mut missing = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return lexical,
+        }
+    }
+    match existing.canonicalize() {
+        Ok(mut real) => {
+            real.extend(missing.iter().rev());
+            real
+        }
+        Err(_) => lexical,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_sandbox_denies_file_builtins() {
+        use crate::evaluator::Evaluator;
+        use crate::fs::MemoryFs;
+        use std::sync::Arc;
+
+        let run = |eval: &mut Evaluator, source: &str| {
+            eval.evaluate_statements(&crate::parser::parse(source).unwrap())
+        };
+        let fs = Arc::new(MemoryFs::new());
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.fs = fs;
+        run(&mut eval, "writefile \"a.txt\" \"hi\"").unwrap();
+
+        eval.permissions = Permissions::sandboxed();
+        for (source, message) in [
+            ("{x} readfile \"a.txt\"", "readfile: reading files is not permitted"),
+            ("writefile \"a.txt\" \"x\"", "writefile: writing files is not permitted"),
+            ("{l} eachline \"a.txt\"\n    echo {l}", "eachline: reading files"),
+        ] {
+            let err = run(&mut eval, source).unwrap_err().to_string();
+            assert!(err.contains(message), "{}: {}", source, err);
+        }
+
+        eval.permissions.read = Access::Allow;
+        run(&mut eval, "{x} readfile \"a.txt\"").unwrap();
+        assert_eq!(eval.resolve_var("x"), "hi");
+    }
+
+    #[test]
+    fn test_path_allowlist() {
+        let data = Access::within(["data", "/srv/shared/"]);
+        assert!(data.allows("data"));
+        assert!(data.allows("data/in/a.txt"));
+        assert!(data.allows("./other/../data/a.txt"));
+        assert!(data.allows("/srv/shared/x"));
+        assert!(!data.allows("database.txt"));
+        assert!(!data.allows("data/../secret.txt"));
+        assert!(!data.allows("/srv/x"));
+
+        let permissions = Permissions {
+            read: data,
+            write: Access::Deny,
+            network: Access::Deny,
+        };
+        let err = permissions
+            .require(Permission::ReadFiles, "readfile", "secret.txt")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("readfile: 'secret.txt' is outside the paths allowed for reading files"));
+    }
+
+    #[test]
+    fn test_host_allowlist() {
+        let api = Access::hosts(["API.example.com", "localhost:8080", "[::1]"]);
+        assert!(api.allows("api.example.com:443"));
+        assert!(api.allows("localhost:8080"));
+        assert!(api.allows("[::1]:25"));
+        assert!(!api.allows("localhost:22"));
+        assert!(!api.allows("evil.com:443"));
+        assert!(!api.allows("api.example.com.evil.com:443"));
+
+        let permissions = Permissions::from_flags(false, None, None, Some(api));
+        assert_eq!(permissions.read, Access::Deny);
+        let err = permissions
+            .require(Permission::Network, "netsend", "evil.com:80")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("netsend: 'evil.com:80' is not a host allowed for network access"));
+    }
+}
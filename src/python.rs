@@ -0,0 +1,107 @@
+//! Python bindings (feature `python`).
+//!
+//! Built with [maturin](https://www.maturin.rs) (`maturin develop`, see
+//! `pyproject.toml`) this becomes an importable `bucl` module:
+//!
+//! ```python
+//! import bucl
+//!
+//! r = bucl.run('{total} math {a} + {b}\necho "sum: {total}"', vars={"a": 2, "b": 3})
+//! r.output           # 'sum: 5'
+//! r.vars["total"]    # '5'
+//! ```
+//!
+//! `vars` values are converted with `str()`, since every BUCL value is a
+//! string.  A script that fails raises `bucl.BuclError`; `exit 0` counts as
+//! success.  Each call runs in a fresh evaluator with the standard library
+//! embedded, and releases the GIL while the script runs.
+
+use std::collections::BTreeMap;
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::evaluator::Evaluator;
+use crate::execute;
+
+create_exception!(bucl, BuclError, PyException, "A BUCL script failed to parse or run.");
+
+/// What [`run`] returns: the script's output and its variables at the end.
+#[pyclass(module = "bucl", frozen, get_all)]
+#[derive(Debug)]
+pub struct RunResult {
+    /// Output lines joined with `\n`.
+    output: String,
+    /// Every variable the script ended with, by name.
+    vars: BTreeMap<String, String>,
+}
+
+#[pymethods]
+impl RunResult {
+    fn __repr__(&self) -> String {
+        format!("RunResult(output={:?}, vars={} variables)", self.output, self.vars.len())
+    }
+}
+
+/// `bucl.run(source, vars=None)` — run `source` with `vars` preset.
+#[pyfunction]
+#[pyo3(signature = (source, vars = None))]
+fn run(py: Python<'_>, source: String, vars: Option<&Bound<'_, PyDict>>) -> PyResult<RunResult> {
+    let mut preset = Vec::new();
+    for (name, value) in vars.into_iter().flat_map(|d| d.iter()) {
+        preset.push((name.str()?.to_string(), value.str()?.to_string()));
+    }
+    py.detach(move || {
+        let mut builder = Evaluator::builder()
+            .with_stdlib()
+            .output_sink(std::sync::Arc::new(|_: &str| {}));
+        for (name, value) in preset {
+            builder = builder.var(name, value);
+        }
+        let mut eval = builder.build();
+        execute(&mut eval, &source).map_err(|failure| BuclError::new_err(failure.error.to_string()))?;
+        Ok(RunResult {
+            output: eval.output_buffer.join("\n"),
+            vars: eval.variables_snapshot().into_iter().collect(),
+        })
+    })
+}
+
+#[pymodule]
+#[pyo3(name = "bucl")]
+fn bucl_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(run, m)?)?;
+    m.add_class::<RunResult>()?;
+    m.add("BuclError", m.py().get_type::<BuclError>())?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_returns_output_and_vars() {
+        Python::initialize();
+        Python::attach(|py| {
+            let vars = PyDict::new(py);
+            vars.set_item("a", 2).unwrap();
+            vars.set_item("b", "3").unwrap();
+            let source = "{total} math {a} + {b}\necho \"sum: {total}\"\n";
+            let r = run(py, source.into(), Some(&vars)).unwrap();
+            assert_eq!(r.output, "sum: 5");
+            assert_eq!(r.vars["total"], "5");
+            assert_eq!(r.vars["a"], "2");
+
+            let err = run(py, "nosuchfunction\n".into(), None).unwrap_err();
+            assert!(err.is_instance_of::<BuclError>(py));
+            assert!(err.to_string().contains("nosuchfunction"), "{}", err);
+        });
+    }
+}
@@ -0,0 +1,217 @@
+//! ed25519 script signatures (feature `signing`).
+//!
+//! A script is signed over its exact bytes.  The signature travels either
+//! **embedded** as a trailing comment line, so signed scripts still run on
+//! interpreters that don't check signatures:
+//!
+//! ```text
+//! echo "hello"
+//! # bucl-signature: 3f9a…e1
+//! ```
+//!
+//! or **detached** in a `<script>.sig` file next to the script.  Keys and
+//! signatures are stored as lowercase hex.
+//!
+//! Embedders verify a script before parsing it:
+//!
+//! ```ignore
+//! let policy = TrustPolicy::new(vec![parse_verifying_key(&pub_hex)?]);
+//! let body = policy.verify(&source, detached_sig.as_deref())?;
+//! let stmts = parser::parse(body)?;
+//! ```
+
+use std::fmt;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+/// Prefix of the trailing comment line that carries an embedded signature.
+pub const SIGNATURE_PREFIX: &str = "# bucl-signature:";
+
+/// Why a script was refused.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SignatureError {
+    /// The script carries no signature at all.
+    Unsigned,
+    /// A signature is present but no trusted key accepts it — the script
+    /// was modified after signing, or signed by someone else.
+    Invalid,
+    /// A key or signature could not be decoded.
+    Malformed(String),
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsigned => write!(f, "script is not signed"),
+            Self::Invalid => write!(f, "signature does not match any trusted key (script modified?)"),
+            Self::Malformed(msg) => write!(f, "malformed signature data: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// The keys a host trusts.  Scripts must be signed by one of them.
+pub struct TrustPolicy {
+    keys: Vec<VerifyingKey>,
+}
+
+impl TrustPolicy {
+    pub fn new(keys: Vec<VerifyingKey>) -> Self {
+        Self { keys }
+    }
+
+    /// Check `source` against the trusted keys and return the signed body
+    /// (the source without its signature line).
+    ///
+    /// An embedded signature is used when present, otherwise `detached`.
+    pub fn verify<'a>(
+        &self,
+        source: &'a str,
+        detached: Option<&str>,
+    ) -> Result<&'a str, SignatureError> {
+        let (body, embedded) = split_signature(source);
+        let sig_hex = embedded.or(detached).ok_or(SignatureError::Unsigned)?;
+        let signature = parse_signature(sig_hex)?;
+        if self
+            .keys
+            .iter()
+            .any(|key| key.verify(body.as_bytes(), &signature).is_ok())
+        {
+            Ok(body)
+        } else {
+            Err(SignatureError::Invalid)
+        }
+    }
+}
+
+/// Sign `body` and return the signature as hex.
+pub fn sign(body: &str, key: &SigningKey) -> String {
+    encode_hex(&key.sign(body.as_bytes()).to_bytes())
+}
+
+/// Return `source` with an embedded signature, replacing any existing one.
+pub fn sign_embedded(source: &str, key: &SigningKey) -> String {
+    let (body, _) = split_signature(source);
+    let mut signed = body.to_string();
+    if !signed.is_empty() && !signed.ends_with('\n') {
+        signed.push('\n');
+    }
+    let signature = sign(&signed, key);
+    signed.push_str(SIGNATURE_PREFIX);
+    signed.push(' ');
+    signed.push_str(&signature);
+    signed.push('\n');
+    signed
+}
+
+/// Split off an embedded signature: the last non-blank line, if it starts
+/// with [`SIGNATURE_PREFIX`].  Returns `(body, signature_hex)`.
+pub fn split_signature(source: &str) -> (&str, Option<&str>) {
+    let trimmed = source.trim_end();
+    let line_start = trimmed.rfind('\n').map_or(0, |i| i + 1);
+    match trimmed[line_start..].strip_prefix(SIGNATURE_PREFIX) {
+        Some(sig) => (&source[..line_start], Some(sig.trim())),
+        None => (source, None),
+    }
+}
+
+/// Generate a new signing key from the OS random number generator.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn generate_key() -> SigningKey {
+    use rand::RngCore;
+
+    let mut seed = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut seed);
+    SigningKey::from_bytes(&seed)
+}
+
+// ---------------------------------------------------------------------------
+// Hex encoding of keys and signatures
+// ---------------------------------------------------------------------------
+
+/// Hex form of a signing (secret) key.
+pub fn format_signing_key(key: &SigningKey) -> String {
+    encode_hex(&key.to_bytes())
+}
+
+/// Hex form of a verifying (public) key.
+pub fn format_verifying_key(key: &VerifyingKey) -> String {
+    encode_hex(key.as_bytes())
+}
+
+pub fn parse_signing_key(text: &str) -> Result<SigningKey, SignatureError> {
+    Ok(SigningKey::from_bytes(&decode_fixed::<32>(text, "signing key")?))
+}
+
+pub fn parse_verifying_key(text: &str) -> Result<VerifyingKey, SignatureError> {
+    VerifyingKey::from_bytes(&decode_fixed::<32>(text, "public key")?)
+        .map_err(|e| SignatureError::Malformed(format!("public key: {}", e)))
+}
+
+fn parse_signature(text: &str) -> Result<Signature, SignatureError> {
+    Ok(Signature::from_bytes(&decode_fixed::<64>(text, "signature")?))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_fixed<const N: usize>(text: &str, what: &str) -> Result<[u8; N], SignatureError> {
+    let text = text.trim();
+    let malformed = || SignatureError::Malformed(format!("{} must be {} hex digits", what, N * 2));
+    if text.len() != N * 2 || !text.is_ascii() {
+        return Err(malformed());
+    }
+    let mut out = [0u8; N];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[i * 2..i * 2 + 2], 16).map_err(|_| malformed())?;
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    #[test]
+    fn test_embedded_signature_round_trip() {
+        let signed = sign_embedded("echo \"hi\"", &key(1));
+        let policy = TrustPolicy::new(vec![key(1).verifying_key()]);
+        assert_eq!(policy.verify(&signed, None), Ok("echo \"hi\"\n"));
+
+        // Re-signing replaces the old trailer instead of stacking another.
+        let resigned = sign_embedded(&signed, &key(1));
+        assert_eq!(resigned.matches(SIGNATURE_PREFIX).count(), 1);
+    }
+
+    #[test]
+    fn test_rejects_tampered_unsigned_and_untrusted() {
+        let signed = sign_embedded("echo \"hi\"\n", &key(1));
+        let policy = TrustPolicy::new(vec![key(1).verifying_key()]);
+
+        let tampered = signed.replace("hi", "bye");
+        assert_eq!(policy.verify(&tampered, None), Err(SignatureError::Invalid));
+        assert_eq!(policy.verify("echo \"hi\"\n", None), Err(SignatureError::Unsigned));
+
+        let other = TrustPolicy::new(vec![key(2).verifying_key()]);
+        assert_eq!(other.verify(&signed, None), Err(SignatureError::Invalid));
+    }
+
+    #[test]
+    fn test_detached_signature_and_key_hex() {
+        let body = "echo \"hi\"\n";
+        let sig = sign(body, &key(3));
+        let public = parse_verifying_key(&format_verifying_key(&key(3).verifying_key())).unwrap();
+        assert_eq!(TrustPolicy::new(vec![public]).verify(body, Some(&sig)), Ok(body));
+        assert!(matches!(parse_signing_key("abc"), Err(SignatureError::Malformed(_))));
+    }
+}
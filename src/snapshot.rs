@@ -0,0 +1,265 @@
+//! Saved variable stores and the differences between them.
+//!
+//! A [`Snapshot`] is the final variable store of a run.  It is saved as plain
+//! text, one `name = value` line per variable in name order, with `\`,
+//! newlines and carriage returns in values escaped as `\\`, `\n` and `\r`:
+//!
+//! ```text
+//! total = 42
+//! report = line one\nline two
+//! ```
+//!
+//! [`diff`] compares two snapshots, which makes a regression check for a
+//! data-producing script a matter of saving a known-good snapshot once and
+//! diffing every later run against it (`bucl --save-vars` / `--diff-vars`).
+//!
+//! A [`StateSnapshot`] is an in-memory copy of an evaluator's variable store
+//! that [`Evaluator::restore`] puts back exactly — an undo point for a REPL,
+//! or a clean slate for each case of a test suite:
+//!
+//! ```ignore
+//! let before = eval.snapshot();
+//! eval.evaluate_statements(&stmts)?;
+//! eval.restore(&before);
+//! ```
+//!
+//! With the `serde` feature it can also be saved as JSON and loaded again
+//! ([`StateSnapshot::to_json`] / [`StateSnapshot::from_json`]), so a long
+//! workflow can checkpoint its progress to disk and resume in a later
+//! process (`bucl --resume state.json`):
+//!
+//! ```text
+//! {
+//!   "variables": {
+//!     "done/fetch": "1",
+//!     "total": "42"
+//!   },
+//!   "readonly": []
+//! }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::evaluator::Evaluator;
+
+/// Variable name → value, ordered by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    pub vars: BTreeMap<String, String>,
+}
+
+impl Snapshot {
+    /// Capture the evaluator's current top-level variables.
+    pub fn capture(eval: &Evaluator) -> Self {
+        Self {
+            vars: eval.variables_snapshot().into_iter().collect(),
+        }
+    }
+
+    /// Read a snapshot written by [`Snapshot::to_text`].
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut vars = BTreeMap::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let (name, value) = line
+                .split_once(" = ")
+                .ok_or_else(|| format!("line {}: expected 'name = value'", i + 1))?;
+            vars.insert(name.to_string(), unescape(value));
+        }
+        Ok(Self { vars })
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for (name, value) in &self.vars {
+            out.push_str(name);
+            out.push_str(" = ");
+            out.push_str(&escape(value));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// An evaluator's variables, and which of them are read-only, at one
+/// moment (see [`Evaluator::snapshot`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateSnapshot {
+    pub(crate) variables: BTreeMap<String, String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) readonly: BTreeSet<String>,
+}
+
+impl StateSnapshot {
+    /// The variables as a [`Snapshot`], e.g. to [`diff`] two undo points.
+    pub fn to_snapshot(&self) -> Snapshot {
+        Snapshot {
+            vars: self.variables.clone(),
+        }
+    }
+
+    /// The state as pretty-printed JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a map of strings always serializes")
+    }
+
+    /// Read state written by [`to_json`](StateSnapshot::to_json).
+    #[cfg(feature = "serde")]
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }
+}
+
+/// One difference between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added { name: String, value: String },
+    Removed { name: String, value: String },
+    Changed { name: String, old: String, new: String },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { name, value } => write!(f, "+ {} = {}", name, escape(value)),
+            Self::Removed { name, value } => write!(f, "- {} = {}", name, escape(value)),
+            Self::Changed { name, old, new } => {
+                write!(f, "~ {}: {} -> {}", name, escape(old), escape(new))
+            }
+        }
+    }
+}
+
+/// Everything that differs from `old` to `new`, in name order.
+pub fn diff(old: &Snapshot, new: &Snapshot) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for (name, old_value) in &old.vars {
+        match new.vars.get(name) {
+            None => changes.push(Change::Removed {
+                name: name.clone(),
+                value: old_value.clone(),
+            }),
+            Some(new_value) if new_value != old_value => changes.push(Change::Changed {
+                name: name.clone(),
+                old: old_value.clone(),
+                new: new_value.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (name, value) in &new.vars {
+        if !old.vars.contains_key(name) {
+            changes.push(Change::Added {
+                name: name.clone(),
+                value: value.clone(),
+            });
+        }
+    }
+    changes.sort_by(|a, b| change_name(a).cmp(change_name(b)));
+    changes
+}
+
+fn change_name(change: &Change) -> &str {
+    match change {
+        Change::Added { name, .. } | Change::Removed { name, .. } | Change::Changed { name, .. } => {
+            name
+        }
+    }
+}
+
+/// Escape `\`, newlines and carriage returns so a value fits on one line.
+pub fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(pairs: &[(&str, &str)]) -> Snapshot {
+        Snapshot {
+            vars: pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let s = snap(&[("a", "x = y"), ("b", "two\nlines \\ slash"), ("c", "")]);
+        assert_eq!(Snapshot::parse(&s.to_text()), Ok(s));
+    }
+
+    #[test]
+    fn test_diff_reports_added_removed_changed() {
+        let old = snap(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        let new = snap(&[("a", "1"), ("b", "20"), ("d", "4")]);
+        let lines: Vec<String> = diff(&old, &new).iter().map(|c| c.to_string()).collect();
+        assert_eq!(lines, vec!["~ b: 2 -> 20", "- c = 3", "+ d = 4"]);
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_restore_state_snapshot() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        let run = |eval: &mut Evaluator, source: &str| {
+            eval.evaluate_statements(&crate::parser::parse(source).unwrap())
+        };
+        run(&mut eval, "{a} = \"1\"").unwrap();
+        let before = eval.snapshot();
+
+        run(&mut eval, "{a} = \"2\"\nconst {b} = \"x\"").unwrap();
+        let lines: Vec<String> = diff(&before.to_snapshot(), &eval.snapshot().to_snapshot())
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(lines, vec!["~ a: 1 -> 2", "+ b = x", "+ b/count = 1", "+ b/length = 1"]);
+
+        eval.restore(&before);
+        assert_eq!(eval.snapshot(), before);
+        // {b} is no longer a constant.
+        run(&mut eval, "{b} = \"y\"").unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_state_json_round_trip() {
+        let mut state = StateSnapshot::default();
+        state.variables.insert("path".into(), "C:\\tmp\n\"x\"".into());
+        state.readonly.insert("path".into());
+        assert_eq!(StateSnapshot::from_json(&state.to_json()), Ok(state));
+
+        let state = StateSnapshot::from_json(r#"{"variables": {"a": "1"}}"#).unwrap();
+        assert_eq!(state.variables["a"], "1");
+        assert!(StateSnapshot::from_json(r#"{"variables": {"a": 1}}"#).is_err());
+    }
+}
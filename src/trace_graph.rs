@@ -0,0 +1,238 @@
+//! Graph of what a run actually executed, as Graphviz DOT or Mermaid.
+//!
+//! [`TraceGraph`] is an [`Observer`]: install it on an evaluator, run the
+//! script, then render it.  Nodes are the script, every `.bucl` function
+//! that was called, and every block statement (`if`, `repeat`, `each`, …)
+//! that ran, each labelled with the number of statements executed directly
+//! inside it.  An edge `a → b` means `b` was entered from `a`, labelled with
+//! how many times.
+//!
+//! ```ignore
+//! let graph = Arc::new(TraceGraph::new());
+//! eval.observer = Some(graph.clone());
+//! eval.evaluate_statements(&stmts)?;
+//! std::fs::write("run.dot", graph.to_dot())?;
+//! ```
+//!
+//! A block inside a function is one node however many callers reach it;
+//! blocks are identified by the function (or script) they are written in,
+//! their line and their statement name.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+
+use crate::ast::Statement;
+use crate::evaluator::Observer;
+
+const SCRIPT: &str = "script";
+
+struct Node {
+    label: String,
+    is_function: bool,
+    statements: usize,
+}
+
+#[derive(Default)]
+struct State {
+    /// Node keys, in first-seen order.
+    order: Vec<String>,
+    nodes: BTreeMap<String, Node>,
+    edges: BTreeMap<(String, String), usize>,
+    /// Keys of the nodes currently being executed, innermost last.
+    stack: Vec<String>,
+    /// Names of the `.bucl` functions currently being executed.
+    scopes: Vec<String>,
+}
+
+impl State {
+    fn current(&self) -> &str {
+        self.stack.last().map_or(SCRIPT, String::as_str)
+    }
+
+    fn enter(&mut self, key: String, label: String, is_function: bool) {
+        if !self.nodes.contains_key(&key) {
+            self.order.push(key.clone());
+            self.nodes.insert(
+                key.clone(),
+                Node {
+                    label,
+                    is_function,
+                    statements: 0,
+                },
+            );
+        }
+        *self
+            .edges
+            .entry((self.current().to_string(), key.clone()))
+            .or_insert(0) += 1;
+        self.stack.push(key);
+    }
+}
+
+/// Records function calls and block nesting; see the module docs.
+pub struct TraceGraph {
+    state: Mutex<State>,
+}
+
+impl Default for TraceGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TraceGraph {
+    pub fn new() -> Self {
+        let mut state = State::default();
+        state.order.push(SCRIPT.to_string());
+        state.nodes.insert(
+            SCRIPT.to_string(),
+            Node {
+                label: SCRIPT.to_string(),
+                is_function: true,
+                statements: 0,
+            },
+        );
+        Self {
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Render as a Graphviz `digraph`.
+    pub fn to_dot(&self) -> String {
+        let state = self.state.lock().unwrap();
+        let mut out = String::from("digraph bucl {\n    node [fontname=\"monospace\"];\n");
+        for key in &state.order {
+            let node = &state.nodes[key];
+            let _ = writeln!(
+                out,
+                "    \"{}\" [label=\"{}\\n{}\" shape={}];",
+                dot_escape(key),
+                dot_escape(&node.label),
+                statements_label(node.statements),
+                if node.is_function { "box" } else { "ellipse" }
+            );
+        }
+        for ((from, to), count) in &state.edges {
+            let _ = writeln!(
+                out,
+                "    \"{}\" -> \"{}\" [label=\"{}×\"];",
+                dot_escape(from),
+                dot_escape(to),
+                count
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render as a Mermaid `flowchart`.
+    pub fn to_mermaid(&self) -> String {
+        let state = self.state.lock().unwrap();
+        let id = |key: &String| format!("n{}", state.order.iter().position(|k| k == key).unwrap());
+        let mut out = String::from("flowchart TD\n");
+        for key in &state.order {
+            let node = &state.nodes[key];
+            let label = format!(
+                "{}<br/>{}",
+                node.label.replace('"', "#quot;"),
+                statements_label(node.statements)
+            );
+            if node.is_function {
+                let _ = writeln!(out, "    {}[\"{}\"]", id(key), label);
+            } else {
+                let _ = writeln!(out, "    {}([\"{}\"])", id(key), label);
+            }
+        }
+        for ((from, to), count) in &state.edges {
+            let _ = writeln!(out, "    {} -->|{}×| {}", id(from), count, id(to));
+        }
+        out
+    }
+}
+
+impl Observer for TraceGraph {
+    fn statement(&self, _stmt: &Statement) {
+        let mut state = self.state.lock().unwrap();
+        let key = state.current().to_string();
+        if let Some(node) = state.nodes.get_mut(&key) {
+            node.statements += 1;
+        }
+    }
+
+    fn enter_function(&self, name: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.enter(format!("fn:{}", name), format!("{}()", name), true);
+        state.scopes.push(name.to_string());
+    }
+
+    fn exit_function(&self, _name: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.stack.pop();
+        state.scopes.pop();
+    }
+
+    fn enter_block(&self, stmt: &Statement) {
+        let mut state = self.state.lock().unwrap();
+        let scope = state.scopes.last().map_or(SCRIPT, String::as_str);
+        let key = format!("{}:{}:{}", scope, stmt.line, stmt.function);
+        let label = match state.scopes.last() {
+            Some(func) => format!("{} ({}.bucl line {})", stmt.function, func, stmt.line),
+            None => format!("{} (line {})", stmt.function, stmt.line),
+        };
+        state.enter(key, label, false);
+    }
+
+    fn exit_block(&self, _stmt: &Statement) {
+        self.state.lock().unwrap().stack.pop();
+    }
+}
+
+fn statements_label(n: usize) -> String {
+    if n == 1 {
+        "1 statement".to_string()
+    } else {
+        format!("{} statements", n)
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(all(test, feature = "math"))]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::evaluator::Evaluator;
+
+    #[test]
+    fn test_counts_blocks_and_calls() {
+        let mut eval = Evaluator::new();
+        crate::functions::register_all(&mut eval);
+        eval.embed_function("double", "{return} math \"{0} * 2\"\n");
+        let graph = Arc::new(TraceGraph::new());
+        eval.observer = Some(graph.clone());
+
+        let stmts = crate::parser::parse(concat!(
+            "{n} = \"0\"\n",
+            "repeat 3\n",
+            "    {n} double {n}\n",
+        ))
+        .unwrap();
+        eval.evaluate_statements(&stmts).unwrap();
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("\"script\" -> \"script:2:repeat\" [label=\"1×\"]"), "{}", dot);
+        assert!(dot.contains("\"script:2:repeat\" -> \"fn:double\" [label=\"3×\"]"), "{}", dot);
+        assert!(dot.contains("label=\"double()\\n3 statements\""), "{}", dot);
+
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.contains("n1 -->|3×| n2"), "{}", mermaid);
+    }
+}